@@ -1,13 +1,9 @@
 #![no_main]
 
 use {
-  bitcoin::{
-    locktime, opcodes,
-    script::self,
-    Transaction, TxOut,
-  },
+  bitcoin::{blockdata::script, opcodes, PackedLockTime, Transaction, TxOut},
+  dunes_protocol::{Dunestone, Rules},
   libfuzzer_sys::fuzz_target,
-  ord::dunes::Dunestone,
 };
 
 fuzz_target!(|input: Vec<Vec<u8>>| {
@@ -16,15 +12,12 @@ fuzz_target!(|input: Vec<Vec<u8>>| {
     .push_slice(b"D");
 
   for slice in input {
-    let Ok(push): Result<&[u8], _> = slice.as_slice().try_into() else {
-      continue;
-    };
-    builder = builder.push_slice(push);
+    builder = builder.push_slice(&slice);
   }
 
   let tx = Transaction {
     input: Vec::new(),
-    lock_time: locktime::absolute::LockTime::ZERO,
+    lock_time: PackedLockTime::ZERO,
     output: vec![TxOut {
       script_pubkey: builder.into_script(),
       value: 0,
@@ -32,5 +25,6 @@ fuzz_target!(|input: Vec<Vec<u8>>| {
     version: 0,
   };
 
-  Dunestone::from_transaction(&tx);
+  Dunestone::from_transaction_with_rules(&tx, Rules::Legacy);
+  Dunestone::from_transaction_with_rules(&tx, Rules::Strict);
 });
@@ -0,0 +1,10 @@
+use super::*;
+
+/// A malformed dunestone. All dunes in the inputs of a transaction containing
+/// a cenotaph are burned, and a dune etched by it has no supply and cannot be
+/// minted.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Copy, Clone)]
+pub struct Cenotaph {
+  pub etching: Option<Dune>,
+  pub flaw: Flaw,
+}
@@ -1,5 +1,25 @@
 use super::*;
 
+const SUBSIDY_HALVING_INTERVAL_10X: u32 =
+  bitcoin::blockdata::constants::SUBSIDY_HALVING_INTERVAL * 10;
+
+#[derive(Debug, PartialEq)]
+pub enum Error {
+  Character(char),
+  Range,
+}
+
+impl Display for Error {
+  fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+    match self {
+      Self::Character(c) => write!(f, "invalid character in dune name: {c}"),
+      Self::Range => write!(f, "out of range"),
+    }
+  }
+}
+
+impl std::error::Error for Error {}
+
 #[derive(Default, Debug, PartialEq, Copy, Clone, PartialOrd, Ord, Eq, Hash)]
 pub struct Dune(pub u128);
 
@@ -35,17 +55,46 @@ impl Dune {
     166461473448801533683942072758341510102,
   ];
 
-  pub(crate) fn minimum_at_height(chain: Chain, height: Height) -> Self {
-    let offset = height.0.saturating_add(1);
+  /// First dune reserved for etchings that don't specify a dune name
+  pub const FIRST_RESERVED: Self = Self(RESERVED);
+
+  /// Number of blocks after which the minimum length of dune names decreases
+  /// by one letter
+  pub const UNLOCK_INTERVAL: u32 = SUBSIDY_HALVING_INTERVAL_10X / 12;
+
+  /// Minimum length of dune names when dunes are first indexed
+  pub const INITIAL_LENGTH: u32 = 13;
+
+  /// Returns the smallest dune that may be etched in block `height`, on a
+  /// chain where dunes are first indexed at `first_dune_height`
+  pub fn minimum_at_height(first_dune_height: u32, height: u32) -> Self {
+    Self::minimum_at_height_with_schedule(
+      first_dune_height,
+      Self::UNLOCK_INTERVAL,
+      Self::INITIAL_LENGTH,
+      height,
+    )
+  }
 
-    const INTERVAL: u32 = SUBSIDY_HALVING_INTERVAL_10X / 12;
+  /// Returns the smallest dune that may be etched in block `height`, on a
+  /// chain where dunes are first indexed at `first_dune_height` with names of
+  /// at least `initial_length` letters, one fewer every `interval` blocks
+  pub fn minimum_at_height_with_schedule(
+    first_dune_height: u32,
+    interval: u32,
+    initial_length: u32,
+    height: u32,
+  ) -> Self {
+    let offset = height.saturating_add(1);
+
+    let steps = initial_length.clamp(1, u32::try_from(Self::STEPS.len()).unwrap()) - 1;
 
-    let start = chain.first_dune_height();
+    let start = first_dune_height;
 
-    let end = start + SUBSIDY_HALVING_INTERVAL_10X;
+    let end = start.saturating_add(interval.saturating_mul(steps));
 
     if offset < start {
-      return Dune(Self::STEPS[12]);
+      return Dune(Self::STEPS[usize::try_from(steps).unwrap()]);
     }
 
     if offset >= end {
@@ -54,23 +103,35 @@ impl Dune {
 
     let progress = offset.saturating_sub(start);
 
-    let length = 12u32.saturating_sub((progress as u32) / INTERVAL);
+    let length = steps.saturating_sub(progress / interval);
 
     let end = Self::STEPS[usize::try_from(length - 1).unwrap()];
 
     let start = Self::STEPS[usize::try_from(length).unwrap()];
 
-    let remainder = u128::from((progress as u32) % INTERVAL);
+    let remainder = u128::from(progress % interval);
+
+    Dune(start - ((start - end) * remainder / u128::from(interval)))
+  }
 
-    Dune(start - ((start - end) * remainder / u128::from(INTERVAL)))
+  pub fn is_reserved(self) -> bool {
+    self.is_reserved_from(Self::FIRST_RESERVED)
   }
 
-  pub(crate) fn is_reserved(self) -> bool {
-    self.0 >= RESERVED
+  /// Whether this dune is reserved, on a chain where reserved dunes start at
+  /// `first_reserved`
+  pub fn is_reserved_from(self, first_reserved: Self) -> bool {
+    self >= first_reserved
   }
 
-  pub(crate) fn reserved(n: u128) -> Self {
-    Dune(RESERVED.checked_add(n).unwrap())
+  pub fn reserved(n: u128) -> Self {
+    Self::reserved_from(Self::FIRST_RESERVED, n)
+  }
+
+  /// Returns the `n`th reserved dune, on a chain where reserved dunes start at
+  /// `first_reserved`
+  pub fn reserved_from(first_reserved: Self, n: u128) -> Self {
+    Dune(first_reserved.0.checked_add(n).unwrap())
   }
 }
 
@@ -120,22 +181,22 @@ impl Display for Dune {
 }
 
 impl FromStr for Dune {
-  type Err = crate::Error;
+  type Err = Error;
 
-  fn from_str(s: &str) -> crate::Result<Self> {
+  fn from_str(s: &str) -> Result<Self, Self::Err> {
     let mut x = 0u128;
     for (i, c) in s.chars().enumerate() {
       if i > 0 {
         x += 1;
       }
-      x = x.checked_mul(26).ok_or_else(|| anyhow!("out of range"))?;
+      x = x.checked_mul(26).ok_or(Error::Range)?;
       match c {
         'A'..='Z' => {
           x = x
             .checked_add(c as u128 - 'A' as u128)
-            .ok_or_else(|| anyhow!("out of range"))?;
+            .ok_or(Error::Range)?;
         }
-        _ => bail!("invalid character in dune name: {c}"),
+        _ => return Err(Error::Character(c)),
       }
     }
     Ok(Dune(x))
@@ -144,7 +205,6 @@ impl FromStr for Dune {
 
 #[cfg(test)]
 mod tests {
-  use bitcoin::blockdata::constants::COIN_VALUE;
   use super::*;
 
   #[test]
@@ -190,8 +250,12 @@ mod tests {
   }
 
   #[test]
-  fn from_str_out_of_range() {
-    "BCGDENLQRQWDSLRUGSNLBTMFIJAW".parse::<Dune>().unwrap_err();
+  fn from_str_error() {
+    assert_eq!(
+      "BCGDENLQRQWDSLRUGSNLBTMFIJAW".parse::<Dune>().unwrap_err(),
+      Error::Range,
+    );
+    assert_eq!("Ax".parse::<Dune>().unwrap_err(), Error::Character('x'));
   }
 
   #[test]
@@ -202,12 +266,12 @@ mod tests {
     #[track_caller]
     fn case(height: u32, minimum: &str) {
       assert_eq!(
-        Dune::minimum_at_height(Chain::Mainnet, Height(height)).to_string(),
+        Dune::minimum_at_height(START, height).to_string(),
         minimum,
       );
     }
 
-    const START: u32 = 5058400;
+    const START: u32 = 5084000;
     const END: u32 = START + SUBSIDY_HALVING_INTERVAL_10X;
     const INTERVAL: u32 = SUBSIDY_HALVING_INTERVAL_10X / 12;
 
@@ -283,35 +347,51 @@ mod tests {
   #[test]
   fn minimum_at_height() {
     #[track_caller]
-    fn case(chain: Chain, height: u32, minimum: &str) {
+    fn case(first_dune_height: u32, height: u32, minimum: &str) {
       assert_eq!(
-        Dune::minimum_at_height(chain, Height(height as u64)).to_string(),
+        Dune::minimum_at_height(first_dune_height, height).to_string(),
         minimum,
       );
     }
 
-    case(Chain::Testnet, 0, "AAAAAAAAAAAAA");
-    case(
-      Chain::Testnet,
-      SUBSIDY_HALVING_INTERVAL_10X * 12 - 1,
-      "AAAAAAAAAAAAA",
-    );
-    case(
-      Chain::Testnet,
-      SUBSIDY_HALVING_INTERVAL_10X * 12,
-      "ZZYZXBRKWXVA",
-    );
-    case(
-      Chain::Testnet,
-      SUBSIDY_HALVING_INTERVAL_10X * 12 + 1,
-      "ZZXZUDIVTVQA",
-    );
+    const START: u32 = SUBSIDY_HALVING_INTERVAL_10X * 12;
 
-    case(Chain::Signet, 0, "ZZYZXBRKWXVA");
-    case(Chain::Signet, 1, "ZZXZUDIVTVQA");
+    case(START, 0, "AAAAAAAAAAAAA");
+    case(START, START - 1, "AAAAAAAAAAAAA");
+    case(START, START, "ZZYZXBRKWXVA");
+    case(START, START + 1, "ZZXZUDIVTVQA");
 
-    case(Chain::Regtest, 0, "ZZYZXBRKWXVA");
-    case(Chain::Regtest, 1, "ZZXZUDIVTVQA");
+    case(0, 0, "ZZYZXBRKWXVA");
+    case(0, 1, "ZZXZUDIVTVQA");
+  }
+
+  #[test]
+  fn minimum_at_height_with_schedule() {
+    #[track_caller]
+    fn case(height: u32, minimum: &str) {
+      assert_eq!(
+        Dune::minimum_at_height_with_schedule(100, 10, 4, height).to_string(),
+        minimum,
+      );
+    }
+
+    case(0, "AAAA");
+    case(98, "AAAA");
+    case(99, "AAAA");
+    case(109, "AAA");
+    case(119, "AA");
+    case(128, "D");
+    case(129, "A");
+
+    assert_eq!(
+      Dune::minimum_at_height_with_schedule(
+        5084000,
+        Dune::UNLOCK_INTERVAL,
+        Dune::INITIAL_LENGTH,
+        5084000 + 1000,
+      ),
+      Dune::minimum_at_height(5084000, 5084000 + 1000),
+    );
   }
 
   #[test]
@@ -332,6 +412,10 @@ mod tests {
 
     assert_eq!(Dune::reserved(0), Dune(RESERVED));
     assert_eq!(Dune::reserved(1), Dune(RESERVED + 1));
+
+    assert_eq!(Dune::reserved_from(Dune(26), 1), Dune(27));
+    assert!(Dune(26).is_reserved_from(Dune(26)));
+    assert!(!Dune(25).is_reserved_from(Dune(26)));
   }
 
   #[test]
@@ -1,4 +1,26 @@
-use {super::*, std::num::TryFromIntError};
+use {
+  super::*,
+  std::num::{ParseIntError, TryFromIntError},
+};
+
+#[derive(Debug, PartialEq)]
+pub enum Error {
+  Height(ParseIntError),
+  Index(ParseIntError),
+  Separator,
+}
+
+impl Display for Error {
+  fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+    match self {
+      Self::Height(err) => write!(f, "invalid dune ID height: {err}"),
+      Self::Index(err) => write!(f, "invalid dune ID index: {err}"),
+      Self::Separator => write!(f, "invalid dune ID: missing `:` separator"),
+    }
+  }
+}
+
+impl std::error::Error for Error {}
 
 #[derive(Debug, PartialEq, Copy, Clone, Hash, Eq, Ord, PartialOrd)]
 pub struct DuneId {
@@ -30,16 +52,14 @@ impl Display for DuneId {
 }
 
 impl FromStr for DuneId {
-  type Err = crate::Error;
+  type Err = Error;
 
   fn from_str(s: &str) -> Result<Self, Self::Err> {
-    let (height, index) = s
-        .split_once(':')
-        .ok_or_else(|| anyhow!("invalid dune ID: {s}"))?;
+    let (height, index) = s.split_once(':').ok_or(Error::Separator)?;
 
     Ok(Self {
-      height: height.parse()?,
-      index: index.parse()?,
+      height: height.parse().map_err(Error::Height)?,
+      index: index.parse().map_err(Error::Index)?,
     })
   }
 }
@@ -92,6 +112,15 @@ mod tests {
 
   #[test]
   fn from_str() {
+    assert_eq!("1".parse::<DuneId>().unwrap_err(), Error::Separator);
+    assert!(matches!(
+      "a:2".parse::<DuneId>().unwrap_err(),
+      Error::Height(_)
+    ));
+    assert!(matches!(
+      "1:a".parse::<DuneId>().unwrap_err(),
+      Error::Index(_)
+    ));
     assert!(":".parse::<DuneId>().is_err());
     assert!("1:".parse::<DuneId>().is_err());
     assert!(":2".parse::<DuneId>().is_err());
@@ -116,7 +145,7 @@ mod tests {
       }
     );
 
-    assert!(DuneId::try_from(0x07060504030201).is_err());
+    assert!(DuneId::try_from(1 << 80).is_err());
   }
 
   #[test]
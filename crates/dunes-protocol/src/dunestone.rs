@@ -0,0 +1,1391 @@
+use super::*;
+
+const MAX_SPACERS: u32 = 0b00000111_11111111_11111111_11111111;
+
+#[derive(Default, Serialize, Debug, PartialEq)]
+pub struct Dunestone {
+  pub edicts: Vec<Edict>,
+  pub etching: Option<Etching>,
+  pub pointer: Option<u32>,
+  pub flaw: Option<Flaw>,
+}
+
+
+struct Message {
+  flaw: Option<Flaw>,
+  fields: HashMap<u128, u128>,
+  edicts: Vec<Edict>,
+}
+
+impl Message {
+  fn from_integers(tx: &Transaction, payload: &[u128], rules: Rules) -> Self {
+    let mut edicts = Vec::new();
+    let mut fields = HashMap::new();
+    let mut flaw = None;
+
+    for i in (0..payload.len()).step_by(2) {
+      let tag = payload[i];
+
+      if Tag::Body == tag {
+        let body = &payload[i + 1..];
+        let mut id = 0u128;
+        for chunk in body.chunks_exact(3) {
+          id = id.saturating_add(chunk[0]);
+          match Edict::from_integers(tx, id, chunk[1], chunk[2]) {
+            Ok(edict) => edicts.push(edict),
+            Err(err) => {
+              flaw.get_or_insert(err);
+            }
+          }
+        }
+
+        if rules == Rules::Strict && body.len() % 3 != 0 {
+          flaw.get_or_insert(Flaw::TrailingIntegers);
+        }
+
+        break;
+      }
+
+      let Some(&value) = payload.get(i + 1) else {
+        break;
+      };
+
+      fields.entry(tag).or_insert(value);
+    }
+
+    Self { flaw, fields, edicts }
+  }
+}
+
+impl Dunestone {
+  /// Decipher the dunestone in `transaction` under `rules`, which must be the
+  /// rules of the block the transaction is, or will be, included in.
+  pub fn from_transaction_with_rules(transaction: &Transaction, rules: Rules) -> Option<Self> {
+    Self::decipher_with_rules(transaction, rules).ok().flatten()
+  }
+
+  pub fn is_cenotaph(&self) -> bool {
+    self.flaw.is_some()
+  }
+
+  /// Returns the cenotaph this dunestone deciphers to, if it is flawed.
+  pub fn cenotaph(&self) -> Option<Cenotaph> {
+    Some(Cenotaph {
+      etching: self.etching.and_then(|etching| etching.dune),
+      flaw: self.flaw?,
+    })
+  }
+
+  #[cfg(test)]
+  fn decipher(transaction: &Transaction) -> Result<Option<Self>, script::Error> {
+    Self::decipher_with_rules(transaction, Rules::Strict)
+  }
+
+  fn decipher_with_rules(
+    transaction: &Transaction,
+    rules: Rules,
+  ) -> Result<Option<Self>, script::Error> {
+    let Some(payload) = Dunestone::payload(transaction)? else {
+      return Ok(None);
+    };
+
+    let integers = match rules {
+      Rules::Legacy => Dunestone::saturating_integers(&payload),
+      Rules::Strict => match Dunestone::integers(&payload) {
+        Ok(integers) => integers,
+        Err(flaw) => {
+          return Ok(Some(Self {
+            flaw: Some(flaw),
+            ..Default::default()
+          }))
+        }
+      },
+    };
+
+    let Message { flaw, mut fields, mut edicts } =
+      Message::from_integers(transaction, &integers, rules);
+
+    /* Ignore deadline
+    let deadline = Tag::Deadline
+        .take(&mut fields)
+        .and_then(|deadline| u32::try_from(deadline).ok());*/
+
+    let pointer = Tag::Pointer
+        .take(&mut fields)
+        .and_then(|default| u32::try_from(default).ok());
+
+    let divisibility = Tag::Divisibility
+        .take(&mut fields)
+        .and_then(|divisibility| u8::try_from(divisibility).ok())
+        .and_then(|divisibility| (divisibility <= MAX_DIVISIBILITY).then_some(divisibility));
+
+    let limit = Tag::Limit
+      .take(&mut fields)
+      .map(|limit| limit.clamp(0, MAX_LIMIT));
+
+    let dune = Tag::Dune.take(&mut fields).map(Dune);
+
+    let cap = Tag::Cap
+        .take(&mut fields)
+        .map(|cap| cap);
+
+    let premine = Tag::Premine
+        .take(&mut fields)
+        .map(|premine| premine);
+
+    if premine.unwrap_or_default() > 0 {
+      edicts.push(Edict{
+        id: 0,
+        amount: premine.unwrap_or_default(),
+        output: 1,
+      });
+    }
+
+    let spacers = Tag::Spacers
+        .take(&mut fields)
+        .and_then(|spacers| u32::try_from(spacers).ok())
+        .and_then(|spacers| (spacers <= MAX_SPACERS).then_some(spacers));
+
+    let symbol = Tag::Symbol
+        .take(&mut fields)
+        .and_then(|symbol| u32::try_from(symbol).ok())
+        .and_then(char::from_u32);
+
+    let height = (
+    Tag::HeightStart.take(&mut fields)
+        .and_then(|start_height| u64::try_from(start_height).ok()),
+    Tag::HeightEnd.take(&mut fields)
+        .and_then(|end_height| u64::try_from(end_height).ok())
+    );
+
+    let offset = (
+      Tag::OffsetStart.take(&mut fields)
+          .and_then(|start_offset| u64::try_from(start_offset).ok()),
+      Tag::OffsetEnd.take(&mut fields)
+          .and_then(|end_offset| u64::try_from(end_offset).ok())
+    );
+
+    let mut flags = Tag::Flags.take(&mut fields).unwrap_or_default();
+
+    let etch = Flag::Etching.take(&mut flags);
+
+    let terms = Flag::Terms.take(&mut flags);
+
+    let turbo = Flag::Turbo.take(&mut flags);
+
+    let overflow = (|| {
+      let premine = premine.unwrap_or_default();
+      let cap = cap.unwrap_or_default();
+      let limit = limit.unwrap_or_default();
+      premine.checked_add(cap.checked_mul(limit)?)
+    })()
+        .is_none();
+
+    let etching = if etch {
+      Some(Etching {
+        divisibility,
+        dune,
+        spacers,
+        symbol,
+        terms: terms.then_some(Terms {
+          cap,
+          height,
+          limit,
+          offset,
+        }),
+        premine,
+        turbo,
+      })
+    } else {
+      None
+    };
+
+    let flaw = flaw
+      .or(overflow.then_some(Flaw::SupplyOverflow))
+      .or((flags != 0).then_some(Flaw::UnrecognizedFlag))
+      .or(fields.keys().any(|tag| tag % 2 == 0).then_some(Flaw::UnrecognizedEvenTag));
+
+    Ok(Some(Self {
+      flaw,
+      pointer,
+      edicts,
+      etching,
+    }))
+  }
+
+  pub fn encipher(&self) -> Script {
+    let mut payload = Vec::new();
+
+    if let Some(etching) = self.etching {
+      let mut flags = 0;
+      Flag::Etching.set(&mut flags);
+
+      if etching.terms.is_some() {
+        Flag::Terms.set(&mut flags);
+      }
+
+      if etching.turbo {
+        Flag::Turbo.set(&mut flags);
+      }
+
+      Tag::Flags.encode(flags, &mut payload);
+
+      if let Some(dune) = etching.dune {
+        Tag::Dune.encode(dune.0, &mut payload);
+      }
+
+      if let Some(divisibility) = etching.divisibility {
+        Tag::Divisibility.encode(divisibility.into(), &mut payload);
+      }
+
+      if let Some(spacers) = etching.spacers {
+        Tag::Spacers.encode(spacers.into(), &mut payload);
+      }
+
+      if let Some(symbol) = etching.symbol {
+        Tag::Symbol.encode(symbol.into(), &mut payload);
+      }
+
+      if let Some(premine) = etching.premine {
+        Tag::Premine.encode(premine, &mut payload);
+      }
+
+      if let Some(mint) = etching.terms {
+        if let Some(limit) = mint.limit {
+          Tag::Limit.encode(limit, &mut payload);
+        }
+
+        if let Some(start) = mint.height.0 {
+          Tag::HeightStart.encode(start.into(), &mut payload);
+        }
+
+        if let Some(end) = mint.height.1 {
+          Tag::HeightEnd.encode(end.into(), &mut payload);
+        }
+
+        if let Some(start) = mint.offset.0 {
+          Tag::OffsetStart.encode(start.into(), &mut payload);
+        }
+
+        if let Some(end) = mint.offset.1 {
+          Tag::OffsetEnd.encode(end.into(), &mut payload);
+        }
+
+        if let Some(cap) = mint.cap {
+          Tag::Cap.encode(cap, &mut payload);
+        }
+      }
+    }
+
+    if let Some(default_output) = self.pointer {
+      Tag::Pointer.encode(default_output.into(), &mut payload);
+    }
+
+    // The original flaw cannot be encoded, so flawed dunestones are
+    // enciphered with the cenotaph tag, an unrecognized even tag
+    if self.flaw.is_some() {
+      Tag::Cenotaph.encode(0, &mut payload);
+    }
+
+    if !self.edicts.is_empty() {
+      varint::encode_to_vec(Tag::Body.into(), &mut payload);
+
+      let mut edicts = self.edicts.clone();
+      edicts.sort_by_key(|edict| edict.id);
+
+      let mut id = 0;
+      for edict in edicts {
+        varint::encode_to_vec(edict.id - id, &mut payload);
+        varint::encode_to_vec(edict.amount, &mut payload);
+        varint::encode_to_vec(edict.output, &mut payload);
+        id = edict.id;
+      }
+    }
+
+    let mut builder = script::Builder::new()
+        .push_opcode(opcodes::all::OP_RETURN)
+        .push_slice(b"D");
+
+    for chunk in payload.chunks(bitcoin::blockdata::constants::MAX_SCRIPT_ELEMENT_SIZE) {
+      let push= chunk.try_into().unwrap();
+      builder = builder.push_slice(push);
+    }
+
+    builder.into_script()
+  }
+
+  pub fn payload(transaction: &Transaction) -> Result<Option<Vec<u8>>, script::Error> {
+    for output in &transaction.output {
+      let mut instructions = output.script_pubkey.instructions();
+
+      if instructions.next().transpose()? != Some(Instruction::Op(opcodes::all::OP_RETURN)) {
+        continue;
+      }
+
+      if instructions.next().transpose()? != Some(Instruction::PushBytes(b"D".as_ref().into())) {
+        continue;
+      }
+
+      let mut payload = Vec::new();
+
+      for result in instructions {
+        if let Instruction::PushBytes(push) = result? {
+          payload.extend_from_slice(push.as_ref().into());
+        }
+      }
+
+      return Ok(Some(payload));
+    }
+
+    Ok(None)
+  }
+
+  pub fn integers(payload: &[u8]) -> Result<Vec<u128>, Flaw> {
+    match Self::partial_integers(payload) {
+      (integers, None) => Ok(integers),
+      (_, Some(flaw)) => Err(flaw),
+    }
+  }
+
+  /// Decode `payload`, stopping at the first varint that is truncated or
+  /// overflows. Returns the integers decoded before it, along with the flaw.
+  pub fn partial_integers(payload: &[u8]) -> (Vec<u128>, Option<Flaw>) {
+    let mut integers = Vec::new();
+    let mut i = 0;
+
+    while i < payload.len() {
+      let Some((integer, length)) = varint::try_decode(&payload[i..]) else {
+        return (integers, Some(Flaw::Varint));
+      };
+      integers.push(integer);
+      i += length;
+    }
+
+    (integers, None)
+  }
+
+  /// Decode `payload` under the legacy rules, saturating varints that
+  /// overflow
+  fn saturating_integers(payload: &[u8]) -> Vec<u128> {
+    let mut integers = Vec::new();
+    let mut i = 0;
+
+    while i < payload.len() {
+      let (integer, length) = varint::decode(&payload[i..]);
+      integers.push(integer);
+      i += length;
+    }
+
+    integers
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use {
+    super::*,
+    bitcoin::{PackedLockTime, Script, TxOut},
+  };
+
+  /// ID of the first dune etched in block 1, the smallest valid edict ID
+  const ID: u128 = 1 << 16;
+
+  fn payload(integers: &[u128]) -> Vec<u8> {
+    let mut payload = Vec::new();
+
+    for integer in integers {
+      payload.extend(varint::encode(*integer));
+    }
+
+    payload
+  }
+
+  fn dunestone_script(payload: &[u8]) -> Script {
+    script::Builder::new()
+      .push_opcode(opcodes::all::OP_RETURN)
+      .push_slice(b"D")
+      .push_slice(payload)
+      .into_script()
+  }
+
+  /// A transaction with `outputs`, followed by three empty outputs for
+  /// edicts to allocate to
+  fn transaction(outputs: Vec<Script>) -> Transaction {
+    Transaction {
+      input: Vec::new(),
+      output: outputs
+        .into_iter()
+        .chain([Script::new(), Script::new(), Script::new()])
+        .map(|script_pubkey| TxOut {
+          script_pubkey,
+          value: 0,
+        })
+        .collect(),
+      lock_time: PackedLockTime::ZERO,
+      version: 0,
+    }
+  }
+
+  fn decipher(integers: &[u128]) -> Dunestone {
+    Dunestone::decipher(&transaction(vec![dunestone_script(&payload(integers))]))
+      .unwrap()
+      .unwrap()
+  }
+
+  #[test]
+  fn from_transaction_with_rules_returns_none_if_decipher_returns_error() {
+    assert_eq!(
+      Dunestone::from_transaction_with_rules(
+        &Transaction {
+          input: Vec::new(),
+          output: vec![TxOut {
+            script_pubkey: Script::from(vec![opcodes::all::OP_PUSHBYTES_4.to_u8()]),
+            value: 0,
+          }],
+          lock_time: PackedLockTime::ZERO,
+          version: 0,
+        },
+        Rules::Strict
+      ),
+      None
+    );
+  }
+
+  #[test]
+  fn deciphering_transaction_with_no_outputs_returns_none() {
+    assert_eq!(
+      Dunestone::decipher(&Transaction {
+        input: Vec::new(),
+        output: Vec::new(),
+        lock_time: PackedLockTime::ZERO,
+        version: 0,
+      }),
+      Ok(None)
+    );
+  }
+
+  #[test]
+  fn deciphering_transaction_with_non_op_return_output_returns_none() {
+    assert_eq!(
+      Dunestone::decipher(&transaction(vec![script::Builder::new()
+        .push_slice(&[])
+        .into_script()])),
+      Ok(None)
+    );
+  }
+
+  #[test]
+  fn deciphering_transaction_with_bare_op_return_returns_none() {
+    assert_eq!(
+      Dunestone::decipher(&transaction(vec![script::Builder::new()
+        .push_opcode(opcodes::all::OP_RETURN)
+        .into_script()])),
+      Ok(None)
+    );
+  }
+
+  #[test]
+  fn deciphering_transaction_with_non_matching_op_return_returns_none() {
+    assert_eq!(
+      Dunestone::decipher(&transaction(vec![script::Builder::new()
+        .push_opcode(opcodes::all::OP_RETURN)
+        .push_slice(b"FOOO")
+        .into_script()])),
+      Ok(None)
+    );
+  }
+
+  #[test]
+  fn deciphering_valid_dunestone_with_invalid_script_returns_script_error() {
+    assert!(Dunestone::decipher(&transaction(vec![Script::from(vec![
+      opcodes::all::OP_PUSHBYTES_4.to_u8()
+    ])]))
+    .is_err());
+  }
+
+  #[test]
+  fn deciphering_valid_dunestone_with_invalid_script_postfix_returns_script_error() {
+    let mut script_pubkey = script::Builder::new()
+      .push_opcode(opcodes::all::OP_RETURN)
+      .push_slice(b"D")
+      .into_script()
+      .to_bytes();
+
+    script_pubkey.push(opcodes::all::OP_PUSHBYTES_4.to_u8());
+
+    assert!(Dunestone::decipher(&transaction(vec![Script::from(script_pubkey)])).is_err());
+  }
+
+  #[test]
+  fn deciphering_dunestone_with_invalid_varint_is_a_flaw() {
+    assert_eq!(
+      Dunestone::decipher(&transaction(vec![dunestone_script(&[128])])),
+      Ok(Some(Dunestone {
+        flaw: Some(Flaw::Varint),
+        ..Default::default()
+      }))
+    );
+  }
+
+  #[test]
+  fn partial_integers_are_decoded_up_to_invalid_varint() {
+    assert_eq!(Dunestone::partial_integers(&[1, 2]), (vec![1, 2], None));
+    assert_eq!(
+      Dunestone::partial_integers(&[1, 2, 128]),
+      (vec![1, 2], Some(Flaw::Varint)),
+    );
+    assert_eq!(Dunestone::integers(&[1, 2, 128]), Err(Flaw::Varint));
+  }
+
+  #[test]
+  fn non_push_opcodes_in_dunestone_are_ignored() {
+    assert_eq!(
+      Dunestone::decipher(&transaction(vec![script::Builder::new()
+        .push_opcode(opcodes::all::OP_RETURN)
+        .push_slice(b"D")
+        .push_slice(&payload(&[0, ID]))
+        .push_opcode(opcodes::all::OP_VERIFY)
+        .push_slice(&payload(&[2, 3]))
+        .into_script()])),
+      Ok(Some(Dunestone {
+        edicts: vec![Edict {
+          id: ID,
+          amount: 2,
+          output: 3,
+        }],
+        ..Default::default()
+      })),
+    );
+  }
+
+  #[test]
+  fn deciphering_empty_dunestone_is_successful() {
+    assert_eq!(
+      Dunestone::decipher(&transaction(vec![dunestone_script(&[])])),
+      Ok(Some(Dunestone::default()))
+    );
+  }
+
+  #[test]
+  fn flaw_in_first_dunestone_aborts_search_for_dunestone() {
+    assert_eq!(
+      Dunestone::decipher(&transaction(vec![
+        dunestone_script(&[128]),
+        dunestone_script(&payload(&[0, ID, 2, 3])),
+      ])),
+      Ok(Some(Dunestone {
+        flaw: Some(Flaw::Varint),
+        ..Default::default()
+      }))
+    );
+  }
+
+  #[test]
+  fn deciphering_non_empty_dunestone_is_successful() {
+    assert_eq!(
+      decipher(&[0, ID, 2, 3]),
+      Dunestone {
+        edicts: vec![Edict {
+          id: ID,
+          amount: 2,
+          output: 3,
+        }],
+        ..Default::default()
+      }
+    );
+  }
+
+  #[test]
+  fn decipher_etching() {
+    assert_eq!(
+      decipher(&[2, 1, 4, 4, 0, ID, 2, 3]),
+      Dunestone {
+        edicts: vec![Edict {
+          id: ID,
+          amount: 2,
+          output: 3,
+        }],
+        etching: Some(Etching {
+          dune: Some(Dune(4)),
+          ..Default::default()
+        }),
+        ..Default::default()
+      }
+    );
+  }
+
+  #[test]
+  fn etching_flag_is_required_for_etching() {
+    assert_eq!(
+      decipher(&[4, 4, 0, ID, 2, 3]),
+      Dunestone {
+        edicts: vec![Edict {
+          id: ID,
+          amount: 2,
+          output: 3,
+        }],
+        ..Default::default()
+      }
+    );
+  }
+
+  #[test]
+  fn duplicate_tags_are_ignored() {
+    assert_eq!(
+      decipher(&[2, 1, 4, 4, 4, 5, 0, ID, 2, 3]),
+      Dunestone {
+        edicts: vec![Edict {
+          id: ID,
+          amount: 2,
+          output: 3,
+        }],
+        etching: Some(Etching {
+          dune: Some(Dune(4)),
+          ..Default::default()
+        }),
+        ..Default::default()
+      }
+    );
+  }
+
+  #[test]
+  fn unrecognized_odd_tag_is_ignored() {
+    assert_eq!(
+      decipher(&[127, 100, 0, ID, 2, 3]),
+      Dunestone {
+        edicts: vec![Edict {
+          id: ID,
+          amount: 2,
+          output: 3,
+        }],
+        ..Default::default()
+      }
+    );
+  }
+
+  #[test]
+  fn tag_with_no_value_is_ignored() {
+    assert_eq!(
+      decipher(&[2, 1, 4, 4, 4]),
+      Dunestone {
+        etching: Some(Etching {
+          dune: Some(Dune(4)),
+          ..Default::default()
+        }),
+        ..Default::default()
+      }
+    );
+  }
+
+  #[test]
+  fn additional_integers_in_body_are_a_flaw() {
+    assert_eq!(
+      decipher(&[2, 1, 4, 4, 0, ID, 2, 3, 4, 5]),
+      Dunestone {
+        edicts: vec![Edict {
+          id: ID,
+          amount: 2,
+          output: 3,
+        }],
+        etching: Some(Etching {
+          dune: Some(Dune(4)),
+          ..Default::default()
+        }),
+        flaw: Some(Flaw::TrailingIntegers),
+        ..Default::default()
+      }
+    );
+  }
+
+  #[test]
+  fn additional_integers_in_body_are_ignored_under_legacy_rules() {
+    assert_eq!(
+      Dunestone::decipher_with_rules(
+        &transaction(vec![dunestone_script(&payload(&[
+          2, 1, 4, 4, 0, ID, 2, 3, 4, 5
+        ]))]),
+        Rules::Legacy,
+      )
+      .unwrap()
+      .unwrap(),
+      Dunestone {
+        edicts: vec![Edict {
+          id: ID,
+          amount: 2,
+          output: 3,
+        }],
+        etching: Some(Etching {
+          dune: Some(Dune(4)),
+          ..Default::default()
+        }),
+        ..Default::default()
+      }
+    );
+  }
+
+  #[test]
+  fn overlong_varints_saturate_under_legacy_rules() {
+    let mut payload = payload(&[0, ID]);
+    payload.extend([0xFF; 19]);
+    payload.push(0);
+    payload.push(3);
+
+    assert_eq!(
+      Dunestone::decipher_with_rules(
+        &transaction(vec![dunestone_script(&payload)]),
+        Rules::Legacy,
+      )
+      .unwrap()
+      .unwrap(),
+      Dunestone {
+        edicts: vec![Edict {
+          id: ID,
+          amount: u128::MAX,
+          output: 3,
+        }],
+        ..Default::default()
+      }
+    );
+
+    assert_eq!(
+      Dunestone::decipher(&transaction(vec![dunestone_script(&payload)]))
+        .unwrap()
+        .unwrap()
+        .flaw,
+      Some(Flaw::Varint),
+    );
+  }
+
+  #[test]
+  fn decipher_etching_with_divisibility() {
+    assert_eq!(
+      decipher(&[2, 1, 4, 4, 1, 5, 0, ID, 2, 3]),
+      Dunestone {
+        edicts: vec![Edict {
+          id: ID,
+          amount: 2,
+          output: 3,
+        }],
+        etching: Some(Etching {
+          dune: Some(Dune(4)),
+          divisibility: Some(5),
+          ..Default::default()
+        }),
+        ..Default::default()
+      }
+    );
+  }
+
+  #[test]
+  fn divisibility_above_max_is_ignored() {
+    assert_eq!(
+      decipher(&[2, 1, 4, 4, 1, (MAX_DIVISIBILITY + 1).into(), 0, ID, 2, 3]),
+      Dunestone {
+        edicts: vec![Edict {
+          id: ID,
+          amount: 2,
+          output: 3,
+        }],
+        etching: Some(Etching {
+          dune: Some(Dune(4)),
+          ..Default::default()
+        }),
+        ..Default::default()
+      }
+    );
+  }
+
+  #[test]
+  fn symbol_above_max_is_ignored() {
+    assert_eq!(
+      decipher(&[
+        2,
+        1,
+        4,
+        4,
+        5,
+        u128::from(u32::from(char::MAX) + 1),
+        0,
+        ID,
+        2,
+        3
+      ]),
+      Dunestone {
+        edicts: vec![Edict {
+          id: ID,
+          amount: 2,
+          output: 3,
+        }],
+        etching: Some(Etching {
+          dune: Some(Dune(4)),
+          ..Default::default()
+        }),
+        ..Default::default()
+      }
+    );
+  }
+
+  #[test]
+  fn decipher_etching_with_symbol() {
+    assert_eq!(
+      decipher(&[2, 1, 4, 4, 5, 'a'.into(), 0, ID, 2, 3]),
+      Dunestone {
+        edicts: vec![Edict {
+          id: ID,
+          amount: 2,
+          output: 3,
+        }],
+        etching: Some(Etching {
+          dune: Some(Dune(4)),
+          symbol: Some('a'),
+          ..Default::default()
+        }),
+        ..Default::default()
+      }
+    );
+  }
+
+  #[test]
+  fn decipher_etching_with_divisibility_and_symbol() {
+    assert_eq!(
+      decipher(&[2, 1, 4, 4, 1, 1, 5, 'a'.into(), 0, ID, 2, 3]),
+      Dunestone {
+        edicts: vec![Edict {
+          id: ID,
+          amount: 2,
+          output: 3,
+        }],
+        etching: Some(Etching {
+          dune: Some(Dune(4)),
+          divisibility: Some(1),
+          symbol: Some('a'),
+          ..Default::default()
+        }),
+        ..Default::default()
+      }
+    );
+  }
+
+  #[test]
+  fn tag_values_are_not_parsed_as_tags() {
+    assert_eq!(
+      decipher(&[2, 1, 4, 4, 1, 0, 0, ID, 2, 3]),
+      Dunestone {
+        edicts: vec![Edict {
+          id: ID,
+          amount: 2,
+          output: 3,
+        }],
+        etching: Some(Etching {
+          dune: Some(Dune(4)),
+          divisibility: Some(0),
+          ..Default::default()
+        }),
+        ..Default::default()
+      }
+    );
+  }
+
+  #[test]
+  fn dunestone_may_contain_multiple_edicts() {
+    assert_eq!(
+      decipher(&[0, ID, 2, 3, 3, 5, 0]),
+      Dunestone {
+        edicts: vec![
+          Edict {
+            id: ID,
+            amount: 2,
+            output: 3,
+          },
+          Edict {
+            id: ID + 3,
+            amount: 5,
+            output: 0,
+          },
+        ],
+        ..Default::default()
+      }
+    );
+  }
+
+  #[test]
+  fn id_deltas_saturate_to_max() {
+    assert_eq!(
+      decipher(&[0, ID, 2, 3, u128::max_value(), 5, 0]),
+      Dunestone {
+        edicts: vec![Edict {
+          id: ID,
+          amount: 2,
+          output: 3,
+        }],
+        flaw: Some(Flaw::EdictDuneId),
+        ..Default::default()
+      }
+    );
+  }
+
+  #[test]
+  fn payload_pushes_are_concatenated() {
+    let mut builder = script::Builder::new()
+      .push_opcode(opcodes::all::OP_RETURN)
+      .push_slice(b"D");
+
+    for integer in [2, 1, 4, 4, 1, 5, 0, ID, 2, 3] {
+      builder = builder.push_slice(&varint::encode(integer));
+    }
+
+    assert_eq!(
+      Dunestone::decipher(&transaction(vec![builder.into_script()])),
+      Ok(Some(Dunestone {
+        edicts: vec![Edict {
+          id: ID,
+          amount: 2,
+          output: 3,
+        }],
+        etching: Some(Etching {
+          dune: Some(Dune(4)),
+          divisibility: Some(5),
+          ..Default::default()
+        }),
+        ..Default::default()
+      }))
+    );
+  }
+
+  #[test]
+  fn dunestone_may_be_in_second_output() {
+    assert_eq!(
+      Dunestone::decipher(&transaction(vec![
+        Script::new(),
+        dunestone_script(&payload(&[0, ID, 2, 3])),
+      ])),
+      Ok(Some(Dunestone {
+        edicts: vec![Edict {
+          id: ID,
+          amount: 2,
+          output: 3,
+        }],
+        ..Default::default()
+      }))
+    );
+  }
+
+  #[test]
+  fn dunestone_may_be_after_non_matching_op_return() {
+    assert_eq!(
+      Dunestone::decipher(&transaction(vec![
+        script::Builder::new()
+          .push_opcode(opcodes::all::OP_RETURN)
+          .push_slice(b"FOO")
+          .into_script(),
+        dunestone_script(&payload(&[0, ID, 2, 3])),
+      ])),
+      Ok(Some(Dunestone {
+        edicts: vec![Edict {
+          id: ID,
+          amount: 2,
+          output: 3,
+        }],
+        ..Default::default()
+      }))
+    );
+  }
+
+  #[test]
+  fn dunestone_size() {
+    #[track_caller]
+    fn case(edicts: Vec<Edict>, etching: Option<Etching>, size: usize) {
+      assert_eq!(
+        Dunestone {
+          edicts,
+          etching,
+          ..Default::default()
+        }
+            .encipher()
+            .len()
+            - 1
+            - b"D".len(),
+        size
+      );
+    }
+
+    case(Vec::new(), None, 1);
+
+    case(
+      Vec::new(),
+      Some(Etching {
+        dune: Some(Dune(0)),
+        ..Default::default()
+      }),
+      6,
+    );
+
+    case(
+      Vec::new(),
+      Some(Etching {
+        divisibility: Some(MAX_DIVISIBILITY),
+        dune: Some(Dune(0)),
+        ..Default::default()
+      }),
+      8,
+    );
+
+    case(
+      Vec::new(),
+      Some(Etching {
+        divisibility: Some(MAX_DIVISIBILITY),
+        dune: Some(Dune(0)),
+        symbol: Some('$'),
+        ..Default::default()
+      }),
+      10,
+    );
+
+    case(
+      Vec::new(),
+      Some(Etching {
+        dune: Some(Dune(u128::max_value())),
+        ..Default::default()
+      }),
+      24,
+    );
+
+    case(
+      vec![Edict {
+        amount: 0,
+        id: DuneId {
+          height: 0,
+          index: 0,
+        }
+            .into(),
+        output: 0,
+      }],
+      Some(Etching {
+        divisibility: Some(MAX_DIVISIBILITY),
+        dune: Some(Dune(u128::max_value())),
+        ..Default::default()
+      }),
+      30,
+    );
+
+    case(
+      vec![Edict {
+        amount: u128::max_value(),
+        id: DuneId {
+          height: 0,
+          index: 0,
+        }
+            .into(),
+        output: 0,
+      }],
+      Some(Etching {
+        divisibility: Some(MAX_DIVISIBILITY),
+        dune: Some(Dune(u128::max_value())),
+        ..Default::default()
+      }),
+      48,
+    );
+
+    case(
+      vec![Edict {
+        amount: 0,
+        id: DuneId {
+          height: 1_000_000,
+          index: u16::max_value().into(),
+        }
+            .into(),
+        output: 0,
+      }],
+      None,
+      11,
+    );
+
+    case(
+      vec![Edict {
+        amount: 0,
+        id: CLAIM_BIT,
+        output: 0,
+      }],
+      None,
+      12,
+    );
+
+    case(
+      vec![Edict {
+        amount: u128::max_value(),
+        id: DuneId {
+          height: 1_000_000,
+          index: u16::max_value().into(),
+        }
+            .into(),
+        output: 0,
+      }],
+      None,
+      29,
+    );
+
+    case(
+      vec![
+        Edict {
+          amount: u128::max_value(),
+          id: DuneId {
+            height: 1_000_000,
+            index: u16::max_value().into(),
+          }
+              .into(),
+          output: 0,
+        },
+        Edict {
+          amount: u128::max_value(),
+          id: DuneId {
+            height: 1_000_000,
+            index: u16::max_value().into(),
+          }
+              .into(),
+          output: 0,
+        },
+      ],
+      None,
+      50,
+    );
+
+    case(
+      vec![
+        Edict {
+          amount: u128::max_value(),
+          id: DuneId {
+            height: 1_000_000,
+            index: u16::max_value().into(),
+          }
+              .into(),
+          output: 0,
+        },
+        Edict {
+          amount: u128::max_value(),
+          id: DuneId {
+            height: 1_000_000,
+            index: u16::max_value().into(),
+          }
+              .into(),
+          output: 0,
+        },
+        Edict {
+          amount: u128::max_value(),
+          id: DuneId {
+            height: 1_000_000,
+            index: u16::max_value().into(),
+          }
+              .into(),
+          output: 0,
+        },
+      ],
+      None,
+      71,
+    );
+
+    case(
+      vec![
+        Edict {
+          amount: u64::max_value().into(),
+          id: DuneId {
+            height: 1_000_000,
+            index: u16::max_value().into(),
+          }
+              .into(),
+          output: 0,
+        };
+        4
+      ],
+      None,
+      56,
+    );
+
+    case(
+      vec![
+        Edict {
+          amount: u64::max_value().into(),
+          id: DuneId {
+            height: 1_000_000,
+            index: u16::max_value().into(),
+          }
+              .into(),
+          output: 0,
+        };
+        5
+      ],
+      None,
+      68,
+    );
+
+    case(
+      vec![
+        Edict {
+          amount: u64::max_value().into(),
+          id: DuneId {
+            height: 0,
+            index: u16::max_value().into(),
+          }
+              .into(),
+          output: 0,
+        };
+        5
+      ],
+      None,
+      65,
+    );
+
+    case(
+      vec![
+        Edict {
+          amount: 1_000_000_000_000_000_000,
+          id: DuneId {
+            height: 1_000_000,
+            index: u16::max_value().into(),
+          }
+              .into(),
+          output: 0,
+        };
+        5
+      ],
+      None,
+      63,
+    );
+  }
+
+  #[test]
+  fn etching_with_terms_and_turbo_round_trips() {
+    let dunestone = Dunestone {
+      etching: Some(Etching {
+        divisibility: Some(2),
+        dune: Some(Dune(4)),
+        premine: Some(100),
+        terms: Some(Terms {
+          cap: Some(10),
+          limit: Some(1000),
+          height: (Some(5), Some(50)),
+          offset: (Some(1), Some(20)),
+        }),
+        turbo: true,
+        ..Default::default()
+      }),
+      ..Default::default()
+    };
+
+    assert_eq!(
+      Dunestone::from_transaction_with_rules(
+        &Transaction {
+          input: Vec::new(),
+          output: vec![TxOut {
+            script_pubkey: dunestone.encipher(),
+            value: 0,
+          }],
+          lock_time: PackedLockTime::ZERO,
+          version: 0,
+        },
+        Rules::Strict
+      ),
+      Some(Dunestone {
+        edicts: vec![Edict {
+          id: 0,
+          amount: 100,
+          output: 1,
+        }],
+        ..dunestone
+      })
+    );
+  }
+
+  #[test]
+  fn etching_with_limit_greater_than_maximum_is_clamped() {
+    assert_eq!(
+      decipher(&[2, 3, 4, 4, 6, u128::from(u64::max_value()) + 1]),
+      Dunestone {
+        etching: Some(Etching {
+          dune: Some(Dune(4)),
+          terms: Some(Terms {
+            limit: Some(MAX_LIMIT),
+            ..Default::default()
+          }),
+          ..Default::default()
+        }),
+        ..Default::default()
+      }
+    );
+  }
+
+  fn flaw(integers: &[u128]) -> Option<Flaw> {
+    let payload = payload(integers);
+
+    Dunestone::from_transaction_with_rules(
+      &Transaction {
+        input: Vec::new(),
+        output: vec![
+          TxOut {
+            script_pubkey: script::Builder::new()
+              .push_opcode(opcodes::all::OP_RETURN)
+              .push_slice(b"D")
+              .push_slice(&payload)
+              .into_script(),
+            value: 0,
+          },
+          TxOut {
+            script_pubkey: Script::new(),
+            value: 0,
+          },
+        ],
+        lock_time: PackedLockTime::ZERO,
+        version: 0,
+      },
+      Rules::Strict,
+    )
+    .unwrap()
+    .flaw
+  }
+
+  #[test]
+  fn flaws() {
+    assert_eq!(flaw(&[0, 1 << 16, 2, 1]), None);
+    assert_eq!(flaw(&[0, 1 << 16, 2, 3]), Some(Flaw::EdictOutput));
+    assert_eq!(flaw(&[0, 1 << 16, 2]), Some(Flaw::TrailingIntegers));
+    assert_eq!(flaw(&[0, 1, 2, 1]), Some(Flaw::EdictDuneId));
+    assert_eq!(flaw(&[2, 1 << 100]), Some(Flaw::UnrecognizedFlag));
+    assert_eq!(flaw(&[254, 0]), Some(Flaw::UnrecognizedEvenTag));
+    assert_eq!(
+      flaw(&[22, 1, 20, u128::MAX, 6, 2]),
+      Some(Flaw::SupplyOverflow)
+    );
+  }
+
+  #[test]
+  fn first_flaw_in_body_is_reported() {
+    assert_eq!(flaw(&[0, 1 << 16, 2, 3, u128::MAX, 2, 1]), Some(Flaw::EdictOutput));
+    assert_eq!(flaw(&[0, 1, 2, 1, 1 << 16, 2, 3]), Some(Flaw::EdictDuneId));
+  }
+
+  #[test]
+  fn body_flaws_are_reported_before_field_flaws() {
+    assert_eq!(flaw(&[254, 0, 0, 1 << 16, 2, 3]), Some(Flaw::EdictOutput));
+  }
+
+  #[test]
+  fn cenotaph_includes_etched_dune() {
+    let dunestone = Dunestone {
+      etching: Some(Etching {
+        dune: Some(Dune(4)),
+        ..Default::default()
+      }),
+      flaw: Some(Flaw::UnrecognizedFlag),
+      ..Default::default()
+    };
+
+    assert_eq!(
+      dunestone.cenotaph(),
+      Some(Cenotaph {
+        etching: Some(Dune(4)),
+        flaw: Flaw::UnrecognizedFlag,
+      })
+    );
+
+    assert_eq!(Dunestone::default().cenotaph(), None);
+  }
+}
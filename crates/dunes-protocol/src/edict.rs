@@ -8,22 +8,22 @@ pub struct Edict {
 }
 
 impl Edict {
-  pub(crate) fn from_integers(
+  pub fn from_integers(
     tx: &Transaction,
     id: u128,
     amount: u128,
     output: u128,
-  ) -> Option<Self> {
-    let dune_id = DuneId::try_from(id).ok()?;
+  ) -> Result<Self, Flaw> {
+    let dune_id = DuneId::try_from(id).map_err(|_| Flaw::EdictDuneId)?;
 
     if dune_id.height == 0 && dune_id.index > 0 {
-      return None;
+      return Err(Flaw::EdictDuneId);
     }
 
-    if output > u128::try_from(tx.output.len()).ok()? {
-      return None;
+    if output > u128::try_from(tx.output.len()).unwrap() {
+      return Err(Flaw::EdictOutput);
     }
 
-    Some(Self { id, amount, output })
+    Ok(Self { id, amount, output })
   }
 }
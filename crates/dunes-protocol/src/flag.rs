@@ -1,4 +1,5 @@
-pub(super) enum Flag {
+/// Bits of the value of the `Flags` field
+pub enum Flag {
   Etching = 0,
   Terms = 1,
   Turbo = 2,
@@ -7,18 +8,18 @@ pub(super) enum Flag {
 }
 
 impl Flag {
-  pub(super) fn mask(self) -> u128 {
+  pub fn mask(self) -> u128 {
     1 << self as u128
   }
 
-  pub(super) fn take(self, flags: &mut u128) -> bool {
+  pub fn take(self, flags: &mut u128) -> bool {
     let mask = self.mask();
     let set = *flags & mask != 0;
     *flags &= !mask;
     set
   }
 
-  pub(super) fn set(self, flags: &mut u128) {
+  pub fn set(self, flags: &mut u128) {
     *flags |= self.mask()
   }
 }
@@ -29,25 +30,25 @@ mod tests {
 
   #[test]
   fn mask() {
-    assert_eq!(Flag::Etch.mask(), 0b1);
+    assert_eq!(Flag::Etching.mask(), 0b1);
     assert_eq!(Flag::Cenotaph.mask(), 1 << 127);
   }
 
   #[test]
   fn take() {
     let mut flags = 1;
-    assert!(Flag::Etch.take(&mut flags));
+    assert!(Flag::Etching.take(&mut flags));
     assert_eq!(flags, 0);
 
     let mut flags = 0;
-    assert!(!Flag::Etch.take(&mut flags));
+    assert!(!Flag::Etching.take(&mut flags));
     assert_eq!(flags, 0);
   }
 
   #[test]
   fn set() {
     let mut flags = 0;
-    Flag::Etch.set(&mut flags);
+    Flag::Etching.set(&mut flags);
     assert_eq!(flags, 1);
   }
 }
@@ -0,0 +1,27 @@
+use super::*;
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Copy, Clone)]
+#[serde(rename_all = "kebab-case")]
+pub enum Flaw {
+  EdictDuneId,
+  EdictOutput,
+  SupplyOverflow,
+  TrailingIntegers,
+  UnrecognizedEvenTag,
+  UnrecognizedFlag,
+  Varint,
+}
+
+impl Display for Flaw {
+  fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+    match self {
+      Self::EdictDuneId => write!(f, "invalid dune ID in edict"),
+      Self::EdictOutput => write!(f, "edict output greater than transaction output count"),
+      Self::SupplyOverflow => write!(f, "supply overflows u128"),
+      Self::TrailingIntegers => write!(f, "trailing integers in body"),
+      Self::UnrecognizedEvenTag => write!(f, "unrecognized even tag"),
+      Self::UnrecognizedFlag => write!(f, "unrecognized flag"),
+      Self::Varint => write!(f, "invalid varint"),
+    }
+  }
+}
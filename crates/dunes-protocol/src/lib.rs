@@ -0,0 +1,63 @@
+//! Types for encoding and decoding dunestones, the OP_RETURN messages of the
+//! dunes fungible token protocol on Dogecoin.
+
+#![deny(
+  clippy::cast_lossless,
+  clippy::cast_possible_truncation,
+  clippy::cast_possible_wrap,
+  clippy::cast_sign_loss
+)]
+
+use {
+  self::deserialize_from_str::DeserializeFromStr,
+  bitcoin::{
+    blockdata::opcodes,
+    blockdata::script::{self, Instruction},
+    Script, Transaction,
+  },
+  serde::{Deserialize, Deserializer, Serialize, Serializer},
+  std::{
+    collections::HashMap,
+    fmt::{self, Display, Formatter},
+    str::FromStr,
+  },
+};
+
+pub use {
+  cenotaph::Cenotaph,
+  dune::{Dune, Error as DuneError},
+  dune_id::{DuneId, Error as DuneIdError},
+  dunestone::Dunestone,
+  edict::Edict,
+  etching::Etching,
+  flag::Flag,
+  flaw::Flaw,
+  pile::Pile,
+  rules::Rules,
+  spaced_dune::{Error as SpacedDuneError, SpacedDune},
+  tag::Tag,
+  terms::Terms,
+};
+
+/// Set on an edict's dune ID to claim the dune's mint limit instead of
+/// transferring dunes from the inputs
+pub const CLAIM_BIT: u128 = 1 << 48;
+pub const MAX_DIVISIBILITY: u8 = 38;
+pub const MAX_LIMIT: u128 = u64::MAX as u128;
+const RESERVED: u128 = 6402364363415443603228541259936211926;
+
+mod cenotaph;
+mod deserialize_from_str;
+mod dune;
+mod dune_id;
+mod dunestone;
+mod edict;
+mod etching;
+mod flag;
+mod flaw;
+mod pile;
+mod rules;
+mod spaced_dune;
+mod tag;
+mod terms;
+pub mod varint;
@@ -1,10 +1,10 @@
 use super::*;
 
 #[derive(Debug, Deserialize, Serialize, PartialEq)]
-pub(crate) struct Pile {
-  pub(crate) amount: u128,
-  pub(crate) divisibility: u8,
-  pub(crate) symbol: Option<char>,
+pub struct Pile {
+  pub amount: u128,
+  pub divisibility: u8,
+  pub symbol: Option<char>,
 }
 
 impl Display for Pile {
@@ -45,7 +45,7 @@ mod tests {
         symbol: None,
       }
       .to_string(),
-      "0"
+      "0\u{00A0}¤"
     );
     assert_eq!(
       Pile {
@@ -54,7 +54,7 @@ mod tests {
         symbol: None,
       }
       .to_string(),
-      "25"
+      "25\u{00A0}¤"
     );
     assert_eq!(
       Pile {
@@ -63,7 +63,7 @@ mod tests {
         symbol: None,
       }
       .to_string(),
-      "0"
+      "0\u{00A0}¤"
     );
     assert_eq!(
       Pile {
@@ -72,7 +72,7 @@ mod tests {
         symbol: None,
       }
       .to_string(),
-      "0.1"
+      "0.1\u{00A0}¤"
     );
     assert_eq!(
       Pile {
@@ -81,7 +81,7 @@ mod tests {
         symbol: None,
       }
       .to_string(),
-      "0.01"
+      "0.01\u{00A0}¤"
     );
     assert_eq!(
       Pile {
@@ -90,7 +90,7 @@ mod tests {
         symbol: None,
       }
       .to_string(),
-      "0.1"
+      "0.1\u{00A0}¤"
     );
     assert_eq!(
       Pile {
@@ -99,7 +99,7 @@ mod tests {
         symbol: None,
       }
       .to_string(),
-      "1.1"
+      "1.1\u{00A0}¤"
     );
     assert_eq!(
       Pile {
@@ -108,7 +108,7 @@ mod tests {
         symbol: None,
       }
       .to_string(),
-      "1"
+      "1\u{00A0}¤"
     );
     assert_eq!(
       Pile {
@@ -117,7 +117,7 @@ mod tests {
         symbol: None,
       }
       .to_string(),
-      "1.01"
+      "1.01\u{00A0}¤"
     );
     assert_eq!(
       Pile {
@@ -126,7 +126,7 @@ mod tests {
         symbol: None,
       }
       .to_string(),
-      "340282366920938463463.374607431768211455"
+      "340282366920938463463.374607431768211455\u{00A0}¤"
     );
     assert_eq!(
       Pile {
@@ -135,7 +135,7 @@ mod tests {
         symbol: None,
       }
       .to_string(),
-      "3.40282366920938463463374607431768211455"
+      "3.40282366920938463463374607431768211455\u{00A0}¤"
     );
     assert_eq!(
       Pile {
@@ -0,0 +1,12 @@
+/// Rules for deciphering dunestones. Dunestones were originally deciphered
+/// under the legacy rules, which saturate varints that overflow a u128 and
+/// ignore integers left over at the end of the body. Under the strict rules
+/// both are flaws, making the dunestone a cenotaph. Since this changes which
+/// transactions burn their dunes, indexers must switch to the strict rules at
+/// the same height.
+#[derive(Debug, PartialEq, Copy, Clone, Default)]
+pub enum Rules {
+  Legacy,
+  #[default]
+  Strict,
+}
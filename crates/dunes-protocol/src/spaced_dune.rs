@@ -1,9 +1,32 @@
 use super::*;
 
+#[derive(Debug, PartialEq)]
+pub enum Error {
+  Character(char),
+  DoubleSpacer,
+  Dune(dune::Error),
+  LeadingSpacer,
+  TrailingSpacer,
+}
+
+impl Display for Error {
+  fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+    match self {
+      Self::Character(_) => write!(f, "invalid character"),
+      Self::DoubleSpacer => write!(f, "double spacer"),
+      Self::Dune(err) => write!(f, "{err}"),
+      Self::LeadingSpacer => write!(f, "leading spacer"),
+      Self::TrailingSpacer => write!(f, "trailing spacer"),
+    }
+  }
+}
+
+impl std::error::Error for Error {}
+
 #[derive(Copy, Clone, Debug, PartialEq, Ord, PartialOrd, Eq, Hash)]
 pub struct SpacedDune {
-  pub(crate) dune: Dune,
-  pub(crate) spacers: u32,
+  pub dune: Dune,
+  pub spacers: u32,
 }
 
 impl SpacedDune {
@@ -23,22 +46,22 @@ impl FromStr for SpacedDune {
       match c {
         'A'..='Z' => dune.push(c),
         '.' | '•' => {
-          let flag = 1 << dune.len().checked_sub(1).context("leading spacer")?;
+          let flag = 1 << dune.len().checked_sub(1).ok_or(Error::LeadingSpacer)?;
           if spacers & flag != 0 {
-            bail!("double spacer");
+            return Err(Error::DoubleSpacer);
           }
           spacers |= flag;
         }
-        _ => bail!("invalid character"),
+        _ => return Err(Error::Character(c)),
       }
     }
 
     if 32 - spacers.leading_zeros() >= dune.len().try_into().unwrap() {
-      bail!("trailing spacer")
+      return Err(Error::TrailingSpacer);
     }
 
     Ok(SpacedDune {
-      dune: dune.parse()?,
+      dune: dune.parse().map_err(Error::Dune)?,
       spacers,
     })
   }
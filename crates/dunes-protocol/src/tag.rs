@@ -1,7 +1,9 @@
 use super::*;
 
+/// Keys of the fields of a dunestone message. Even tags that are not
+/// recognized make a dunestone a cenotaph, odd tags are ignored.
 #[derive(Copy, Clone, Debug)]
-pub(super) enum Tag {
+pub enum Tag {
   Body = 0,
   Flags = 2,
   Dune = 4,
@@ -26,11 +28,11 @@ pub(super) enum Tag {
 }
 
 impl Tag {
-  pub(super) fn take(self, fields: &mut HashMap<u128, u128>) -> Option<u128> {
+  pub fn take(self, fields: &mut HashMap<u128, u128>) -> Option<u128> {
     fields.remove(&self.into())
   }
 
-  pub(super) fn encode(self, value: u128, payload: &mut Vec<u8>) {
+  pub fn encode(self, value: u128, payload: &mut Vec<u8>) {
     varint::encode_to_vec(self.into(), payload);
     varint::encode_to_vec(value, payload);
   }
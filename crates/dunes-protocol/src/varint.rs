@@ -51,6 +51,26 @@ pub fn decode(buffer: &[u8]) -> (u128, usize) {
   }
 }
 
+/// Like `decode`, but returns `None` if the varint overflows a u128 or is
+/// truncated, instead of saturating.
+pub fn try_decode(buffer: &[u8]) -> Option<(u128, usize)> {
+  let mut n: u128 = 0;
+
+  for (i, b) in buffer.iter().enumerate() {
+    let b = u128::from(*b);
+
+    n = n.checked_mul(128)?;
+
+    if b < 128 {
+      return Some((n.checked_add(b)?, i + 1));
+    }
+
+    n = n.checked_add(b - 127)?;
+  }
+
+  None
+}
+
 #[cfg(test)]
 mod tests {
   use super::*;
@@ -59,7 +79,7 @@ mod tests {
   fn u128_max_round_trips_successfully() {
     let n = u128::max_value();
     let encoded = encode(n);
-    let (decoded, length) = decode(&encoded).unwrap();
+    let (decoded, length) = decode(&encoded);
     assert_eq!(decoded, n);
     assert_eq!(length, encoded.len());
   }
@@ -69,7 +89,7 @@ mod tests {
     for i in 0..128 {
       let n = 1 << i;
       let encoded = encode(n);
-      let (decoded, length) = decode(&encoded).unwrap();
+      let (decoded, length) = decode(&encoded);
       assert_eq!(decoded, n);
       assert_eq!(length, encoded.len());
     }
@@ -82,20 +102,20 @@ mod tests {
     for i in 0..129 {
       n = n << 1 | (i % 2);
       let encoded = encode(n);
-      let (decoded, length) = decode(&encoded).unwrap();
+      let (decoded, length) = decode(&encoded);
       assert_eq!(decoded, n);
       assert_eq!(length, encoded.len());
     }
   }
 
   #[test]
-  fn decoding_integer_over_max_is_an_error() {
+  fn decoding_integer_over_max_saturates() {
     assert_eq!(
       decode(&[
         130, 254, 254, 254, 254, 254, 254, 254, 254, 254, 254, 254, 254, 254, 254, 254, 254, 255,
         0,
       ]),
-      Err(Error::Varint)
+      (u128::MAX, 19)
     );
   }
 
@@ -118,14 +138,27 @@ mod tests {
     for (n, encoding) in TEST_VECTORS {
       let actual = encode(*n);
       assert_eq!(actual, *encoding);
-      let (actual, length) = decode(encoding).unwrap();
+      let (actual, length) = decode(encoding);
       assert_eq!(actual, *n);
       assert_eq!(length, encoding.len());
     }
   }
 
   #[test]
-  fn truncated_varint_returns_error() {
-    assert_eq!(decode(&[128]), Err(Error::Varint));
+  fn truncated_varint_is_decoded_to_end_of_buffer() {
+    assert_eq!(decode(&[128]), (1, 1));
+  }
+
+  #[test]
+  fn try_decode_rejects_overflow_and_truncation() {
+    assert_eq!(try_decode(&encode(u128::MAX)), Some((u128::MAX, 19)));
+    assert_eq!(try_decode(&[128]), None);
+    assert_eq!(
+      try_decode(&[
+        130, 254, 254, 254, 254, 254, 254, 254, 254, 254, 254, 254, 254, 254, 254, 254, 254, 255,
+        0,
+      ]),
+      None
+    );
   }
 }
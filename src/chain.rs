@@ -32,6 +32,16 @@ impl Chain {
     }
   }
 
+  /// Network magic that precedes each block in Dogecoin Core's block files
+  pub(crate) fn block_file_magic(self) -> [u8; 4] {
+    match self {
+      Self::Mainnet => [0xc0, 0xc0, 0xc0, 0xc0],
+      Self::Regtest => [0xfa, 0xbf, 0xb5, 0xda],
+      Self::Signet => [0x0a, 0x03, 0xcf, 0x40],
+      Self::Testnet => [0xfc, 0xc1, 0xb7, 0xdc],
+    }
+  }
+
   pub(crate) fn inscription_content_size_limit(self) -> Option<usize> {
     match self {
       Self::Mainnet | Self::Regtest => None,
@@ -39,21 +49,40 @@ impl Chain {
     }
   }
 
-  pub(crate) fn first_inscription_height(self) -> u32 {
-    match self {
-      Self::Mainnet => 4600000,
-      Self::Regtest => 0,
-      Self::Signet => 0,
-      Self::Testnet => 4250000,
+  pub(crate) fn protocol_parameters(self) -> ProtocolParameters {
+    let (first_inscription_height, first_dune_height) = match self {
+      Self::Mainnet => (4600000, 5084000),
+      Self::Regtest => (0, 0),
+      Self::Signet => (0, 0),
+      Self::Testnet => (4250000, 4250000),
+    };
+
+    ProtocolParameters {
+      first_inscription_height,
+      first_dune_height,
+      dune_unlock_height: first_dune_height,
+      initial_dune_length: Dune::INITIAL_LENGTH,
+      dune_unlock_interval: Dune::UNLOCK_INTERVAL,
+      first_reserved_dune: Dune::FIRST_RESERVED,
+      max_op_return_size: ProtocolParameters::MAX_STANDARD_OP_RETURN_SIZE,
+      // Strict dunestone rules have not been scheduled on mainnet or testnet,
+      // where dunestones already in the chain were deciphered under the
+      // legacy rules
+      strict_dunestone_height: match self {
+        Self::Mainnet | Self::Testnet => u32::MAX,
+        Self::Regtest | Self::Signet => 0,
+      },
     }
   }
 
-  pub(crate) fn first_dune_height(self) -> u32 {
-    match self {
-      Self::Mainnet => 5084000,
-      Self::Regtest => 0,
-      Self::Signet => 0,
-      Self::Testnet => 4250000,
+  /// The parameters indexes were built with before ord recorded them in the
+  /// index, when dune names unlocked on mainnet's schedule on every chain and
+  /// dunestones were always deciphered under the legacy rules
+  pub(crate) fn legacy_protocol_parameters(self) -> ProtocolParameters {
+    ProtocolParameters {
+      dune_unlock_height: Self::Mainnet.protocol_parameters().first_dune_height,
+      strict_dunestone_height: u32::MAX,
+      ..self.protocol_parameters()
     }
   }
 
@@ -2,7 +2,10 @@ use super::*;
 
 #[derive(Deserialize, Default, PartialEq, Debug)]
 pub(crate) struct Config {
+  #[serde(default)]
   pub(crate) hidden: HashSet<InscriptionId>,
+  #[serde(default)]
+  pub(crate) protocol: ProtocolParametersConfig,
 }
 
 impl Config {
@@ -27,6 +30,7 @@ mod tests {
 
     let config = Config {
       hidden: iter::once(a).collect(),
+      ..Default::default()
     };
 
     assert!(config.is_hidden(a));
@@ -1,30 +1,9 @@
-use {
-  self::{flag::Flag, tag::Tag},
-  super::*,
-};
-
-pub use {edict::Edict, dune::Dune, dune_id::DuneId, dunestone::Dunestone, terms::Terms};
-
-pub(crate) use {etching::Etching, pile::Pile, spaced_dune::SpacedDune};
+use super::*;
 
-pub(crate) const CLAIM_BIT: u128 = 1 << 48;
-pub const MAX_DIVISIBILITY: u8 = 38;
-pub(crate) const MAX_LIMIT: u128 = u64::MAX as u128;
-const RESERVED: u128 = 6402364363415443603228541259936211926;
-
-mod edict;
-mod etching;
-mod flag;
-mod terms;
-mod pile;
-mod dune;
-mod dune_id;
-mod dunestone;
-mod spaced_dune;
-mod tag;
-pub mod varint;
-
-type Result<T, E = Error> = std::result::Result<T, E>;
+pub use dunes_protocol::{
+  varint, Cenotaph, Dune, DuneId, Dunestone, Edict, Etching, Flaw, Pile, Rules, SpacedDune,
+  Terms, CLAIM_BIT, MAX_DIVISIBILITY, MAX_LIMIT,
+};
 
 #[derive(Debug, PartialEq)]
 pub enum MintError {
@@ -1,10 +1,14 @@
 use {
   self::{
+    block_files::BlockFiles,
     entry::{
-      BlockHashValue, Entry, InscriptionEntry, InscriptionEntryValue, InscriptionIdValue,
-      OutPointValue, DuneEntryValue, DuneIdValue, SatPointValue, SatRange,
+      AddressOutPointValue, BlockHashValue, CenotaphValue, DuneBalanceHistory, DuneBalanceHistoryValue,
+      DuneHolderRankValue, DuneHolderValue, DuneOutPointValue, Entry, InscriptionEntry,
+      InscriptionEntryValue, InscriptionIdValue, OutPointValue, DuneEntryValue, DuneEventIdValue,
+      DuneEventValue, DuneIdValue, SatPointValue, SatRange,
     },
     dunes::{Dune, DuneId},
+    reorg::{Reorg, ReorgError},
     updater::Updater,
   },
   super::*,
@@ -16,12 +20,12 @@ use {
   indicatif::{ProgressBar, ProgressStyle},
   log::log_enabled,
   redb::{
-    Database, DatabaseError, MultimapTable, MultimapTableDefinition, MultimapTableHandle,
-    ReadOnlyTable, ReadableMultimapTable, ReadableTable, RepairSession, StorageError, Table,
-    TableDefinition, TableHandle, TableStats, WriteTransaction,
+    Database, DatabaseError, ReadOnlyTable, ReadTransaction, ReadableTable, RepairSession,
+    StorageError, Table, TableDefinition, TableHandle, TableStats, WriteTransaction,
   },
   std::collections::HashMap,
   std::io::Cursor,
+  std::ops::Bound,
   std::sync::atomic::{self, AtomicBool},
   url::Url
 };
@@ -29,15 +33,38 @@ use crate::index::entry::TxidValue;
 use crate::sat::Sat;
 use crate::sat_point::SatPoint;
 
-pub(crate) use {self::entry::DuneEntry};
-use crate::templates::BlockHashAndConfirmations;
+pub(crate) use self::{
+  commit_policy::{CommitPolicy, DEFAULT_COMMIT_INTERVAL},
+  entry::{DuneEntry, DuneEvent, DuneEventId, DuneEventKind},
+  event::Event,
+  mempool::Mempool,
+  migration::{Migration, MigrationOutput},
+  snapshot::Snapshot,
+};
+use crate::templates::{BlockHashAndConfirmations, DuneDistribution, DuneHolder};
 
 mod entry;
+mod block_files;
+mod commit_policy;
+mod event;
 mod fetcher;
+mod mempool;
+mod migration;
+mod reorg;
 mod rtx;
+mod snapshot;
+#[cfg(test)]
+pub(crate) mod testing;
 mod updater;
 
-const SCHEMA_VERSION: u64 = 5;
+const SCHEMA_VERSION: u64 = 6;
+
+/// Number of most recent write transactions over which indexing speed is
+/// measured
+const INDEXING_RATE_WINDOW: usize = 10;
+
+/// Events buffered for each subscriber before the oldest are dropped
+const EVENT_CHANNEL_CAPACITY: usize = 1024;
 
 macro_rules! define_table {
   ($name:ident, $key:ty, $value:ty) => {
@@ -45,13 +72,6 @@ macro_rules! define_table {
   };
 }
 
-macro_rules! define_multimap_table {
-  ($name:ident, $key:ty, $value:ty) => {
-    const $name: MultimapTableDefinition<$key, $value> =
-      MultimapTableDefinition::new(stringify!($name));
-  };
-}
-
 define_table! { HEIGHT_TO_BLOCK_HASH, u32, &BlockHashValue }
 define_table! { INSCRIPTION_ID_TO_INSCRIPTION_ENTRY, &InscriptionIdValue, InscriptionEntryValue }
 define_table! { INSCRIPTION_ID_TO_DUNE, &InscriptionIdValue, u128 }
@@ -61,31 +81,44 @@ define_table! { OUTPOINT_TO_DUNE_BALANCES, &OutPointValue, &[u8] }
 define_table! { INSCRIPTION_ID_TO_TXIDS, &InscriptionIdValue, &[u8] }
 define_table! { INSCRIPTION_TXID_TO_TX, &[u8], &[u8] }
 define_table! { PARTIAL_TXID_TO_INSCRIPTION_TXIDS, &[u8], &[u8] }
+define_table! { OUTPOINT_TO_DUNE_HOLDER, &OutPointValue, &[u8; 34] }
 define_table! { OUTPOINT_TO_SAT_RANGES, &OutPointValue, &[u8] }
-define_table! { OUTPOINT_TO_VALUE, &OutPointValue, u64}
+define_table! { OUTPOINT_TO_VALUE, &OutPointValue, (u64, u32) }
+define_table! { PROTOCOL_PARAMETER_TO_VALUE, &str, u128 }
+define_table! { DUNE_OUTPOINT_TO_BALANCE_HISTORY, &DuneOutPointValue, DuneBalanceHistoryValue }
+define_table! { DUNE_HOLDER_RANK, &DuneHolderRankValue, () }
+define_table! { DUNE_HOLDER_TO_BALANCE, &DuneHolderValue, u128 }
+define_table! { DUNE_EVENT_ID_TO_DUNE_EVENT, DuneEventIdValue, DuneEventValue }
 define_table! { DUNE_ID_TO_DUNE_ENTRY, DuneIdValue, DuneEntryValue }
+define_table! { DUNE_ID_TO_HOLDER_COUNT, DuneIdValue, u64 }
 define_table! { DUNE_TO_DUNE_ID, u128, DuneIdValue }
 define_table! { SATPOINT_TO_INSCRIPTION_ID, &SatPointValue, &InscriptionIdValue }
 define_table! { SAT_TO_INSCRIPTION_ID, u64, &InscriptionIdValue }
 define_table! { SAT_TO_SATPOINT, u64, &SatPointValue }
 define_table! { STATISTIC_TO_COUNT, u64, u64 }
+define_table! { TRANSACTION_ID_TO_CENOTAPH, &TxidValue, CenotaphValue }
 define_table! { TRANSACTION_ID_TO_DUNE, &TxidValue, u128 }
 define_table! { TRANSACTION_ID_TO_TRANSACTION, &TxidValue, &[u8] }
-define_multimap_table! { ADDRESS_TO_OUTPOINT, &[u8], &OutPointValue}
+define_table! { ADDRESS_TO_OUTPOINT, &AddressOutPointValue, u64 }
 define_table! { WRITE_TRANSACTION_STARTING_BLOCK_COUNT_TO_TIMESTAMP, u32, u128 }
 
 pub(crate) struct Index {
   auth: Auth,
+  block_files: Option<Arc<Mutex<BlockFiles>>>,
+  chain: Chain,
   client: Client,
+  commit_policy: CommitPolicy,
   database: Database,
+  events: tokio::sync::broadcast::Sender<Event>,
   path: PathBuf,
-  first_inscription_height: u32,
-  first_dune_height: u32,
   genesis_block_coinbase_transaction: Transaction,
   genesis_block_coinbase_txid: Txid,
   height_limit: Option<u32>,
+  index_dune_events: bool,
+  index_dune_history: bool,
   index_dunes: bool,
   index_sats: bool,
+  protocol: ProtocolParameters,
   reorged: AtomicBool,
   rpc_url: String,
 }
@@ -108,6 +141,8 @@ pub(crate) enum Statistic {
   Dunes,
   SatRanges,
   Schema,
+  IndexDuneEvents,
+  IndexDuneHistory,
 }
 
 impl Statistic {
@@ -125,7 +160,9 @@ impl From<Statistic> for u64 {
 #[derive(Serialize)]
 pub(crate) struct Info {
   pub(crate) blocks_indexed: u32,
+  pub(crate) blocks_per_second: Option<f64>,
   pub(crate) branch_pages: u64,
+  pub(crate) eta_seconds: Option<u64>,
   pub(crate) fragmented_bytes: u64,
   pub(crate) index_file_size: u64,
   pub(crate) index_path: PathBuf,
@@ -197,22 +234,18 @@ impl Index {
 
     let client = Client::new(&rpc_url, auth.clone()).context("failed to connect to RPC URL")?;
 
-    let data_dir = options.data_dir()?;
-
-    if let Err(err) = fs::create_dir_all(&data_dir) {
-      bail!("failed to create data dir `{}`: {err}", data_dir.display());
-    }
+    let path = Self::database_path(options)?;
 
-    let path = if let Some(path) = &options.index {
-      path.clone()
-    } else {
-      data_dir.join("index.redb")
-    };
+    let protocol = options.protocol_parameters()?;
 
+    let index_dune_events;
+    let index_dune_history;
     let index_dunes;
     let index_sats;
 
-    let database = match unsafe { Database::builder().open(&path) } {
+    let db_cache_size = options.db_cache_size()?;
+
+    let database = match unsafe { Database::builder().set_cache_size(db_cache_size).open(&path) } {
       Ok(database) => {
         {
           let tx = database.begin_read()?;
@@ -223,6 +256,11 @@ impl Index {
               .unwrap_or(0);
 
           match schema_version.cmp(&SCHEMA_VERSION) {
+            cmp::Ordering::Less if Migration::path(schema_version).is_some() =>
+              bail!(
+              "index at `{}` uses index schema {schema_version}, ord schema {SCHEMA_VERSION}, run `ord index migrate` to upgrade it",
+              path.display()
+            ),
             cmp::Ordering::Less =>
               bail!(
               "index at `{}` appears to have been built with an older, incompatible version of ord, consider deleting and rebuilding the index: index schema {schema_version}, ord schema {SCHEMA_VERSION}",
@@ -249,20 +287,27 @@ impl Index {
               .unwrap()
               .value()
               != 0;
+          index_dune_events = statistics
+              .get(&Statistic::IndexDuneEvents.key())?
+              .map(|x| x.value())
+              .unwrap_or(0)
+              != 0;
+          index_dune_history = statistics
+              .get(&Statistic::IndexDuneHistory.key())?
+              .map(|x| x.value())
+              .unwrap_or(0)
+              != 0;
+
+          Self::check_protocol_parameters(
+            &tx,
+            protocol,
+            options.legacy_protocol_parameters()?,
+          )?;
         }
 
         database
       }
       Err(DatabaseError::Storage(StorageError::Io(error))) if error.kind() == io::ErrorKind::NotFound => {
-        let db_cache_size = match options.db_cache_size {
-          Some(db_cache_size) => db_cache_size,
-          None => {
-            let mut sys = System::new();
-            sys.refresh_memory();
-            usize::try_from(sys.total_memory() / 4)?
-          }
-        };
-
         let database = Database::builder()
           .set_cache_size(db_cache_size)
           .create(&path)?;
@@ -285,7 +330,7 @@ impl Index {
         tx.open_table(INSCRIPTION_TXID_TO_TX)?;
         tx.open_table(PARTIAL_TXID_TO_INSCRIPTION_TXIDS)?;
         tx.open_table(OUTPOINT_TO_VALUE)?;
-        tx.open_multimap_table(ADDRESS_TO_OUTPOINT)?;
+        tx.open_table(ADDRESS_TO_OUTPOINT)?;
         tx.open_table(SATPOINT_TO_INSCRIPTION_ID)?;
         tx.open_table(SAT_TO_INSCRIPTION_ID)?;
         tx.open_table(SAT_TO_SATPOINT)?;
@@ -300,6 +345,8 @@ impl Index {
           }
 
           index_dunes = options.index_dunes();
+          index_dune_events = index_dunes && options.index_dune_events;
+          index_dune_history = index_dunes && options.index_dune_history;
           index_sats = options.index_sats;
 
           statistics.insert(
@@ -307,9 +354,41 @@ impl Index {
             &u64::from(index_dunes),
           )?;
 
+          statistics.insert(
+            &Statistic::IndexDuneEvents.key(),
+            &u64::from(index_dune_events),
+          )?;
+
+          if index_dunes {
+            tx.open_table(DUNE_HOLDER_RANK)?;
+            tx.open_table(DUNE_HOLDER_TO_BALANCE)?;
+            tx.open_table(DUNE_ID_TO_HOLDER_COUNT)?;
+            tx.open_table(OUTPOINT_TO_DUNE_HOLDER)?;
+            tx.open_table(TRANSACTION_ID_TO_CENOTAPH)?;
+          }
+
+          if index_dune_events {
+            tx.open_table(DUNE_EVENT_ID_TO_DUNE_EVENT)?;
+          }
+
+          statistics.insert(
+            &Statistic::IndexDuneHistory.key(),
+            &u64::from(index_dune_history),
+          )?;
+
+          if index_dune_history {
+            tx.open_table(DUNE_OUTPOINT_TO_BALANCE_HISTORY)?;
+          }
+
           statistics.insert(&Statistic::IndexSats.key(), &u64::from(index_sats))?;
 
           statistics.insert(&Statistic::Schema.key(), &SCHEMA_VERSION)?;
+
+          let mut protocol_parameters = tx.open_table(PROTOCOL_PARAMETER_TO_VALUE)?;
+
+          for (name, value) in protocol.entries() {
+            protocol_parameters.insert(name, value)?;
+          }
         }
 
         tx.commit()?;
@@ -322,24 +401,99 @@ impl Index {
     let genesis_block_coinbase_transaction =
       options.chain().genesis_block().coinbase().unwrap().clone();
 
+    // Block files are scanned when blocks are first read from them, and only
+    // newly written blocks are scanned on later updates
+    let block_files = if options.index_from_block_files {
+      Some(Arc::new(Mutex::new(BlockFiles::new(
+        options.dogecoin_blocks_dir()?,
+        options.chain().block_file_magic(),
+      ))))
+    } else {
+      None
+    };
+
     Ok(Self {
       genesis_block_coinbase_txid: genesis_block_coinbase_transaction.txid(),
       auth,
+      block_files,
+      chain: options.chain(),
       client,
+      commit_policy: options.commit_policy(),
       database,
+      events: tokio::sync::broadcast::channel(EVENT_CHANNEL_CAPACITY).0,
       path,
-      first_inscription_height: options.first_inscription_height(),
-      first_dune_height: options.first_dune_height(),
       genesis_block_coinbase_transaction,
       height_limit: options.height_limit,
+      index_dune_events,
+      index_dune_history,
       index_dunes,
       index_sats,
+      protocol,
       reorged: AtomicBool::new(false),
       rpc_url,
     })
   }
 
-  pub(crate) fn get_unspent_outputs(&self, _wallet: Wallet) -> Result<BTreeMap<OutPoint, Amount>> {
+  /// Check that the index was built with the protocol parameters
+  /// `protocol`, since blocks indexed under different parameters would have
+  /// to be reindexed. Indexes built before the parameters were recorded were
+  /// built with `legacy`.
+  fn check_protocol_parameters(
+    rtx: &ReadTransaction,
+    protocol: ProtocolParameters,
+    legacy: ProtocolParameters,
+  ) -> Result {
+    let indexed = match rtx.open_table(PROTOCOL_PARAMETER_TO_VALUE) {
+      Ok(table) => protocol
+        .entries()
+        .into_iter()
+        .map(|(name, _)| Ok((name, table.get(name)?.map(|value| value.value()))))
+        .collect::<Result<HashMap<&str, Option<u128>>>>()?,
+      Err(redb::TableError::TableDoesNotExist(_)) => legacy
+        .entries()
+        .into_iter()
+        .map(|(name, value)| (name, Some(value)))
+        .collect(),
+      Err(err) => return Err(err.into()),
+    };
+
+    for (name, value) in protocol.entries() {
+      let indexed = indexed[name];
+
+      ensure!(
+        indexed == Some(value),
+        "index was built with protocol parameter {name} {}, but ord uses {value}, delete and rebuild the index or use the parameters it was built with",
+        indexed.map_or_else(|| "unset".into(), |indexed| indexed.to_string()),
+      );
+    }
+
+    Ok(())
+  }
+
+  /// Path of the index database, creating the data dir if it doesn't exist
+  fn database_path(options: &Options) -> Result<PathBuf> {
+    let data_dir = options.data_dir()?;
+
+    if let Err(err) = fs::create_dir_all(&data_dir) {
+      bail!("failed to create data dir `{}`: {err}", data_dir.display());
+    }
+
+    Ok(if let Some(path) = &options.index {
+      path.clone()
+    } else {
+      data_dir.join("index.redb")
+    })
+  }
+
+  pub(crate) fn protocol(&self) -> ProtocolParameters {
+    self.protocol
+  }
+
+  pub(crate) fn get_unspent_outputs(&self, wallet: &Wallet) -> Result<BTreeMap<OutPoint, Amount>> {
+    if let Wallet::Native(wallet) = wallet {
+      return wallet.get_unspent_outputs(self);
+    }
+
     let mut utxos = BTreeMap::new();
     utxos.extend(
       self
@@ -384,7 +538,7 @@ impl Index {
 
   pub(crate) fn get_unspent_output_ranges(
     &self,
-    wallet: Wallet,
+    wallet: &Wallet,
   ) -> Result<Vec<(OutPoint, Vec<(u64, u64)>)>> {
     self
       .get_unspent_outputs(wallet)?
@@ -397,10 +551,33 @@ impl Index {
       .collect()
   }
 
+  /// Subscribe to events broadcast as blocks are committed to the index
+  pub(crate) fn subscribe(&self) -> tokio::sync::broadcast::Receiver<Event> {
+    self.events.subscribe()
+  }
+
+  /// Events are only collected while there are subscribers to receive them
+  fn has_event_subscribers(&self) -> bool {
+    self.events.receiver_count() > 0
+  }
+
+  fn broadcast(&self, event: Event) {
+    // Sending only fails if there are no subscribers
+    self.events.send(event).ok();
+  }
+
   pub(crate) fn has_dune_index(&self) -> bool {
     self.index_dunes
   }
 
+  pub(crate) fn has_dune_event_index(&self) -> bool {
+    self.index_dune_events
+  }
+
+  pub(crate) fn has_dune_history_index(&self) -> bool {
+    self.index_dune_history
+  }
+
   pub(crate) fn has_sat_index(&self) -> bool {
     self.index_sats
   }
@@ -428,19 +605,27 @@ impl Index {
           starting_timestamp: starting_timestamp.value(),
         }))
         .collect::<Result<Vec<_>, _>>()?;
+      let blocks_indexed = wtx
+        .open_table(HEIGHT_TO_BLOCK_HASH)?
+        .range(0..)?
+        .rev()
+        .next()
+        .map(|result| {
+          result.map(|(height, _hash)| height.value() + 1)
+        })
+        .transpose()?
+        .unwrap_or(0);
+      let blocks_per_second = Self::blocks_per_second(&transactions);
+      let blocks_remaining =
+        (self.client.get_block_count()? + 1).saturating_sub(blocks_indexed.into());
       Info {
         index_path: self.path.clone(),
-        blocks_indexed: wtx
-          .open_table(HEIGHT_TO_BLOCK_HASH)?
-          .range(0..)?
-          .rev()
-          .next()
-          .map(|result| {
-            result.map(|(height, _hash)| height.value() + 1)
-          })
-          .transpose()?
-          .unwrap_or(0),
+        blocks_indexed,
+        blocks_per_second,
         branch_pages: stats.branch_pages(),
+        eta_seconds: blocks_per_second
+          .filter(|rate| *rate > 0.0)
+          .map(|rate| (blocks_remaining as f64 / rate).round() as u64),
         fragmented_bytes: stats.fragmented_bytes(),
         index_file_size: fs::metadata(&self.path)?.len(),
         leaf_pages: stats.leaf_pages(),
@@ -458,14 +643,49 @@ impl Index {
     Ok(info)
   }
 
-  pub(crate) fn update(&self) -> Result {
-    let mut updater = Updater::new(self)?;
+  /// Blocks indexed per second over the last `INDEXING_RATE_WINDOW` write
+  /// transactions, measured from their starting block counts and timestamps
+  fn blocks_per_second(transactions: &[TransactionInfo]) -> Option<f64> {
+    let recent = &transactions[transactions.len().saturating_sub(INDEXING_RATE_WINDOW + 1)..];
 
+    let (first, last) = (recent.first()?, recent.last()?);
+
+    let elapsed = last.starting_timestamp.checked_sub(first.starting_timestamp)?;
+
+    if elapsed == 0 {
+      return None;
+    }
+
+    Some(
+      f64::from(last.starting_block_count.saturating_sub(first.starting_block_count))
+        / (elapsed as f64 / 1000.0),
+    )
+  }
+
+  pub(crate) fn update(&self) -> Result {
     loop {
+      let mut updater = Updater::new(self)?;
+
       match updater.update_index() {
         Ok(ok) => return Ok(ok),
         Err(err) => {
           log::info!("{}", err.to_string());
+
+          match err.downcast_ref() {
+            Some(&ReorgError::Recoverable { height, depth }) => {
+              if let Err(err) = Reorg::handle_reorg(self, height, depth) {
+                self.reorged.store(true, atomic::Ordering::Relaxed);
+                return Err(err);
+              }
+
+              self.broadcast(Event::Reorg { height, depth });
+            }
+            Some(&ReorgError::Unrecoverable) => {
+              self.reorged.store(true, atomic::Ordering::Relaxed);
+              return Err(err);
+            }
+            None => {}
+          }
         }
       }
     }
@@ -500,6 +720,13 @@ impl Index {
     Ok(())
   }
 
+  fn set_statistic(wtx: &WriteTransaction, statistic: Statistic, value: u64) -> Result {
+    wtx
+      .open_table(STATISTIC_TO_COUNT)?
+      .insert(&statistic.key(), &value)?;
+    Ok(())
+  }
+
   #[cfg(test)]
   pub(crate) fn statistic(&self, statistic: Statistic) -> u64 {
     self
@@ -612,30 +839,178 @@ impl Index {
     Ok(entries)
   }
 
-  pub(crate) fn get_dune_balance(&self, outpoint: OutPoint, id: DuneId) -> Result<u128> {
+  /// Returns up to `page_size` events of dune `id`, newest first, starting
+  /// at event `from`, or at the latest event if `from` is `None`, along with
+  /// the events starting the previous and next pages
+  pub(crate) fn get_dune_events(
+    &self,
+    id: DuneId,
+    from: Option<DuneEventId>,
+    page_size: usize,
+  ) -> Result<(Vec<DuneEvent>, Option<DuneEventId>, Option<DuneEventId>)> {
     let rtx = self.database.begin_read()?;
 
-    let outpoint_to_balances = rtx.open_table(OUTPOINT_TO_DUNE_BALANCES)?;
+    let dune_event_id_to_dune_event = rtx.open_table(DUNE_EVENT_ID_TO_DUNE_EVENT)?;
 
-    let Some(balances) = outpoint_to_balances.get(&outpoint.store())? else {
-      return Ok(0);
+    let first = DuneEventId {
+      height: 0,
+      transaction: 0,
+      number: 0,
     };
 
-    let balances_buffer = balances.value();
+    let from = from.unwrap_or(DuneEventId::MAX);
+
+    let mut events = Vec::new();
+    let mut next = None;
+
+    for result in dune_event_id_to_dune_event
+        .range(first.key(id)..=from.key(id))?
+        .rev()
+    {
+      let (key, event) = result?;
+
+      if events.len() == page_size {
+        next = Some(DuneEventId::from_key(key.value()));
+        break;
+      }
+
+      events.push(DuneEvent::load(event.value()));
+    }
+
+    let mut prev = None;
+
+    for result in dune_event_id_to_dune_event
+        .range((
+          Bound::Excluded(from.key(id)),
+          Bound::Included(DuneEventId::MAX.key(id)),
+        ))?
+        .take(page_size)
+    {
+      prev = Some(DuneEventId::from_key(result?.0.value()));
+    }
+
+    Ok((events, prev, next))
+  }
+
+  /// Returns the balance of dune `id` held by each address at the end of
+  /// block `height`. Outputs without an address are keyed by outpoint.
+  pub(crate) fn get_dune_snapshot(&self, id: DuneId, height: u32) -> Result<BTreeMap<String, u128>> {
+    let rtx = self.database.begin_read()?;
+
+    let history = rtx.open_table(DUNE_OUTPOINT_TO_BALANCE_HISTORY)?;
+
+    let mut start = (id, OutPoint::null()).store();
+    start[12..].fill(0);
+    let mut end = start;
+    end[12..].fill(u8::MAX);
+
+    let mut holders = BTreeMap::new();
 
-    let mut i = 0;
-    while i < balances_buffer.len() {
-      let (balance_id, length) = dunes::varint::decode(&balances_buffer[i..]);
-      i += length;
-      let (amount, length) = dunes::varint::decode(&balances_buffer[i..]);
-      i += length;
+    for result in history.range(&start..=&end)? {
+      let (key, entry) = result?;
 
-      if DuneId::try_from(balance_id).unwrap() == id {
-        return Ok(amount);
+      let entry = DuneBalanceHistory::load(entry.value());
+
+      if !entry.unspent_at(height) {
+        continue;
       }
+
+      let holder = if entry.address == [0; 34] {
+        <(DuneId, OutPoint)>::load(*key.value()).1.to_string()
+      } else {
+        String::from_utf8_lossy(&entry.address).into_owned()
+      };
+
+      *holders.entry(holder).or_default() += entry.balance;
+    }
+
+    Ok(holders)
+  }
+
+  /// Returns the `limit` largest holders of dune `id`, along with the number
+  /// of addresses holding it and the share of its circulating supply held by
+  /// its largest holders. Outputs without an address are not counted. Returns
+  /// `None` if the index does not track dune holders.
+  pub(crate) fn get_dune_distribution(
+    &self,
+    id: DuneId,
+    entry: &DuneEntry,
+    limit: usize,
+  ) -> Result<Option<DuneDistribution>> {
+    let rtx = self.database.begin_read()?;
+
+    let holder_count = match rtx.open_table(DUNE_ID_TO_HOLDER_COUNT) {
+      Ok(holder_count) => holder_count,
+      Err(redb::TableError::TableDoesNotExist(_)) => return Ok(None),
+      Err(err) => return Err(err.into()),
+    };
+
+    let holders = holder_count
+      .get(id.store())?
+      .map(|count| count.value())
+      .unwrap_or_default();
+
+    let start = (id, 0, [0; 34]).store();
+    let end = (id, u128::MAX, [u8::MAX; 34]).store();
+
+    let mut top = Vec::new();
+
+    for result in rtx
+      .open_table(DUNE_HOLDER_RANK)?
+      .range(&start..=&end)?
+      .rev()
+      .take(limit.max(100))
+    {
+      let (key, _) = result?;
+
+      let (_, balance, address) = <(DuneId, u128, [u8; 34])>::load(*key.value());
+
+      top.push(DuneHolder {
+        address: String::from_utf8_lossy(&address).into_owned(),
+        balance,
+      });
     }
 
-    Ok(0)
+    let circulating = entry.supply().saturating_sub(entry.burned);
+
+    let share = |n: usize| {
+      if circulating == 0 {
+        return 0.0;
+      }
+
+      let held = top.iter().take(n).map(|holder| holder.balance).sum::<u128>();
+
+      held as f64 / circulating as f64 * 100.0
+    };
+
+    let top_10_share = share(10);
+    let top_100_share = share(100);
+
+    top.truncate(limit);
+
+    Ok(Some(DuneDistribution {
+      holders,
+      top,
+      top_10_share,
+      top_100_share,
+    }))
+  }
+
+  pub(crate) fn get_dune_balance(&self, outpoint: OutPoint, id: DuneId) -> Result<u128> {
+    let rtx = self.database.begin_read()?;
+
+    let outpoint_to_balances = rtx.open_table(OUTPOINT_TO_DUNE_BALANCES)?;
+
+    let Some(balances) = outpoint_to_balances.get(&outpoint.store())? else {
+      return Ok(0);
+    };
+
+    Ok(
+      entry::decode_dune_balances(balances.value())
+        .find(|(balance_id, _)| DuneId::try_from(*balance_id).unwrap() == id)
+        .map(|(_, amount)| amount)
+        .unwrap_or_default(),
+    )
   }
 
   pub(crate) fn get_dune_balances_for_outpoint(
@@ -653,21 +1028,14 @@ impl Index {
       return Ok(Vec::new());
     };
 
-    let balances_buffer = balances.value();
-
-    let mut balances = Vec::new();
-    let mut i = 0;
-    while i < balances_buffer.len() {
-      let (id, length) = dunes::varint::decode(&balances_buffer[i..]);
-      i += length;
-      let (amount, length) = dunes::varint::decode(&balances_buffer[i..]);
-      i += length;
+    let mut dune_balances = Vec::new();
 
+    for (id, amount) in entry::decode_dune_balances(balances.value()) {
       let id = DuneId::try_from(id).unwrap();
 
       let entry = DuneEntry::load(id_to_dune_entries.get(id.store())?.unwrap().value());
 
-      balances.push((
+      dune_balances.push((
         entry.spaced_dune(),
         Pile {
           amount,
@@ -677,7 +1045,20 @@ impl Index {
       ));
     }
 
-    Ok(balances)
+    Ok(dune_balances)
+  }
+
+  /// Returns the dune balances of `outpoint` as stored in the index, as
+  /// varint encoded pairs of dune ID and amount
+  pub(crate) fn get_encoded_dune_balances(&self, outpoint: OutPoint) -> Result<Option<Vec<u8>>> {
+    Ok(
+      self
+        .database
+        .begin_read()?
+        .open_table(OUTPOINT_TO_DUNE_BALANCES)?
+        .get(&outpoint.store())?
+        .map(|balances| balances.value().to_vec()),
+    )
   }
 
   pub(crate) fn get_dunic_outputs(&self, outpoints: &[OutPoint]) -> Result<BTreeSet<OutPoint>> {
@@ -696,6 +1077,32 @@ impl Index {
     Ok(dunic)
   }
 
+  pub(crate) fn get_dune_balances_for_outpoints(
+    &self,
+    outpoints: &[OutPoint],
+  ) -> Result<BTreeMap<OutPoint, BTreeMap<DuneId, u128>>> {
+    let rtx = self.database.begin_read()?;
+
+    let outpoint_to_balances = rtx.open_table(OUTPOINT_TO_DUNE_BALANCES)?;
+
+    let mut dune_balances = BTreeMap::new();
+
+    for outpoint in outpoints {
+      let Some(balances) = outpoint_to_balances.get(&outpoint.store())? else {
+        continue;
+      };
+
+      dune_balances.insert(
+        *outpoint,
+        entry::decode_dune_balances(balances.value())
+          .map(|(id, amount)| (DuneId::try_from(id).unwrap(), amount))
+          .collect(),
+      );
+    }
+
+    Ok(dune_balances)
+  }
+
   pub(crate) fn get_dune_balance_map(&self) -> Result<BTreeMap<SpacedDune, BTreeMap<OutPoint, u128>>> {
     let outpoint_balances = self.get_dune_balances()?;
 
@@ -736,15 +1143,9 @@ impl Index {
     {
       let (outpoint, balances_buffer) = entry?;
       let outpoint = OutPoint::load(*outpoint.value());
-      let balances_buffer = balances_buffer.value();
 
       let mut balances = Vec::new();
-      let mut i = 0;
-      while i < balances_buffer.len() {
-        let (id, length) = dunes::varint::decode(&balances_buffer[i..]);
-        i += length;
-        let (balance, length) = dunes::varint::decode(&balances_buffer[i..]);
-        i += length;
+      for (id, balance) in entry::decode_dune_balances(balances_buffer.value()) {
         balances.push((DuneId::try_from(id)?, balance));
       }
 
@@ -754,22 +1155,57 @@ impl Index {
     Ok(result)
   }
 
-  pub(crate) fn get_account_outputs(&self, address: String) -> Result<Vec<OutPoint>> {
-    let mut result: Vec<OutPoint> = Vec::new();
+  /// Returns up to `limit` of `address`'s unspent outputs with the height
+  /// they were created at and their value, oldest first, skipping those for
+  /// which `include` returns false. Scanning starts at `start`, a position
+  /// returned by a previous call, and stops after `max_scanned` outputs, so
+  /// that a selective `include` can't walk an address's entire history. The
+  /// position at which scanning stopped is returned if there are more.
+  pub(crate) fn get_address_outputs(
+    &self,
+    address: &str,
+    start: Option<(u32, OutPoint)>,
+    limit: usize,
+    max_scanned: usize,
+    mut include: impl FnMut(OutPoint, u64) -> Result<bool>,
+  ) -> Result<(Vec<(u32, OutPoint, u64)>, Option<(u32, OutPoint)>)> {
+    let Ok(address) = <[u8; 34]>::try_from(address.as_bytes()) else {
+      return Ok((Vec::new(), None));
+    };
 
-    self.database
-        .begin_read()?
-        .open_multimap_table(ADDRESS_TO_OUTPOINT)?
-        .get(address.as_bytes())?
-        .for_each(|res| {
-          if let Ok(item) = res {
-            result.push(OutPoint::load(*item.value()));
-          } else {
-            println!("Error: {:?}", res.err().unwrap());
-          }
-        });
+    let mut end = [u8::MAX; 74];
+    end[..34].copy_from_slice(&address);
 
-    Ok(result)
+    let start = match start {
+      Some((height, outpoint)) => (address, height, outpoint).store(),
+      None => {
+        let mut start = [0; 74];
+        start[..34].copy_from_slice(&address);
+        start
+      }
+    };
+
+    let rtx = self.database.begin_read()?;
+
+    let address_to_outpoint = rtx.open_table(ADDRESS_TO_OUTPOINT)?;
+
+    let mut outputs = Vec::new();
+
+    for (scanned, result) in address_to_outpoint.range(&start..=&end)?.enumerate() {
+      let (key, value) = result?;
+
+      let (_, height, outpoint) = <([u8; 34], u32, OutPoint)>::load(*key.value());
+
+      if outputs.len() == limit || scanned == max_scanned {
+        return Ok((outputs, Some((height, outpoint))));
+      }
+
+      if include(outpoint, value.value())? {
+        outputs.push((height, outpoint, value.value()));
+      }
+    }
+
+    Ok((outputs, None))
   }
 
   pub(crate) fn block_header(&self, hash: BlockHash) -> Result<Option<BlockHeader>> {
@@ -836,6 +1272,21 @@ impl Index {
     Ok(Some(DuneEntry::load(entry.value()).spaced_dune()))
   }
 
+  pub(crate) fn get_cenotaph(&self, txid: Txid) -> Result<Option<Cenotaph>> {
+    if !self.index_dunes {
+      return Ok(None);
+    }
+
+    Ok(
+      self
+        .database
+        .begin_read()?
+        .open_table(TRANSACTION_ID_TO_CENOTAPH)?
+        .get(&txid.store())?
+        .map(|cenotaph| Cenotaph::load(cenotaph.value())),
+    )
+  }
+
   pub(crate) fn get_inscription_id_by_sat(&self, sat: Sat) -> Result<Option<InscriptionId>> {
     Ok(
       self
@@ -895,6 +1346,34 @@ impl Index {
     )
   }
 
+  /// Returns the transactions revealed so far by each inscription whose
+  /// reveal spans several transactions and has not been completed.
+  pub(crate) fn get_partial_inscriptions(&self) -> Result<Vec<Vec<Transaction>>> {
+    let rtx = self.database.begin_read()?;
+
+    let txid_to_tx = rtx.open_table(INSCRIPTION_TXID_TO_TX)?;
+
+    let mut partials = Vec::new();
+
+    for result in rtx.open_table(PARTIAL_TXID_TO_INSCRIPTION_TXIDS)?.iter()? {
+      let (_partial_txid, txids) = result?;
+
+      let mut txs = Vec::new();
+
+      for txid in txids.value().chunks(32) {
+        let tx = txid_to_tx
+          .get(txid)?
+          .ok_or_else(|| anyhow!("partial inscription transaction missing from index"))?;
+
+        txs.push(consensus::encode::deserialize(tx.value())?);
+      }
+
+      partials.push(txs);
+    }
+
+    Ok(partials)
+  }
+
   pub(crate) fn get_inscription_by_id(
     &self,
     inscription_id: InscriptionId,
@@ -980,6 +1459,17 @@ impl Index {
       .collect()
   }
 
+  /// Whether Dogecoin Core considers `outpoint` unspent, taking transactions
+  /// in its mempool into account
+  pub(crate) fn is_unspent_including_mempool(&self, outpoint: OutPoint) -> Result<bool> {
+    Ok(
+      self
+        .client
+        .get_tx_out(&outpoint.txid, outpoint.vout, Some(true))?
+        .is_some(),
+    )
+  }
+
   pub(crate) fn get_transaction(&self, txid: Txid) -> Result<Option<Transaction>> {
     if txid == self.genesis_block_coinbase_txid {
       Ok(Some(self.genesis_block_coinbase_transaction.clone()))
@@ -1421,8 +1911,30 @@ mod tests {
   }
 
   #[test]
-  fn inscriptions_below_first_inscription_height_are_skipped() {
-    let inscription = inscription("text/plain;charset=utf-8", "hello");
+  fn blocks_per_second_is_measured_over_recent_transactions() {
+    let transaction = |starting_block_count, starting_timestamp| TransactionInfo {
+      starting_block_count,
+      starting_timestamp,
+    };
+
+    assert_eq!(Index::blocks_per_second(&[]), None);
+    assert_eq!(Index::blocks_per_second(&[transaction(0, 1000)]), None);
+    assert_eq!(
+      Index::blocks_per_second(&[transaction(0, 1000), transaction(100, 3000)]),
+      Some(50.0),
+    );
+
+    let mut transactions = vec![transaction(0, 0)];
+    for i in 1..=u32::try_from(INDEXING_RATE_WINDOW).unwrap() {
+      transactions.push(transaction(1_000_000 + i * 10, 1_000_000 + u128::from(i) * 1000));
+    }
+
+    assert_eq!(Index::blocks_per_second(&transactions), Some(10.0));
+  }
+
+  #[test]
+  fn inscriptions_below_first_inscription_height_are_skipped() {
+    let inscription = inscription("text/plain;charset=utf-8", "hello");
     let template = TransactionTemplate {
       inputs: &[(1, 0, 0)],
       witness: inscription.to_witness(),
@@ -2440,6 +2952,61 @@ mod tests {
       format!("index at `{}{delimiter}regtest{delimiter}index.redb` appears to have been built with a newer, incompatible version of ord, consider updating ord: index schema {}, ord schema {SCHEMA_VERSION}", path.display(), u64::MAX));
   }
 
+  #[test]
+  fn protocol_parameters_must_match_index() {
+    let tempdir = Context::builder().build().tempdir;
+
+    assert_eq!(
+      Context::builder()
+        .args(["--first-dune-height", "9"])
+        .tempdir(tempdir)
+        .try_build()
+        .err()
+        .unwrap()
+        .to_string(),
+      "index was built with protocol parameter first_dune_height 0, but ord uses 9, delete and rebuild the index or use the parameters it was built with",
+    );
+  }
+
+  #[test]
+  fn indexes_without_recorded_protocol_parameters_were_built_with_legacy_parameters() {
+    let unrecorded = || {
+      let context = Context::builder().build();
+
+      let wtx = context.index.database.begin_write().unwrap();
+      wtx.delete_table(PROTOCOL_PARAMETER_TO_VALUE).unwrap();
+      wtx.commit().unwrap();
+
+      context.tempdir
+    };
+
+    assert_eq!(
+      Context::builder()
+        .tempdir(unrecorded())
+        .try_build()
+        .err()
+        .unwrap()
+        .to_string(),
+      "index was built with protocol parameter dune_unlock_height 5084000, but ord uses 0, delete and rebuild the index or use the parameters it was built with",
+    );
+
+    let tempdir = unrecorded();
+
+    let config = tempdir.path().join("ord.yaml");
+
+    fs::write(
+      &config,
+      "protocol:\n  dune_unlock_height: 5084000\n  strict_dunestone_height: 4294967295\n",
+    )
+    .unwrap();
+
+    Context::builder()
+      .arg("--config")
+      .arg(config)
+      .tempdir(tempdir)
+      .build();
+  }
+
   #[test]
   fn inscriptions_on_output() {
     for context in Context::configurations() {
@@ -2652,11 +3219,790 @@ mod tests {
       assert_regex_match!(
         context
           .index
-          .get_unspent_outputs(Wallet::load(&context.options).unwrap())
+          .get_unspent_outputs(&Wallet::load(&context.options).unwrap())
           .unwrap_err()
           .to_string(),
         r"output in Dogecoin Core wallet but not in ord index: [[:xdigit:]]{64}:\d+"
       );
     }
   }
+
+  #[test]
+  fn recover_from_reorg() {
+    for depth in 1..=5 {
+      let context = Context::builder().build();
+
+      context.mine_blocks(19);
+
+      for _ in 0..depth {
+        context.rpc_server.invalidate_tip();
+      }
+
+      let blocks = context.mine_blocks(depth + 1);
+
+      assert!(!context.index.is_reorged());
+      assert_eq!(context.index.block_count().unwrap(), 21);
+      assert_eq!(
+        context.index.blocks(1).unwrap(),
+        [(20, blocks.last().unwrap().block_hash())]
+      );
+    }
+  }
+
+  #[test]
+  fn reorg_rolls_back_inscriptions() {
+    let context = Context::builder().build();
+
+    context.mine_blocks(19);
+
+    let inscription = inscription("text/plain;charset=utf-8", "hello");
+    let txid = context.rpc_server.broadcast_tx(TransactionTemplate {
+      inputs: &[(1, 0, 0)],
+      script_sig: inscription.to_script_sig(),
+      ..Default::default()
+    });
+    let inscription_id = InscriptionId::from(txid);
+
+    context.mine_blocks(1);
+
+    assert_eq!(
+      context.index.get_inscription_by_id(inscription_id).unwrap(),
+      Some(inscription)
+    );
+
+    context.rpc_server.invalidate_tip();
+    context.mine_blocks(2);
+
+    assert_eq!(
+      context.index.get_inscription_by_id(inscription_id).unwrap(),
+      None
+    );
+    assert_eq!(context.index.block_count().unwrap(), 22);
+  }
+
+  #[test]
+  fn abandoned_partial_inscriptions_are_no_longer_pending() {
+    let context = Context::builder().build();
+
+    context.mine_blocks(1);
+
+    let partials = inscription("text/plain", [0; 5000]).to_p2sh_partials();
+
+    let txid = context.rpc_server.broadcast_tx(TransactionTemplate {
+      inputs: &[(1, 0, 0)],
+      script_sig: partials[0].clone(),
+      ..Default::default()
+    });
+
+    context.mine_blocks(1);
+
+    assert_eq!(
+      context
+        .index
+        .get_partial_inscriptions()
+        .unwrap()
+        .into_iter()
+        .map(|txs| txs.iter().map(Transaction::txid).collect::<Vec<Txid>>())
+        .collect::<Vec<Vec<Txid>>>(),
+      [[txid]],
+    );
+
+    context.rpc_server.broadcast_tx(TransactionTemplate {
+      inputs: &[(2, 1, 0)],
+      ..Default::default()
+    });
+
+    context.mine_blocks(1);
+
+    assert!(context.index.get_partial_inscriptions().unwrap().is_empty());
+  }
+
+  #[test]
+  fn reorg_deeper_than_savepoints_is_unrecoverable() {
+    let context = Context::builder().build();
+
+    context.mine_blocks(19);
+
+    for _ in 0..15 {
+      context.rpc_server.invalidate_tip();
+    }
+
+    context.rpc_server.mine_blocks(16);
+
+    assert_eq!(
+      context
+        .index
+        .update()
+        .unwrap_err()
+        .downcast_ref::<ReorgError>(),
+      Some(&ReorgError::Unrecoverable)
+    );
+    assert!(context.index.is_reorged());
+  }
+
+  #[test]
+  fn mempool_overlay_tracks_outputs_spent_by_unconfirmed_transactions() {
+    let context = Context::builder().build();
+
+    let coinbase = OutPoint {
+      txid: context.mine_blocks(1)[0].txdata[0].txid(),
+      vout: 0,
+    };
+
+    let mut mempool = Mempool::default();
+    mempool.update(&context.index).unwrap();
+    assert!(!mempool.is_spent(coinbase));
+
+    let txid = context.rpc_server.broadcast_tx(TransactionTemplate {
+      inputs: &[(1, 0, 0)],
+      outputs: 2,
+      fee: 0,
+      ..Default::default()
+    });
+
+    mempool.update(&context.index).unwrap();
+    assert!(mempool.is_spent(coinbase));
+    assert!(!mempool.is_spent(OutPoint { txid, vout: 0 }));
+    assert!(mempool
+      .get_dune_balances_for_outpoint(OutPoint { txid, vout: 0 })
+      .is_empty());
+
+    context.mine_blocks(1);
+
+    mempool.update(&context.index).unwrap();
+    assert!(!mempool.is_spent(coinbase));
+  }
+
+  #[test]
+  fn mempool_overlay_allocates_dunes_of_unconfirmed_transactions() {
+    let context = Context::builder().arg("--index-dunes").build();
+
+    context.mine_blocks(1);
+
+    context.rpc_server.broadcast_tx(TransactionTemplate {
+      inputs: &[(1, 0, 0)],
+      output_scripts: &[p2pkh(1)],
+      op_return: Some(
+        Dunestone {
+          edicts: vec![Edict {
+            id: 0,
+            amount: 1000,
+            output: 0,
+          }],
+          etching: Some(Etching::default()),
+          ..Default::default()
+        }
+        .encipher(),
+      ),
+      ..Default::default()
+    });
+
+    context.mine_blocks(1);
+
+    let id = DuneId {
+      height: 2,
+      index: 1,
+    };
+
+    let txid = context.rpc_server.broadcast_tx(TransactionTemplate {
+      inputs: &[(2, 1, 0)],
+      outputs: 2,
+      output_scripts: &[p2pkh(2), p2pkh(3)],
+      op_return: Some(
+        Dunestone {
+          edicts: vec![Edict {
+            id: id.into(),
+            amount: 400,
+            output: 1,
+          }],
+          ..Default::default()
+        }
+        .encipher(),
+      ),
+      ..Default::default()
+    });
+
+    let mut mempool = Mempool::default();
+    mempool.update(&context.index).unwrap();
+
+    let balances = |vout| {
+      mempool
+        .get_dune_balances_for_outpoint(OutPoint { txid, vout })
+        .into_iter()
+        .map(|(dune, pile)| (dune, pile.amount))
+        .collect::<Vec<(SpacedDune, u128)>>()
+    };
+
+    let dune = context.index.dunes().unwrap()[0].1.spaced_dune();
+
+    assert_eq!(balances(0), [(dune, 600)]);
+    assert_eq!(balances(1), [(dune, 400)]);
+    assert!(balances(2).is_empty());
+
+    let address = Chain::Mainnet
+      .address_from_script(&p2pkh(3))
+      .unwrap()
+      .to_string();
+
+    assert_eq!(
+      mempool
+        .get_address_outputs(&address)
+        .iter()
+        .map(|(outpoint, _)| *outpoint)
+        .collect::<Vec<OutPoint>>(),
+      [OutPoint { txid, vout: 1 }],
+    );
+  }
+
+  #[test]
+  fn mempool_overlay_marks_outputs_that_may_hold_inscriptions() {
+    let context = Context::builder().build();
+
+    context.mine_blocks(2);
+
+    let reveal = context.rpc_server.broadcast_tx(TransactionTemplate {
+      inputs: &[(1, 0, 0)],
+      script_sig: inscription("text/plain", "hello").to_script_sig(),
+      ..Default::default()
+    });
+
+    let plain = context.rpc_server.broadcast_tx(TransactionTemplate {
+      inputs: &[(2, 0, 0)],
+      ..Default::default()
+    });
+
+    let mut mempool = Mempool::default();
+    mempool.update(&context.index).unwrap();
+
+    assert!(mempool.may_hold_inscriptions(OutPoint {
+      txid: reveal,
+      vout: 0,
+    }));
+    assert!(!mempool.may_hold_inscriptions(OutPoint {
+      txid: plain,
+      vout: 0,
+    }));
+
+    context.mine_blocks(1);
+
+    let transfer = context.rpc_server.broadcast_tx(TransactionTemplate {
+      inputs: &[(3, 1, 0)],
+      ..Default::default()
+    });
+
+    mempool.update(&context.index).unwrap();
+
+    assert!(mempool.may_hold_inscriptions(OutPoint {
+      txid: transfer,
+      vout: 0,
+    }));
+  }
+
+  #[test]
+  fn address_outputs_are_ordered_by_height_and_paginated() {
+    let context = Context::builder().build();
+
+    let address = "D".repeat(34);
+    let other = "E".repeat(34);
+
+    let outpoint = |n: u8| OutPoint {
+      txid: Txid::from_inner([n; 32]),
+      vout: 0,
+    };
+
+    {
+      let wtx = context.index.database.begin_write().unwrap();
+      let mut address_to_outpoint = wtx.open_table(ADDRESS_TO_OUTPOINT).unwrap();
+
+      for (address, height, n) in [
+        (&address, 3, 1),
+        (&address, 1, 2),
+        (&address, 2, 4),
+        (&address, 2, 3),
+        (&other, 0, 5),
+      ] {
+        let address = <[u8; 34]>::try_from(address.as_bytes()).unwrap();
+
+        address_to_outpoint
+          .insert(&(address, height, outpoint(n)).store(), u64::from(n))
+          .unwrap();
+      }
+
+      drop(address_to_outpoint);
+
+      wtx.commit().unwrap();
+    }
+
+    let (outputs, next) = context
+      .index
+      .get_address_outputs(&address, None, 2, usize::MAX, |_, _| Ok(true))
+      .unwrap();
+
+    assert_eq!(outputs, [(1, outpoint(2), 2), (2, outpoint(3), 3)]);
+    assert_eq!(next, Some((2, outpoint(4))));
+
+    let (outputs, next) = context
+      .index
+      .get_address_outputs(&address, next, 2, usize::MAX, |_, _| Ok(true))
+      .unwrap();
+
+    assert_eq!(outputs, [(2, outpoint(4), 4), (3, outpoint(1), 1)]);
+    assert_eq!(next, None);
+
+    let (outputs, next) = context
+      .index
+      .get_address_outputs(&address, None, 1, usize::MAX, |_, value| Ok(value % 2 == 1))
+      .unwrap();
+
+    assert_eq!(outputs, [(2, outpoint(3), 3)]);
+    assert_eq!(next, Some((2, outpoint(4))));
+
+    let (outputs, next) = context
+      .index
+      .get_address_outputs(&address, None, 10, 2, |_, value| Ok(value > 3))
+      .unwrap();
+
+    assert!(outputs.is_empty());
+    assert_eq!(next, Some((2, outpoint(4))));
+
+    let (outputs, next) = context
+      .index
+      .get_address_outputs(&address, next, 10, 2, |_, value| Ok(value > 3))
+      .unwrap();
+
+    assert_eq!(outputs, [(2, outpoint(4), 4)]);
+    assert_eq!(next, None);
+  }
+
+  #[test]
+  fn events_are_only_collected_for_subscribers() {
+    let context = Context::builder().build();
+
+    context.mine_blocks(1);
+
+    let mut events = context.index.subscribe();
+
+    let blocks = context.mine_blocks(1);
+
+    assert_eq!(
+      events.try_recv().unwrap(),
+      Event::Block {
+        height: 2,
+        hash: blocks[0].block_hash(),
+      },
+    );
+    assert!(events.try_recv().is_err());
+
+    drop(events);
+
+    assert!(!context.index.has_event_subscribers());
+  }
+
+  #[test]
+  fn dune_holders_are_tracked_across_transfers_splits_and_burns() {
+    let context = Context::builder().arg("--index-dunes").build();
+
+    context.mine_blocks(1);
+
+    context.rpc_server.broadcast_tx(TransactionTemplate {
+      inputs: &[(1, 0, 0)],
+      output_scripts: &[p2pkh(1)],
+      op_return: Some(
+        Dunestone {
+          edicts: vec![Edict {
+            id: 0,
+            amount: 1000,
+            output: 0,
+          }],
+          etching: Some(Etching::default()),
+          ..Default::default()
+        }
+        .encipher(),
+      ),
+      ..Default::default()
+    });
+
+    context.mine_blocks(1);
+
+    let id = DuneId {
+      height: 2,
+      index: 1,
+    };
+
+    let holders = || {
+      let entry = context.index.dunes().unwrap()[0].1;
+
+      let distribution = context
+        .index
+        .get_dune_distribution(id, &entry, 100)
+        .unwrap()
+        .unwrap();
+
+      (
+        distribution.holders,
+        distribution
+          .top
+          .into_iter()
+          .map(|holder| (holder.address, holder.balance))
+          .collect::<BTreeMap<String, u128>>(),
+      )
+    };
+
+    let holder = |n| String::from_utf8(updater::address(&p2pkh(n)).unwrap().to_vec()).unwrap();
+
+    assert_eq!(holders(), (1, [(holder(1), 1000)].into()));
+
+    // Transfer 400 to a new holder, and the remaining 600 to another
+    context.rpc_server.broadcast_tx(TransactionTemplate {
+      inputs: &[(2, 1, 0)],
+      outputs: 2,
+      output_scripts: &[p2pkh(2), p2pkh(3)],
+      op_return: Some(
+        Dunestone {
+          edicts: vec![Edict {
+            id: id.into(),
+            amount: 400,
+            output: 1,
+          }],
+          ..Default::default()
+        }
+        .encipher(),
+      ),
+      ..Default::default()
+    });
+
+    context.mine_blocks(1);
+
+    assert_eq!(holders(), (2, [(holder(2), 600), (holder(3), 400)].into()));
+
+    // Split 600 between an existing holder, a new holder and an output
+    // without an address
+    context.rpc_server.broadcast_tx(TransactionTemplate {
+      inputs: &[(3, 1, 0)],
+      outputs: 3,
+      output_scripts: &[p2pkh(2), p2pkh(4)],
+      op_return: Some(
+        Dunestone {
+          edicts: vec![
+            Edict {
+              id: id.into(),
+              amount: 200,
+              output: 1,
+            },
+            Edict {
+              id: id.into(),
+              amount: 200,
+              output: 2,
+            },
+          ],
+          ..Default::default()
+        }
+        .encipher(),
+      ),
+      ..Default::default()
+    });
+
+    context.mine_blocks(1);
+
+    assert_eq!(
+      holders(),
+      (
+        3,
+        [(holder(2), 200), (holder(3), 400), (holder(4), 200)].into()
+      ),
+    );
+
+    // Burn 400 by transferring it to the OP_RETURN output
+    context.rpc_server.broadcast_tx(TransactionTemplate {
+      inputs: &[(3, 1, 1)],
+      op_return: Some(
+        Dunestone {
+          edicts: vec![Edict {
+            id: id.into(),
+            amount: 400,
+            output: 1,
+          }],
+          ..Default::default()
+        }
+        .encipher(),
+      ),
+      ..Default::default()
+    });
+
+    context.mine_blocks(1);
+
+    assert_eq!(holders(), (2, [(holder(2), 200), (holder(4), 200)].into()));
+
+    assert_eq!(context.index.dunes().unwrap()[0].1.burned, 400);
+  }
+
+  #[test]
+  fn dune_events_record_etches_mints_transfers_and_burns() {
+    let context = Context::builder()
+      .arg("--index-dunes")
+      .arg("--index-dune-events")
+      .build();
+
+    context.mine_blocks(1);
+
+    let etch = context.rpc_server.broadcast_tx(TransactionTemplate {
+      inputs: &[(1, 0, 0)],
+      op_return: Some(
+        Dunestone {
+          edicts: vec![Edict {
+            id: 0,
+            amount: 1000,
+            output: 0,
+          }],
+          etching: Some(Etching {
+            terms: Some(Terms {
+              limit: Some(100),
+              ..Default::default()
+            }),
+            ..Default::default()
+          }),
+          ..Default::default()
+        }
+        .encipher(),
+      ),
+      ..Default::default()
+    });
+
+    context.mine_blocks(1);
+
+    let id = DuneId {
+      height: 2,
+      index: 1,
+    };
+
+    // Mint 100 into output 1 and move 400 of the premine there as well,
+    // leaving the remaining 600 to output 0
+    let transfer = context.rpc_server.broadcast_tx(TransactionTemplate {
+      inputs: &[(2, 1, 0)],
+      outputs: 2,
+      op_return: Some(
+        Dunestone {
+          edicts: vec![
+            Edict {
+              id: u128::from(id) | crate::dunes::CLAIM_BIT,
+              amount: 100,
+              output: 1,
+            },
+            Edict {
+              id: id.into(),
+              amount: 400,
+              output: 1,
+            },
+          ],
+          ..Default::default()
+        }
+        .encipher(),
+      ),
+      ..Default::default()
+    });
+
+    context.mine_blocks(1);
+
+    // Burn 200 of output 1 by transferring it to the OP_RETURN output
+    let burn = context.rpc_server.broadcast_tx(TransactionTemplate {
+      inputs: &[(3, 1, 1)],
+      op_return: Some(
+        Dunestone {
+          edicts: vec![Edict {
+            id: id.into(),
+            amount: 200,
+            output: 1,
+          }],
+          ..Default::default()
+        }
+        .encipher(),
+      ),
+      ..Default::default()
+    });
+
+    context.mine_blocks(1);
+
+    let event = |amount, height, kind, txid, vout| DuneEvent {
+      amount,
+      height,
+      kind,
+      transaction: 1,
+      txid,
+      vout,
+    };
+
+    let event_id = |height, number| DuneEventId {
+      height,
+      transaction: 1,
+      number,
+    };
+
+    assert_eq!(
+      context.index.get_dune_events(id, None, 4).unwrap(),
+      (
+        vec![
+          event(200, 4, DuneEventKind::Burn, burn, None),
+          event(300, 4, DuneEventKind::Transfer, burn, Some(0)),
+          event(400, 3, DuneEventKind::Transfer, transfer, Some(1)),
+          event(600, 3, DuneEventKind::Transfer, transfer, Some(0)),
+        ],
+        None,
+        Some(event_id(3, 0)),
+      )
+    );
+
+    assert_eq!(
+      context
+        .index
+        .get_dune_events(id, Some(event_id(3, 0)), 4)
+        .unwrap(),
+      (
+        vec![
+          event(100, 3, DuneEventKind::Mint, transfer, None),
+          event(1000, 2, DuneEventKind::Etch, etch, None),
+        ],
+        Some(event_id(4, 1)),
+        None,
+      )
+    );
+  }
+
+  #[test]
+  fn dune_balance_history_records_created_and_spent_heights() {
+    let context = Context::builder()
+      .arg("--index-dunes")
+      .arg("--index-dune-history")
+      .build();
+
+    context.mine_blocks(1);
+
+    let etch = context.rpc_server.broadcast_tx(TransactionTemplate {
+      inputs: &[(1, 0, 0)],
+      output_scripts: &[p2pkh(1)],
+      op_return: Some(
+        Dunestone {
+          edicts: vec![Edict {
+            id: 0,
+            amount: 1000,
+            output: 0,
+          }],
+          etching: Some(Etching::default()),
+          ..Default::default()
+        }
+        .encipher(),
+      ),
+      ..Default::default()
+    });
+
+    context.mine_blocks(1);
+
+    let id = DuneId {
+      height: 2,
+      index: 1,
+    };
+
+    let transfer = context.rpc_server.broadcast_tx(TransactionTemplate {
+      inputs: &[(2, 1, 0)],
+      outputs: 2,
+      output_scripts: &[p2pkh(2), p2pkh(3)],
+      op_return: Some(
+        Dunestone {
+          edicts: vec![Edict {
+            id: id.into(),
+            amount: 400,
+            output: 1,
+          }],
+          ..Default::default()
+        }
+        .encipher(),
+      ),
+      ..Default::default()
+    });
+
+    context.mine_blocks(1);
+
+    let holder = |n| updater::address(&p2pkh(n)).unwrap();
+
+    let history = |txid, vout| {
+      let rtx = context.index.database.begin_read().unwrap();
+
+      let table = rtx.open_table(DUNE_OUTPOINT_TO_BALANCE_HISTORY).unwrap();
+
+      table
+        .get(&(id, OutPoint { txid, vout }).store())
+        .unwrap()
+        .map(|entry| DuneBalanceHistory::load(entry.value()))
+    };
+
+    assert_eq!(
+      history(etch, 0),
+      Some(DuneBalanceHistory {
+        address: holder(1),
+        balance: 1000,
+        created: 2,
+        spent: Some(3),
+      })
+    );
+
+    assert_eq!(
+      history(transfer, 0),
+      Some(DuneBalanceHistory {
+        address: holder(2),
+        balance: 600,
+        created: 3,
+        spent: None,
+      })
+    );
+
+    assert_eq!(
+      history(transfer, 1),
+      Some(DuneBalanceHistory {
+        address: holder(3),
+        balance: 400,
+        created: 3,
+        spent: None,
+      })
+    );
+
+    let address = |n| String::from_utf8(holder(n).to_vec()).unwrap();
+
+    assert_eq!(context.index.get_dune_snapshot(id, 1).unwrap(), BTreeMap::new());
+
+    assert_eq!(
+      context.index.get_dune_snapshot(id, 2).unwrap(),
+      [(address(1), 1000)].into()
+    );
+
+    assert_eq!(
+      context.index.get_dune_snapshot(id, 3).unwrap(),
+      [(address(2), 600), (address(3), 400)].into()
+    );
+  }
+
+  #[test]
+  fn dune_distribution_is_none_without_holder_tables() {
+    let context = Context::builder().arg("--index-dunes").build();
+
+    {
+      let wtx = context.index.database.begin_write().unwrap();
+      wtx.delete_table(DUNE_ID_TO_HOLDER_COUNT).unwrap();
+      wtx.delete_table(DUNE_HOLDER_RANK).unwrap();
+      wtx.commit().unwrap();
+    }
+
+    assert_eq!(
+      context
+        .index
+        .get_dune_distribution(
+          DuneId {
+            height: 2,
+            index: 1,
+          },
+          &DuneEntry::default(),
+          10,
+        )
+        .unwrap(),
+      None,
+    );
+  }
 }
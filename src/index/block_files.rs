@@ -0,0 +1,392 @@
+use {
+  super::*,
+  bitcoin::{consensus::Decodable, BlockHeader, TxMerkleNode},
+  std::io::{BufReader, ErrorKind, Read, Seek, SeekFrom},
+};
+
+/// Version bit of merge mined blocks, whose header is followed by an
+/// auxiliary proof of work before their transactions
+const AUXPOW_VERSION_FLAG: i32 = 1 << 8;
+
+const HEADER_SIZE: u32 = 80;
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+struct Location {
+  file: usize,
+  offset: u64,
+  prev_blockhash: BlockHash,
+}
+
+/// Raw blocks in Dogecoin Core's `blk*.dat` files, which store blocks in the
+/// order they were received, each preceded by the network magic and its
+/// size. Unlike recent versions of Bitcoin Core, Dogecoin Core does not
+/// obfuscate block files, so blocks are read as is.
+pub(crate) struct BlockFiles {
+  blocks: HashMap<BlockHash, Location>,
+  dir: PathBuf,
+  files: Vec<PathBuf>,
+  magic: [u8; 4],
+  /// Offset of the first block of the last file that has not been scanned
+  offset: u64,
+}
+
+impl BlockFiles {
+  /// Block files in `dir`, none of which have been scanned yet
+  pub(crate) fn new(dir: PathBuf, magic: [u8; 4]) -> Self {
+    Self {
+      blocks: HashMap::new(),
+      dir,
+      files: Vec::new(),
+      magic,
+      offset: 0,
+    }
+  }
+
+  pub(crate) fn dir(&self) -> &Path {
+    &self.dir
+  }
+
+  /// Scan the headers of blocks written since the last scan, stopping at the
+  /// first incomplete block of each file, since Dogecoin Core may still be
+  /// writing it. Dogecoin Core only appends to its last block file, so the
+  /// scan resumes where the last one stopped, unless files were pruned.
+  pub(crate) fn update(&mut self) -> Result {
+    let mut files = fs::read_dir(&self.dir)
+      .with_context(|| format!("failed to read block files in `{}`", self.dir.display()))?
+      .map(|entry| Ok(entry?.path()))
+      .collect::<Result<Vec<PathBuf>>>()?
+      .into_iter()
+      .filter(|path| {
+        path
+          .file_name()
+          .and_then(|name| name.to_str())
+          .map(|name| name.starts_with("blk") && name.ends_with(".dat"))
+          .unwrap_or_default()
+      })
+      .collect::<Vec<PathBuf>>();
+
+    files.sort();
+
+    if !files.starts_with(&self.files) {
+      self.blocks.clear();
+      self.files.clear();
+      self.offset = 0;
+    }
+
+    let first = self.files.len().saturating_sub(1);
+
+    for (file, path) in files.iter().enumerate().skip(first) {
+      let start = if file < self.files.len() {
+        self.offset
+      } else {
+        0
+      };
+
+      self.offset = self.scan(file, path, start)?;
+    }
+
+    self.files = files;
+
+    log::info!(
+      "Found {} blocks in {} block files in `{}`",
+      self.blocks.len(),
+      self.files.len(),
+      self.dir.display(),
+    );
+
+    Ok(())
+  }
+
+  /// Scan the headers of the blocks in `path` from `offset`, returning the
+  /// offset at which the scan stopped
+  fn scan(&mut self, file: usize, path: &Path, mut offset: u64) -> Result<u64> {
+    let file_len = fs::metadata(path)?.len();
+
+    let mut reader = BufReader::new(
+      File::open(path).with_context(|| format!("failed to open `{}`", path.display()))?,
+    );
+
+    reader.seek(SeekFrom::Start(offset))?;
+
+    loop {
+      let mut prefix = [0; 8];
+
+      match reader.read_exact(&mut prefix) {
+        Err(err) if err.kind() == ErrorKind::UnexpectedEof => break,
+        result => result?,
+      }
+
+      // Block files are preallocated and padded with zeros
+      if prefix[..4] == [0; 4] {
+        break;
+      }
+
+      ensure!(
+        prefix[..4] == self.magic,
+        "unexpected magic {} in `{}` at offset {offset}",
+        hex::encode(&prefix[..4]),
+        path.display(),
+      );
+
+      let size = u32::from_le_bytes(prefix[4..].try_into().unwrap());
+
+      if offset + 8 + u64::from(size) > file_len {
+        log::warn!(
+          "stopped reading `{}` at incomplete block at offset {offset}",
+          path.display(),
+        );
+        break;
+      }
+
+      let header = BlockHeader::consensus_decode(&mut reader)?;
+
+      self.blocks.insert(
+        header.block_hash(),
+        Location {
+          file,
+          offset: offset + 8,
+          prev_blockhash: header.prev_blockhash,
+        },
+      );
+
+      reader.seek_relative(i64::from(size.saturating_sub(HEADER_SIZE)))?;
+
+      offset += 8 + u64::from(size);
+    }
+
+    Ok(offset)
+  }
+
+  /// Returns the hashes of the blocks from `height` to `tip_height` of the
+  /// chain ending at `tip`, or `None` if any of them are missing
+  pub(crate) fn chain(
+    &self,
+    tip: BlockHash,
+    tip_height: u32,
+    height: u32,
+  ) -> Option<Vec<BlockHash>> {
+    let mut hashes = Vec::new();
+    let mut hash = tip;
+
+    for _ in height..=tip_height {
+      hashes.push(hash);
+      hash = self.blocks.get(&hash)?.prev_blockhash;
+    }
+
+    hashes.reverse();
+
+    Some(hashes)
+  }
+
+  /// Read block `hash`, skipping its transactions if `txdata` is false
+  pub(crate) fn read(&self, hash: BlockHash, txdata: bool) -> Result<Block> {
+    let location = self
+      .blocks
+      .get(&hash)
+      .with_context(|| format!("block {hash} not found in block files"))?;
+
+    let path = &self.files[location.file];
+
+    let mut file =
+      File::open(path).with_context(|| format!("failed to open `{}`", path.display()))?;
+
+    file.seek(SeekFrom::Start(location.offset))?;
+
+    Self::decode(&mut BufReader::new(file), txdata)
+      .with_context(|| format!("failed to decode block {hash} in `{}`", path.display()))
+  }
+
+  fn decode<R: Read>(reader: &mut R, txdata: bool) -> Result<Block> {
+    let header = BlockHeader::consensus_decode(reader)?;
+
+    if !txdata {
+      return Ok(Block {
+        header,
+        txdata: Vec::new(),
+      });
+    }
+
+    if header.version & AUXPOW_VERSION_FLAG != 0 {
+      // parent block coinbase transaction and hash
+      Transaction::consensus_decode(reader)?;
+      BlockHash::consensus_decode(reader)?;
+
+      // coinbase and chain merkle branches, each followed by its index
+      for _ in 0..2 {
+        Vec::<TxMerkleNode>::consensus_decode(reader)?;
+        i32::consensus_decode(reader)?;
+      }
+
+      // parent block header
+      BlockHeader::consensus_decode(reader)?;
+    }
+
+    Ok(Block {
+      header,
+      txdata: Vec::<Transaction>::consensus_decode(reader)?,
+    })
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use {super::*, bitcoin::consensus::Encodable, tempfile::TempDir};
+
+  const MAGIC: [u8; 4] = [0xfa, 0xbf, 0xb5, 0xda];
+
+  fn open(dir: &Path) -> Result<BlockFiles> {
+    let mut block_files = BlockFiles::new(dir.into(), MAGIC);
+    block_files.update()?;
+    Ok(block_files)
+  }
+
+  fn child(parent: &Block, auxpow: bool) -> Block {
+    let mut header = parent.header;
+    header.prev_blockhash = parent.block_hash();
+    header.version = if auxpow { AUXPOW_VERSION_FLAG | 4 } else { 4 };
+
+    Block {
+      header,
+      txdata: parent.txdata.clone(),
+    }
+  }
+
+  fn serialize(block: &Block, auxpow: bool) -> Vec<u8> {
+    let mut buffer = Vec::new();
+
+    block.header.consensus_encode(&mut buffer).unwrap();
+
+    if auxpow {
+      block.txdata[0].consensus_encode(&mut buffer).unwrap();
+      BlockHash::all_zeros()
+        .consensus_encode(&mut buffer)
+        .unwrap();
+      for _ in 0..2 {
+        vec![TxMerkleNode::all_zeros()]
+          .consensus_encode(&mut buffer)
+          .unwrap();
+        0i32.consensus_encode(&mut buffer).unwrap();
+      }
+      block.header.consensus_encode(&mut buffer).unwrap();
+    }
+
+    block.txdata.consensus_encode(&mut buffer).unwrap();
+
+    let mut record = MAGIC.to_vec();
+    record.extend(u32::try_from(buffer.len()).unwrap().to_le_bytes());
+    record.extend(buffer);
+    record
+  }
+
+  #[test]
+  fn blocks_are_read_in_chain_order() {
+    let dir = TempDir::new().unwrap();
+
+    let genesis = Chain::Regtest.genesis_block();
+    let first = child(&genesis, false);
+    let second = child(&first, true);
+
+    fs::write(
+      dir.path().join("blk00000.dat"),
+      [
+        serialize(&second, true),
+        serialize(&genesis, false),
+        vec![0; 16],
+      ]
+      .concat(),
+    )
+    .unwrap();
+
+    fs::write(dir.path().join("blk00001.dat"), serialize(&first, false)).unwrap();
+
+    fs::write(dir.path().join("rev00000.dat"), [1; 16]).unwrap();
+
+    let block_files = open(dir.path()).unwrap();
+
+    assert_eq!(
+      block_files.chain(second.block_hash(), 2, 0),
+      Some(vec![
+        genesis.block_hash(),
+        first.block_hash(),
+        second.block_hash()
+      ]),
+    );
+
+    assert_eq!(
+      block_files.chain(second.block_hash(), 2, 1),
+      Some(vec![first.block_hash(), second.block_hash()]),
+    );
+
+    assert_eq!(block_files.read(second.block_hash(), true).unwrap(), second);
+
+    assert_eq!(
+      block_files.read(first.block_hash(), false).unwrap().txdata,
+      Vec::new(),
+    );
+  }
+
+  #[test]
+  fn missing_blocks_are_reported() {
+    let dir = TempDir::new().unwrap();
+
+    let genesis = Chain::Regtest.genesis_block();
+    let first = child(&genesis, false);
+    let second = child(&first, false);
+
+    fs::write(
+      dir.path().join("blk00000.dat"),
+      [serialize(&genesis, false), serialize(&second, false)].concat(),
+    )
+    .unwrap();
+
+    let block_files = open(dir.path()).unwrap();
+
+    assert_eq!(block_files.chain(second.block_hash(), 2, 0), None);
+  }
+
+  #[test]
+  fn unexpected_magic_is_an_error() {
+    let dir = TempDir::new().unwrap();
+
+    fs::write(dir.path().join("blk00000.dat"), [1; 16]).unwrap();
+
+    assert!(open(dir.path())
+      .unwrap_err()
+      .to_string()
+      .starts_with("unexpected magic 01010101"));
+  }
+
+  #[test]
+  fn update_scans_only_blocks_written_since_last_scan() {
+    let dir = TempDir::new().unwrap();
+
+    let genesis = Chain::Regtest.genesis_block();
+    let first = child(&genesis, false);
+    let second = child(&first, false);
+
+    let blk0 = dir.path().join("blk00000.dat");
+
+    fs::write(&blk0, serialize(&genesis, false)).unwrap();
+
+    let mut block_files = open(dir.path()).unwrap();
+
+    assert_eq!(block_files.chain(second.block_hash(), 2, 0), None);
+
+    // blocks that were already scanned are not scanned again
+    let mut contents = fs::read(&blk0).unwrap();
+    contents[..4].copy_from_slice(&[1; 4]);
+    contents.extend(serialize(&first, false));
+    fs::write(&blk0, contents).unwrap();
+
+    fs::write(dir.path().join("blk00001.dat"), serialize(&second, false)).unwrap();
+
+    block_files.update().unwrap();
+
+    assert_eq!(
+      block_files.chain(second.block_hash(), 2, 1),
+      Some(vec![first.block_hash(), second.block_hash()]),
+    );
+
+    assert_eq!(block_files.read(second.block_hash(), true).unwrap(), second);
+  }
+}
@@ -0,0 +1,75 @@
+use super::*;
+
+pub(crate) const DEFAULT_COMMIT_INTERVAL: u32 = 5000;
+
+/// When the updater commits its write transaction, flushing the in-memory
+/// output caches to the database. Committing more often loses less progress
+/// if indexing is interrupted and bounds memory use, at the cost of indexing
+/// speed.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) struct CommitPolicy {
+  /// Commit after indexing this many blocks
+  pub(crate) blocks: u32,
+  /// Commit once this much time has passed since the last commit
+  pub(crate) interval: Option<Duration>,
+  /// Commit once the output caches hold approximately this many bytes
+  pub(crate) cache_size: Option<usize>,
+}
+
+impl Default for CommitPolicy {
+  fn default() -> Self {
+    Self {
+      blocks: DEFAULT_COMMIT_INTERVAL,
+      interval: None,
+      cache_size: None,
+    }
+  }
+}
+
+impl CommitPolicy {
+  pub(crate) fn should_commit(
+    &self,
+    uncommitted: u32,
+    elapsed: Duration,
+    cache_size: usize,
+  ) -> bool {
+    uncommitted >= self.blocks
+      || self.interval.map_or(false, |interval| elapsed >= interval)
+      || self.cache_size.map_or(false, |limit| cache_size >= limit)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn default_commits_every_5000_blocks() {
+    let policy = CommitPolicy::default();
+
+    assert!(!policy.should_commit(4999, Duration::from_secs(86400), usize::MAX));
+    assert!(policy.should_commit(5000, Duration::ZERO, 0));
+  }
+
+  #[test]
+  fn elapsed_time_triggers_commit() {
+    let policy = CommitPolicy {
+      interval: Some(Duration::from_secs(60)),
+      ..Default::default()
+    };
+
+    assert!(!policy.should_commit(1, Duration::from_secs(59), 0));
+    assert!(policy.should_commit(1, Duration::from_secs(60), 0));
+  }
+
+  #[test]
+  fn cache_size_triggers_commit() {
+    let policy = CommitPolicy {
+      cache_size: Some(1 << 20),
+      ..Default::default()
+    };
+
+    assert!(!policy.should_commit(1, Duration::ZERO, (1 << 20) - 1));
+    assert!(policy.should_commit(1, Duration::ZERO, 1 << 20));
+  }
+}
@@ -1,4 +1,4 @@
-use crate::dunes::MintError;
+use crate::dunes::{varint, MintError};
 use crate::sat::Sat;
 use crate::sat_point::SatPoint;
 use super::*;
@@ -305,6 +305,338 @@ impl Entry for DuneId {
   }
 }
 
+/// Key of the dune balance history table: the dune ID, big-endian so that
+/// all outputs of a dune are contiguous, followed by the outpoint
+pub(super) type DuneOutPointValue = [u8; 48];
+
+impl Entry for (DuneId, OutPoint) {
+  type Value = DuneOutPointValue;
+
+  fn load(value: Self::Value) -> Self {
+    (
+      DuneId {
+        height: u64::from_be_bytes(value[..8].try_into().unwrap()),
+        index: u32::from_be_bytes(value[8..12].try_into().unwrap()),
+      },
+      OutPoint::load(value[12..].try_into().unwrap()),
+    )
+  }
+
+  fn store(self) -> Self::Value {
+    let mut value = [0; 48];
+    value[..8].copy_from_slice(&self.0.height.to_be_bytes());
+    value[8..12].copy_from_slice(&self.0.index.to_be_bytes());
+    value[12..].copy_from_slice(&self.1.store());
+    value
+  }
+}
+
+/// Key of the dune holder balance table: the dune ID followed by the holder's
+/// address
+pub(super) type DuneHolderValue = [u8; 46];
+
+impl Entry for (DuneId, [u8; 34]) {
+  type Value = DuneHolderValue;
+
+  fn load(value: Self::Value) -> Self {
+    (
+      DuneId {
+        height: u64::from_be_bytes(value[..8].try_into().unwrap()),
+        index: u32::from_be_bytes(value[8..12].try_into().unwrap()),
+      },
+      value[12..].try_into().unwrap(),
+    )
+  }
+
+  fn store(self) -> Self::Value {
+    let mut value = [0; 46];
+    value[..8].copy_from_slice(&self.0.height.to_be_bytes());
+    value[8..12].copy_from_slice(&self.0.index.to_be_bytes());
+    value[12..].copy_from_slice(&self.1);
+    value
+  }
+}
+
+/// Key of the dune holder rank table: the dune ID, the holder's balance and
+/// the holder's address, all big-endian so that iterating over a dune's keys
+/// in reverse yields its holders from largest to smallest
+pub(super) type DuneHolderRankValue = [u8; 62];
+
+impl Entry for (DuneId, u128, [u8; 34]) {
+  type Value = DuneHolderRankValue;
+
+  fn load(value: Self::Value) -> Self {
+    (
+      DuneId {
+        height: u64::from_be_bytes(value[..8].try_into().unwrap()),
+        index: u32::from_be_bytes(value[8..12].try_into().unwrap()),
+      },
+      u128::from_be_bytes(value[12..28].try_into().unwrap()),
+      value[28..].try_into().unwrap(),
+    )
+  }
+
+  fn store(self) -> Self::Value {
+    let mut value = [0; 62];
+    value[..8].copy_from_slice(&self.0.height.to_be_bytes());
+    value[8..12].copy_from_slice(&self.0.index.to_be_bytes());
+    value[12..28].copy_from_slice(&self.1.to_be_bytes());
+    value[28..].copy_from_slice(&self.2);
+    value
+  }
+}
+
+/// Decode the dune balances of an output stored in `OUTPOINT_TO_DUNE_BALANCES`
+/// as varint encoded pairs of dune ID and amount
+pub(super) fn decode_dune_balances(buffer: &[u8]) -> impl Iterator<Item = (u128, u128)> + '_ {
+  let mut i = 0;
+
+  std::iter::from_fn(move || {
+    if i >= buffer.len() {
+      return None;
+    }
+
+    let (id, length) = varint::decode(&buffer[i..]);
+    i += length;
+    let (amount, length) = varint::decode(&buffer[i..]);
+    i += length;
+
+    Some((id, amount))
+  })
+}
+
+#[derive(Debug, PartialEq, Copy, Clone)]
+pub(crate) struct DuneBalanceHistory {
+  pub(crate) address: [u8; 34],
+  pub(crate) balance: u128,
+  pub(crate) created: u32,
+  pub(crate) spent: Option<u32>,
+}
+
+pub(super) type DuneBalanceHistoryValue = (
+  u128,        // balance
+  u32,         // created height
+  Option<u32>, // spent height
+  [u8; 34],    // address
+);
+
+impl Entry for DuneBalanceHistory {
+  type Value = DuneBalanceHistoryValue;
+
+  fn load((balance, created, spent, address): Self::Value) -> Self {
+    Self {
+      address,
+      balance,
+      created,
+      spent,
+    }
+  }
+
+  fn store(self) -> Self::Value {
+    (self.balance, self.created, self.spent, self.address)
+  }
+}
+
+impl DuneBalanceHistory {
+  /// Whether the output held this balance at the end of block `height`
+  pub(crate) fn unspent_at(&self, height: u32) -> bool {
+    self.created <= height && self.spent.map(|spent| spent > height).unwrap_or(true)
+  }
+}
+
+#[derive(Debug, PartialEq, Copy, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub(crate) enum DuneEventKind {
+  Etch,
+  Mint,
+  Transfer,
+  Burn,
+}
+
+impl DuneEventKind {
+  fn load(value: u8) -> Self {
+    match value {
+      0 => Self::Etch,
+      1 => Self::Mint,
+      2 => Self::Transfer,
+      _ => Self::Burn,
+    }
+  }
+
+  fn store(self) -> u8 {
+    match self {
+      Self::Etch => 0,
+      Self::Mint => 1,
+      Self::Transfer => 2,
+      Self::Burn => 3,
+    }
+  }
+}
+
+impl Display for DuneEventKind {
+  fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+    write!(
+      f,
+      "{}",
+      match self {
+        Self::Etch => "etch",
+        Self::Mint => "mint",
+        Self::Transfer => "transfer",
+        Self::Burn => "burn",
+      }
+    )
+  }
+}
+
+#[derive(Debug, PartialEq, Copy, Clone, Serialize, Deserialize)]
+pub(crate) struct DuneEvent {
+  pub(crate) amount: u128,
+  pub(crate) height: u32,
+  pub(crate) kind: DuneEventKind,
+  pub(crate) transaction: u32,
+  pub(crate) txid: Txid,
+  pub(crate) vout: Option<u32>,
+}
+
+pub(super) type DuneEventIdValue = (
+  u64, // dune id height
+  u32, // dune id index
+  u32, // height
+  u32, // transaction index
+  u32, // event number within transaction
+);
+
+pub(super) type DuneEventValue = (
+  u128,        // amount
+  u32,         // height
+  u8,          // kind
+  u32,         // transaction index
+  TxidValue,   // txid
+  Option<u32>, // vout
+);
+
+impl Entry for DuneEvent {
+  type Value = DuneEventValue;
+
+  fn load((amount, height, kind, transaction, txid, vout): DuneEventValue) -> Self {
+    Self {
+      amount,
+      height,
+      kind: DuneEventKind::load(kind),
+      transaction,
+      txid: Txid::load(txid),
+      vout,
+    }
+  }
+
+  fn store(self) -> Self::Value {
+    (
+      self.amount,
+      self.height,
+      self.kind.store(),
+      self.transaction,
+      self.txid.store(),
+      self.vout,
+    )
+  }
+}
+
+/// Position of an event in the history of its dune: the height and index of
+/// its transaction and its number among the events of that transaction,
+/// formatted as `HEIGHT:TRANSACTION:NUMBER`
+#[derive(Debug, PartialEq, Copy, Clone)]
+pub(crate) struct DuneEventId {
+  pub(crate) height: u32,
+  pub(crate) transaction: u32,
+  pub(crate) number: u32,
+}
+
+impl DuneEventId {
+  pub(super) const MAX: Self = Self {
+    height: u32::MAX,
+    transaction: u32::MAX,
+    number: u32::MAX,
+  };
+
+  /// Key of this event of dune `id` in `DUNE_EVENT_ID_TO_DUNE_EVENT`
+  pub(super) fn key(self, id: DuneId) -> DuneEventIdValue {
+    (id.height, id.index, self.height, self.transaction, self.number)
+  }
+
+  pub(super) fn from_key((_, _, height, transaction, number): DuneEventIdValue) -> Self {
+    Self {
+      height,
+      transaction,
+      number,
+    }
+  }
+}
+
+impl FromStr for DuneEventId {
+  type Err = Error;
+
+  fn from_str(s: &str) -> Result<Self, Self::Err> {
+    let mut parts = s.split(':');
+
+    let (Some(height), Some(transaction), Some(number), None) =
+      (parts.next(), parts.next(), parts.next(), parts.next())
+    else {
+      bail!("invalid dune event id `{s}`");
+    };
+
+    Ok(Self {
+      height: height.parse()?,
+      transaction: transaction.parse()?,
+      number: number.parse()?,
+    })
+  }
+}
+
+impl Display for DuneEventId {
+  fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+    write!(f, "{}:{}:{}", self.height, self.transaction, self.number)
+  }
+}
+
+pub(super) type CenotaphValue = (
+  u8,           // flaw
+  Option<u128>, // etched dune
+);
+
+impl Entry for Cenotaph {
+  type Value = CenotaphValue;
+
+  fn load((flaw, etching): CenotaphValue) -> Self {
+    Self {
+      etching: etching.map(Dune),
+      flaw: match flaw {
+        0 => Flaw::EdictDuneId,
+        1 => Flaw::EdictOutput,
+        2 => Flaw::SupplyOverflow,
+        3 => Flaw::TrailingIntegers,
+        4 => Flaw::UnrecognizedEvenTag,
+        5 => Flaw::UnrecognizedFlag,
+        _ => Flaw::Varint,
+      },
+    }
+  }
+
+  fn store(self) -> Self::Value {
+    (
+      match self.flaw {
+        Flaw::EdictDuneId => 0,
+        Flaw::EdictOutput => 1,
+        Flaw::SupplyOverflow => 2,
+        Flaw::TrailingIntegers => 3,
+        Flaw::UnrecognizedEvenTag => 4,
+        Flaw::UnrecognizedFlag => 5,
+        Flaw::Varint => 6,
+      },
+      self.etching.map(|dune| dune.0),
+    )
+  }
+}
+
 pub(crate) struct InscriptionEntry {
   pub(crate) fee: u64,
   pub(crate) height: u32,
@@ -376,9 +708,10 @@ impl Entry for InscriptionId {
 pub(crate) struct OutPointMap {
   pub(crate) value: u64,
   pub(crate) address: [u8; 34],
+  pub(crate) height: u32,
 }
 
-pub(crate) type OutPointMapValue = (u64, [u8; 34]);
+pub(crate) type OutPointMapValue = (u64, [u8; 34], u32);
 
 impl Entry for OutPointMap {
   type Value = OutPointMapValue;
@@ -388,6 +721,7 @@ impl Entry for OutPointMap {
     Self {
       value: value.0,
       address: value.1,
+      height: value.2,
     }
   }
 
@@ -395,10 +729,36 @@ impl Entry for OutPointMap {
     (
       self.value,
       self.address,
+      self.height,
     )
   }
 }
 
+/// Key of the address output table: the address, the height the output was
+/// created at and the outpoint, so that iterating over an address's keys
+/// yields its outputs oldest first
+pub(super) type AddressOutPointValue = [u8; 74];
+
+impl Entry for ([u8; 34], u32, OutPoint) {
+  type Value = AddressOutPointValue;
+
+  fn load(value: Self::Value) -> Self {
+    (
+      value[..34].try_into().unwrap(),
+      u32::from_be_bytes(value[34..38].try_into().unwrap()),
+      OutPoint::load(value[38..].try_into().unwrap()),
+    )
+  }
+
+  fn store(self) -> Self::Value {
+    let mut value = [0; 74];
+    value[..34].copy_from_slice(&self.0);
+    value[34..38].copy_from_slice(&self.1.to_be_bytes());
+    value[38..].copy_from_slice(&self.2.store());
+    value
+  }
+}
+
 pub(super) type OutPointValue = [u8; 36];
 
 impl Entry for OutPoint {
@@ -0,0 +1,119 @@
+use super::*;
+
+/// A change to the index, broadcast to subscribers once the block that caused
+/// it has been committed.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "type", rename_all = "kebab-case")]
+pub(crate) enum Event {
+  Block {
+    height: u32,
+    hash: BlockHash,
+  },
+  Inscription {
+    height: u32,
+    inscription_id: InscriptionId,
+    inscription_number: u64,
+    satpoint: SatPoint,
+    address: Option<String>,
+  },
+  Dune {
+    height: u32,
+    id: DuneId,
+    kind: DuneEventKind,
+    amount: u128,
+    txid: Txid,
+    vout: Option<u32>,
+    address: Option<String>,
+  },
+  Reorg {
+    height: u32,
+    depth: u32,
+  },
+}
+
+impl Event {
+  /// The name of the event, used as the SSE event type
+  pub(crate) fn name(&self) -> &'static str {
+    match self {
+      Self::Block { .. } => "block",
+      Self::Inscription { .. } => "inscription",
+      Self::Dune { .. } => "dune",
+      Self::Reorg { .. } => "reorg",
+    }
+  }
+
+  /// Whether the event passes the subscriber's filters. Block and reorg
+  /// events are always sent. A dune filter only passes dune events for that
+  /// dune, and an address filter only passes events with that address.
+  pub(crate) fn matches(&self, dune: Option<DuneId>, address: Option<&str>) -> bool {
+    match self {
+      Self::Block { .. } | Self::Reorg { .. } => true,
+      Self::Inscription {
+        address: event_address,
+        ..
+      } => {
+        dune.is_none() && address.map_or(true, |address| event_address.as_deref() == Some(address))
+      }
+      Self::Dune {
+        id,
+        address: event_address,
+        ..
+      } => {
+        dune.map_or(true, |dune| dune == *id)
+          && address.map_or(true, |address| event_address.as_deref() == Some(address))
+      }
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn filters() {
+    let id = DuneId {
+      height: 1,
+      index: 0,
+    };
+
+    let dune = Event::Dune {
+      height: 1,
+      id,
+      kind: DuneEventKind::Transfer,
+      amount: 1,
+      txid: Txid::all_zeros(),
+      vout: Some(0),
+      address: Some("foo".into()),
+    };
+
+    let reorg = Event::Reorg {
+      height: 1,
+      depth: 1,
+    };
+
+    assert!(dune.matches(None, None));
+    assert!(dune.matches(Some(id), Some("foo")));
+    assert!(!dune.matches(
+      Some(DuneId {
+        height: 2,
+        index: 0
+      }),
+      None
+    ));
+    assert!(!dune.matches(None, Some("bar")));
+    assert!(reorg.matches(Some(id), Some("bar")));
+  }
+
+  #[test]
+  fn serialization() {
+    assert_eq!(
+      serde_json::to_string(&Event::Reorg {
+        height: 10,
+        depth: 2
+      })
+      .unwrap(),
+      r#"{"type":"reorg","height":10,"depth":2}"#,
+    );
+  }
+}
@@ -0,0 +1,291 @@
+use {
+  super::{
+    updater::{allocate_edicts, allocate_remainder, claim},
+    *,
+  },
+  crate::dunes::Dunestone,
+};
+
+/// An in-memory overlay of unconfirmed transactions on top of the index.
+///
+/// Dune balances are allocated with the same edict and pointer logic used
+/// when indexing blocks. Etchings are ignored, since a dune's ID is only
+/// assigned once its etching confirms, and mints are checked against the
+/// dune's terms at the next block height without counting other unconfirmed
+/// mints.
+///
+/// Inscriptions are not tracked through unconfirmed transactions. Instead,
+/// every output of a transaction that reveals an inscription or spends an
+/// output that may hold one is considered to possibly hold inscriptions.
+#[derive(Clone, Default)]
+pub(crate) struct Mempool {
+  address_outputs: HashMap<String, Vec<(OutPoint, u64)>>,
+  dune_balances: HashMap<OutPoint, Vec<(DuneId, u128)>>,
+  entries: HashMap<DuneId, DuneEntry>,
+  inscribed: HashSet<OutPoint>,
+  spent: HashSet<OutPoint>,
+  transactions: HashMap<Txid, Transaction>,
+}
+
+impl Mempool {
+  /// Rebuild the overlay from the node's current mempool. Transactions seen
+  /// by a previous update are not fetched again.
+  pub(crate) fn update(&mut self, index: &Index) -> Result {
+    let mut transactions = HashMap::new();
+
+    for txid in index.client.get_raw_mempool()? {
+      let transaction = match self.transactions.remove(&txid) {
+        Some(transaction) => transaction,
+        None => match index.client.get_raw_transaction(&txid).into_option()? {
+          Some(transaction) => transaction,
+          // confirmed or evicted since the call to `getrawmempool`
+          None => continue,
+        },
+      };
+
+      transactions.insert(txid, transaction);
+    }
+
+    let mut mempool = Self {
+      transactions,
+      ..Default::default()
+    };
+
+    mempool.index_transactions(index)?;
+
+    *self = mempool;
+
+    Ok(())
+  }
+
+  pub(crate) fn is_spent(&self, outpoint: OutPoint) -> bool {
+    self.spent.contains(&outpoint)
+  }
+
+  pub(crate) fn may_hold_inscriptions(&self, outpoint: OutPoint) -> bool {
+    self.inscribed.contains(&outpoint)
+  }
+
+  pub(crate) fn get_address_outputs(&self, address: &str) -> &[(OutPoint, u64)] {
+    self
+      .address_outputs
+      .get(address)
+      .map(Vec::as_slice)
+      .unwrap_or_default()
+  }
+
+  pub(crate) fn get_dune_balances_for_outpoint(
+    &self,
+    outpoint: OutPoint,
+  ) -> Vec<(SpacedDune, Pile)> {
+    self
+      .dune_balances
+      .get(&outpoint)
+      .into_iter()
+      .flatten()
+      .map(|(id, amount)| {
+        let entry = &self.entries[id];
+        (
+          entry.spaced_dune(),
+          Pile {
+            amount: *amount,
+            divisibility: entry.divisibility,
+            symbol: entry.symbol,
+          },
+        )
+      })
+      .collect()
+  }
+
+  fn index_transactions(&mut self, index: &Index) -> Result {
+    let height = index.block_count()?;
+    let rules = index.protocol().dunestone_rules(height);
+
+    let rtx = index.database.begin_read()?;
+    let outpoint_to_balances = rtx.open_table(OUTPOINT_TO_DUNE_BALANCES)?;
+    let id_to_entry = rtx.open_table(DUNE_ID_TO_DUNE_ENTRY)?;
+    let satpoint_to_id = rtx.open_table(SATPOINT_TO_INSCRIPTION_ID)?;
+
+    // Transactions are indexed after the unconfirmed transactions they
+    // spend, so that their inputs' balances are known
+    let mut indexed = HashSet::new();
+    let mut pending = self.transactions.keys().copied().collect::<Vec<Txid>>();
+
+    while !pending.is_empty() {
+      let (ready, waiting) = pending.into_iter().partition::<Vec<Txid>, _>(|txid| {
+        self.transactions[txid].input.iter().all(|input| {
+          let parent = input.previous_output.txid;
+          !self.transactions.contains_key(&parent) || indexed.contains(&parent)
+        })
+      });
+
+      if ready.is_empty() {
+        break;
+      }
+
+      for txid in ready {
+        let tx = self.transactions[&txid].clone();
+
+        self.index_transaction(
+          &tx,
+          txid,
+          height,
+          rules,
+          index.has_dune_index(),
+          &outpoint_to_balances,
+          &id_to_entry,
+          &satpoint_to_id,
+        )?;
+
+        indexed.insert(txid);
+      }
+
+      pending = waiting;
+    }
+
+    Ok(())
+  }
+
+  fn index_transaction(
+    &mut self,
+    tx: &Transaction,
+    txid: Txid,
+    height: u32,
+    rules: Rules,
+    index_dunes: bool,
+    outpoint_to_balances: &impl ReadableTable<&'static OutPointValue, &'static [u8]>,
+    id_to_entry: &impl ReadableTable<DuneIdValue, DuneEntryValue>,
+    satpoint_to_id: &impl ReadableTable<&'static SatPointValue, &'static InscriptionIdValue>,
+  ) -> Result {
+    let mut unallocated: HashMap<u128, u128> = HashMap::new();
+
+    let mut inscribed = !matches!(
+      Inscription::from_transactions(vec![tx.clone()]),
+      ParsedInscription::None
+    );
+
+    for input in &tx.input {
+      self.spent.insert(input.previous_output);
+
+      inscribed = inscribed
+        || self.inscribed.contains(&input.previous_output)
+        || Index::inscriptions_on_output(satpoint_to_id, input.previous_output)?
+          .next()
+          .is_some();
+
+      let balances = match self.dune_balances.get(&input.previous_output) {
+        Some(balances) => balances.clone(),
+        None => outpoint_to_balances
+          .get(&input.previous_output.store())?
+          .map(|balances| {
+            entry::decode_dune_balances(balances.value())
+              .map(|(id, amount)| (DuneId::try_from(id).unwrap(), amount))
+              .collect()
+          })
+          .unwrap_or_default(),
+      };
+
+      for (id, balance) in balances {
+        *unallocated.entry(id.into()).or_default() += balance;
+      }
+    }
+
+    let mut allocated: Vec<HashMap<u128, u128>> = vec![HashMap::new(); tx.output.len()];
+
+    if index_dunes {
+      let dunestone = Dunestone::from_transaction_with_rules(tx, rules);
+
+      // Cenotaphs burn all input dunes, so nothing is allocated
+      if !dunestone
+        .as_ref()
+        .map(|dunestone| dunestone.is_cenotaph())
+        .unwrap_or_default()
+      {
+        let pointer = dunestone
+          .as_ref()
+          .and_then(|dunestone| dunestone.pointer)
+          .and_then(|pointer| usize::try_from(pointer).ok());
+
+        if let Some(dunestone) = dunestone {
+          let mut mintable = HashMap::new();
+
+          for id in dunestone.edicts.iter().filter_map(|edict| claim(edict.id)) {
+            let Ok(key) = DuneId::try_from(id) else {
+              continue;
+            };
+
+            if let Some(entry) = id_to_entry.get(key.store())? {
+              if let Ok(limit) = DuneEntry::load(entry.value()).mintable(height.into()) {
+                mintable.insert(id, limit);
+              }
+            }
+          }
+
+          allocate_edicts(
+            tx,
+            dunestone.edicts,
+            None,
+            &mut mintable,
+            &mut unallocated,
+            &mut allocated,
+          );
+        }
+
+        allocate_remainder(
+          tx,
+          pointer,
+          unallocated,
+          &mut allocated,
+          &mut HashMap::new(),
+        );
+      }
+    }
+
+    for (vout, (tx_out, balances)) in tx.output.iter().zip(allocated).enumerate() {
+      if tx_out.script_pubkey.is_op_return() {
+        continue;
+      }
+
+      let outpoint = OutPoint {
+        txid,
+        vout: vout.try_into().unwrap(),
+      };
+
+      if inscribed {
+        self.inscribed.insert(outpoint);
+      }
+
+      // Addresses are keyed the same way as in `ADDRESS_TO_OUTPOINT`
+      if let Ok(address) = Chain::Mainnet.address_from_script(&tx_out.script_pubkey) {
+        self
+          .address_outputs
+          .entry(address.to_string())
+          .or_default()
+          .push((outpoint, tx_out.value));
+      }
+
+      let mut dune_balances = Vec::new();
+
+      for (id, balance) in balances {
+        let id = DuneId::try_from(id).unwrap();
+
+        if !self.entries.contains_key(&id) {
+          let Some(entry) = id_to_entry.get(id.store())? else {
+            continue;
+          };
+
+          self.entries.insert(id, DuneEntry::load(entry.value()));
+        }
+
+        dune_balances.push((id, balance));
+      }
+
+      if !dune_balances.is_empty() {
+        dune_balances.sort();
+        self.dune_balances.insert(outpoint, dune_balances);
+      }
+    }
+
+    Ok(())
+  }
+}
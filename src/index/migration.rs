@@ -0,0 +1,908 @@
+use {
+  super::*,
+  redb::{MultimapTableDefinition, ReadableMultimapTable},
+};
+
+/// Upgrades an index from schema `from` to schema `from + 1` in place,
+/// returning the number of entries it wrote to each table
+pub(crate) struct Migration {
+  pub(crate) from: u64,
+  pub(crate) description: &'static str,
+  run: fn(&WriteTransaction, &Client, &Options) -> Result<BTreeMap<String, u64>>,
+}
+
+/// Migrations in schema order. When changing the layout of the index, bump
+/// `SCHEMA_VERSION` and add a migration from the previous version here.
+const MIGRATIONS: &[Migration] = &[Migration {
+  from: 5,
+  description:
+    "add creation height to output values, key address outputs by height, index dune holders and cenotaphs and record protocol parameters",
+  run: v5_to_v6,
+}];
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+pub struct MigrationOutput {
+  pub from: u64,
+  pub to: u64,
+  pub description: String,
+  pub entries: BTreeMap<String, u64>,
+}
+
+impl Migration {
+  /// Migrations that upgrade an index at `schema_version` to
+  /// `SCHEMA_VERSION`, or `None` if there is no such path
+  pub(crate) fn path(schema_version: u64) -> Option<Vec<&'static Migration>> {
+    (schema_version..SCHEMA_VERSION)
+      .map(|from| MIGRATIONS.iter().find(|migration| migration.from == from))
+      .collect()
+  }
+}
+
+impl Index {
+  /// Run all migrations needed to bring the index up to `SCHEMA_VERSION` in
+  /// a single write transaction, which is aborted instead of committed if
+  /// `dry_run` is set
+  pub(crate) fn migrate(options: &Options, dry_run: bool) -> Result<Vec<MigrationOutput>> {
+    let path = Self::database_path(options)?;
+
+    ensure!(path.exists(), "no index at `{}`", path.display());
+
+    let database = Database::builder()
+      .set_cache_size(options.db_cache_size()?)
+      .open(&path)?;
+
+    let wtx = database.begin_write()?;
+
+    let schema_version = wtx
+      .open_table(STATISTIC_TO_COUNT)?
+      .get(&Statistic::Schema.key())?
+      .map(|x| x.value())
+      .unwrap_or(0);
+
+    ensure!(
+      schema_version <= SCHEMA_VERSION,
+      "index at `{}` appears to have been built with a newer, incompatible version of ord, consider updating ord: index schema {schema_version}, ord schema {SCHEMA_VERSION}",
+      path.display(),
+    );
+
+    let migrations = Migration::path(schema_version).with_context(|| {
+      format!(
+        "no migration from index schema {schema_version} to ord schema {SCHEMA_VERSION}, consider deleting and rebuilding the index"
+      )
+    })?;
+
+    if migrations.is_empty() {
+      return Ok(Vec::new());
+    }
+
+    // Events and balance history can only be recorded while indexing blocks,
+    // so an index with them enabled must be rebuilt
+    ensure!(
+      !options.index_dune_events && !options.index_dune_history,
+      "dune events and balance history cannot be added to an existing index, migrate without `--index-dune-events` and `--index-dune-history` or delete and rebuild the index",
+    );
+
+    let client = options.dogecoin_rpc_client()?;
+
+    // Restoring a savepoint taken before the migration would roll the index
+    // back to the old schema
+    for savepoint in wtx.list_persistent_savepoints()?.collect::<Vec<u64>>() {
+      wtx.delete_persistent_savepoint(savepoint)?;
+    }
+
+    let mut outputs = Vec::new();
+
+    for migration in migrations {
+      log::info!(
+        "Migrating index from schema {} to {}: {}",
+        migration.from,
+        migration.from + 1,
+        migration.description,
+      );
+
+      let entries = (migration.run)(&wtx, &client, options)?;
+
+      Index::set_statistic(&wtx, Statistic::Schema, migration.from + 1)?;
+
+      outputs.push(MigrationOutput {
+        from: migration.from,
+        to: migration.from + 1,
+        description: migration.description.into(),
+        entries,
+      });
+    }
+
+    if dry_run {
+      wtx.abort()?;
+    } else {
+      Index::increment_statistic(&wtx, Statistic::Commits, 1)?;
+      wtx.commit()?;
+    }
+
+    Ok(outputs)
+  }
+}
+
+const OUTPOINT_TO_VALUE_V5: TableDefinition<&OutPointValue, u64> =
+  TableDefinition::new("OUTPOINT_TO_VALUE");
+const ADDRESS_TO_OUTPOINT_V5: MultimapTableDefinition<&[u8], &OutPointValue> =
+  MultimapTableDefinition::new("ADDRESS_TO_OUTPOINT");
+
+// redb can't rename tables or change their types, so new entries are written
+// to staging tables, which are copied into place once the old tables are
+// deleted
+const OUTPOINT_TO_VALUE_STAGING: TableDefinition<&OutPointValue, (u64, u32)> =
+  TableDefinition::new("OUTPOINT_TO_VALUE_STAGING");
+const ADDRESS_TO_OUTPOINT_STAGING: TableDefinition<&AddressOutPointValue, u64> =
+  TableDefinition::new("ADDRESS_TO_OUTPOINT_STAGING");
+
+/// Schema 6 records the height at which each unspent output was created,
+/// replaces the `ADDRESS_TO_OUTPOINT` multimap with a table keyed by address,
+/// height and outpoint, indexes the holders of dunes and cenotaphs, and
+/// records the protocol parameters of the index. Schema 5 indexes were built
+/// with the legacy protocol parameters. Heights, scripts and dunestones come
+/// from the indexed blocks, each of which is fetched from Dogecoin Core once.
+fn v5_to_v6(
+  wtx: &WriteTransaction,
+  client: &Client,
+  options: &Options,
+) -> Result<BTreeMap<String, u64>> {
+  let protocol = options.legacy_protocol_parameters()?;
+
+  let index_dunes = wtx
+    .open_table(STATISTIC_TO_COUNT)?
+    .get(&Statistic::IndexDunes.key())?
+    .map(|x| x.value())
+    .unwrap_or(0)
+    != 0;
+
+  let mut entries = BTreeMap::new();
+  let mut outputs = 0;
+  let mut dune_holders = 0;
+  let mut cenotaphs = 0;
+  let mut balances = BTreeMap::<(DuneId, [u8; 34]), u128>::new();
+
+  {
+    let height_to_block_hash = wtx.open_table(HEIGHT_TO_BLOCK_HASH)?;
+    let outpoint_to_value = wtx.open_table(OUTPOINT_TO_VALUE_V5)?;
+    let mut staging = wtx.open_table(OUTPOINT_TO_VALUE_STAGING)?;
+
+    let mut dunes = if index_dunes {
+      Some((
+        wtx.open_table(DUNE_ID_TO_DUNE_ENTRY)?,
+        wtx.open_table(OUTPOINT_TO_DUNE_BALANCES)?,
+        wtx.open_table(OUTPOINT_TO_DUNE_HOLDER)?,
+        wtx.open_table(TRANSACTION_ID_TO_CENOTAPH)?,
+      ))
+    } else {
+      None
+    };
+
+    let mut dune_outputs = 0;
+
+    for result in height_to_block_hash.iter()? {
+      let (height, hash) = result?;
+      let height = height.value();
+
+      if height % 10_000 == 0 {
+        log::info!("Migrating outputs created in block {height}");
+      }
+
+      let block = client.get_block(&BlockHash::load(*hash.value()))?;
+
+      for (index, tx) in block.txdata.iter().enumerate() {
+        let txid = tx.txid();
+
+        let start = OutPoint { txid, vout: 0 }.store();
+        let end = OutPoint {
+          txid,
+          vout: u32::MAX,
+        }
+        .store();
+
+        for result in outpoint_to_value.range(&start..=&end)? {
+          let (outpoint, value) = result?;
+          staging.insert(outpoint.value(), (value.value(), height))?;
+          outputs += 1;
+        }
+
+        let Some((
+          id_to_entry,
+          outpoint_to_balances,
+          outpoint_to_dune_holder,
+          transaction_id_to_cenotaph,
+        )) = dunes.as_mut()
+        else {
+          continue;
+        };
+
+        for result in outpoint_to_balances.range(&start..=&end)? {
+          let (outpoint, buffer) = result?;
+          let outpoint = OutPoint::load(*outpoint.value());
+
+          dune_outputs += 1;
+
+          let Some(holder) = tx
+            .output
+            .get(usize::try_from(outpoint.vout).unwrap())
+            .and_then(|output| updater::address(&output.script_pubkey))
+          else {
+            continue;
+          };
+
+          outpoint_to_dune_holder.insert(&outpoint.store(), &holder)?;
+          dune_holders += 1;
+
+          for (id, balance) in entry::decode_dune_balances(buffer.value()) {
+            *balances
+              .entry((DuneId::try_from(id).unwrap(), holder))
+              .or_default() += balance;
+          }
+        }
+
+        if height < protocol.first_dune_height {
+          continue;
+        }
+
+        let Some(Dunestone {
+          flaw: Some(flaw), ..
+        }) = Dunestone::from_transaction_with_rules(tx, protocol.dunestone_rules(height))
+        else {
+          continue;
+        };
+
+        // Cenotaphs still etch their dune, which is recorded under the ID of
+        // the etching transaction
+        let etching = id_to_entry
+          .get(
+            DuneId {
+              height: height.into(),
+              index: u32::try_from(index).unwrap(),
+            }
+            .store(),
+          )?
+          .map(|entry| DuneEntry::load(entry.value()))
+          .filter(|entry| entry.etching == txid)
+          .map(|entry| entry.dune);
+
+        transaction_id_to_cenotaph.insert(&txid.store(), Cenotaph { etching, flaw }.store())?;
+        cenotaphs += 1;
+      }
+    }
+
+    let unmigrated = outpoint_to_value.len()? - outputs;
+
+    ensure!(
+      unmigrated == 0,
+      "{unmigrated} outputs in OUTPOINT_TO_VALUE were not created in an indexed block",
+    );
+
+    if let Some((_, outpoint_to_balances, _, _)) = dunes {
+      let unmigrated = outpoint_to_balances.len()? - dune_outputs;
+
+      ensure!(
+        unmigrated == 0,
+        "{unmigrated} outputs in OUTPOINT_TO_DUNE_BALANCES were not created in an indexed block",
+      );
+
+      entries.insert(OUTPOINT_TO_DUNE_HOLDER.name().into(), dune_holders);
+      entries.insert(TRANSACTION_ID_TO_CENOTAPH.name().into(), cenotaphs);
+    }
+
+    entries.insert(OUTPOINT_TO_VALUE.name().into(), outputs);
+  }
+
+  {
+    let address_to_outpoint = wtx.open_multimap_table(ADDRESS_TO_OUTPOINT_V5)?;
+    let outpoint_to_value = wtx.open_table(OUTPOINT_TO_VALUE_STAGING)?;
+    let mut staging = wtx.open_table(ADDRESS_TO_OUTPOINT_STAGING)?;
+
+    let mut address_outputs = 0;
+
+    for result in address_to_outpoint.iter()? {
+      let (address, outpoints) = result?;
+
+      let address = <[u8; 34]>::try_from(address.value()).map_err(|_| {
+        anyhow!(
+          "ADDRESS_TO_OUTPOINT key `{}` is {} bytes long, expected 34",
+          String::from_utf8_lossy(address.value()),
+          address.value().len(),
+        )
+      })?;
+
+      for outpoint in outpoints {
+        let outpoint = OutPoint::load(*outpoint?.value());
+
+        let Some(entry) = outpoint_to_value.get(&outpoint.store())? else {
+          continue;
+        };
+
+        let (value, height) = entry.value();
+
+        staging.insert(&(address, height, outpoint).store(), value)?;
+        address_outputs += 1;
+      }
+    }
+
+    entries.insert(ADDRESS_TO_OUTPOINT.name().into(), address_outputs);
+  }
+
+  wtx.delete_table(OUTPOINT_TO_VALUE_V5)?;
+  wtx.delete_multimap_table(ADDRESS_TO_OUTPOINT_V5)?;
+
+  {
+    let staging = wtx.open_table(OUTPOINT_TO_VALUE_STAGING)?;
+    let mut outpoint_to_value = wtx.open_table(OUTPOINT_TO_VALUE)?;
+
+    for result in staging.iter()? {
+      let (outpoint, value) = result?;
+      outpoint_to_value.insert(outpoint.value(), value.value())?;
+    }
+  }
+
+  {
+    let staging = wtx.open_table(ADDRESS_TO_OUTPOINT_STAGING)?;
+    let mut address_to_outpoint = wtx.open_table(ADDRESS_TO_OUTPOINT)?;
+
+    for result in staging.iter()? {
+      let (key, value) = result?;
+      address_to_outpoint.insert(key.value(), value.value())?;
+    }
+  }
+
+  wtx.delete_table(OUTPOINT_TO_VALUE_STAGING)?;
+  wtx.delete_table(ADDRESS_TO_OUTPOINT_STAGING)?;
+
+  if index_dunes {
+    let mut dune_holder_to_balance = wtx.open_table(DUNE_HOLDER_TO_BALANCE)?;
+    let mut dune_holder_rank = wtx.open_table(DUNE_HOLDER_RANK)?;
+    let mut dune_id_to_holder_count = wtx.open_table(DUNE_ID_TO_HOLDER_COUNT)?;
+
+    entries.insert(
+      DUNE_HOLDER_TO_BALANCE.name().into(),
+      balances.len().try_into()?,
+    );
+    entries.insert(DUNE_HOLDER_RANK.name().into(), balances.len().try_into()?);
+
+    let mut holders = BTreeMap::<DuneId, u64>::new();
+
+    for ((id, holder), balance) in balances {
+      dune_holder_to_balance.insert(&(id, holder).store(), balance)?;
+      dune_holder_rank.insert(&(id, balance, holder).store(), ())?;
+      *holders.entry(id).or_default() += 1;
+    }
+
+    entries.insert(
+      DUNE_ID_TO_HOLDER_COUNT.name().into(),
+      holders.len().try_into()?,
+    );
+
+    for (id, count) in holders {
+      dune_id_to_holder_count.insert(id.store(), count)?;
+    }
+  }
+
+  {
+    let mut protocol_parameters = wtx.open_table(PROTOCOL_PARAMETER_TO_VALUE)?;
+
+    for (name, value) in protocol.entries() {
+      protocol_parameters.insert(name, value)?;
+    }
+
+    entries.insert(
+      PROTOCOL_PARAMETER_TO_VALUE.name().into(),
+      protocol.entries().len().try_into()?,
+    );
+  }
+
+  Ok(entries)
+}
+
+#[cfg(test)]
+mod tests {
+  use {
+    super::*,
+    crate::index::testing::{Context, ContextBuilder},
+  };
+
+  #[test]
+  fn every_schema_since_5_can_be_migrated() {
+    for schema_version in 5..=SCHEMA_VERSION {
+      assert_eq!(
+        Migration::path(schema_version).unwrap().len(),
+        usize::try_from(SCHEMA_VERSION - schema_version).unwrap(),
+      );
+    }
+
+    assert!(Migration::path(4).is_none());
+  }
+
+  /// Schema 5 indexes were built with the legacy protocol parameters, which
+  /// regtest only uses when configured to
+  fn legacy_context() -> ContextBuilder {
+    let tempdir = TempDir::new().unwrap();
+
+    let config = tempdir.path().join("ord.yaml");
+
+    fs::write(
+      &config,
+      "protocol:\n  dune_unlock_height: 5084000\n  strict_dunestone_height: 4294967295\n",
+    )
+    .unwrap();
+
+    Context::builder()
+      .arg("--config")
+      .arg(config)
+      .tempdir(tempdir)
+  }
+
+  /// Replace `index` with an index laid out as schema 5 ord would have
+  /// written it, with the blocks and dune entries of `index`, `outputs` as
+  /// the unspent outputs of `address` and `balances` as the dune balances of
+  /// outputs
+  fn schema_5_fixture(
+    index: Index,
+    address: &str,
+    outputs: &[(OutPoint, u64)],
+    balances: &[(OutPoint, Vec<(DuneId, u128)>)],
+  ) {
+    let path = index.path.clone();
+
+    let (blocks, dunes) = {
+      let rtx = index.database.begin_read().unwrap();
+
+      let blocks = rtx
+        .open_table(HEIGHT_TO_BLOCK_HASH)
+        .unwrap()
+        .iter()
+        .unwrap()
+        .map(|result| {
+          let (height, hash) = result.unwrap();
+          (height.value(), *hash.value())
+        })
+        .collect::<Vec<(u32, BlockHashValue)>>();
+
+      let dunes = match rtx.open_table(DUNE_ID_TO_DUNE_ENTRY) {
+        Ok(table) => table
+          .iter()
+          .unwrap()
+          .map(|result| {
+            let (id, entry) = result.unwrap();
+            (id.value(), entry.value())
+          })
+          .collect::<Vec<(DuneIdValue, DuneEntryValue)>>(),
+        Err(redb::TableError::TableDoesNotExist(_)) => Vec::new(),
+        Err(err) => panic!("{err}"),
+      };
+
+      (blocks, dunes)
+    };
+
+    drop(index);
+
+    fs::remove_file(&path).unwrap();
+
+    let database = Database::builder().create(&path).unwrap();
+
+    let wtx = database.begin_write().unwrap();
+
+    wtx.open_table(INSCRIPTION_ID_TO_INSCRIPTION_ENTRY).unwrap();
+    wtx.open_table(INSCRIPTION_ID_TO_DUNE).unwrap();
+    wtx.open_table(INSCRIPTION_ID_TO_SATPOINT).unwrap();
+    wtx
+      .open_table(INSCRIPTION_NUMBER_TO_INSCRIPTION_ID)
+      .unwrap();
+    wtx.open_table(INSCRIPTION_ID_TO_TXIDS).unwrap();
+    wtx.open_table(INSCRIPTION_TXID_TO_TX).unwrap();
+    wtx.open_table(PARTIAL_TXID_TO_INSCRIPTION_TXIDS).unwrap();
+    wtx.open_table(OUTPOINT_TO_SAT_RANGES).unwrap();
+    wtx.open_table(DUNE_TO_DUNE_ID).unwrap();
+    wtx.open_table(SATPOINT_TO_INSCRIPTION_ID).unwrap();
+    wtx.open_table(SAT_TO_INSCRIPTION_ID).unwrap();
+    wtx.open_table(SAT_TO_SATPOINT).unwrap();
+    wtx.open_table(TRANSACTION_ID_TO_DUNE).unwrap();
+    wtx.open_table(TRANSACTION_ID_TO_TRANSACTION).unwrap();
+    wtx
+      .open_table(WRITE_TRANSACTION_STARTING_BLOCK_COUNT_TO_TIMESTAMP)
+      .unwrap();
+
+    {
+      let mut height_to_block_hash = wtx.open_table(HEIGHT_TO_BLOCK_HASH).unwrap();
+
+      for (height, hash) in blocks {
+        height_to_block_hash.insert(height, &hash).unwrap();
+      }
+
+      let mut dune_id_to_dune_entry = wtx.open_table(DUNE_ID_TO_DUNE_ENTRY).unwrap();
+
+      for (id, entry) in dunes {
+        dune_id_to_dune_entry.insert(id, entry).unwrap();
+      }
+    }
+
+    {
+      let mut outpoint_to_value = wtx.open_table(OUTPOINT_TO_VALUE_V5).unwrap();
+      let mut address_to_outpoint = wtx.open_multimap_table(ADDRESS_TO_OUTPOINT_V5).unwrap();
+
+      for (outpoint, value) in outputs {
+        outpoint_to_value.insert(&outpoint.store(), value).unwrap();
+        address_to_outpoint
+          .insert(address.as_bytes(), &outpoint.store())
+          .unwrap();
+      }
+    }
+
+    {
+      let mut outpoint_to_balances = wtx.open_table(OUTPOINT_TO_DUNE_BALANCES).unwrap();
+
+      for (outpoint, balances) in balances {
+        let mut buffer = Vec::new();
+
+        for (id, balance) in balances {
+          dunes::varint::encode_to_vec(u128::from(*id), &mut buffer);
+          dunes::varint::encode_to_vec(*balance, &mut buffer);
+        }
+
+        outpoint_to_balances
+          .insert(&outpoint.store(), buffer.as_slice())
+          .unwrap();
+      }
+    }
+
+    Index::set_statistic(&wtx, Statistic::Schema, 5).unwrap();
+    Index::set_statistic(&wtx, Statistic::IndexDunes, 1).unwrap();
+    Index::set_statistic(&wtx, Statistic::IndexSats, 0).unwrap();
+
+    wtx.commit().unwrap();
+  }
+
+  #[test]
+  fn schema_5_index_is_migrated() {
+    let context = legacy_context().build();
+
+    let blocks = context.mine_blocks(2);
+
+    let outpoint = |block: &Block| OutPoint {
+      txid: block.txdata[0].txid(),
+      vout: 0,
+    };
+
+    let address = "D".repeat(34);
+
+    let Context {
+      options,
+      index,
+      rpc_server: _rpc_server,
+      tempdir: _tempdir,
+    } = context;
+
+    schema_5_fixture(
+      index,
+      &address,
+      &[(outpoint(&blocks[1]), 200), (outpoint(&blocks[0]), 100)],
+      &[],
+    );
+
+    assert_eq!(
+      Index::open(&options).err().unwrap().to_string(),
+      format!(
+        "index at `{}` uses index schema 5, ord schema {SCHEMA_VERSION}, run `ord index migrate` to upgrade it",
+        options.data_dir().unwrap().join("index.redb").display(),
+      ),
+    );
+
+    let expected = vec![MigrationOutput {
+      from: 5,
+      to: 6,
+      description: MIGRATIONS[0].description.into(),
+      entries: [
+        ("ADDRESS_TO_OUTPOINT", 2),
+        ("DUNE_HOLDER_RANK", 0),
+        ("DUNE_HOLDER_TO_BALANCE", 0),
+        ("DUNE_ID_TO_HOLDER_COUNT", 0),
+        ("OUTPOINT_TO_DUNE_HOLDER", 0),
+        ("OUTPOINT_TO_VALUE", 2),
+        ("PROTOCOL_PARAMETER_TO_VALUE", 8),
+        ("TRANSACTION_ID_TO_CENOTAPH", 0),
+      ]
+      .into_iter()
+      .map(|(table, entries)| (table.into(), entries))
+      .collect(),
+    }];
+
+    assert_eq!(Index::migrate(&options, true).unwrap(), expected);
+
+    assert!(Index::open(&options).is_err());
+
+    assert_eq!(Index::migrate(&options, false).unwrap(), expected);
+
+    let index = Index::open(&options).unwrap();
+
+    assert_eq!(
+      index
+        .database
+        .begin_read()
+        .unwrap()
+        .open_table(PROTOCOL_PARAMETER_TO_VALUE)
+        .unwrap()
+        .iter()
+        .unwrap()
+        .map(|result| {
+          let (name, value) = result.unwrap();
+          (name.value().to_string(), value.value())
+        })
+        .collect::<BTreeMap<String, u128>>(),
+      Chain::Regtest
+        .legacy_protocol_parameters()
+        .entries()
+        .into_iter()
+        .map(|(name, value)| (name.to_string(), value))
+        .collect::<BTreeMap<String, u128>>(),
+    );
+
+    let (outputs, next) = index
+      .get_address_outputs(&address, None, 10, |_, _| Ok(true))
+      .unwrap();
+
+    assert_eq!(
+      outputs,
+      [
+        (1, outpoint(&blocks[0]), 100),
+        (2, outpoint(&blocks[1]), 200)
+      ],
+    );
+    assert_eq!(next, None);
+
+    assert_eq!(
+      index
+        .database
+        .begin_read()
+        .unwrap()
+        .open_table(OUTPOINT_TO_VALUE)
+        .unwrap()
+        .get(&outpoint(&blocks[1]).store())
+        .unwrap()
+        .unwrap()
+        .value(),
+      (200, 2),
+    );
+
+    assert_eq!(Index::migrate(&options, false).unwrap(), Vec::new());
+  }
+
+  #[test]
+  fn migration_indexes_dune_holders() {
+    let context = legacy_context().build();
+
+    context.mine_blocks(1);
+
+    let txid = context.rpc_server.broadcast_tx(TransactionTemplate {
+      inputs: &[(1, 0, 0)],
+      outputs: 3,
+      output_scripts: &[p2pkh(1), p2pkh(2)],
+      ..Default::default()
+    });
+
+    context.mine_blocks(1);
+
+    let outpoint = |vout| OutPoint { txid, vout };
+
+    let id = DuneId {
+      height: 2,
+      index: 1,
+    };
+
+    let other = DuneId {
+      height: 2,
+      index: 2,
+    };
+
+    let Context {
+      options,
+      index,
+      rpc_server: _rpc_server,
+      tempdir: _tempdir,
+    } = context;
+
+    schema_5_fixture(
+      index,
+      "",
+      &[],
+      &[
+        (outpoint(0), vec![(id, 100), (other, 5)]),
+        (outpoint(1), vec![(id, 50)]),
+        (outpoint(2), vec![(id, 25)]),
+      ],
+    );
+
+    let entries = &Index::migrate(&options, false).unwrap()[0].entries;
+
+    assert_eq!(entries["OUTPOINT_TO_DUNE_HOLDER"], 2);
+    assert_eq!(entries["DUNE_HOLDER_TO_BALANCE"], 3);
+    assert_eq!(entries["DUNE_HOLDER_RANK"], 3);
+    assert_eq!(entries["DUNE_ID_TO_HOLDER_COUNT"], 2);
+
+    let index = Index::open(&options).unwrap();
+
+    let rtx = index.database.begin_read().unwrap();
+
+    let holder = |n| updater::address(&p2pkh(n)).unwrap();
+
+    assert_eq!(
+      rtx
+        .open_table(OUTPOINT_TO_DUNE_HOLDER)
+        .unwrap()
+        .iter()
+        .unwrap()
+        .map(|result| {
+          let (outpoint, holder) = result.unwrap();
+          (OutPoint::load(*outpoint.value()), *holder.value())
+        })
+        .collect::<Vec<(OutPoint, [u8; 34])>>(),
+      [(outpoint(0), holder(1)), (outpoint(1), holder(2))],
+    );
+
+    let dune_holder_to_balance = rtx.open_table(DUNE_HOLDER_TO_BALANCE).unwrap();
+
+    let balance = |id: DuneId, n| {
+      dune_holder_to_balance
+        .get(&(id, holder(n)).store())
+        .unwrap()
+        .map(|balance| balance.value())
+    };
+
+    assert_eq!(balance(id, 1), Some(100));
+    assert_eq!(balance(id, 2), Some(50));
+    assert_eq!(balance(other, 1), Some(5));
+    assert_eq!(balance(other, 2), None);
+
+    assert_eq!(
+      rtx
+        .open_table(DUNE_HOLDER_RANK)
+        .unwrap()
+        .iter()
+        .unwrap()
+        .map(|result| <(DuneId, u128, [u8; 34])>::load(*result.unwrap().0.value()))
+        .collect::<Vec<(DuneId, u128, [u8; 34])>>(),
+      [
+        (id, 50, holder(2)),
+        (id, 100, holder(1)),
+        (other, 5, holder(1)),
+      ],
+    );
+
+    let dune_id_to_holder_count = rtx.open_table(DUNE_ID_TO_HOLDER_COUNT).unwrap();
+
+    assert_eq!(
+      dune_id_to_holder_count
+        .get(id.store())
+        .unwrap()
+        .unwrap()
+        .value(),
+      2
+    );
+    assert_eq!(
+      dune_id_to_holder_count
+        .get(other.store())
+        .unwrap()
+        .unwrap()
+        .value(),
+      1
+    );
+
+    assert_eq!(index.get_cenotaph(txid).unwrap(), None);
+  }
+
+  #[test]
+  fn migration_indexes_cenotaphs() {
+    let context = legacy_context().arg("--index-dunes").build();
+
+    context.mine_blocks(1);
+
+    let txid = context.rpc_server.broadcast_tx(TransactionTemplate {
+      inputs: &[(1, 0, 0)],
+      op_return: Some(
+        Dunestone {
+          etching: Some(Etching::default()),
+          flaw: Some(Flaw::UnrecognizedEvenTag),
+          ..Default::default()
+        }
+        .encipher(),
+      ),
+      ..Default::default()
+    });
+
+    context.mine_blocks(1);
+
+    let cenotaph = context.index.get_cenotaph(txid).unwrap();
+
+    assert!(cenotaph.unwrap().etching.is_some());
+
+    let Context {
+      options,
+      index,
+      rpc_server: _rpc_server,
+      tempdir: _tempdir,
+    } = context;
+
+    schema_5_fixture(index, "", &[], &[]);
+
+    assert_eq!(
+      Index::migrate(&options, false).unwrap()[0].entries["TRANSACTION_ID_TO_CENOTAPH"],
+      1
+    );
+
+    assert_eq!(
+      Index::open(&options).unwrap().get_cenotaph(txid).unwrap(),
+      cenotaph
+    );
+  }
+
+  #[test]
+  fn address_keys_that_are_not_34_bytes_are_an_error() {
+    let context = legacy_context().build();
+
+    let blocks = context.mine_blocks(1);
+
+    let Context {
+      options,
+      index,
+      rpc_server: _rpc_server,
+      tempdir: _tempdir,
+    } = context;
+
+    schema_5_fixture(
+      index,
+      "DDD",
+      &[(
+        OutPoint {
+          txid: blocks[0].txdata[0].txid(),
+          vout: 0,
+        },
+        100,
+      )],
+      &[],
+    );
+
+    assert_eq!(
+      Index::migrate(&options, false).unwrap_err().to_string(),
+      "ADDRESS_TO_OUTPOINT key `DDD` is 3 bytes long, expected 34",
+    );
+  }
+
+  #[test]
+  fn outputs_not_created_in_indexed_blocks_are_an_error() {
+    let Context {
+      options,
+      index,
+      rpc_server: _rpc_server,
+      tempdir: _tempdir,
+    } = legacy_context().build();
+
+    schema_5_fixture(index, "", &[(OutPoint::null(), 100)], &[]);
+
+    assert_eq!(
+      Index::migrate(&options, false).unwrap_err().to_string(),
+      "1 outputs in OUTPOINT_TO_VALUE were not created in an indexed block",
+    );
+  }
+
+  #[test]
+  fn indexes_with_dune_events_or_history_are_not_migrated() {
+    for flag in ["--index-dune-events", "--index-dune-history"] {
+      let Context {
+        options,
+        index,
+        rpc_server: _rpc_server,
+        tempdir: _tempdir,
+      } = Context::builder().args(["--index-dunes", flag]).build();
+
+      schema_5_fixture(index, "", &[], &[]);
+
+      assert_eq!(
+        Index::migrate(&options, false).unwrap_err().to_string(),
+        "dune events and balance history cannot be added to an existing index, migrate without `--index-dune-events` and `--index-dune-history` or delete and rebuild the index",
+      );
+    }
+  }
+}
@@ -0,0 +1,140 @@
+use {super::*, super::updater::BlockData};
+
+#[derive(Debug, PartialEq)]
+pub(crate) enum ReorgError {
+  Recoverable { height: u32, depth: u32 },
+  Unrecoverable,
+}
+
+impl Display for ReorgError {
+  fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+    match self {
+      Self::Recoverable { height, depth } => {
+        write!(f, "{depth} block deep reorg detected at height {height}")
+      }
+      Self::Unrecoverable => write!(f, "unrecoverable reorg detected"),
+    }
+  }
+}
+
+impl std::error::Error for ReorgError {}
+
+const MAX_SAVEPOINTS: u32 = 2;
+const SAVEPOINT_INTERVAL: u32 = 10;
+const CHAIN_TIP_DISTANCE: u32 = 21;
+
+pub(crate) struct Reorg {}
+
+impl Reorg {
+  pub(crate) fn detect_reorg(
+    block: &BlockData,
+    height: u32,
+    index: &Index,
+    height_to_block_hash: &impl ReadableTable<u32, &'static BlockHashValue>,
+  ) -> Result {
+    let Some(prev_height) = height.checked_sub(1) else {
+      return Ok(());
+    };
+
+    let index_prev_blockhash = height_to_block_hash
+      .get(&prev_height)?
+      .map(|hash| BlockHash::load(*hash.value()));
+
+    if index_prev_blockhash == Some(block.header.prev_blockhash) || index_prev_blockhash.is_none() {
+      return Ok(());
+    }
+
+    let max_recoverable_reorg_depth =
+      (MAX_SAVEPOINTS - 1) * SAVEPOINT_INTERVAL + height % SAVEPOINT_INTERVAL;
+
+    for depth in 1..max_recoverable_reorg_depth {
+      let Some(fork_height) = height.checked_sub(depth + 1) else {
+        break;
+      };
+
+      let index_block_hash = height_to_block_hash
+        .get(&fork_height)?
+        .map(|hash| BlockHash::load(*hash.value()));
+
+      let dogecoin_block_hash = index
+        .client
+        .get_block_hash(fork_height.into())
+        .into_option()?;
+
+      if index_block_hash == dogecoin_block_hash {
+        return Err(anyhow!(ReorgError::Recoverable { height, depth }));
+      }
+    }
+
+    Err(anyhow!(ReorgError::Unrecoverable))
+  }
+
+  pub(crate) fn handle_reorg(index: &Index, height: u32, depth: u32) -> Result {
+    log::info!("rolling back index after {depth} block deep reorg at height {height}");
+
+    let mut wtx = index.database.begin_write()?;
+
+    let Some(oldest_savepoint) = wtx.list_persistent_savepoints()?.min() else {
+      return Err(anyhow!(ReorgError::Unrecoverable));
+    };
+
+    let savepoint = wtx.get_persistent_savepoint(oldest_savepoint)?;
+
+    wtx.restore_savepoint(&savepoint)?;
+
+    Index::increment_statistic(&wtx, Statistic::Commits, 1)?;
+    wtx.commit()?;
+
+    let block_count = index.block_count()?;
+
+    // A savepoint taken before the fork point is required, otherwise blocks
+    // from the stale branch would remain in the index.
+    if height.saturating_sub(depth) < block_count {
+      return Err(anyhow!(ReorgError::Unrecoverable));
+    }
+
+    log::info!("rolled back index to height {block_count}");
+
+    Ok(())
+  }
+
+  /// Returns true if the index should commit at `height` so that a savepoint
+  /// can be taken. Savepoints are only kept for blocks close to the chain tip,
+  /// where reorgs are likely.
+  pub(crate) fn is_savepoint_height(height: u32, chain_block_count: u32) -> bool {
+    (height < SAVEPOINT_INTERVAL || height % SAVEPOINT_INTERVAL == 0)
+      && chain_block_count.saturating_sub(height) <= CHAIN_TIP_DISTANCE
+  }
+
+  /// Take a savepoint of the index, which holds `height` blocks, if `height`
+  /// is a savepoint height. `chain_block_count` is the number of blocks in
+  /// Dogecoin Core's best chain when the update started.
+  pub(crate) fn update_savepoints(index: &Index, height: u32, chain_block_count: u32) -> Result {
+    if !Self::is_savepoint_height(height, chain_block_count) {
+      return Ok(());
+    }
+
+    // Persistent savepoints require a durable commit, so these write
+    // transactions always use the default durability.
+    let wtx = index.database.begin_write()?;
+
+    let savepoints = wtx.list_persistent_savepoints()?.collect::<Vec<u64>>();
+
+    if savepoints.len() >= usize::try_from(MAX_SAVEPOINTS).unwrap() {
+      wtx.delete_persistent_savepoint(savepoints.into_iter().min().unwrap())?;
+    }
+
+    Index::increment_statistic(&wtx, Statistic::Commits, 1)?;
+    wtx.commit()?;
+
+    let wtx = index.database.begin_write()?;
+
+    log::debug!("creating savepoint at height {height}");
+    wtx.persistent_savepoint()?;
+
+    Index::increment_statistic(&wtx, Statistic::Commits, 1)?;
+    wtx.commit()?;
+
+    Ok(())
+  }
+}
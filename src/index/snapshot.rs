@@ -0,0 +1,375 @@
+use {
+  super::*,
+  bitcoin::hashes::{sha256, HashEngine},
+  std::io::{BufReader, BufWriter, Read, Write},
+  tempfile::NamedTempFile,
+};
+
+const MAGIC: [u8; 8] = *b"ORDINDEX";
+
+const FORMAT_VERSION: u32 = 2;
+
+/// Description of an index snapshot, stored in its header. A snapshot file
+/// consists of `MAGIC`, `FORMAT_VERSION`, the length of the JSON encoded
+/// snapshot description and the description itself, followed by a compacted
+/// copy of the index database.
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+pub(crate) struct Snapshot {
+  pub(crate) schema_version: u64,
+  pub(crate) chain: Chain,
+  pub(crate) height: u32,
+  pub(crate) block_hash: BlockHash,
+  pub(crate) index_sats: bool,
+  pub(crate) index_dunes: bool,
+  pub(crate) index_dune_events: bool,
+  pub(crate) index_dune_history: bool,
+  pub(crate) protocol: ProtocolParameters,
+  pub(crate) database_size: u64,
+  pub(crate) database_sha256: sha256::Hash,
+}
+
+impl Snapshot {
+  fn write_header(&self, writer: &mut impl Write) -> Result {
+    let description = serde_json::to_vec(self)?;
+
+    writer.write_all(&MAGIC)?;
+    writer.write_all(&FORMAT_VERSION.to_le_bytes())?;
+    writer.write_all(&u32::try_from(description.len())?.to_le_bytes())?;
+    writer.write_all(&description)?;
+
+    Ok(())
+  }
+
+  fn read_header(reader: &mut impl Read) -> Result<Self> {
+    let mut magic = [0; 8];
+    reader.read_exact(&mut magic)?;
+    ensure!(magic == MAGIC, "file is not an index snapshot");
+
+    let mut u32_bytes = [0; 4];
+
+    reader.read_exact(&mut u32_bytes)?;
+    let format_version = u32::from_le_bytes(u32_bytes);
+    ensure!(
+      format_version == FORMAT_VERSION,
+      "unsupported snapshot format version {format_version}, expected {FORMAT_VERSION}",
+    );
+
+    reader.read_exact(&mut u32_bytes)?;
+    let mut description = vec![0; usize::try_from(u32::from_le_bytes(u32_bytes))?];
+    reader.read_exact(&mut description)?;
+
+    Ok(serde_json::from_slice(&description)?)
+  }
+
+  /// Copy `len` bytes from `reader` to `writer`, returning their hash
+  fn copy(reader: &mut impl Read, writer: &mut impl Write, len: u64) -> Result<sha256::Hash> {
+    let mut engine = sha256::Hash::engine();
+    let mut buffer = vec![0; 1 << 20];
+    let mut reader = reader.take(len);
+
+    loop {
+      let n = reader.read(&mut buffer)?;
+
+      if n == 0 {
+        break;
+      }
+
+      engine.input(&buffer[..n]);
+      writer.write_all(&buffer[..n])?;
+    }
+
+    Ok(sha256::Hash::from_engine(engine))
+  }
+
+  /// Check that the database at `path` matches this snapshot's description
+  fn verify_database(&self, path: &Path) -> Result {
+    let database = Database::builder().open(path)?;
+    let rtx = database.begin_read()?;
+
+    let tip = rtx
+      .open_table(HEIGHT_TO_BLOCK_HASH)?
+      .range(0..)?
+      .next_back()
+      .transpose()?
+      .map(|(height, hash)| (height.value(), Entry::load(*hash.value())));
+
+    ensure!(
+      tip == Some((self.height, self.block_hash)),
+      "snapshot database does not end at block {} at height {}",
+      self.block_hash,
+      self.height,
+    );
+
+    let statistics = rtx.open_table(STATISTIC_TO_COUNT)?;
+
+    let statistic = |statistic: Statistic| -> Result<u64> {
+      Ok(
+        statistics
+          .get(&statistic.key())?
+          .map(|x| x.value())
+          .unwrap_or(0),
+      )
+    };
+
+    ensure!(
+      statistic(Statistic::Schema)? == self.schema_version
+        && (statistic(Statistic::IndexSats)? != 0) == self.index_sats
+        && (statistic(Statistic::IndexDunes)? != 0) == self.index_dunes
+        && (statistic(Statistic::IndexDuneEvents)? != 0) == self.index_dune_events
+        && (statistic(Statistic::IndexDuneHistory)? != 0) == self.index_dune_history,
+      "snapshot database schema version or index flags do not match snapshot header",
+    );
+
+    Index::check_protocol_parameters(&rtx, self.protocol, self.chain.legacy_protocol_parameters())
+      .context("snapshot database protocol parameters do not match snapshot header")?;
+
+    Ok(())
+  }
+}
+
+impl Index {
+  /// Write a compacted snapshot of the index to `destination`. The index
+  /// must be at `height`, and is closed so that its database can be copied.
+  pub(crate) fn export(self, height: u32, destination: &Path) -> Result<Snapshot> {
+    let (tip, block_hash) = self
+      .blocks(1)?
+      .into_iter()
+      .next()
+      .context("index contains no blocks")?;
+
+    ensure!(
+      tip == height,
+      "index is at height {tip}, cannot export snapshot at height {height}",
+    );
+
+    let chain = self.chain;
+    let index_sats = self.index_sats;
+    let index_dunes = self.index_dunes;
+    let index_dune_events = self.index_dune_events;
+    let index_dune_history = self.index_dune_history;
+    let protocol = self.protocol;
+    let path = self.path.clone();
+
+    drop(self);
+
+    let dir = parent_dir(destination);
+
+    let compacted = NamedTempFile::new_in(dir)?;
+
+    fs::copy(&path, compacted.path())?;
+
+    {
+      let mut database = Database::builder().open(compacted.path())?;
+
+      // Savepoints prevent compaction, and the blocks they allow rolling back
+      // to are unlikely to be reorged by the time the snapshot is imported
+      let wtx = database.begin_write()?;
+      for savepoint in wtx.list_persistent_savepoints()?.collect::<Vec<u64>>() {
+        wtx.delete_persistent_savepoint(savepoint)?;
+      }
+      wtx.commit()?;
+
+      database.compact()?;
+    }
+
+    let database_size = fs::metadata(compacted.path())?.len();
+
+    let database_sha256 = Snapshot::copy(
+      &mut BufReader::new(File::open(compacted.path())?),
+      &mut io::sink(),
+      database_size,
+    )?;
+
+    let snapshot = Snapshot {
+      schema_version: SCHEMA_VERSION,
+      chain,
+      height,
+      block_hash,
+      index_sats,
+      index_dunes,
+      index_dune_events,
+      index_dune_history,
+      protocol,
+      database_size,
+      database_sha256,
+    };
+
+    let output = NamedTempFile::new_in(dir)?;
+
+    {
+      let mut writer = BufWriter::new(output.as_file());
+      snapshot.write_header(&mut writer)?;
+      Snapshot::copy(
+        &mut BufReader::new(File::open(compacted.path())?),
+        &mut writer,
+        database_size,
+      )?;
+      writer.flush()?;
+    }
+
+    output.as_file().sync_all()?;
+
+    output
+      .persist_noclobber(destination)
+      .with_context(|| format!("failed to write snapshot to `{}`", destination.display()))?;
+
+    Ok(snapshot)
+  }
+
+  /// Adopt the snapshot at `source` as the index, after checking that it was
+  /// built for this chain and version of ord, that it is intact, and that
+  /// its last block is part of Dogecoin Core's best chain
+  pub(crate) fn import(options: &Options, source: &Path) -> Result<Snapshot> {
+    let path = Self::database_path(options)?;
+
+    ensure!(
+      !path.exists(),
+      "index already exists at `{}`, remove it before importing a snapshot",
+      path.display(),
+    );
+
+    let mut reader = BufReader::new(
+      File::open(source).with_context(|| format!("failed to open `{}`", source.display()))?,
+    );
+
+    let snapshot = Snapshot::read_header(&mut reader)?;
+
+    ensure!(
+      snapshot.schema_version == SCHEMA_VERSION,
+      "snapshot has index schema {}, ord schema {SCHEMA_VERSION}",
+      snapshot.schema_version,
+    );
+
+    ensure!(
+      snapshot.chain == options.chain(),
+      "snapshot is for {} but ord is on {}",
+      snapshot.chain,
+      options.chain(),
+    );
+
+    ensure!(
+      snapshot.protocol == options.protocol_parameters()?,
+      "snapshot was built with different protocol parameters than ord uses",
+    );
+
+    let client = options.dogecoin_rpc_client()?;
+
+    let block_hash = client
+      .get_block_hash(snapshot.height.into())
+      .into_option()?
+      .with_context(|| {
+        format!(
+          "Dogecoin Core has no block at snapshot height {}",
+          snapshot.height
+        )
+      })?;
+
+    ensure!(
+      block_hash == snapshot.block_hash,
+      "snapshot block {} at height {} is not in Dogecoin Core's best chain, which has block {block_hash}",
+      snapshot.block_hash,
+      snapshot.height,
+    );
+
+    let database = NamedTempFile::new_in(parent_dir(&path))?;
+
+    let database_sha256 = {
+      let mut writer = BufWriter::new(database.as_file());
+      let hash = Snapshot::copy(&mut reader, &mut writer, snapshot.database_size)?;
+      writer.flush()?;
+      hash
+    };
+
+    ensure!(
+      database.as_file().metadata()?.len() == snapshot.database_size,
+      "snapshot is truncated",
+    );
+
+    ensure!(
+      database_sha256 == snapshot.database_sha256,
+      "snapshot checksum {database_sha256} does not match expected checksum {}",
+      snapshot.database_sha256,
+    );
+
+    database.as_file().sync_all()?;
+
+    snapshot.verify_database(database.path())?;
+
+    database
+      .persist_noclobber(&path)
+      .with_context(|| format!("failed to write index to `{}`", path.display()))?;
+
+    Ok(snapshot)
+  }
+}
+
+/// Temporary files are created next to their destination, so that they can
+/// be moved into place without copying
+fn parent_dir(path: &Path) -> &Path {
+  match path.parent() {
+    Some(parent) if !parent.as_os_str().is_empty() => parent,
+    _ => Path::new("."),
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn snapshot() -> Snapshot {
+    Snapshot {
+      schema_version: SCHEMA_VERSION,
+      chain: Chain::Regtest,
+      height: 10,
+      block_hash: Chain::Regtest.genesis_block().block_hash(),
+      index_sats: true,
+      index_dunes: false,
+      index_dune_events: false,
+      index_dune_history: false,
+      protocol: Chain::Regtest.protocol_parameters(),
+      database_size: 3,
+      database_sha256: sha256::Hash::hash(&[1, 2, 3]),
+    }
+  }
+
+  #[test]
+  fn header_round_trips() {
+    let mut buffer = Vec::new();
+    snapshot().write_header(&mut buffer).unwrap();
+    buffer.extend([1, 2, 3]);
+
+    let mut reader = buffer.as_slice();
+    assert_eq!(Snapshot::read_header(&mut reader).unwrap(), snapshot());
+
+    let mut database = Vec::new();
+    assert_eq!(
+      Snapshot::copy(&mut reader, &mut database, 3).unwrap(),
+      snapshot().database_sha256,
+    );
+    assert_eq!(database, [1, 2, 3]);
+  }
+
+  #[test]
+  fn other_files_are_rejected() {
+    assert_eq!(
+      Snapshot::read_header(&mut b"SQLite format 3\0".as_slice())
+        .unwrap_err()
+        .to_string(),
+      "file is not an index snapshot",
+    );
+  }
+
+  #[test]
+  fn unknown_format_versions_are_rejected() {
+    let mut buffer = Vec::new();
+    snapshot().write_header(&mut buffer).unwrap();
+    buffer[8..12].copy_from_slice(&1u32.to_le_bytes());
+
+    assert_eq!(
+      Snapshot::read_header(&mut buffer.as_slice())
+        .unwrap_err()
+        .to_string(),
+      "unsupported snapshot format version 1, expected 2",
+    );
+  }
+}
@@ -1,7 +1,7 @@
 use bitcoincore_rpc::bitcoin::BlockHeader;
 use {
   self::{inscription_updater::InscriptionUpdater, dune_updater::DuneUpdater},
-  super::{fetcher::Fetcher, *},
+  super::{block_files::BlockFiles, fetcher::Fetcher, *},
   futures::future::try_join_all,
   std::sync::mpsc,
   tokio::sync::mpsc::{error::TryRecvError, Receiver, Sender},
@@ -13,6 +13,18 @@ use crate::sat_point::SatPoint;
 mod inscription_updater;
 mod dune_updater;
 
+pub(super) use dune_updater::{address, allocate_edicts, allocate_remainder, claim};
+
+/// Blocks within this distance of the chain tip are always fetched over RPC
+const BLOCK_FILES_TIP_DISTANCE: u64 = 100;
+
+/// Approximate memory used by a `range_cache` entry, excluding its sat ranges
+const RANGE_CACHE_ENTRY_SIZE: usize =
+  std::mem::size_of::<OutPointValue>() + std::mem::size_of::<Vec<u8>>();
+
+/// Approximate memory used by a `value_cache` entry
+const VALUE_CACHE_ENTRY_SIZE: usize = std::mem::size_of::<(OutPoint, OutPointMapValue)>();
+
 pub(crate) struct BlockData {
   pub(crate) header: BlockHeader,
   pub(crate) txdata: Vec<(Transaction, Txid)>,
@@ -36,6 +48,8 @@ impl From<Block> for BlockData {
 
 pub(crate) struct Updater<'index> {
   range_cache: HashMap<OutPointValue, Vec<u8>>,
+  range_cache_size: usize,
+  events: Vec<Event>,
   height: u32,
   index: &'index Index,
   sat_ranges_since_flush: u64,
@@ -48,6 +62,8 @@ impl<'index> Updater<'_> {
   pub(crate) fn new(index: &'index Index) -> Result<Updater<'index>> {
     Ok(Updater {
       range_cache: HashMap::new(),
+      range_cache_size: 0,
+      events: Vec::new(),
       height: index.block_count()?,
       index,
       sat_ranges_since_flush: 0,
@@ -90,6 +106,8 @@ impl<'index> Updater<'_> {
 
     let (mut outpoint_sender, mut value_receiver) = Self::spawn_fetcher(self.index)?;
 
+    let commit_policy = self.index.commit_policy;
+    let mut last_commit = Instant::now();
     let mut uncommitted = 0;
     let mut value_cache = HashMap::new();
     while let Ok(block) = rx.recv() {
@@ -116,10 +134,16 @@ impl<'index> Updater<'_> {
 
       uncommitted += 1;
 
-      if uncommitted == 5000 {
-        self.commit(wtx, value_cache)?;
+      if commit_policy.should_commit(
+        uncommitted,
+        last_commit.elapsed(),
+        self.range_cache_size + value_cache.len() * VALUE_CACHE_ENTRY_SIZE,
+      ) || Reorg::is_savepoint_height(self.height, starting_height)
+      {
+        self.commit(wtx, value_cache, starting_height)?;
         value_cache = HashMap::new();
         uncommitted = 0;
+        last_commit = Instant::now();
         wtx = self.index.begin_write()?;
         let height = wtx
           .open_table(HEIGHT_TO_BLOCK_HASH)?
@@ -145,12 +169,13 @@ impl<'index> Updater<'_> {
       }
 
       if SHUTTING_DOWN.load(atomic::Ordering::Relaxed) {
+        log::info!("Shutting down, committing {uncommitted} blocks indexed since last commit");
         break;
       }
     }
 
     if uncommitted > 0 {
-      self.commit(wtx, value_cache)?;
+      self.commit(wtx, value_cache, starting_height)?;
     }
 
     if let Some(progress_bar) = &mut progress_bar {
@@ -172,34 +197,118 @@ impl<'index> Updater<'_> {
     let client =
       Client::new(&index.rpc_url, index.auth.clone()).context("failed to connect to RPC URL")?;
 
-    let first_inscription_height = index.first_inscription_height;
-
-    thread::spawn(move || loop {
-      if let Some(height_limit) = height_limit {
-        if height >= height_limit {
-          break;
+    let first_inscription_height = index.protocol.first_inscription_height;
+
+    let block_files = index.block_files.clone();
+
+    thread::spawn(move || {
+      if let Some(block_files) = block_files {
+        let mut block_files = block_files.lock().unwrap();
+
+        match Self::send_blocks_from_files(
+          &client,
+          &mut block_files,
+          &mut height,
+          height_limit,
+          index_sats,
+          first_inscription_height,
+          &tx,
+        ) {
+          Ok(true) => {}
+          Ok(false) => return,
+          Err(err) => log::warn!(
+            "failed to read blocks from `{}`, fetching block {height} and later over RPC: {err}",
+            block_files.dir().display(),
+          ),
         }
       }
 
-      match Self::get_block_with_retries(&client, height, index_sats, first_inscription_height) {
-        Ok(Some(block)) => {
-          if let Err(err) = tx.send(block.into()) {
-            log::info!("Block receiver disconnected: {err}");
+      loop {
+        if let Some(height_limit) = height_limit {
+          if height >= height_limit {
             break;
           }
-          height += 1;
         }
-        Ok(None) => break,
-        Err(err) => {
-          log::error!("failed to fetch block {height}: {err}");
+
+        if SHUTTING_DOWN.load(atomic::Ordering::Relaxed) {
           break;
         }
+
+        match Self::get_block_with_retries(&client, height, index_sats, first_inscription_height) {
+          Ok(Some(block)) => {
+            if let Err(err) = tx.send(block.into()) {
+              log::info!("Block receiver disconnected: {err}");
+              break;
+            }
+            height += 1;
+          }
+          Ok(None) => break,
+          Err(err) => {
+            log::error!("failed to fetch block {height}: {err}");
+            break;
+          }
+        }
       }
     });
 
     Ok(rx)
   }
 
+  /// Send blocks that are at least `BLOCK_FILES_TIP_DISTANCE` blocks below the
+  /// chain tip from Dogecoin Core's block files, advancing `height` past them.
+  /// Blocks closer to the tip are left to be fetched over RPC, since they may
+  /// not have been written yet and are more likely to be reorged. Returns
+  /// `false` if the block receiver disconnected.
+  fn send_blocks_from_files(
+    client: &Client,
+    block_files: &mut BlockFiles,
+    height: &mut u32,
+    height_limit: Option<u32>,
+    index_sats: bool,
+    first_inscription_height: u32,
+    tx: &mpsc::SyncSender<BlockData>,
+  ) -> Result<bool> {
+    let Some(end) = client.get_block_count()?.checked_sub(BLOCK_FILES_TIP_DISTANCE) else {
+      return Ok(true);
+    };
+
+    let mut end = u32::try_from(end).unwrap();
+
+    if let Some(height_limit) = height_limit {
+      end = end.min(height_limit.saturating_sub(1));
+    }
+
+    if *height > end {
+      return Ok(true);
+    }
+
+    block_files.update()?;
+
+    let hashes = block_files
+      .chain(client.get_block_hash(end.into())?, end, *height)
+      .with_context(|| format!("blocks {height} through {end} are not all in block files"))?;
+
+    for hash in hashes {
+      if SHUTTING_DOWN.load(atomic::Ordering::Relaxed) {
+        return Ok(false);
+      }
+
+      let block = block_files.read(
+        hash,
+        index_sats || *height >= first_inscription_height,
+      )?;
+
+      if let Err(err) = tx.send(block.into()) {
+        log::info!("Block receiver disconnected: {err}");
+        return Ok(false);
+      }
+
+      *height += 1;
+    }
+
+    Ok(true)
+  }
+
   fn get_block_with_retries(
     client: &Client,
     height: u32,
@@ -226,7 +335,7 @@ impl<'index> Updater<'_> {
             .transpose()
         }) {
         Err(err) => {
-          if cfg!(test) {
+          if cfg!(test) || SHUTTING_DOWN.load(atomic::Ordering::Relaxed) {
             return Err(err);
           }
 
@@ -339,9 +448,9 @@ impl<'index> Updater<'_> {
     };
 
     let mut outpoint_to_value = wtx.open_table(OUTPOINT_TO_VALUE)?;
-    let mut address_to_outpoint = wtx.open_multimap_table(ADDRESS_TO_OUTPOINT)?;
+    let mut address_to_outpoint = wtx.open_table(ADDRESS_TO_OUTPOINT)?;
 
-    let index_inscriptions = self.height >= index.first_inscription_height;
+    let index_inscriptions = self.height >= index.protocol.first_inscription_height;
 
     if index_inscriptions {
       // Send all missing input outpoints to be fetched right away
@@ -398,13 +507,15 @@ impl<'index> Updater<'_> {
         .map(|lost_sats| lost_sats.value())
         .unwrap_or(0);
 
-    if let Some(prev_height) = self.height.checked_sub(1) {
-      let prev_hash = height_to_block_hash.get(&prev_height)?.unwrap();
+    Reorg::detect_reorg(&block, self.height, index, &height_to_block_hash)?;
 
-      if prev_hash.value() != block.header.prev_blockhash.as_ref() {
-        index.reorged.store(true, atomic::Ordering::Relaxed);
-        return Err(anyhow!("reorg detected at or before {prev_height}"));
-      }
+    let collect_events = index.has_event_subscribers();
+
+    if collect_events {
+      self.events.push(Event::Block {
+        height: self.height,
+        hash: block.header.block_hash(),
+      });
     }
 
     {
@@ -426,6 +537,7 @@ impl<'index> Updater<'_> {
         &mut satpoint_to_inscription_id,
         block.header.time,
         value_cache,
+        collect_events,
       )?;
 
       if self.index.index_sats {
@@ -452,6 +564,7 @@ impl<'index> Updater<'_> {
             let sat_ranges = match self.range_cache.remove(&key) {
               Some(sat_ranges) => {
                 self.outputs_cached += 1;
+                self.range_cache_size -= RANGE_CACHE_ENTRY_SIZE + sat_ranges.len();
                 sat_ranges
               }
               None => outpoint_to_sat_ranges
@@ -527,14 +640,31 @@ impl<'index> Updater<'_> {
       }
 
       statistic_to_count.insert(&Statistic::LostSats.key(), &lost_sats)?;
+
+      self.events.append(&mut inscription_updater.events);
     }
 
-    if index.index_dunes && self.height >= self.index.first_dune_height {
+    if index.index_dunes && self.height >= index.protocol.first_dune_height {
       let mut outpoint_to_dune_balances = wtx.open_table(OUTPOINT_TO_DUNE_BALANCES)?;
       let mut dune_id_to_dune_entry = wtx.open_table(DUNE_ID_TO_DUNE_ENTRY)?;
       let mut dune_to_dune_id = wtx.open_table(DUNE_TO_DUNE_ID)?;
       let mut inscription_id_to_dune = wtx.open_table(INSCRIPTION_ID_TO_DUNE)?;
+      let mut transaction_id_to_cenotaph = wtx.open_table(TRANSACTION_ID_TO_CENOTAPH)?;
       let mut transaction_id_to_dune = wtx.open_table(TRANSACTION_ID_TO_DUNE)?;
+      let mut dune_holder_rank = wtx.open_table(DUNE_HOLDER_RANK)?;
+      let mut dune_holder_to_balance = wtx.open_table(DUNE_HOLDER_TO_BALANCE)?;
+      let mut dune_id_to_holder_count = wtx.open_table(DUNE_ID_TO_HOLDER_COUNT)?;
+      let mut outpoint_to_dune_holder = wtx.open_table(OUTPOINT_TO_DUNE_HOLDER)?;
+      let mut dune_event_id_to_dune_event = if index.index_dune_events {
+        Some(wtx.open_table(DUNE_EVENT_ID_TO_DUNE_EVENT)?)
+      } else {
+        None
+      };
+      let mut dune_outpoint_to_balance_history = if index.index_dune_history {
+        Some(wtx.open_table(DUNE_OUTPOINT_TO_BALANCE_HISTORY)?)
+      } else {
+        None
+      };
       let mut dune_updater = DuneUpdater::new(
         self.height,
         &mut outpoint_to_dune_balances,
@@ -544,12 +674,22 @@ impl<'index> Updater<'_> {
         &mut dune_to_dune_id,
         &mut statistic_to_count,
         block.header.time,
-        Dune::minimum_at_height(Chain::Mainnet, Height(self.height)),
+        index.protocol,
+        &mut transaction_id_to_cenotaph,
         &mut transaction_id_to_dune,
+        &mut dune_holder_rank,
+        &mut dune_holder_to_balance,
+        &mut dune_id_to_holder_count,
+        &mut outpoint_to_dune_holder,
+        dune_event_id_to_dune_event.as_mut(),
+        dune_outpoint_to_balance_history.as_mut(),
+        collect_events,
       )?;
       for (i, (tx, txid)) in block.txdata.iter().enumerate() {
         dune_updater.index_dunes(i, tx, *txid)?;
       }
+
+      self.events.append(&mut dune_updater.events);
     }
 
     height_to_block_hash.insert(&self.height, &block.header.block_hash().store())?;
@@ -624,6 +764,7 @@ impl<'index> Updater<'_> {
 
       *outputs_traversed += 1;
 
+      self.range_cache_size += RANGE_CACHE_ENTRY_SIZE + sats.len();
       self.range_cache.insert(outpoint.store(), sats);
       self.outputs_inserted_since_flush += 1;
     }
@@ -631,7 +772,12 @@ impl<'index> Updater<'_> {
     Ok(())
   }
 
-  fn commit(&mut self, wtx: WriteTransaction, value_cache: HashMap<OutPoint, OutPointMapValue>) -> Result {
+  fn commit(
+    &mut self,
+    wtx: WriteTransaction,
+    value_cache: HashMap<OutPoint, OutPointMapValue>,
+    chain_block_count: u32,
+  ) -> Result {
     log::info!(
       "Committing at block height {}, {} outputs traversed, {} in map, {} cached",
       self.height,
@@ -654,17 +800,19 @@ impl<'index> Updater<'_> {
         outpoint_to_sat_ranges.insert(&outpoint, sat_range.as_slice())?;
       }
 
+      self.range_cache_size = 0;
+
       self.outputs_inserted_since_flush = 0;
     }
 
     {
       let mut outpoint_to_value = wtx.open_table(OUTPOINT_TO_VALUE)?;
-      let mut address_to_outpoint = wtx.open_multimap_table(ADDRESS_TO_OUTPOINT)?;
+      let mut address_to_outpoint = wtx.open_table(ADDRESS_TO_OUTPOINT)?;
 
-      for (outpoint, map) in value_cache {
-        outpoint_to_value.insert(&outpoint.store(), map.0)?;
-        if map.1 != [0u8; 34] {
-          address_to_outpoint.insert(map.1.as_slice(), &outpoint.store())?;
+      for (outpoint, (value, address, height)) in value_cache {
+        outpoint_to_value.insert(&outpoint.store(), (value, height))?;
+        if address != [0u8; 34] {
+          address_to_outpoint.insert(&(address, height, outpoint).store(), value)?;
         }
       }
     }
@@ -677,6 +825,12 @@ impl<'index> Updater<'_> {
 
     wtx.commit()?;
 
+    for event in self.events.drain(..) {
+      self.index.broadcast(event);
+    }
+
+    Reorg::update_savepoints(self.index, self.height, chain_block_count)?;
+
     Ok(())
   }
 }
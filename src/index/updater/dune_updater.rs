@@ -2,13 +2,155 @@ use {
   super::*,
   crate::dunes::{varint, Edict, Dunestone},
 };
-use crate::index::entry::{TxidValue};
+use crate::index::entry::{
+  CenotaphValue, DuneBalanceHistory, DuneBalanceHistoryValue, DuneEventIdValue, DuneEventValue,
+  DuneHolderRankValue, DuneHolderValue, DuneOutPointValue, TxidValue,
+};
 use crate::dunes::CLAIM_BIT;
 
-fn claim(id: u128) -> Option<u128> {
+pub(crate) fn claim(id: u128) -> Option<u128> {
   (id & CLAIM_BIT != 0).then_some(id ^ CLAIM_BIT)
 }
 
+// Addresses are stored the same way as in `ADDRESS_TO_OUTPOINT`
+pub(crate) fn address(script: &Script) -> Option<[u8; 34]> {
+  Chain::Mainnet
+    .address_from_script(script)
+    .ok()
+    .and_then(|address| address.to_string().as_bytes().try_into().ok())
+}
+
+/// Apply `edicts` to `tx`, moving balances out of the etching, the mintable
+/// claims, and the unallocated input balances into `allocated`. Returns the
+/// amount of the etched dune that was premined.
+pub(crate) fn allocate_edicts(
+  tx: &Transaction,
+  edicts: Vec<Edict>,
+  mut etching: Option<(&mut u128, u128)>,
+  mintable: &mut HashMap<u128, u128>,
+  unallocated: &mut HashMap<u128, u128>,
+  allocated: &mut [HashMap<u128, u128>],
+) -> u128 {
+  let mut premine_amount = 0;
+
+  for Edict { id, amount, output } in edicts {
+    let Ok(output) = usize::try_from(output) else {
+      continue;
+    };
+
+    // Skip edicts not referring to valid outputs
+    if output >= tx.output.len() {
+      continue;
+    }
+
+    let (balance, id) = if id == 0 {
+      // If this edict allocates new issuance dunes, skip it
+      // if no issuance was present, or if the issuance was invalid.
+      // Additionally, replace ID 0 with the newly assigned ID, and
+      // get the unallocated balance of the issuance.
+      match etching.as_mut() {
+        Some((balance, id)) => {
+          premine_amount += amount;
+          (&mut **balance, *id)
+        }
+        None => continue,
+      }
+    } else if let Some(claim) = claim(id) {
+      match mintable.get_mut(&claim) {
+        Some(balance) => (balance, claim),
+        None => continue,
+      }
+    } else {
+      // Get the unallocated balance of the given ID
+      match unallocated.get_mut(&id) {
+        Some(balance) => (balance, id),
+        None => continue,
+      }
+    };
+
+    let mut allocate = |balance: &mut u128, amount: u128, output: usize| {
+      if amount > 0 {
+        *balance -= amount;
+        *allocated[output].entry(id).or_default() += amount;
+      }
+    };
+
+    if output == tx.output.len() {
+      // find non-OP_RETURN outputs
+      let destinations = tx
+        .output
+        .iter()
+        .enumerate()
+        .filter_map(|(output, tx_out)| (!tx_out.script_pubkey.is_op_return()).then_some(output))
+        .collect::<Vec<usize>>();
+
+      if amount == 0 {
+        // if amount is zero, divide balance between eligible outputs
+        let amount = *balance / destinations.len() as u128;
+        let remainder = usize::try_from(*balance % destinations.len() as u128).unwrap();
+
+        for (i, output) in destinations.iter().enumerate() {
+          allocate(
+            balance,
+            if i < remainder { amount + 1 } else { amount },
+            *output,
+          );
+        }
+      } else {
+        // if amount is non-zero, distribute amount to eligible outputs
+        for output in destinations {
+          allocate(balance, amount.min(*balance), output);
+        }
+      }
+    } else {
+      // Get the allocatable amount
+      let amount = if amount == 0 {
+        *balance
+      } else {
+        amount.min(*balance)
+      };
+
+      allocate(balance, amount, output);
+    }
+  }
+
+  premine_amount
+}
+
+/// Assign all un-allocated dunes to the default output, or the first non
+/// OP_RETURN output if there is no default, or if the default output is too
+/// large. Dunes are burned if there is no such output.
+pub(crate) fn allocate_remainder(
+  tx: &Transaction,
+  default_output: Option<usize>,
+  unallocated: HashMap<u128, u128>,
+  allocated: &mut [HashMap<u128, u128>],
+  burned: &mut HashMap<u128, u128>,
+) {
+  if let Some(vout) = default_output
+    .filter(|vout| *vout < allocated.len())
+    .or_else(|| {
+      tx.output
+        .iter()
+        .enumerate()
+        .find(|(_vout, tx_out)| !tx_out.script_pubkey.is_op_return())
+        .map(|(vout, _tx_out)| vout)
+    })
+  {
+    for (id, balance) in unallocated {
+      if balance > 0 {
+        *allocated[vout].entry(id).or_default() += balance;
+      }
+    }
+  } else {
+    for (id, balance) in unallocated {
+      if balance > 0 {
+        *burned.entry(id).or_default() += balance;
+      }
+    }
+  }
+}
+
 struct Allocation {
   balance: u128,
   divisibility: u8,
@@ -22,6 +164,15 @@ struct Allocation {
 }
 
 pub(super) struct DuneUpdater<'a, 'db, 'tx> {
+  collect_events: bool,
+  pub(super) events: Vec<Event>,
+  dune_event_id_to_dune_event:
+  Option<&'a mut Table<'db, 'tx, DuneEventIdValue, DuneEventValue>>,
+  dune_holder_rank: &'a mut Table<'db, 'tx, &'static DuneHolderRankValue, ()>,
+  dune_holder_to_balance: &'a mut Table<'db, 'tx, &'static DuneHolderValue, u128>,
+  dune_id_to_holder_count: &'a mut Table<'db, 'tx, DuneIdValue, u64>,
+  dune_outpoint_to_balance_history:
+  Option<&'a mut Table<'db, 'tx, &'static DuneOutPointValue, DuneBalanceHistoryValue>>,
   height: u32,
   id_to_entry: &'a mut Table<'db, 'tx, DuneIdValue, DuneEntryValue>,
   inscription_id_to_inscription_entry:
@@ -29,10 +180,13 @@ pub(super) struct DuneUpdater<'a, 'db, 'tx> {
   inscription_id_to_dune: &'a mut Table<'db, 'tx, &'static InscriptionIdValue, u128>,
   minimum: Dune,
   outpoint_to_balances: &'a mut Table<'db, 'tx, &'static OutPointValue, &'static [u8]>,
+  outpoint_to_dune_holder: &'a mut Table<'db, 'tx, &'static OutPointValue, &'static [u8; 34]>,
+  protocol: ProtocolParameters,
   dune_to_id: &'a mut Table<'db, 'tx, u128, DuneIdValue>,
   dunes: u64,
   statistic_to_count: &'a mut Table<'db, 'tx, u64, u64>,
   timestamp: u32,
+  transaction_id_to_cenotaph: &'a mut Table<'db, 'tx, &'static TxidValue, CenotaphValue>,
   transaction_id_to_dune: &'a mut Table<'db, 'tx, &'static TxidValue, u128>,
 }
 
@@ -51,55 +205,92 @@ impl<'a, 'db, 'tx> DuneUpdater<'a, 'db, 'tx> {
     dune_to_id: &'a mut Table<'db, 'tx, u128, DuneIdValue>,
     statistic_to_count: &'a mut Table<'db, 'tx, u64, u64>,
     timestamp: u32,
-    minimum: Dune,
+    protocol: ProtocolParameters,
+    transaction_id_to_cenotaph: &'a mut Table<'db, 'tx, &'static TxidValue, CenotaphValue>,
     transaction_id_to_dune: &'a mut Table<'db, 'tx, &'static TxidValue, u128>,
+    dune_holder_rank: &'a mut Table<'db, 'tx, &'static DuneHolderRankValue, ()>,
+    dune_holder_to_balance: &'a mut Table<'db, 'tx, &'static DuneHolderValue, u128>,
+    dune_id_to_holder_count: &'a mut Table<'db, 'tx, DuneIdValue, u64>,
+    outpoint_to_dune_holder: &'a mut Table<'db, 'tx, &'static OutPointValue, &'static [u8; 34]>,
+    dune_event_id_to_dune_event: Option<
+      &'a mut Table<'db, 'tx, DuneEventIdValue, DuneEventValue>,
+    >,
+    dune_outpoint_to_balance_history: Option<
+      &'a mut Table<'db, 'tx, &'static DuneOutPointValue, DuneBalanceHistoryValue>,
+    >,
+    collect_events: bool,
   ) -> Result<Self> {
     let dunes = statistic_to_count
         .get(&Statistic::Dunes.into())?
         .map(|x| x.value())
         .unwrap_or(0);
     Ok(Self {
+      collect_events,
+      events: Vec::new(),
+      dune_event_id_to_dune_event,
+      dune_holder_rank,
+      dune_holder_to_balance,
+      dune_id_to_holder_count,
+      dune_outpoint_to_balance_history,
       height,
       id_to_entry,
-      minimum,
+      minimum: protocol.minimum_dune_at_height(height),
       outpoint_to_balances,
+      outpoint_to_dune_holder,
+      protocol,
       inscription_id_to_inscription_entry,
       inscription_id_to_dune,
       dune_to_id,
       dunes,
       statistic_to_count,
       timestamp,
+      transaction_id_to_cenotaph,
       transaction_id_to_dune,
     })
   }
 
   pub(super) fn index_dunes(&mut self, index: usize, tx: &Transaction, txid: Txid) -> Result<()> {
-    let dunestone = Dunestone::from_transaction(tx);
+    let dunestone =
+      Dunestone::from_transaction_with_rules(tx, self.protocol.dunestone_rules(self.height));
 
     // A mapping of dune ID to un-allocated balance of that dune
     let mut unallocated: HashMap<u128, u128> = HashMap::new();
 
+    // Dune balances spent by this transaction, recorded in the balance
+    // history and deducted from their holders
+    let mut spent = Vec::new();
+
     // Increment unallocated dunes with the dunes in this transaction's inputs
     for input in &tx.input {
       if let Some(guard) = self
         .outpoint_to_balances
         .remove(&input.previous_output.store())?
       {
-        let buffer = guard.value();
-        let mut i = 0;
-        while i < buffer.len() {
-          let (id, len) = varint::decode(&buffer[i..]);
-          i += len;
-          let (balance, len) = varint::decode(&buffer[i..]);
-          i += len;
+        let holder = self
+          .outpoint_to_dune_holder
+          .remove(&input.previous_output.store())?
+          .map(|holder| *holder.value());
+        for (id, balance) in entry::decode_dune_balances(guard.value()) {
           *unallocated.entry(id).or_default() += balance;
+          spent.push((id, input.previous_output, balance, holder));
         }
       }
     }
 
+    for (id, outpoint, balance, holder) in spent {
+      self.spend(id, outpoint)?;
+
+      if let Some(holder) = holder {
+        self.debit(id, holder, balance)?;
+      }
+    }
+
+    let transaction = u32::try_from(index).unwrap();
+    let mut events = 0;
+
     let cenotaph = dunestone
         .as_ref()
-        .map(|dunestone| dunestone.cenotaph)
+        .map(|dunestone| dunestone.is_cenotaph())
         .unwrap_or_default();
 
     let default_output = dunestone.as_ref().and_then(|dunestone| {
@@ -111,13 +302,17 @@ impl<'a, 'db, 'tx> DuneUpdater<'a, 'db, 'tx> {
     // A vector of allocated transaction output dune balances
     let mut allocated: Vec<HashMap<u128, u128>> = vec![HashMap::new(); tx.output.len()];
 
+    // The part of each output's balances moved from the inputs, which is
+    // recorded as a transfer
+    let mut transferred: Vec<HashMap<u128, u128>> = vec![HashMap::new(); tx.output.len()];
+
     if let Some(dunestone) = dunestone {
       // Determine if this dunestone contains a valid issuance
       let mut allocation = match dunestone.etching {
         Some(etching) => {
           if etching
               .dune
-              .map(|dune| dune < self.minimum || dune.is_reserved())
+              .map(|dune| dune < self.minimum || self.protocol.is_reserved(dune))
               .unwrap_or_default()
               || etching
               .dune
@@ -140,7 +335,7 @@ impl<'a, 'db, 'tx> DuneUpdater<'a, 'db, 'tx> {
                   .statistic_to_count
                   .insert(&Statistic::ReservedDunes.into(), reserved_dunes + 1)?;
 
-              Dune::reserved(reserved_dunes.into())
+              self.protocol.reserved_dune(reserved_dunes.into())
             };
 
             // Construct an allocation, representing the new dunes that may be
@@ -202,88 +397,27 @@ impl<'a, 'db, 'tx> DuneUpdater<'a, 'db, 'tx> {
 
         let limits = mintable.clone();
 
-        for Edict { id, amount, output } in dunestone.edicts {
-          let Ok(output) = usize::try_from(output) else {
-            continue;
-          };
-
-          // Skip edicts not referring to valid outputs
-          if output >= tx.output.len() {
-            continue;
-          }
-
-          let (balance, id) = if id == 0 {
-            // If this edict allocates new issuance dunes, skip it
-            // if no issuance was present, or if the issuance was invalid.
-            // Additionally, replace ID 0 with the newly assigned ID, and
-            // get the unallocated balance of the issuance.
-            match allocation.as_mut() {
-              Some(Allocation { balance, id, .. }) => {
-                premine_amount = premine_amount + amount;
-                (balance, *id)
-              },
-              None => continue,
-            }
-          } else if let Some(claim) = claim(id) {
-            match mintable.get_mut(&claim) {
-              Some(balance) => (balance, claim),
-              None => continue,
-            }
-          } else {
-            // Get the unallocated balance of the given ID
-            match unallocated.get_mut(&id) {
-              Some(balance) => (balance, id),
-              None => continue,
-            }
-          };
-
-          let mut allocate = |balance: &mut u128, amount: u128, output: usize| {
-            if amount > 0 {
-              *balance -= amount;
-              *allocated[output].entry(id).or_default() += amount;
-            }
-          };
-
-          if output == tx.output.len() {
-            // find non-OP_RETURN outputs
-            let destinations = tx
-                .output
-                .iter()
-                .enumerate()
-                .filter_map(|(output, tx_out)| {
-                  (!tx_out.script_pubkey.is_op_return()).then_some(output)
-                })
-                .collect::<Vec<usize>>();
-
-            if amount == 0 {
-              // if amount is zero, divide balance between eligible outputs
-              let amount = *balance / destinations.len() as u128;
-              let remainder = usize::try_from(*balance % destinations.len() as u128).unwrap();
-
-              for (i, output) in destinations.iter().enumerate() {
-                allocate(
-                  balance,
-                  if i < remainder { amount + 1 } else { amount },
-                  *output,
-                );
-              }
-            } else {
-              // if amount is non-zero, distribute amount to eligible outputs
-              for output in destinations {
-                allocate(balance, amount.min(*balance), output);
-              }
-            }
-          } else {
-            // Get the allocatable amount
-            let amount = if amount == 0 {
-              *balance
-            } else {
-              amount.min(*balance)
-            };
-
-            allocate(balance, amount, output);
-          }
-        }
+        // Edicts draw on each balance independently, so applying them to the
+        // input balances alone allocates the same amounts from the inputs
+        allocate_edicts(
+          tx,
+          dunestone.edicts.clone(),
+          None,
+          &mut HashMap::new(),
+          &mut unallocated.clone(),
+          &mut transferred,
+        );
+
+        premine_amount = allocate_edicts(
+          tx,
+          dunestone.edicts,
+          allocation
+              .as_mut()
+              .map(|allocation| (&mut allocation.balance, allocation.id)),
+          &mut mintable,
+          &mut unallocated,
+          &mut allocated,
+        );
 
         // increment entries with minted dunes
         for (id, amount) in mintable {
@@ -294,10 +428,34 @@ impl<'a, 'db, 'tx> DuneUpdater<'a, 'db, 'tx> {
             entry.supply += minted;
             entry.mints += 1;
             self.id_to_entry.insert(id, entry.store())?;
+            self.event(
+              DuneId::load(id),
+              &mut events,
+              DuneEvent {
+                amount: minted,
+                height: self.height,
+                kind: DuneEventKind::Mint,
+                transaction,
+                txid,
+                vout: None,
+              },
+              None,
+            )?;
           }
         }
       }
 
+      if let Some(flaw) = dunestone.flaw {
+        self.transaction_id_to_cenotaph.insert(
+          &txid.store(),
+          Cenotaph {
+            etching: allocation.as_ref().map(|allocation| allocation.dune),
+            flaw,
+          }
+          .store(),
+        )?;
+      }
+
       if let Some(Allocation {
         balance,
         divisibility,
@@ -340,6 +498,20 @@ impl<'a, 'db, 'tx> DuneUpdater<'a, 'db, 'tx> {
               .store(),
         )?;
 
+        self.event(
+          id,
+          &mut events,
+          DuneEvent {
+            amount: premine_amount,
+            height: self.height,
+            kind: DuneEventKind::Etch,
+            transaction,
+            txid,
+            vout: None,
+          },
+          None,
+        )?;
+
         let inscription_id = InscriptionId { txid, index: 0 };
 
         if self
@@ -361,31 +533,14 @@ impl<'a, 'db, 'tx> DuneUpdater<'a, 'db, 'tx> {
         *burned.entry(id).or_default() += balance;
       }
     } else {
-      // assign all un-allocated dunes to the default output, or the first non
-      // OP_RETURN output if there is no default, or if the default output is
-      // too large
-      if let Some(vout) = default_output
-          .filter(|vout| *vout < allocated.len())
-          .or_else(|| {
-            tx.output
-                .iter()
-                .enumerate()
-                .find(|(_vout, tx_out)| !tx_out.script_pubkey.is_op_return())
-                .map(|(vout, _tx_out)| vout)
-          })
-      {
-        for (id, balance) in unallocated {
-          if balance > 0 {
-            *allocated[vout].entry(id).or_default() += balance;
-          }
-        }
-      } else {
-        for (id, balance) in unallocated {
-          if balance > 0 {
-            *burned.entry(id).or_default() += balance;
-          }
-        }
-      }
+      allocate_remainder(
+        tx,
+        default_output,
+        unallocated.clone(),
+        &mut transferred,
+        &mut HashMap::new(),
+      );
+      allocate_remainder(tx, default_output, unallocated, &mut allocated, &mut burned);
     }
 
     // update outpoint balances
@@ -405,6 +560,8 @@ impl<'a, 'db, 'tx> DuneUpdater<'a, 'db, 'tx> {
 
       buffer.clear();
 
+      let holder = address(&tx.output[vout].script_pubkey);
+
       let mut balances = balances.into_iter().collect::<Vec<(u128, u128)>>();
 
       // Sort balances by id so tests can assert balances in a fixed order
@@ -413,6 +570,36 @@ impl<'a, 'db, 'tx> DuneUpdater<'a, 'db, 'tx> {
       for (id, balance) in balances {
         varint::encode_to_vec(id, &mut buffer);
         varint::encode_to_vec(balance, &mut buffer);
+
+        self.create(
+          id,
+          OutPoint {
+            txid,
+            vout: vout.try_into().unwrap(),
+          },
+          balance,
+          holder,
+        )?;
+
+        if let Some(holder) = holder {
+          self.credit(id, holder, balance)?;
+        }
+
+        if let Some(amount) = transferred[vout].get(&id).copied() {
+          self.event(
+            DuneId::try_from(id).unwrap(),
+            &mut events,
+            DuneEvent {
+              amount,
+              height: self.height,
+              kind: DuneEventKind::Transfer,
+              transaction,
+              txid,
+              vout: Some(vout.try_into().unwrap()),
+            },
+            holder,
+          )?;
+        }
       }
 
       self.outpoint_to_balances.insert(
@@ -423,18 +610,186 @@ impl<'a, 'db, 'tx> DuneUpdater<'a, 'db, 'tx> {
         .store(),
         buffer.as_slice(),
       )?;
+
+      if let Some(holder) = holder {
+        self.outpoint_to_dune_holder.insert(
+          &OutPoint {
+            txid,
+            vout: vout.try_into().unwrap(),
+          }
+          .store(),
+          &holder,
+        )?;
+      }
     }
 
     // increment entries with burned dunes
     for (id, amount) in burned {
-      let id = DuneId::try_from(id).unwrap().store();
-      let mut entry = DuneEntry::load(self.id_to_entry.get(id)?.unwrap().value());
+      let id = DuneId::try_from(id).unwrap();
+      let mut entry = DuneEntry::load(self.id_to_entry.get(id.store())?.unwrap().value());
       entry.burned += amount;
-      self.id_to_entry.insert(id, entry.store())?;
+      self.id_to_entry.insert(id.store(), entry.store())?;
+      self.event(
+        id,
+        &mut events,
+        DuneEvent {
+          amount,
+          height: self.height,
+          kind: DuneEventKind::Burn,
+          transaction,
+          txid,
+          vout: None,
+        },
+        None,
+      )?;
     }
 
     Ok(())
   }
+
+  fn create(
+    &mut self,
+    id: u128,
+    outpoint: OutPoint,
+    balance: u128,
+    address: Option<[u8; 34]>,
+  ) -> Result {
+    let Some(history) = self.dune_outpoint_to_balance_history.as_mut() else {
+      return Ok(());
+    };
+
+    history.insert(
+      &(DuneId::try_from(id).unwrap(), outpoint).store(),
+      DuneBalanceHistory {
+        address: address.unwrap_or([0; 34]),
+        balance,
+        created: self.height,
+        spent: None,
+      }
+      .store(),
+    )?;
+
+    Ok(())
+  }
+
+  fn spend(&mut self, id: u128, outpoint: OutPoint) -> Result {
+    let Some(history) = self.dune_outpoint_to_balance_history.as_mut() else {
+      return Ok(());
+    };
+
+    let key = (DuneId::try_from(id).unwrap(), outpoint).store();
+
+    let entry = history
+      .get(&key)?
+      .map(|entry| DuneBalanceHistory::load(entry.value()));
+
+    if let Some(mut entry) = entry {
+      entry.spent = Some(self.height);
+      history.insert(&key, entry.store())?;
+    }
+
+    Ok(())
+  }
+
+  fn credit(&mut self, id: u128, holder: [u8; 34], amount: u128) -> Result {
+    let id = DuneId::try_from(id).unwrap();
+    let key = (id, holder).store();
+
+    let balance = self
+      .dune_holder_to_balance
+      .get(&key)?
+      .map(|balance| balance.value())
+      .unwrap_or_default();
+
+    if balance > 0 {
+      self.dune_holder_rank.remove(&(id, balance, holder).store())?;
+    } else {
+      self.adjust_holder_count(id, true)?;
+    }
+
+    self.dune_holder_to_balance.insert(&key, balance + amount)?;
+    self.dune_holder_rank.insert(&(id, balance + amount, holder).store(), ())?;
+
+    Ok(())
+  }
+
+  fn debit(&mut self, id: u128, holder: [u8; 34], amount: u128) -> Result {
+    let id = DuneId::try_from(id).unwrap();
+    let key = (id, holder).store();
+
+    let Some(balance) = self
+      .dune_holder_to_balance
+      .get(&key)?
+      .map(|balance| balance.value())
+    else {
+      return Ok(());
+    };
+
+    self.dune_holder_rank.remove(&(id, balance, holder).store())?;
+
+    let balance = balance.saturating_sub(amount);
+
+    if balance > 0 {
+      self.dune_holder_to_balance.insert(&key, balance)?;
+      self.dune_holder_rank.insert(&(id, balance, holder).store(), ())?;
+    } else {
+      self.dune_holder_to_balance.remove(&key)?;
+      self.adjust_holder_count(id, false)?;
+    }
+
+    Ok(())
+  }
+
+  fn adjust_holder_count(&mut self, id: DuneId, increment: bool) -> Result {
+    let count = self
+      .dune_id_to_holder_count
+      .get(id.store())?
+      .map(|count| count.value())
+      .unwrap_or_default();
+
+    let count = if increment {
+      count + 1
+    } else {
+      count.saturating_sub(1)
+    };
+
+    self.dune_id_to_holder_count.insert(id.store(), count)?;
+
+    Ok(())
+  }
+
+  fn event(
+    &mut self,
+    id: DuneId,
+    events: &mut u32,
+    event: DuneEvent,
+    holder: Option<[u8; 34]>,
+  ) -> Result {
+    if self.collect_events {
+      self.events.push(Event::Dune {
+        height: event.height,
+        id,
+        kind: event.kind,
+        amount: event.amount,
+        txid: event.txid,
+        vout: event.vout,
+        address: holder.map(|holder| String::from_utf8_lossy(&holder).into()),
+      });
+    }
+
+    let Some(dune_event_id_to_dune_event) = self.dune_event_id_to_dune_event.as_mut() else {
+      return Ok(());
+    };
+
+    dune_event_id_to_dune_event.insert(
+      (id.height, id.index, event.height, event.transaction, *events),
+      event.store(),
+    )?;
+
+    *events += 1;
+
+    Ok(())
+  }
 }
 
 #[cfg(test)]
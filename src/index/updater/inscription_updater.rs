@@ -1,4 +1,3 @@
-use redb::MultimapTable;
 use super::*;
 use crate::inscription::ParsedInscription;
 use crate::sat::Sat;
@@ -16,6 +15,8 @@ enum Origin {
 }
 
 pub(super) struct InscriptionUpdater<'a, 'db, 'tx> {
+  collect_events: bool,
+  pub(super) events: Vec<Event>,
   flotsam: Vec<Flotsam>,
   height: u32,
   id_to_satpoint: &'a mut Table<'db, 'tx, &'static InscriptionIdValue, &'static SatPointValue>,
@@ -29,8 +30,8 @@ pub(super) struct InscriptionUpdater<'a, 'db, 'tx> {
   lost_sats: u64,
   next_number: u64,
   number_to_id: &'a mut Table<'db, 'tx, u64, &'static InscriptionIdValue>,
-  outpoint_to_value: &'a mut Table<'db, 'tx, &'static OutPointValue, u64>,
-  address_to_outpoint: &'a mut MultimapTable<'db, 'tx, &'static [u8], &'static OutPointValue>,
+  outpoint_to_value: &'a mut Table<'db, 'tx, &'static OutPointValue, (u64, u32)>,
+  address_to_outpoint: &'a mut Table<'db, 'tx, &'static AddressOutPointValue, u64>,
   reward: u64,
   sat_to_inscription_id: &'a mut Table<'db, 'tx, u64, &'static InscriptionIdValue>,
   satpoint_to_id: &'a mut Table<'db, 'tx, &'static SatPointValue, &'static InscriptionIdValue>,
@@ -51,12 +52,13 @@ impl<'a, 'db, 'tx> InscriptionUpdater<'a, 'db, 'tx> {
     id_to_entry: &'a mut Table<'db, 'tx, &'static InscriptionIdValue, InscriptionEntryValue>,
     lost_sats: u64,
     number_to_id: &'a mut Table<'db, 'tx, u64, &'static InscriptionIdValue>,
-    outpoint_to_value: &'a mut Table<'db, 'tx, &'static OutPointValue, u64>,
-    address_to_outpoint: &'a mut MultimapTable<'db, 'tx, &'static [u8], &'static OutPointValue>,
+    outpoint_to_value: &'a mut Table<'db, 'tx, &'static OutPointValue, (u64, u32)>,
+    address_to_outpoint: &'a mut Table<'db, 'tx, &'static AddressOutPointValue, u64>,
     sat_to_inscription_id: &'a mut Table<'db, 'tx, u64, &'static InscriptionIdValue>,
     satpoint_to_id: &'a mut Table<'db, 'tx, &'static SatPointValue, &'static InscriptionIdValue>,
     timestamp: u32,
     value_cache: &'a mut HashMap<OutPoint, OutPointMapValue>,
+    collect_events: bool,
   ) -> Result<Self> {
     let next_number = number_to_id
         .iter()?
@@ -69,6 +71,8 @@ impl<'a, 'db, 'tx> InscriptionUpdater<'a, 'db, 'tx> {
         .unwrap_or(0);
 
     Ok(Self {
+      collect_events,
+      events: Vec::new(),
       flotsam: Vec::new(),
       height,
       id_to_satpoint,
@@ -136,18 +140,22 @@ impl<'a, 'db, 'tx> InscriptionUpdater<'a, 'db, 'tx> {
             .outpoint_to_value
             .remove(&tx_in.previous_output.store())?
         {
+          let (value, height) = map.value();
           if let Some(transaction) = self
               .transaction_id_to_transaction
               .get(&tx_in.previous_output.txid.store())? {
             let tx: Transaction = consensus::encode::deserialize(transaction.value())?;
             let output = tx.output[tx_in.previous_output.vout as usize].clone();
-            if let Some(address_from_script) = Chain::Mainnet
+            if let Some(address) = Chain::Mainnet
                 .address_from_script(&output.script_pubkey)
-                .ok() {
-              self.address_to_outpoint.remove(address_from_script.to_string().as_bytes(), &tx_in.previous_output.store())?;
+                .ok()
+                .and_then(|address| address.to_string().as_bytes().try_into().ok()) {
+              self
+                .address_to_outpoint
+                .remove(&(address, height, tx_in.previous_output).store())?;
             }
           }
-          map.value()
+          value
         } else {
           self.value_receiver.blocking_recv().ok_or_else(|| {
             anyhow!(
@@ -190,7 +198,19 @@ impl<'a, 'db, 'tx> InscriptionUpdater<'a, 'db, 'tx> {
       };
 
       match Inscription::from_transactions(txs) {
-        ParsedInscription::None => { }
+        ParsedInscription::None => {
+          // Spending the locked output of a partial inscription without
+          // continuing it, as `wallet inscribe --abandon` does, ends the chain
+          if !txids_vec.is_empty() && tx.input[0].previous_output.vout == 0 {
+            self
+                .partial_txid_to_txids
+                .remove(&previous_txid_bytes.as_slice())?;
+
+            for partial_txid in txids_vec.chunks(32) {
+              self.txid_to_tx.remove(partial_txid)?;
+            }
+          }
+        }
 
         ParsedInscription::Partial => {
           let mut txid_vec = txid.into_inner().to_vec();
@@ -282,6 +302,7 @@ impl<'a, 'db, 'tx> InscriptionUpdater<'a, 'db, 'tx> {
           input_sat_ranges,
           inscriptions.next().unwrap(),
           new_satpoint,
+          Some(&tx_out.script_pubkey),
         )?;
       }
 
@@ -308,6 +329,7 @@ impl<'a, 'db, 'tx> InscriptionUpdater<'a, 'db, 'tx> {
         (
           tx_out.clone().value,
           address,
+          self.height,
         )
       );
     }
@@ -318,7 +340,7 @@ impl<'a, 'db, 'tx> InscriptionUpdater<'a, 'db, 'tx> {
           outpoint: OutPoint::null(),
           offset: self.lost_sats + flotsam.offset - output_value,
         };
-        self.update_inscription_location(input_sat_ranges, flotsam, new_satpoint)?;
+        self.update_inscription_location(input_sat_ranges, flotsam, new_satpoint, None)?;
       }
 
       Ok(self.reward - output_value)
@@ -337,6 +359,7 @@ impl<'a, 'db, 'tx> InscriptionUpdater<'a, 'db, 'tx> {
     input_sat_ranges: Option<&VecDeque<(u64, u64)>>,
     flotsam: Flotsam,
     new_satpoint: SatPoint,
+    script_pubkey: Option<&Script>,
   ) -> Result {
     let inscription_id = flotsam.inscription_id.store();
 
@@ -377,6 +400,18 @@ impl<'a, 'db, 'tx> InscriptionUpdater<'a, 'db, 'tx> {
               .store(),
         )?;
 
+        if self.collect_events {
+          self.events.push(Event::Inscription {
+            height: self.height,
+            inscription_id: flotsam.inscription_id,
+            inscription_number: self.next_number,
+            satpoint: new_satpoint,
+            address: script_pubkey
+              .and_then(|script_pubkey| Chain::Mainnet.address_from_script(script_pubkey).ok())
+              .map(|address| address.to_string()),
+          });
+        }
+
         self.next_number += 1;
       }
     }
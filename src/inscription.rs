@@ -9,6 +9,9 @@ use {
 
 const PROTOCOL_ID: &[u8] = b"ord";
 
+const MAX_CHUNK_LEN: usize = 240;
+const MAX_PAYLOAD_LEN: usize = 1500;
+
 #[derive(Debug, PartialEq, Clone, Serialize, Deserialize, Eq, Default)]
 pub(crate) struct Inscription {
   body: Option<Vec<u8>>,
@@ -39,6 +42,26 @@ impl Inscription {
     InscriptionParser::parse(sig_scripts)
   }
 
+  /// Returns the number of body pieces that have yet to be revealed by a
+  /// partial inscription spread over `txs`.
+  pub(crate) fn remaining_pieces(txs: &[Transaction]) -> Option<u64> {
+    let sig_scripts = txs
+      .iter()
+      .map(|tx| Some(tx.input.first()?.script_sig.clone()))
+      .collect::<Option<Vec<Script>>>()?;
+
+    match InscriptionParser::parse_with_remaining(sig_scripts) {
+      (ParsedInscription::Partial, remaining) => Some(remaining),
+      _ => None,
+    }
+  }
+
+  /// Returns the number of body pieces declared by the inscription envelope
+  /// in the first input of `tx`, if any.
+  pub(crate) fn pieces(tx: &Transaction) -> Option<u64> {
+    InscriptionParser::pieces(&tx.input.first()?.script_sig)
+  }
+
   pub(crate) fn from_file(chain: Chain, path: impl AsRef<Path>) -> Result<Self, Error> {
     let path = path.as_ref();
 
@@ -83,6 +106,63 @@ impl Inscription {
     self.append_reveal_script_to_builder(builder).into_script()
   }
 
+  /// Splits the inscription envelope into the push-only script fragments
+  /// unlocked by each transaction of a P2SH inscription chain, in the order
+  /// in which they must be revealed. Each fragment is at most
+  /// `MAX_PAYLOAD_LEN` bytes, so that it fits in a standard `script_sig`
+  /// together with a signature and the redeem script.
+  pub(crate) fn to_p2sh_partials(&self) -> Vec<Script> {
+    let body = self.body().unwrap_or_default();
+
+    let mut pieces = body.chunks(MAX_CHUNK_LEN).collect::<Vec<&[u8]>>();
+
+    if pieces.is_empty() {
+      pieces.push(&[]);
+    }
+
+    let mut pairs = vec![(
+      Self::push_number(pieces.len()),
+      script::Builder::new()
+        .push_slice(self.content_type.as_deref().unwrap_or_default())
+        .into_script(),
+    )];
+
+    for (i, piece) in pieces.iter().enumerate() {
+      pairs.push((
+        Self::push_number(pieces.len() - i - 1),
+        script::Builder::new().push_slice(piece).into_script(),
+      ));
+    }
+
+    let mut partials = Vec::new();
+    let mut partial = script::Builder::new()
+      .push_slice(PROTOCOL_ID)
+      .into_script()
+      .to_bytes();
+    let mut empty = true;
+
+    for (number, data) in pairs {
+      if !empty && partial.len() + number.len() + data.len() > MAX_PAYLOAD_LEN {
+        partials.push(Script::from(partial));
+        partial = Vec::new();
+      }
+
+      partial.extend_from_slice(number.as_bytes());
+      partial.extend_from_slice(data.as_bytes());
+      empty = false;
+    }
+
+    partials.push(Script::from(partial));
+
+    partials
+  }
+
+  fn push_number(n: usize) -> Script {
+    script::Builder::new()
+      .push_int(n.try_into().unwrap())
+      .into_script()
+  }
+
   pub(crate) fn media(&self) -> Media {
     if self.body.is_none() {
       return Media::Unknown;
@@ -111,6 +191,15 @@ impl Inscription {
     str::from_utf8(self.content_type.as_ref()?).ok()
   }
 
+  /// Returns a `script_sig` that reveals the inscription in a single
+  /// transaction
+  #[cfg(test)]
+  pub(crate) fn to_script_sig(&self) -> Script {
+    let mut partials = self.to_p2sh_partials();
+    assert_eq!(partials.len(), 1, "inscription does not fit in one script_sig");
+    partials.remove(0)
+  }
+
   #[cfg(test)]
   pub(crate) fn to_witness(&self) -> Witness {
     let builder = script::Builder::new();
@@ -130,11 +219,17 @@ struct InscriptionParser {}
 
 impl InscriptionParser {
   fn parse(sig_scripts: Vec<Script>) -> ParsedInscription {
+    Self::parse_with_remaining(sig_scripts).0
+  }
+
+  /// Parses the inscription in `sig_scripts`, along with the number of body
+  /// pieces that have yet to be revealed if it is partial.
+  fn parse_with_remaining(sig_scripts: Vec<Script>) -> (ParsedInscription, u64) {
     let sig_script = &sig_scripts[0];
 
     let mut push_datas_vec = match Self::decode_push_datas(sig_script) {
       Some(push_datas) => push_datas,
-      None => return ParsedInscription::None,
+      None => return (ParsedInscription::None, 0),
     };
 
     let mut push_datas = push_datas_vec.as_slice();
@@ -142,24 +237,24 @@ impl InscriptionParser {
     // read protocol
 
     if push_datas.len() < 3 {
-      return ParsedInscription::None;
+      return (ParsedInscription::None, 0);
     }
 
     let protocol = &push_datas[0];
 
     if protocol != PROTOCOL_ID {
-      return ParsedInscription::None;
+      return (ParsedInscription::None, 0);
     }
 
     // read npieces
 
     let mut npieces = match Self::push_data_to_number(&push_datas[1]) {
       Some(n) => n,
-      None => return ParsedInscription::None,
+      None => return (ParsedInscription::None, 0),
     };
 
     if npieces == 0 {
-      return ParsedInscription::None;
+      return (ParsedInscription::None, 0);
     }
 
     // read content type
@@ -184,7 +279,7 @@ impl InscriptionParser {
             body: Some(body),
           };
 
-          return ParsedInscription::Complete(inscription);
+          return (ParsedInscription::Complete(inscription), 0);
         }
 
         if push_datas.len() < 2 {
@@ -207,33 +302,43 @@ impl InscriptionParser {
       }
 
       if sig_scripts.len() <= 1 {
-        return ParsedInscription::Partial;
+        return (ParsedInscription::Partial, npieces);
       }
 
       sig_scripts = &sig_scripts[1..];
 
       push_datas_vec = match Self::decode_push_datas(&sig_scripts[0]) {
         Some(push_datas) => push_datas,
-        None => return ParsedInscription::None,
+        None => return (ParsedInscription::None, 0),
       };
 
       if push_datas_vec.len() < 2 {
-        return ParsedInscription::None;
+        return (ParsedInscription::None, 0);
       }
 
       let next = match Self::push_data_to_number(&push_datas_vec[0]) {
         Some(n) => n,
-        None => return ParsedInscription::None,
+        None => return (ParsedInscription::None, 0),
       };
 
       if next != npieces - 1 {
-        return ParsedInscription::None;
+        return (ParsedInscription::None, 0);
       }
 
       push_datas = push_datas_vec.as_slice();
     }
   }
 
+  fn pieces(sig_script: &Script) -> Option<u64> {
+    let push_datas = Self::decode_push_datas(sig_script)?;
+
+    if push_datas.len() < 3 || push_datas[0] != PROTOCOL_ID {
+      return None;
+    }
+
+    Self::push_data_to_number(&push_datas[1]).filter(|npieces| *npieces > 0)
+  }
+
   fn decode_push_datas(script: &Script) -> Option<Vec<Vec<u8>>> {
     let mut bytes = script.as_bytes();
     let mut push_datas = vec![];
@@ -551,6 +656,41 @@ mod tests {
     );
   }
 
+  #[test]
+  fn p2sh_partials_round_trip() {
+    for len in [0, 1, 240, 241, 1500, 10_000] {
+      let inscription = inscription("text/plain;charset=utf-8", vec![7; len]);
+
+      let partials = inscription.to_p2sh_partials();
+
+      assert!(partials
+        .iter()
+        .all(|partial| partial.len() <= MAX_PAYLOAD_LEN));
+
+      assert_eq!(
+        InscriptionParser::parse(partials),
+        ParsedInscription::Complete(inscription),
+      );
+    }
+  }
+
+  #[test]
+  fn p2sh_partials_are_incomplete_until_last() {
+    let partials = inscription("text/plain", vec![0; 5000]).to_p2sh_partials();
+
+    assert!(partials.len() > 1);
+
+    assert_eq!(
+      InscriptionParser::parse(partials[..partials.len() - 1].to_vec()),
+      ParsedInscription::Partial,
+    );
+
+    let (parsed, remaining) = InscriptionParser::parse_with_remaining(partials[..1].to_vec());
+
+    assert_eq!(parsed, ParsedInscription::Partial);
+    assert_eq!(remaining, 21 - 6);
+  }
+
   fn push_number(script: &mut Vec<Vec<u8>>, num: u64) {
     if num == 0 {
       script.push(vec![0]);
@@ -19,11 +19,12 @@ use {
     deserialize_from_str::DeserializeFromStr,
     epoch::Epoch,
     height::Height,
-    index::{Index, List, DuneEntry},
+    index::{CommitPolicy, Index, List, DuneEntry, DEFAULT_COMMIT_INTERVAL},
     inscription::Inscription,
     inscription_id::InscriptionId,
     media::Media,
     options::Options,
+    protocol_parameters::{ProtocolParameters, ProtocolParametersConfig},
     outgoing::Outgoing,
     representation::Representation,
     dunes::{Etching, Pile, SpacedDune},
@@ -79,7 +80,7 @@ use crate::sat_point::SatPoint;
 
 pub use self::{
   fee_rate::FeeRate, object::Object, rarity::Rarity,
-  dunes::{Edict, Dune, DuneId, Dunestone, Terms},
+  dunes::{Cenotaph, Edict, Dune, DuneId, Dunestone, Etching, Flaw, Rules, Terms, CLAIM_BIT},
   subcommand::wallet::transaction_builder::{Target, TransactionBuilder},
 };
 
@@ -119,6 +120,7 @@ mod object;
 mod options;
 mod outgoing;
 mod page_config;
+mod protocol_parameters;
 mod rarity;
 mod representation;
 mod dunes;
@@ -155,6 +157,9 @@ fn fund_raw_transaction(
             // and *not* fee per vB. So, we multiply the fee rate given by the user
             // by 1000.
             fee_rate: Some(Amount::from_sat((fee_rate.n() * 1000.0).ceil() as u64)),
+            // Append change after the existing outputs, so that edicts and
+            // pointers referring to them by index remain valid
+            change_position: Some(u32::try_from(unfunded_transaction.output.len())?),
             ..Default::default()
           }),
           Some(false),
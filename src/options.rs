@@ -16,6 +16,22 @@ pub(crate) struct Options {
     help = "Use <CHAIN>."
   )]
   pub(crate) chain_argument: Chain,
+  #[arg(
+    long,
+    value_parser = clap::value_parser!(u32).range(1..),
+    help = "Commit index every <COMMIT_INTERVAL> blocks. [default: 5000]"
+  )]
+  pub(crate) commit_interval: Option<u32>,
+  #[arg(
+    long,
+    help = "Also commit index once its output caches exceed <COMMIT_CACHE_SIZE> bytes."
+  )]
+  pub(crate) commit_cache_size: Option<usize>,
+  #[arg(
+    long,
+    help = "Also commit index once <COMMIT_SECONDS> seconds have passed since the last commit."
+  )]
+  pub(crate) commit_seconds: Option<u64>,
   #[arg(long, help = "Load configuration from <CONFIG>.")]
   pub(crate) config: Option<PathBuf>,
   #[arg(long, help = "Load configuration from <CONFIG_DIR>.")]
@@ -43,11 +59,28 @@ pub(crate) struct Options {
   pub(crate) height_limit: Option<u32>,
   #[arg(long, help = "Use index at <INDEX>.")]
   pub(crate) index: Option<PathBuf>,
+  #[arg(
+    long,
+    help = "Read blocks far behind the chain tip from the `blocks` directory of the Dogecoin Core data dir instead of over RPC."
+  )]
+  pub(crate) index_from_block_files: bool,
   #[arg(
   long,
   help = "Track location of dunes. DUNES ARE IN AN UNFINISHED PRE-ALPHA STATE AND SUBJECT TO CHANGE AT ANY TIME."
   )]
   pub(crate) index_dunes: bool,
+  #[arg(
+    long,
+    requires = "index_dunes",
+    help = "Record etch, mint, transfer and burn events for every dune. Requires `--index-dunes`."
+  )]
+  pub(crate) index_dune_events: bool,
+  #[arg(
+    long,
+    requires = "index_dunes",
+    help = "Keep dune balances of spent outputs, enabling balance snapshots at past heights. Requires `--index-dunes`."
+  )]
+  pub(crate) index_dune_history: bool,
   #[arg(long, help = "Track location of all satoshis.")]
   pub(crate) index_sats: bool,
   #[arg(long, short, help = "Use regtest. Equivalent to `--chain regtest`.")]
@@ -75,27 +108,54 @@ impl Options {
     }
   }
 
-  pub(crate) fn first_inscription_height(&self) -> u32 {
-    if self.chain() == Chain::Regtest {
-      self.first_inscription_height.unwrap_or(0)
-    } else if integration_test() {
-      0
+  /// Protocol parameters of the chain, with overrides from the config file
+  /// and `--first-inscription-height` and `--first-dune-height` applied
+  pub(crate) fn protocol_parameters(&self) -> Result<ProtocolParameters> {
+    self.apply_protocol_overrides(self.chain().protocol_parameters())
+  }
+
+  /// Protocol parameters that indexes which don't record their parameters
+  /// were built with, with the same overrides applied
+  pub(crate) fn legacy_protocol_parameters(&self) -> Result<ProtocolParameters> {
+    self.apply_protocol_overrides(self.chain().legacy_protocol_parameters())
+  }
+
+  fn apply_protocol_overrides(&self, parameters: ProtocolParameters) -> Result<ProtocolParameters> {
+    let mut parameters = parameters.with_overrides(&self.load_config()?.protocol)?;
+
+    if integration_test() && self.chain() != Chain::Regtest {
+      parameters.first_inscription_height = 0;
+      parameters.first_dune_height = 0;
     } else {
-      self
-        .first_inscription_height
-        .unwrap_or_else(|| self.chain().first_inscription_height())
+      if let Some(first_inscription_height) = self.first_inscription_height {
+        parameters.first_inscription_height = first_inscription_height;
+      }
+
+      if let Some(first_dune_height) = self.first_dune_height {
+        parameters.first_dune_height = first_dune_height;
+      }
     }
+
+    Ok(parameters)
   }
 
-  pub(crate) fn first_dune_height(&self) -> u32 {
-    if self.chain() == Chain::Regtest {
-      self.first_dune_height.unwrap_or(0)
-    } else if integration_test() {
-      0
-    } else {
-      self
-          .first_dune_height
-          .unwrap_or_else(|| self.chain().first_dune_height())
+  pub(crate) fn commit_policy(&self) -> CommitPolicy {
+    CommitPolicy {
+      blocks: self.commit_interval.unwrap_or(DEFAULT_COMMIT_INTERVAL),
+      interval: self.commit_seconds.map(Duration::from_secs),
+      cache_size: self.commit_cache_size,
+    }
+  }
+
+  /// Size of the index's cache, whether it is being created or opened
+  pub(crate) fn db_cache_size(&self) -> Result<usize> {
+    match self.db_cache_size {
+      Some(db_cache_size) => Ok(db_cache_size),
+      None => {
+        let mut sys = System::new();
+        sys.refresh_memory();
+        Ok(usize::try_from(sys.total_memory() / 4)?)
+      }
     }
   }
 
@@ -118,6 +178,14 @@ impl Options {
       return Ok(cookie_file.clone());
     }
 
+    Ok(self.dogecoin_data_dir()?.join(".cookie"))
+  }
+
+  pub(crate) fn dogecoin_blocks_dir(&self) -> Result<PathBuf> {
+    Ok(self.dogecoin_data_dir()?.join("blocks"))
+  }
+
+  fn dogecoin_data_dir(&self) -> Result<PathBuf> {
     let path = if let Some(dogecoin_data_dir) = &self.dogecoin_data_dir {
       dogecoin_data_dir.clone()
     } else if cfg!(target_os = "linux") {
@@ -130,9 +198,7 @@ impl Options {
         .join("Dogecoin")
     };
 
-    let path = self.chain().join_with_data_dir(&path);
-
-    Ok(path.join(".cookie"))
+    Ok(self.chain().join_with_data_dir(&path))
   }
 
   pub(crate) fn data_dir(&self) -> Result<PathBuf> {
@@ -146,6 +212,15 @@ impl Options {
     Ok(self.chain().join_with_data_dir(&base))
   }
 
+  pub(crate) fn native_wallet_path(&self) -> Result<PathBuf> {
+    Ok(
+      self
+        .data_dir()?
+        .join("wallets")
+        .join(format!("{}.json", self.wallet)),
+    )
+  }
+
   pub(crate) fn load_config(&self) -> Result<Config> {
     match &self.config {
       Some(path) => Ok(serde_yaml::from_reader(File::open(path)?)?),
@@ -219,7 +294,8 @@ impl Options {
       );
     }
 
-    if !create {
+    // Native wallets only use Dogecoin Core to broadcast transactions
+    if !create && !self.native_wallet_path()?.exists() {
       if !client.list_wallets()?.contains(&self.wallet) {
         client.load_wallet(&self.wallet)?;
       }
@@ -231,12 +307,14 @@ impl Options {
         .filter(|descriptor| descriptor.desc.starts_with("tr("))
         .count();
 
-      let rawtr = descriptors
+      let recovery = descriptors
         .iter()
-        .filter(|descriptor| descriptor.desc.starts_with("rawtr("))
+        .filter(|descriptor| {
+          descriptor.desc.starts_with("rawtr(") || descriptor.desc.starts_with("pkh(")
+        })
         .count();
 
-      if tr != 2 || descriptors.len() != 2 + rawtr {
+      if tr != 2 || descriptors.len() != 2 + recovery {
         bail!("wallet \"{}\" contains unexpected output descriptors, and does not appear to be an `ord` wallet, create a new wallet with `ord wallet create`", self.wallet);
       }
     }
@@ -363,6 +441,22 @@ mod tests {
     }));
   }
 
+  #[test]
+  fn blocks_dir_defaults_to_dogecoin_data_dir() {
+    let blocks_dir = Arguments::try_parse_from([
+      "ord",
+      "--dogecoin-data-dir=foo",
+      "--chain=testnet",
+      "index",
+    ])
+    .unwrap()
+    .options
+    .dogecoin_blocks_dir()
+    .unwrap();
+
+    assert_eq!(blocks_dir, Path::new("foo").join("testnet3").join("blocks"));
+  }
+
   #[test]
   fn mainnet_data_dir() {
     let data_dir = Arguments::try_parse_from(["ord", "index"])
@@ -593,10 +687,97 @@ mod tests {
         .unwrap(),
       Config {
         hidden: iter::once(id).collect(),
+        ..Default::default()
       }
     );
   }
 
+  #[test]
+  fn protocol_parameters_are_loaded_from_config() {
+    let tempdir = TempDir::new().unwrap();
+    let path = tempdir.path().join("ord.yaml");
+    fs::write(
+      &path,
+      "protocol:\n  first_dune_height: 7\n  dune_unlock_interval: 100\n",
+    )
+    .unwrap();
+
+    let parameters = Arguments::try_parse_from([
+      "ord",
+      "--regtest",
+      "--config",
+      path.to_str().unwrap(),
+      "index",
+    ])
+    .unwrap()
+    .options
+    .protocol_parameters()
+    .unwrap();
+
+    assert_eq!(parameters.first_dune_height, 7);
+    assert_eq!(parameters.dune_unlock_interval, 100);
+    assert_eq!(parameters.first_inscription_height, 0);
+  }
+
+  #[test]
+  fn first_dune_height_flag_overrides_config() {
+    let tempdir = TempDir::new().unwrap();
+    let path = tempdir.path().join("ord.yaml");
+    fs::write(&path, "protocol:\n  first_dune_height: 7\n").unwrap();
+
+    assert_eq!(
+      Arguments::try_parse_from([
+        "ord",
+        "--regtest",
+        "--config",
+        path.to_str().unwrap(),
+        "--first-dune-height",
+        "9",
+        "index",
+      ])
+      .unwrap()
+      .options
+      .protocol_parameters()
+      .unwrap()
+      .first_dune_height,
+      9,
+    );
+  }
+
+  #[test]
+  fn commit_policy() {
+    assert_eq!(
+      Arguments::try_parse_from(["ord", "index"])
+        .unwrap()
+        .options
+        .commit_policy(),
+      CommitPolicy::default(),
+    );
+
+    assert_eq!(
+      Arguments::try_parse_from([
+        "ord",
+        "--commit-interval",
+        "100",
+        "--commit-seconds",
+        "60",
+        "--commit-cache-size",
+        "1000000",
+        "index",
+      ])
+      .unwrap()
+      .options
+      .commit_policy(),
+      CommitPolicy {
+        blocks: 100,
+        interval: Some(Duration::from_secs(60)),
+        cache_size: Some(1000000),
+      },
+    );
+
+    assert!(Arguments::try_parse_from(["ord", "--commit-interval", "0", "index"]).is_err());
+  }
+
   #[test]
   fn config_is_loaded_from_config_dir_option_path() {
     let id = "8d363b28528b0cb86b5fd48615493fb175bdf132d2a3d20b4251bba3f130a5abi0"
@@ -624,6 +805,7 @@ mod tests {
       .unwrap(),
       Config {
         hidden: iter::once(id).collect(),
+        ..Default::default()
       }
     );
   }
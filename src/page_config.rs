@@ -5,4 +5,5 @@ pub(crate) struct PageConfig {
   pub(crate) chain: Chain,
   pub(crate) domain: Option<String>,
   pub(crate) index_sats: bool,
+  pub(crate) json_api_enabled: bool,
 }
@@ -0,0 +1,246 @@
+use super::*;
+
+/// Inscription and dune rules that differ between chains. Defaults come from
+/// `Chain::protocol_parameters`, and may be overridden in the config file for
+/// private test networks.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub(crate) struct ProtocolParameters {
+  /// Height below which inscriptions are not indexed
+  pub(crate) first_inscription_height: u32,
+  /// Height at which dunes activate
+  pub(crate) first_dune_height: u32,
+  /// Height at which the minimum length of dune names starts to decrease
+  pub(crate) dune_unlock_height: u32,
+  /// Minimum length of dune names before they start to unlock
+  pub(crate) initial_dune_length: u32,
+  /// Number of blocks after which the minimum length of dune names decreases
+  /// by one letter
+  pub(crate) dune_unlock_interval: u32,
+  /// First dune reserved for etchings that don't specify a dune name
+  pub(crate) first_reserved_dune: Dune,
+  /// Maximum size of OP_RETURN outputs relayed by Dogecoin Core
+  pub(crate) max_op_return_size: usize,
+  /// Height from which dunestones with overflowing varints or trailing
+  /// integers are cenotaphs
+  pub(crate) strict_dunestone_height: u32,
+}
+
+/// Overrides of a chain's protocol parameters, loaded from the `protocol` key
+/// of the config file
+#[derive(Deserialize, Default, PartialEq, Debug)]
+#[serde(deny_unknown_fields)]
+pub(crate) struct ProtocolParametersConfig {
+  pub(crate) first_inscription_height: Option<u32>,
+  pub(crate) first_dune_height: Option<u32>,
+  pub(crate) dune_unlock_height: Option<u32>,
+  pub(crate) initial_dune_length: Option<u32>,
+  pub(crate) dune_unlock_interval: Option<u32>,
+  pub(crate) first_reserved_dune: Option<Dune>,
+  pub(crate) max_op_return_size: Option<usize>,
+  pub(crate) strict_dunestone_height: Option<u32>,
+}
+
+impl ProtocolParameters {
+  /// Size of the largest OP_RETURN output relayed by Dogecoin Core with its
+  /// default `-datacarriersize`
+  pub(crate) const MAX_STANDARD_OP_RETURN_SIZE: usize = 82;
+
+  pub(crate) fn with_overrides(self, config: &ProtocolParametersConfig) -> Result<Self> {
+    let parameters = Self {
+      first_inscription_height: config
+        .first_inscription_height
+        .unwrap_or(self.first_inscription_height),
+      first_dune_height: config.first_dune_height.unwrap_or(self.first_dune_height),
+      dune_unlock_height: config.dune_unlock_height.unwrap_or(self.dune_unlock_height),
+      initial_dune_length: config
+        .initial_dune_length
+        .unwrap_or(self.initial_dune_length),
+      dune_unlock_interval: config
+        .dune_unlock_interval
+        .unwrap_or(self.dune_unlock_interval),
+      first_reserved_dune: config
+        .first_reserved_dune
+        .unwrap_or(self.first_reserved_dune),
+      max_op_return_size: config.max_op_return_size.unwrap_or(self.max_op_return_size),
+      strict_dunestone_height: config
+        .strict_dunestone_height
+        .unwrap_or(self.strict_dunestone_height),
+    };
+
+    ensure!(
+      (1..=28).contains(&parameters.initial_dune_length),
+      "initial dune length must be between 1 and 28: {}",
+      parameters.initial_dune_length,
+    );
+
+    ensure!(
+      parameters.dune_unlock_interval > 0,
+      "dune unlock interval must be greater than zero",
+    );
+
+    Ok(parameters)
+  }
+
+  /// The parameters by name, as recorded in the index
+  pub(crate) fn entries(&self) -> [(&'static str, u128); 8] {
+    [
+      (
+        "first_inscription_height",
+        self.first_inscription_height.into(),
+      ),
+      ("first_dune_height", self.first_dune_height.into()),
+      ("dune_unlock_height", self.dune_unlock_height.into()),
+      ("initial_dune_length", self.initial_dune_length.into()),
+      ("dune_unlock_interval", self.dune_unlock_interval.into()),
+      ("first_reserved_dune", self.first_reserved_dune.0),
+      (
+        "max_op_return_size",
+        u128::try_from(self.max_op_return_size).unwrap(),
+      ),
+      (
+        "strict_dunestone_height",
+        self.strict_dunestone_height.into(),
+      ),
+    ]
+  }
+
+  /// Returns the smallest dune that may be etched in block `height`
+  pub(crate) fn minimum_dune_at_height(&self, height: u32) -> Dune {
+    Dune::minimum_at_height_with_schedule(
+      self.dune_unlock_height,
+      self.dune_unlock_interval,
+      self.initial_dune_length,
+      height,
+    )
+  }
+
+  /// Returns the rules for deciphering dunestones in block `height`
+  pub(crate) fn dunestone_rules(&self, height: u32) -> Rules {
+    if height < self.strict_dunestone_height {
+      Rules::Legacy
+    } else {
+      Rules::Strict
+    }
+  }
+
+  pub(crate) fn is_reserved(&self, dune: Dune) -> bool {
+    dune.is_reserved_from(self.first_reserved_dune)
+  }
+
+  /// Returns the `n`th dune assigned to etchings that don't specify a name
+  pub(crate) fn reserved_dune(&self, n: u128) -> Dune {
+    Dune::reserved_from(self.first_reserved_dune, n)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn mainnet_parameters_match_protocol_defaults() {
+    let parameters = Chain::Mainnet.protocol_parameters();
+
+    assert_eq!(
+      parameters.minimum_dune_at_height(parameters.first_dune_height + 1000),
+      Dune::minimum_at_height(
+        parameters.first_dune_height,
+        parameters.first_dune_height + 1000
+      ),
+    );
+
+    assert_eq!(parameters.reserved_dune(5), Dune::reserved(5));
+    assert!(parameters.is_reserved(Dune::reserved(0)));
+  }
+
+  #[test]
+  fn dune_names_unlock_from_first_dune_height() {
+    for chain in [
+      Chain::Mainnet,
+      Chain::Regtest,
+      Chain::Signet,
+      Chain::Testnet,
+    ] {
+      let parameters = chain.protocol_parameters();
+
+      assert_eq!(parameters.dune_unlock_height, parameters.first_dune_height);
+
+      assert_eq!(
+        parameters.minimum_dune_at_height(parameters.first_dune_height + 1000),
+        Dune::minimum_at_height(
+          parameters.first_dune_height,
+          parameters.first_dune_height + 1000
+        ),
+      );
+    }
+  }
+
+  #[test]
+  fn overrides_replace_defaults() {
+    let parameters = Chain::Testnet
+      .protocol_parameters()
+      .with_overrides(&ProtocolParametersConfig {
+        first_dune_height: Some(10),
+        dune_unlock_interval: Some(5),
+        ..Default::default()
+      })
+      .unwrap();
+
+    assert_eq!(parameters.first_dune_height, 10);
+    assert_eq!(parameters.dune_unlock_interval, 5);
+    assert_eq!(
+      parameters.first_inscription_height,
+      Chain::Testnet
+        .protocol_parameters()
+        .first_inscription_height,
+    );
+  }
+
+  #[test]
+  fn strict_dunestone_rules_apply_from_activation_height() {
+    let parameters = Chain::Regtest
+      .protocol_parameters()
+      .with_overrides(&ProtocolParametersConfig {
+        strict_dunestone_height: Some(10),
+        ..Default::default()
+      })
+      .unwrap();
+
+    assert_eq!(parameters.dunestone_rules(9), Rules::Legacy);
+    assert_eq!(parameters.dunestone_rules(10), Rules::Strict);
+
+    assert_eq!(
+      Chain::Mainnet
+        .protocol_parameters()
+        .dunestone_rules(u32::MAX - 1),
+      Rules::Legacy,
+    );
+  }
+
+  #[test]
+  fn invalid_overrides_are_rejected() {
+    assert_eq!(
+      Chain::Regtest
+        .protocol_parameters()
+        .with_overrides(&ProtocolParametersConfig {
+          initial_dune_length: Some(0),
+          ..Default::default()
+        })
+        .unwrap_err()
+        .to_string(),
+      "initial dune length must be between 1 and 28: 0",
+    );
+
+    assert_eq!(
+      Chain::Regtest
+        .protocol_parameters()
+        .with_overrides(&ProtocolParametersConfig {
+          dune_unlock_interval: Some(0),
+          ..Default::default()
+        })
+        .unwrap_err()
+        .to_string(),
+      "dune unlock interval must be greater than zero",
+    );
+  }
+}
@@ -1,9 +1,10 @@
 use super::*;
 
 pub mod balances;
+pub mod decode;
 pub mod epochs;
 pub mod find;
-mod index;
+pub mod index;
 pub mod info;
 pub mod list;
 pub mod parse;
@@ -24,12 +25,14 @@ fn print_json(output: impl Serialize) -> Result {
 pub(crate) enum Subcommand {
   #[command(about = "List all dune balances")]
   Balances,
+  #[command(about = "Decode dunestones and inscriptions in raw transactions")]
+  Decode(decode::Decode),
   #[command(about = "List the first satoshis of each reward epoch")]
   Epochs,
   #[command(about = "Find a satoshi's current location")]
   Find(find::Find),
-  #[command(about = "Update the index")]
-  Index,
+  #[command(about = "Update, export or import the index")]
+  Index(index::IndexCommand),
   #[command(about = "Display index statistics")]
   Info(info::Info),
   #[command(about = "List the satoshis in an output")]
@@ -39,7 +42,7 @@ pub(crate) enum Subcommand {
   #[command(about = "Run an explorer server populated with inscriptions")]
   Preview(preview::Preview),
   #[command(about = "List all dunes")]
-  Dunes,
+  Dunes(dunes::Dunes),
   #[command(about = "Run the explorer server")]
   Server(server::Server),
   #[command(about = "Display information about a block's subsidy")]
@@ -54,14 +57,15 @@ impl Subcommand {
   pub(crate) fn run(self, options: Options) -> SubcommandResult {
     match self {
       Self::Balances => balances::run(options),
+      Self::Decode(decode) => decode.run(options),
       Self::Epochs => epochs::run(),
       Self::Find(find) => find.run(options),
-      Self::Index => index::run(options),
+      Self::Index(index) => index.run(options),
       Self::Info(info) => info.run(options),
       Self::List(list) => list.run(options),
       Self::Parse(parse) => parse.run(),
       Self::Preview(preview) => preview.run(),
-      Self::Dunes => dunes::run(options),
+      Self::Dunes(dunes) => dunes.run(options),
       Self::Server(server) => {
         let index = Arc::new(Index::open(&options)?);
         let handle = axum_server::Handle::new();
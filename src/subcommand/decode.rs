@@ -0,0 +1,280 @@
+use {super::*, crate::inscription::ParsedInscription};
+
+#[derive(Debug, Parser)]
+#[command(group(
+  ArgGroup::new("source")
+    .required(true)
+    .args(&["tx", "txid"]),
+))]
+pub(crate) struct Decode {
+  #[arg(
+    long,
+    help = "Decode raw transaction <TX>, given as hex or as a path to a file containing hex or binary. May be repeated to decode an inscription spread across multiple transactions, in order."
+  )]
+  tx: Vec<String>,
+  #[arg(
+    long,
+    help = "Fetch transaction <TXID> from Dogecoin Core and decode it. May be repeated like `--tx`."
+  )]
+  txid: Vec<Txid>,
+  #[arg(
+    long,
+    help = "Decipher dunestones under the rules of block <HEIGHT>. Defaults to the next block for `--txid`, and to the rules of future blocks for `--tx`, so that it can be decoded offline."
+  )]
+  height: Option<u32>,
+}
+
+#[derive(Serialize, Debug, PartialEq)]
+pub struct Output {
+  pub transactions: Vec<DecodedTransaction>,
+  pub inscription: Option<DecodedInscription>,
+}
+
+#[derive(Serialize, Debug, PartialEq)]
+pub struct DecodedTransaction {
+  pub txid: Txid,
+  pub dunestone: Option<Dunestone>,
+  pub cenotaph: Option<Cenotaph>,
+  pub integers: Option<Vec<u128>>,
+  pub integers_flaw: Option<Flaw>,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+pub struct DecodedInscription {
+  pub complete: bool,
+  pub content_type: Option<String>,
+  pub content_length: Option<usize>,
+  pub pieces: u64,
+  pub transactions: usize,
+}
+
+impl Decode {
+  pub(crate) fn run(self, options: Options) -> SubcommandResult {
+    let protocol = options.protocol_parameters()?;
+
+    let (transactions, height) = if self.txid.is_empty() {
+      (
+        self
+          .tx
+          .iter()
+          .map(|tx| Self::parse_transaction(tx))
+          .collect::<Result<Vec<Transaction>>>()?,
+        self.height,
+      )
+    } else {
+      let client = options.dogecoin_rpc_client()?;
+
+      let transactions = self
+        .txid
+        .iter()
+        .map(|txid| {
+          client
+            .get_raw_transaction(txid, None)
+            .with_context(|| format!("failed to fetch transaction {txid}"))
+        })
+        .collect::<Result<Vec<Transaction>>>()?;
+
+      let height = match self.height {
+        Some(height) => height,
+        None => u32::try_from(client.get_block_count()?).unwrap() + 1,
+      };
+
+      (transactions, Some(height))
+    };
+
+    let rules = match height {
+      Some(height) => protocol.dunestone_rules(height),
+      None if protocol.strict_dunestone_height == u32::MAX => Rules::Legacy,
+      None => Rules::Strict,
+    };
+
+    Ok(Box::new(Self::decode(transactions, rules)))
+  }
+
+  fn parse_transaction(tx: &str) -> Result<Transaction> {
+    let path = Path::new(tx);
+
+    let bytes = if path.is_file() {
+      let contents = fs::read(path).with_context(|| format!("io error reading {tx}"))?;
+
+      match std::str::from_utf8(&contents)
+        .ok()
+        .and_then(|contents| hex::decode(contents.trim()).ok())
+      {
+        Some(bytes) => bytes,
+        None => contents,
+      }
+    } else {
+      hex::decode(tx.trim()).context("transaction is neither hex nor a path to a file")?
+    };
+
+    bitcoin::consensus::deserialize(&bytes).context("failed to deserialize transaction")
+  }
+
+  fn decode(transactions: Vec<Transaction>, rules: Rules) -> Output {
+    let inscription = transactions
+      .first()
+      .and_then(Inscription::pieces)
+      .map(|pieces| {
+        let (complete, inscription) =
+          match Inscription::from_transactions(transactions.clone()) {
+            ParsedInscription::Complete(inscription) => (true, Some(inscription)),
+            ParsedInscription::Partial | ParsedInscription::None => (false, None),
+          };
+
+        DecodedInscription {
+          complete,
+          content_type: inscription
+            .as_ref()
+            .and_then(|inscription| inscription.content_type())
+            .map(str::to_string),
+          content_length: inscription
+            .as_ref()
+            .and_then(|inscription| inscription.content_length()),
+          pieces,
+          transactions: transactions.len(),
+        }
+      });
+
+    Output {
+      transactions: transactions
+        .iter()
+        .map(|transaction| {
+          let dunestone = Dunestone::from_transaction_with_rules(transaction, rules);
+
+          let (integers, integers_flaw) = match Dunestone::payload(transaction).ok().flatten() {
+            Some(payload) => {
+              let (integers, flaw) = Dunestone::partial_integers(&payload);
+              (Some(integers), flaw)
+            }
+            None => (None, None),
+          };
+
+          DecodedTransaction {
+            txid: transaction.txid(),
+            cenotaph: dunestone.as_ref().and_then(Dunestone::cenotaph),
+            dunestone,
+            integers,
+            integers_flaw,
+          }
+        })
+        .collect(),
+      inscription,
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn decode_dunestone() {
+    let dunestone = Dunestone {
+      edicts: vec![Edict {
+        id: 1 << 16,
+        amount: 10,
+        output: 1,
+      }],
+      ..Default::default()
+    };
+
+    let transaction = Transaction {
+      version: 1,
+      lock_time: bitcoin::PackedLockTime::ZERO,
+      input: Vec::new(),
+      output: vec![TxOut {
+        script_pubkey: dunestone.encipher(),
+        value: 0,
+      }],
+    };
+
+    assert_eq!(
+      Decode::decode(vec![transaction.clone()], Rules::Strict),
+      Output {
+        transactions: vec![DecodedTransaction {
+          txid: transaction.txid(),
+          dunestone: Some(dunestone),
+          cenotaph: None,
+          integers: Some(vec![0, 1 << 16, 10, 1]),
+          integers_flaw: None,
+        }],
+        inscription: None,
+      }
+    );
+  }
+
+  #[test]
+  fn decode_flawed_dunestone_integers() {
+    let transaction = Transaction {
+      version: 1,
+      lock_time: bitcoin::PackedLockTime::ZERO,
+      input: Vec::new(),
+      output: vec![TxOut {
+        script_pubkey: script::Builder::new()
+          .push_opcode(opcodes::all::OP_RETURN)
+          .push_slice(b"D")
+          .push_slice(&[0, 1, 128])
+          .into_script(),
+        value: 0,
+      }],
+    };
+
+    let output = Decode::decode(vec![transaction], Rules::Strict);
+
+    assert_eq!(output.transactions[0].integers, Some(vec![0, 1]));
+    assert_eq!(output.transactions[0].integers_flaw, Some(Flaw::Varint));
+  }
+
+  #[test]
+  fn decode_dunestone_under_rules() {
+    let transaction = Transaction {
+      version: 1,
+      lock_time: bitcoin::PackedLockTime::ZERO,
+      input: Vec::new(),
+      output: vec![TxOut {
+        script_pubkey: script::Builder::new()
+          .push_opcode(opcodes::all::OP_RETURN)
+          .push_slice(b"D")
+          .push_slice(&[0, 1, 2])
+          .into_script(),
+        value: 0,
+      }],
+    };
+
+    assert_eq!(
+      Decode::decode(vec![transaction.clone()], Rules::Legacy).transactions[0].cenotaph,
+      None
+    );
+
+    assert_eq!(
+      Decode::decode(vec![transaction], Rules::Strict).transactions[0]
+        .cenotaph
+        .map(|cenotaph| cenotaph.flaw),
+      Some(Flaw::TrailingIntegers)
+    );
+  }
+
+  #[test]
+  fn parse_hex_transaction() {
+    let transaction = Transaction {
+      version: 1,
+      lock_time: bitcoin::PackedLockTime::ZERO,
+      input: vec![TxIn {
+        previous_output: OutPoint::null(),
+        script_sig: Script::new(),
+        sequence: Sequence::MAX,
+        witness: Witness::new(),
+      }],
+      output: Vec::new(),
+    };
+
+    assert_eq!(
+      Decode::parse_transaction(&hex::encode(bitcoin::consensus::serialize(&transaction)))
+        .unwrap(),
+      transaction
+    );
+
+    assert!(Decode::parse_transaction("xyz").is_err());
+  }
+}
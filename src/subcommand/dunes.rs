@@ -1,5 +1,28 @@
 use super::*;
 
+pub mod snapshot;
+
+#[derive(Debug, Parser)]
+pub(crate) struct Dunes {
+  #[command(subcommand)]
+  subcommand: Option<DunesSubcommand>,
+}
+
+#[derive(Debug, Parser)]
+enum DunesSubcommand {
+  #[command(about = "List holders of a dune at the end of a block")]
+  Snapshot(snapshot::Snapshot),
+}
+
+impl Dunes {
+  pub(crate) fn run(self, options: Options) -> SubcommandResult {
+    match self.subcommand {
+      Some(DunesSubcommand::Snapshot(snapshot)) => snapshot.run(options),
+      None => run(options),
+    }
+  }
+}
+
 #[derive(Debug, PartialEq, Serialize, Deserialize)]
 pub struct Output {
   pub dunes: BTreeMap<Dune, DuneInfo>,
@@ -27,7 +50,7 @@ pub struct DuneInfo {
   pub tx: u32,
 }
 
-pub(crate) fn run(options: Options) -> SubcommandResult {
+fn run(options: Options) -> SubcommandResult {
   let index = Index::open(&options)?;
 
   ensure!(
@@ -0,0 +1,49 @@
+use super::*;
+
+#[derive(Debug, Parser)]
+pub(crate) struct Snapshot {
+  #[arg(long, help = "List holders of <DUNE>. May contain `.` or `•` as spacers.")]
+  dune: SpacedDune,
+  #[arg(long, help = "List balances at the end of block <HEIGHT>.")]
+  height: u32,
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+pub struct Output {
+  pub dune: SpacedDune,
+  pub id: DuneId,
+  pub divisibility: u8,
+  pub height: u32,
+  pub holders: BTreeMap<String, u128>,
+}
+
+impl Snapshot {
+  pub(crate) fn run(self, options: Options) -> SubcommandResult {
+    let index = Index::open(&options)?;
+
+    ensure!(
+      index.has_dune_history_index(),
+      "`ord dunes snapshot` requires index created with `--index-dune-history` flag",
+    );
+
+    index.update()?;
+
+    ensure!(
+      self.height < index.block_count()?,
+      "block {} has not been indexed",
+      self.height,
+    );
+
+    let (id, entry) = index
+      .dune(self.dune.dune)?
+      .with_context(|| format!("dune `{}` has not been etched", self.dune))?;
+
+    Ok(Box::new(Output {
+      dune: entry.spaced_dune(),
+      id,
+      divisibility: entry.divisibility,
+      height: self.height,
+      holders: index.get_dune_snapshot(id, self.height)?,
+    }))
+  }
+}
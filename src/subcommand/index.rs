@@ -1,10 +1,41 @@
 use super::*;
 
+pub mod export;
+pub mod import;
+pub mod migrate;
 mod update;
 
-pub(crate) fn run(options: Options) -> SubcommandResult {
-  let index = Index::open(&options)?;
+#[derive(Debug, Parser)]
+pub(crate) struct IndexCommand {
+  #[command(subcommand)]
+  subcommand: Option<IndexSubcommand>,
+}
+
+#[derive(Debug, Parser)]
+pub(crate) enum IndexSubcommand {
+  #[command(about = "Write a snapshot of the index at a block height")]
+  Export(export::Export),
+  #[command(about = "Adopt an index snapshot after verifying it against Dogecoin Core")]
+  Import(import::Import),
+  #[command(about = "Upgrade an index built by an older version of ord")]
+  Migrate(migrate::Migrate),
+  #[command(about = "Update the index")]
+  Update,
+}
+
+impl IndexCommand {
+  pub(crate) fn run(self, options: Options) -> SubcommandResult {
+    match self.subcommand {
+      Some(IndexSubcommand::Export(export)) => export.run(options),
+      Some(IndexSubcommand::Import(import)) => import.run(options),
+      Some(IndexSubcommand::Migrate(migrate)) => migrate.run(options),
+      Some(IndexSubcommand::Update) => update::run(options),
+      None => {
+        let index = Index::open(&options)?;
 
-  index.update()?;
-  Ok(Box::new(()))
+        index.update()?;
+        Ok(Box::new(()))
+      }
+    }
+  }
 }
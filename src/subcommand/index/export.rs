@@ -0,0 +1,36 @@
+use super::*;
+
+#[derive(Debug, Parser)]
+pub(crate) struct Export {
+  #[arg(long, help = "Export index at block <HEIGHT>.")]
+  height: u32,
+  #[arg(help = "Write snapshot to <FILE>.")]
+  file: PathBuf,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct Output {
+  pub snapshot: PathBuf,
+  pub height: u32,
+  pub block_hash: BlockHash,
+  pub size: u64,
+}
+
+impl Export {
+  pub(crate) fn run(self, mut options: Options) -> SubcommandResult {
+    options.height_limit = Some(self.height + 1);
+
+    let index = Index::open(&options)?;
+
+    index.update()?;
+
+    let snapshot = index.export(self.height, &self.file)?;
+
+    Ok(Box::new(Output {
+      size: fs::metadata(&self.file)?.len(),
+      snapshot: self.file,
+      height: snapshot.height,
+      block_hash: snapshot.block_hash,
+    }))
+  }
+}
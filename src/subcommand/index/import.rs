@@ -0,0 +1,28 @@
+use super::*;
+
+#[derive(Debug, Parser)]
+pub(crate) struct Import {
+  #[arg(help = "Import snapshot from <FILE>.")]
+  file: PathBuf,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct Output {
+  pub height: u32,
+  pub block_hash: BlockHash,
+  pub index_sats: bool,
+  pub index_dunes: bool,
+}
+
+impl Import {
+  pub(crate) fn run(self, options: Options) -> SubcommandResult {
+    let snapshot = Index::import(&options, &self.file)?;
+
+    Ok(Box::new(Output {
+      height: snapshot.height,
+      block_hash: snapshot.block_hash,
+      index_sats: snapshot.index_sats,
+      index_dunes: snapshot.index_dunes,
+    }))
+  }
+}
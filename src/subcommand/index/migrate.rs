@@ -0,0 +1,25 @@
+use {super::*, crate::index::MigrationOutput};
+
+#[derive(Debug, Parser)]
+pub(crate) struct Migrate {
+  #[arg(
+    long,
+    help = "Run migrations without saving their changes, to check that they succeed."
+  )]
+  dry_run: bool,
+}
+
+#[derive(Serialize, Debug)]
+pub struct Output {
+  pub dry_run: bool,
+  pub migrations: Vec<MigrationOutput>,
+}
+
+impl Migrate {
+  pub(crate) fn run(self, options: Options) -> SubcommandResult {
+    Ok(Box::new(Output {
+      dry_run: self.dry_run,
+      migrations: Index::migrate(&options, self.dry_run)?,
+    }))
+  }
+}
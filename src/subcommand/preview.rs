@@ -63,6 +63,7 @@ impl Preview {
 
     super::wallet::Wallet::Create(super::wallet::create::Create {
       passphrase: "".into(),
+      native: false,
     })
     .run(options.clone())?;
 
@@ -80,7 +81,9 @@ impl Preview {
           super::wallet::inscribe::Inscribe {
             fee_rate: FeeRate::try_from(1.0).unwrap(),
             commit_fee_rate: None,
-            file,
+            file: Some(file),
+            resume: None,
+            abandon: None,
             no_backup: true,
             satpoint: None,
             dry_run: false,
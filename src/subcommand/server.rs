@@ -1,6 +1,7 @@
 use axum::Json;
 use {
     self::{
+        accept_json::AcceptJson,
         deserialize_from_str::DeserializeFromStr,
         error::{OptionExt, ServerError, ServerResult},
     },
@@ -9,17 +10,21 @@ use {
         extract::{Extension, Path, Query},
         headers::UserAgent,
         http::{header, HeaderMap, HeaderValue, StatusCode, Uri},
-        response::{IntoResponse, Redirect, Response},
+        response::{
+            sse::{self, KeepAlive, Sse},
+            IntoResponse, Redirect, Response,
+        },
         Router,
         routing::get, TypedHeader,
     },
     axum_server::Handle,
     crate::{
         dunes::Dune,
+        index::{DuneEvent, DuneEventId, Mempool},
         page_config::PageConfig,
         templates::{
-            BlockHtml, DuneBalancesHtml, DuneHtml, DunesHtml,
-            HomeHtml, InputHtml, InscriptionHtml,
+            BlockHtml, DuneActivityHtml, DuneActivityJson, DuneBalancesHtml, DuneDistributionJson,
+            DuneHtml, DunesHtml, HomeHtml, InputHtml, InscriptionHtml,
             InscriptionsHtml, OutputHtml, PageContent, PageHtml, PreviewAudioHtml,
             PreviewImageHtml, PreviewPdfHtml, PreviewTextHtml, PreviewUnknownHtml, PreviewVideoHtml, RangeHtml, RareTxt, SatHtml, TransactionHtml,
         },
@@ -31,8 +36,10 @@ use {
         axum::AxumAcceptor,
         caches::DirCache,
     },
-    std::{cmp::Ordering, str},
+    futures::Stream,
+    std::{cmp::Ordering, str, sync::RwLock},
     super::*,
+    tokio::sync::broadcast,
     tokio_stream::StreamExt,
     tower_http::{
         compression::CompressionLayer,
@@ -42,12 +49,24 @@ use {
 
     ,
 };
-use crate::templates::{DuneAddressJson, DuneBalance, DuneOutput, DuneOutputJson, Utxo};
+use crate::templates::{
+    DuneAddressJson, DuneBalance, DuneOutput, DuneOutputJson, LegacyDuneAddressJson, Utxo,
+};
 use linked_hash_map::LinkedHashMap;
 
+mod accept_json;
 mod error;
 mod query;
 
+const DUNE_ACTIVITY_PAGE_SIZE: usize = 100;
+const DUNE_PAGE_TOP_HOLDERS: usize = 10;
+const DUNE_DISTRIBUTION_DEFAULT_LIMIT: usize = 100;
+const DUNE_DISTRIBUTION_MAX_LIMIT: usize = 1000;
+const ADDRESS_OUTPUTS_DEFAULT_LIMIT: usize = 100;
+const ADDRESS_OUTPUTS_MAX_LIMIT: usize = 1000;
+const ADDRESS_OUTPUTS_MAX_SCANNED: usize = 10_000;
+const LEGACY_ADDRESS_PAGE_SIZE: usize = 10;
+
 enum SpawnConfig {
     Https(AxumAcceptor),
     Http,
@@ -61,6 +80,7 @@ struct Search {
 
 #[derive(Deserialize)]
 struct UtxoBalanceQuery {
+    cursor: Option<DeserializeFromStr<query::Cursor>>,
     limit: Option<usize>,
     show_all: Option<bool>,
     show_unsafe: Option<bool>,
@@ -69,6 +89,15 @@ struct UtxoBalanceQuery {
 
 #[derive(Debug, PartialEq, Serialize, Deserialize)]
 pub(crate) struct UtxoAddressJson {
+    pub(crate) utxos: Vec<Utxo>,
+    pub(crate) pending: Vec<Utxo>,
+    pub(crate) next: Option<String>,
+}
+
+/// Response of the deprecated page numbered `/utxos/balance/:address/:page`
+/// endpoint
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+pub(crate) struct LegacyUtxoAddressJson {
     pub(crate) utxos: Vec<Utxo>,
     pub(crate) total_utxos: usize,
     pub(crate) total_shibes: u128,
@@ -77,11 +106,29 @@ pub(crate) struct UtxoAddressJson {
 
 #[derive(Deserialize)]
 struct DunesBalanceQuery {
+    cursor: Option<DeserializeFromStr<query::Cursor>>,
+    limit: Option<usize>,
     show_all: Option<bool>,
     list_dunes: Option<bool>,
     filter: Option<SpacedDune>,
 }
 
+#[derive(Deserialize)]
+struct DuneHoldersQuery {
+    height: Option<u32>,
+}
+
+#[derive(Deserialize)]
+struct DuneDistributionQuery {
+    limit: Option<usize>,
+}
+
+#[derive(Deserialize)]
+struct EventsQuery {
+    dune: Option<SpacedDune>,
+    address: Option<String>,
+}
+
 #[derive(Deserialize)]
 struct OutputsQuery {
     outputs: String,
@@ -142,6 +189,16 @@ pub(crate) struct Server {
     https: bool,
     #[clap(long, help = "Redirect HTTP traffic to HTTPS.")]
     redirect_http_to_https: bool,
+    #[clap(
+    long,
+    help = "Serve JSON from explorer routes to requests whose `Accept` header prefers `application/json`. Without this flag, such requests are served HTML."
+    )]
+    enable_json_api: bool,
+    #[clap(
+    long,
+    help = "Poll the mempool and include unconfirmed outputs, pending dune balances and outputs spent by unconfirmed transactions in address and output endpoints."
+    )]
+    watch_mempool: bool,
 }
 
 impl Server {
@@ -155,6 +212,23 @@ impl Server {
                 thread::sleep(Duration::from_millis(5000));
             });
 
+            let mempool = Arc::new(RwLock::new(Mempool::default()));
+
+            if self.watch_mempool {
+                let index = index.clone();
+                let mempool = mempool.clone();
+                thread::spawn(move || {
+                    let mut overlay = Mempool::default();
+                    loop {
+                        match overlay.update(&index) {
+                            Ok(()) => *mempool.write().unwrap() = overlay.clone(),
+                            Err(error) => log::warn!("{error}"),
+                        }
+                        thread::sleep(Duration::from_millis(5000));
+                    }
+                });
+            }
+
             let config = options.load_config()?;
             let acme_domains = self.acme_domains()?;
 
@@ -162,6 +236,7 @@ impl Server {
                 chain: options.chain(),
                 domain: acme_domains.first().cloned(),
                 index_sats: index.has_sat_index(),
+                json_api_enabled: self.enable_json_api,
             });
 
             let router = Router::new()
@@ -187,13 +262,20 @@ impl Server {
                 .route("/range/:start/:end", get(Self::range))
                 .route("/rare.txt", get(Self::rare_txt))
                 .route("/dune/:dune", get(Self::dune))
+                .route("/dune/:dune/activity", get(Self::dune_activity_unpaginated))
+                .route("/dune/:dune/activity/:from", get(Self::dune_activity))
+                .route("/dune/:dune/holders", get(Self::dune_holders))
                 .route("/dunes", get(Self::dunes))
+                .route("/dunes/activity/:dune", get(Self::dune_activity_json_unpaginated))
+                .route("/dunes/activity/:dune/:from", get(Self::dune_activity_json))
                 .route("/dunes/balances", get(Self::dunes_balances))
-                .route("/dunes/balance/:address", get(Self::dunes_by_address_unpaginated))
-                .route("/dunes/balance/:address/:page", get(Self::dunes_by_address))
-                .route("/utxos/balance/:address", get(Self::utxos_by_address_unpaginated))
-                .route("/utxos/balance/:address/:page", get(Self::utxos_by_address))
+                .route("/dunes/distribution/:dune", get(Self::dune_distribution))
+                .route("/dunes/balance/:address", get(Self::dunes_by_address))
+                .route("/dunes/balance/:address/:page", get(Self::dunes_by_address_legacy))
+                .route("/utxos/balance/:address", get(Self::utxos_by_address))
+                .route("/utxos/balance/:address/:page", get(Self::utxos_by_address_legacy))
                 .route("/dunes_on_outputs", get(Self::dunes_by_outputs))
+                .route("/events", get(Self::events))
                 .route("/sat/:sat", get(Self::sat))
                 .route("/search", get(Self::search_by_query))
                 .route("/search/*query", get(Self::search_by_path))
@@ -201,6 +283,7 @@ impl Server {
                 .route("/status", get(Self::status))
                 .route("/tx/:txid", get(Self::transaction))
                 .layer(Extension(index))
+                .layer(Extension(mempool))
                 .layer(Extension(page_config))
                 .layer(Extension(Arc::new(config)))
                 .layer(SetResponseHeaderLayer::if_not_present(
@@ -391,18 +474,22 @@ impl Server {
         Extension(page_config): Extension<Arc<PageConfig>>,
         Extension(index): Extension<Arc<Index>>,
         Path(DeserializeFromStr(sat)): Path<DeserializeFromStr<Sat>>,
-    ) -> ServerResult<PageHtml<SatHtml>> {
+        accept_json: AcceptJson,
+    ) -> ServerResult<Response> {
         let satpoint = index.rare_sat_satpoint(sat)?;
 
-        Ok(
-            SatHtml {
-                sat,
-                satpoint,
-                blocktime: index.blocktime(sat.height())?,
-                inscription: index.get_inscription_id_by_sat(sat)?,
-            }
-                .page(page_config)
-        )
+        let sat = SatHtml {
+            sat,
+            satpoint,
+            blocktime: index.blocktime(sat.height())?,
+            inscription: index.get_inscription_id_by_sat(sat)?,
+        };
+
+        Ok(if accept_json.0 {
+            Json(sat.to_json()).into_response()
+        } else {
+            sat.page(page_config).into_response()
+        })
     }
 
     async fn ordinal(Path(sat): Path<String>) -> Redirect {
@@ -413,7 +500,8 @@ impl Server {
         Extension(page_config): Extension<Arc<PageConfig>>,
         Extension(index): Extension<Arc<Index>>,
         Path(outpoint): Path<OutPoint>,
-    ) -> ServerResult<PageHtml<OutputHtml>> {
+        accept_json: AcceptJson,
+    ) -> ServerResult<Response> {
         let list = index.list(outpoint)?;
 
         let output = if outpoint == OutPoint::null() {
@@ -443,243 +531,447 @@ impl Server {
 
         let dunes = index.get_dune_balances_for_outpoint(outpoint)?;
 
-        Ok(
-            OutputHtml {
-                outpoint,
-                inscriptions,
-                list,
-                chain: page_config.chain,
-                output,
-                dunes,
-            }
-                .page(page_config)
-        )
+        let output = OutputHtml {
+            outpoint,
+            inscriptions,
+            list,
+            chain: page_config.chain,
+            output,
+            dunes,
+        };
+
+        Ok(if accept_json.0 {
+            Json(output.to_json()).into_response()
+        } else {
+            output.page(page_config).into_response()
+        })
+    }
+
+    fn address_outputs_limit(limit: Option<usize>) -> ServerResult<usize> {
+        let limit = limit.unwrap_or(ADDRESS_OUTPUTS_DEFAULT_LIMIT);
+
+        if limit == 0 || limit > ADDRESS_OUTPUTS_MAX_LIMIT {
+            return Err(ServerError::BadRequest(format!(
+                "limit must be between 1 and {ADDRESS_OUTPUTS_MAX_LIMIT}"
+            )));
+        }
+
+        Ok(limit)
+    }
+
+    fn parse_address(address: &str) -> ServerResult<Address> {
+        address
+            .parse()
+            .map_err(|err| ServerError::BadRequest(format!("invalid address `{address}`: {err}")))
     }
 
     async fn utxos_by_address(
-        Extension(server_config): Extension<Arc<PageConfig>>,
         Extension(index): Extension<Arc<Index>>,
-        Path(params): Path<(String, u32)>,
+        Extension(mempool): Extension<Arc<RwLock<Mempool>>>,
+        Path(address): Path<String>,
         Query(query): Query<UtxoBalanceQuery>,
     ) -> ServerResult<Response> {
-        Self::get_utxos_by_address(index, params.0, Some(params.1), query).await
+        if query.show_all.is_some() {
+            return Self::legacy_utxos_by_address(&index, &mempool, &address, 0, &query);
+        }
+
+        let script = Self::parse_address(&address)?.script_pubkey();
+        let limit = Self::address_outputs_limit(query.limit)?;
+        let show_unsafe = query.show_unsafe.unwrap_or(false);
+        let value_filter = query.value_filter.unwrap_or(0);
+        let first_page = query.cursor.is_none();
+
+        let (outputs, next) = index.get_address_outputs(
+            &address,
+            query.cursor.map(|DeserializeFromStr(cursor)| (cursor.height, cursor.outpoint)),
+            limit,
+            ADDRESS_OUTPUTS_MAX_SCANNED,
+            |outpoint, value| {
+                if value_filter > 0 && value <= value_filter {
+                    return Ok(false);
+                }
+
+                Ok(show_unsafe
+                    || (index.get_dune_balances_for_outpoint(outpoint)?.is_empty()
+                        && index.get_inscriptions_on_output(outpoint)?.is_empty()))
+            },
+        )?;
+
+        let block_count = index.block_count()?;
+
+        let mempool = mempool.read().unwrap();
+
+        let utxos = outputs
+            .into_iter()
+            .map(|(height, outpoint, value)| Utxo {
+                txid: outpoint.txid,
+                vout: outpoint.vout,
+                script: script.clone(),
+                shibes: value,
+                confirmations: Some(block_count.saturating_sub(height)),
+                spent_in_mempool: mempool.is_spent(outpoint),
+            })
+            .collect();
+
+        // Unconfirmed outputs are not paginated, and are only returned with
+        // the first page
+        let pending = if first_page {
+            mempool
+                .get_address_outputs(&address)
+                .iter()
+                .filter(|(outpoint, value)| {
+                    (value_filter == 0 || *value > value_filter)
+                        && (show_unsafe
+                            || (mempool.get_dune_balances_for_outpoint(*outpoint).is_empty()
+                                && !mempool.may_hold_inscriptions(*outpoint)))
+                })
+                .map(|(outpoint, value)| Utxo {
+                    txid: outpoint.txid,
+                    vout: outpoint.vout,
+                    script: script.clone(),
+                    shibes: *value,
+                    confirmations: None,
+                    spent_in_mempool: mempool.is_spent(*outpoint),
+                })
+                .collect()
+        } else {
+            Vec::new()
+        };
+
+        Ok(Json(UtxoAddressJson {
+            utxos,
+            pending,
+            next: next.map(|(height, outpoint)| query::Cursor { height, outpoint }.to_string()),
+        })
+            .into_response())
     }
 
-    async fn utxos_by_address_unpaginated(
-        Extension(server_config): Extension<Arc<PageConfig>>,
+    async fn dunes_by_address(
         Extension(index): Extension<Arc<Index>>,
-        Path(params): Path<(String)>,
+        Extension(mempool): Extension<Arc<RwLock<Mempool>>>,
+        Path(address): Path<String>,
+        Query(query): Query<DunesBalanceQuery>,
+    ) -> ServerResult<Response> {
+        if query.show_all.is_some() {
+            return Self::legacy_dunes_by_address(&index, &mempool, &address, 0, &query);
+        }
+
+        let script = Self::parse_address(&address)?.script_pubkey();
+        let limit = Self::address_outputs_limit(query.limit)?;
+        let list_dunes = query.list_dunes.unwrap_or(false);
+        let first_page = query.cursor.is_none();
+
+        let (outputs, next) = Self::address_dune_outputs(
+            &index,
+            &address,
+            query.cursor.map(|DeserializeFromStr(cursor)| (cursor.height, cursor.outpoint)),
+            limit,
+            ADDRESS_OUTPUTS_MAX_SCANNED,
+            query.filter,
+        )?;
+
+        let mempool = mempool.read().unwrap();
+
+        let dunes = Self::dune_balances(outputs.into_iter(), &script, list_dunes, &mempool);
+
+        // Unconfirmed outputs are not paginated, and are only returned with
+        // the first page
+        let pending = if first_page {
+            Self::dune_balances(
+                mempool
+                    .get_address_outputs(&address)
+                    .iter()
+                    .map(|(outpoint, value)| {
+                        let dunes = mempool
+                            .get_dune_balances_for_outpoint(*outpoint)
+                            .into_iter()
+                            .filter(|(dune, _)| query.filter.map_or(true, |filter| *dune == filter))
+                            .collect();
+                        (*outpoint, *value, dunes)
+                    }),
+                &script,
+                list_dunes,
+                &mempool,
+            )
+        } else {
+            Vec::new()
+        };
+
+        Ok(Json(DuneAddressJson {
+            dunes,
+            pending,
+            next: next.map(|(height, outpoint)| query::Cursor { height, outpoint }.to_string()),
+        })
+            .into_response())
+    }
+
+    /// Returns `address`'s outputs that hold dunes, or only those that hold
+    /// `filter`, along with their dune balances
+    fn address_dune_outputs(
+        index: &Index,
+        address: &str,
+        start: Option<(u32, OutPoint)>,
+        limit: usize,
+        max_scanned: usize,
+        filter: Option<SpacedDune>,
+    ) -> ServerResult<(Vec<(OutPoint, u64, Vec<(SpacedDune, Pile)>)>, Option<(u32, OutPoint)>)> {
+        let mut output_dunes = HashMap::new();
+
+        let (outputs, next) = index.get_address_outputs(
+            address,
+            start,
+            limit,
+            max_scanned,
+            |outpoint, _| {
+                let dunes = index
+                    .get_dune_balances_for_outpoint(outpoint)?
+                    .into_iter()
+                    .filter(|(dune, _)| filter.map_or(true, |filter| *dune == filter))
+                    .collect::<Vec<(SpacedDune, Pile)>>();
+
+                if dunes.is_empty() {
+                    return Ok(false);
+                }
+
+                output_dunes.insert(outpoint, dunes);
+
+                Ok(true)
+            },
+        )?;
+
+        Ok((
+            outputs
+                .into_iter()
+                .map(|(_, outpoint, value)| {
+                    let dunes = output_dunes.remove(&outpoint).unwrap_or_default();
+                    (outpoint, value, dunes)
+                })
+                .collect(),
+            next,
+        ))
+    }
+
+    /// Deprecated page numbered variant of `utxos_by_address`, which scans all
+    /// of an address's outputs to compute its totals
+    async fn utxos_by_address_legacy(
+        Extension(index): Extension<Arc<Index>>,
+        Extension(mempool): Extension<Arc<RwLock<Mempool>>>,
+        Path((address, page)): Path<(String, usize)>,
         Query(query): Query<UtxoBalanceQuery>,
     ) -> ServerResult<Response> {
-        Self::get_utxos_by_address(index, params, None, query).await
+        Self::legacy_utxos_by_address(&index, &mempool, &address, page, &query)
     }
 
-    async fn get_utxos_by_address(
-        index: Arc<Index>,
-        address: String,
-        page: Option<u32>,
-        query: UtxoBalanceQuery
+    fn legacy_utxos_by_address(
+        index: &Index,
+        mempool: &RwLock<Mempool>,
+        address: &str,
+        page: usize,
+        query: &UtxoBalanceQuery,
     ) -> ServerResult<Response> {
-        let (address, page) = (address, page.unwrap_or(0));
+        let script = Self::parse_address(address)?.script_pubkey();
+        let items_per_page = query.limit.unwrap_or(LEGACY_ADDRESS_PAGE_SIZE);
         let show_all = query.show_all.unwrap_or(false);
-        let value_filter = query.value_filter.unwrap_or(0);
         let show_unsafe = query.show_unsafe.unwrap_or(false);
+        let value_filter = query.value_filter.unwrap_or(0);
 
-        let items_per_page = query.limit.unwrap_or(10);
-        let page = page as usize;
-        let mut start_index = if page == 0 || page == 1 { 0 } else { (page - 1) * items_per_page + 1 };
-        let mut element_counter = 0;
-
-        let outpoints: Vec<OutPoint> = index.get_account_outputs(address.clone())?;
-
-        let mut utxos = Vec::new();
-        let mut total_shibes = 0u128;
-        let mut inscription_shibes = 0u128;
-
-        for outpoint in outpoints {
-            if !index.get_dune_balances_for_outpoint(outpoint)?.is_empty() && !show_unsafe {
-                continue;
-            }
-            if !show_all && (element_counter < start_index || element_counter > start_index + items_per_page - 1) {
-                continue;
-            }
+        let mut total_inscription_shibes = 0u128;
 
-            let txid = outpoint.txid;
-            let vout = outpoint.vout;
-            let output = index
-                .get_transaction(txid)?
-                .ok_or_not_found(|| format!("{txid} current transaction"))?
-                .output
-                .into_iter()
-                .nth(vout.try_into().unwrap())
-                .ok_or_not_found(|| format!("{vout} current transaction output"))?;
+        let (outputs, _) = index.get_address_outputs(
+            address,
+            None,
+            usize::MAX,
+            usize::MAX,
+            |outpoint, value| {
+                if value_filter > 0 && value <= value_filter {
+                    return Ok(false);
+                }
 
-            if value_filter > 0 && output.value <= value_filter {
-                continue;
-            }
+                if !show_unsafe && !index.get_dune_balances_for_outpoint(outpoint)?.is_empty() {
+                    return Ok(false);
+                }
 
-            if !index.get_inscriptions_on_output(outpoint)?.is_empty() {
-                inscription_shibes += output.value as u128;
-                if !show_unsafe {
-                    continue;
+                if !index.get_inscriptions_on_output(outpoint)?.is_empty() {
+                    total_inscription_shibes += u128::from(value);
+                    return Ok(show_unsafe);
                 }
-            }
 
-            element_counter += 1;
+                Ok(true)
+            },
+        )?;
 
-            total_shibes += output.value as u128;
+        let total_utxos = outputs.len();
+        let total_shibes = outputs.iter().map(|(_, _, value)| u128::from(*value)).sum();
 
-            let confirmations = if let Some(block_hash_info) = index.get_transaction_blockhash(txid)? {
-                block_hash_info.confirmations
-            } else {
-                None
-            };
+        let block_count = index.block_count()?;
 
-            utxos.push(Utxo {
-                txid,
-                vout,
-                script: output.script_pubkey,
-                shibes: output.value,
-                confirmations,
-            });
-        }
-        Ok(Json(UtxoAddressJson { utxos, total_shibes, total_utxos: element_counter, total_inscription_shibes: inscription_shibes }).into_response())
-    }
+        let mempool = mempool.read().unwrap();
 
-    async fn dunes_by_address(
-        Extension(server_config): Extension<Arc<PageConfig>>,
-        Extension(index): Extension<Arc<Index>>,
-        Path(params): Path<(String, u32)>,
-        Query(query): Query<DunesBalanceQuery>,
-    ) -> ServerResult<Response> {
-        Self::get_dunes_by_address(index, params.0, Some(params.1), query).await
+        let utxos = Self::legacy_page(outputs, page, items_per_page, show_all)
+            .map(|(height, outpoint, value)| Utxo {
+                txid: outpoint.txid,
+                vout: outpoint.vout,
+                script: script.clone(),
+                shibes: value,
+                confirmations: Some(block_count.saturating_sub(height)),
+                spent_in_mempool: mempool.is_spent(outpoint),
+            })
+            .collect();
+
+        Ok(Json(LegacyUtxoAddressJson {
+            utxos,
+            total_utxos,
+            total_shibes,
+            total_inscription_shibes,
+        })
+            .into_response())
     }
 
-    async fn dunes_by_address_unpaginated(
-        Extension(server_config): Extension<Arc<PageConfig>>,
+    /// Deprecated page numbered variant of `dunes_by_address`, which scans all
+    /// of an address's outputs to compute its totals
+    async fn dunes_by_address_legacy(
         Extension(index): Extension<Arc<Index>>,
-        Path(params): Path<(String)>,
+        Extension(mempool): Extension<Arc<RwLock<Mempool>>>,
+        Path((address, page)): Path<(String, usize)>,
         Query(query): Query<DunesBalanceQuery>,
     ) -> ServerResult<Response> {
-        Self::get_dunes_by_address(index, params, None, query).await
+        Self::legacy_dunes_by_address(&index, &mempool, &address, page, &query)
     }
 
-    async fn get_dunes_by_address(
-        index: Arc<Index>,
-        address: String,
-        page: Option<u32>,
-        query: DunesBalanceQuery
+    fn legacy_dunes_by_address(
+        index: &Index,
+        mempool: &RwLock<Mempool>,
+        address: &str,
+        page: usize,
+        query: &DunesBalanceQuery,
     ) -> ServerResult<Response> {
-        let (address, page) = (address, page.unwrap_or(0));
+        let script = Self::parse_address(address)?.script_pubkey();
         let show_all = query.show_all.unwrap_or(false);
         let list_dunes = query.list_dunes.unwrap_or(false);
 
-        let outpoints = index.get_account_outputs(address)?;
+        let (outputs, _) = Self::address_dune_outputs(
+            index,
+            address,
+            None,
+            usize::MAX,
+            usize::MAX,
+            query.filter,
+        )?;
+
+        let mempool = mempool.read().unwrap();
+
+        let balances = Self::dune_balances(outputs.into_iter(), &script, list_dunes, &mempool);
+
+        let total_dunes = balances.len();
+        let total_elements = u32::try_from(
+            balances
+                .iter()
+                .map(|balance| balance.total_outputs)
+                .sum::<u128>(),
+        )
+            .unwrap_or(u32::MAX);
 
-        let items_per_page = 10usize;
-        let page = page as usize;
-        let mut start_index = if page == 0 { 0 } else { (page - 1) * items_per_page };
-        let mut elements_counter = 0;
+        // Without `list_dunes`, pages are of outputs rather than of dunes
+        let dunes = if list_dunes || show_all {
+            Self::legacy_page(balances, page, LEGACY_ADDRESS_PAGE_SIZE, show_all).collect()
+        } else {
+            let outputs = balances
+                .iter()
+                .flat_map(|balance| balance.balances.iter().map(move |output| (balance, output)))
+                .collect::<Vec<(&DuneBalance, &DuneOutput)>>();
 
-        let mut dune_balances_map: LinkedHashMap<SpacedDune, DuneBalance> = LinkedHashMap::new();
+            let mut dunes: Vec<DuneBalance> = Vec::new();
 
-        for outpoint in outpoints {
-            let dunes = index.get_dune_balances_for_outpoint(outpoint)?;
-            for (dune, balances) in dunes {
-                if let Some(filter) = query.filter {
-                    if dune != filter {
-                        continue;
-                    }
+            for (balance, output) in
+                Self::legacy_page(outputs, page, LEGACY_ADDRESS_PAGE_SIZE, false)
+            {
+                match dunes.last_mut() {
+                    Some(last) if last.dune == balance.dune => last.balances.push(output.clone()),
+                    _ => dunes.push(DuneBalance {
+                        dune: balance.dune,
+                        divisibility: balance.divisibility,
+                        symbol: balance.symbol,
+                        total_balance: balance.total_balance,
+                        total_outputs: balance.total_outputs,
+                        balances: vec![output.clone()],
+                    }),
                 }
-                let dune_balance = dune_balances_map.entry(dune.clone()).or_insert_with(|| DuneBalance {
-                    dune: dune.clone(),
-                    divisibility: balances.divisibility,
-                    symbol: balances.symbol,
+            }
+
+            dunes
+        };
+
+        Ok(Json(LegacyDuneAddressJson {
+            dunes,
+            total_dunes,
+            total_elements,
+        })
+            .into_response())
+    }
+
+    /// Legacy pages are numbered from one, with page zero also returning the
+    /// first page
+    fn legacy_page<T>(
+        items: Vec<T>,
+        page: usize,
+        items_per_page: usize,
+        show_all: bool,
+    ) -> impl Iterator<Item = T> {
+        let (skip, take) = if show_all {
+            (0, usize::MAX)
+        } else {
+            (page.saturating_sub(1).saturating_mul(items_per_page), items_per_page)
+        };
+
+        items.into_iter().skip(skip).take(take)
+    }
+
+    fn dune_balances(
+        outputs: impl Iterator<Item = (OutPoint, u64, Vec<(SpacedDune, Pile)>)>,
+        script: &Script,
+        list_dunes: bool,
+        mempool: &Mempool,
+    ) -> Vec<DuneBalance> {
+        let mut dune_balances: LinkedHashMap<SpacedDune, DuneBalance> = LinkedHashMap::new();
+
+        for (outpoint, value, dunes) in outputs {
+            for (dune, pile) in dunes {
+                let dune_balance = dune_balances.entry(dune).or_insert_with(|| DuneBalance {
+                    dune,
+                    divisibility: pile.divisibility,
+                    symbol: pile.symbol,
                     total_balance: 0,
                     total_outputs: 0,
                     balances: Vec::new(),
                 });
 
                 if !list_dunes {
-                    let txid = outpoint.txid;
-                    let vout = outpoint.vout;
-                    let output = index
-                        .get_transaction(txid)?
-                        .ok_or_not_found(|| format!("dunes {txid} current transaction"))?
-                        .output
-                        .into_iter()
-                        .nth(vout.try_into().unwrap())
-                        .ok_or_not_found(|| format!("dunes {vout} current transaction output"))?;
-
                     dune_balance.balances.push(DuneOutput {
-                        txid,
-                        vout,
-                        script: output.script_pubkey,
-                        shibes: output.value,
-                        balance: balances.amount,
+                        txid: outpoint.txid,
+                        vout: outpoint.vout,
+                        script: script.clone(),
+                        shibes: value,
+                        balance: pile.amount,
+                        spent_in_mempool: mempool.is_spent(outpoint),
                     });
                 }
 
-                dune_balance.total_balance += balances.amount;
+                dune_balance.total_balance += pile.amount;
                 dune_balance.total_outputs += 1;
-                elements_counter += 1;
             }
         }
 
-        let dune_balances: Vec<DuneBalance> = if show_all {
-            dune_balances_map
-                .values()
-                .cloned()
-                .collect()
-        } else if list_dunes {
-            dune_balances_map
-                .values()
-                .cloned()
-                .skip(start_index)
-                .take(items_per_page)
-                .collect()
-        } else {
-            let mut values: Vec<DuneBalance> = dune_balances_map
-                .values()
-                .cloned()
-                .collect();
-            let mut items_collected = 0;
-            let mut result = Vec::new();
-            for value in values.iter() {
-                let balances: Vec<DuneOutput> = value.balances
-                    .iter()
-                    .skip(start_index)
-                    .take(items_per_page - items_collected)
-                    .cloned()
-                    .collect();
-                items_collected += balances.len();
-                start_index -= value.balances.len().min(start_index);
-                if balances.is_empty() {
-                    continue;
-                }
-                result.push(DuneBalance {
-                    dune: value.dune.clone(),
-                    divisibility: value.divisibility,
-                    symbol: value.symbol.clone(),
-                    total_balance: value.total_balance,
-                    total_outputs: value.total_outputs,
-                    balances,
-                });
-                if items_collected >= items_per_page {
-                    break;
-                }
-            }
-            result
-        };
-
-        Ok(Json(DuneAddressJson { dunes: dune_balances, total_dunes: dune_balances_map.len(), total_elements: elements_counter }).into_response())
+        dune_balances.into_iter().map(|(_, balance)| balance).collect()
     }
 
     async fn dunes_by_outputs(
         Extension(server_config): Extension<Arc<PageConfig>>,
         Extension(index): Extension<Arc<Index>>,
+        Extension(mempool): Extension<Arc<RwLock<Mempool>>>,
         Query(query): Query<OutputsQuery>,
     ) -> ServerResult<Response> {
+        let mempool = mempool.read().unwrap();
+
         let mut all_dunes_jsons = Vec::new();
 
         // Split the outputs string into individual outputs
@@ -701,10 +993,22 @@ impl Server {
             // Create OutPoint
             let outpoint = OutPoint::new(tx_id, vout);
 
-            let dunes = index.get_dune_balances_for_outpoint(outpoint)?;
+            let mut dunes = index.get_dune_balances_for_outpoint(outpoint)?;
+
+            // Outputs of unconfirmed transactions are not in the index
+            let pending = dunes.is_empty();
+
+            if pending {
+                dunes = mempool.get_dune_balances_for_outpoint(outpoint);
+            }
 
             for (dune, balances) in dunes {
-                all_dunes_jsons.push(DuneOutputJson { dune, balances });
+                all_dunes_jsons.push(DuneOutputJson {
+                    dune,
+                    balances,
+                    pending,
+                    spent_in_mempool: mempool.is_spent(outpoint),
+                });
             }
         }
 
@@ -735,7 +1039,8 @@ impl Server {
         Extension(page_config): Extension<Arc<PageConfig>>,
         Extension(index): Extension<Arc<Index>>,
         Path(DeserializeFromStr(spaced_dune)): Path<DeserializeFromStr<SpacedDune>>,
-    ) -> ServerResult<PageHtml<DuneHtml>> {
+        accept_json: AcceptJson,
+    ) -> ServerResult<Response> {
         let (id, entry) = index.dune(spaced_dune.dune)?.ok_or_else(|| {
             ServerError::NotFound(
                 "tracking dunes requires index created with `--index-dunes` flag".into(),
@@ -757,29 +1062,213 @@ impl Server {
             .inscription_exists(inscription)?
             .then_some(inscription);
 
-        Ok(
-          DuneHtml {
+        let distribution = index.get_dune_distribution(id, &entry, DUNE_PAGE_TOP_HOLDERS)?;
+
+        let dune = DuneHtml {
             id,
             entry,
             mintable,
             inscription,
-          }
-              .page(page_config),
-        )
+            distribution,
+        };
+
+        Ok(if accept_json.0 {
+            Json(dune.to_json()).into_response()
+        } else {
+            dune.page(page_config).into_response()
+        })
     }
 
-    async fn dunes(
+    async fn dune_activity_unpaginated(
+        Extension(page_config): Extension<Arc<PageConfig>>,
+        Extension(index): Extension<Arc<Index>>,
+        Path(DeserializeFromStr(spaced_dune)): Path<DeserializeFromStr<SpacedDune>>,
+    ) -> ServerResult<PageHtml<DuneActivityHtml>> {
+        Self::dune_activity_html(page_config, &index, spaced_dune, None)
+    }
+
+    async fn dune_activity(
         Extension(page_config): Extension<Arc<PageConfig>>,
         Extension(index): Extension<Arc<Index>>,
-    ) -> ServerResult<PageHtml<DunesHtml>> {
+        Path((DeserializeFromStr(spaced_dune), DeserializeFromStr(from))): Path<(
+            DeserializeFromStr<SpacedDune>,
+            DeserializeFromStr<DuneEventId>,
+        )>,
+    ) -> ServerResult<PageHtml<DuneActivityHtml>> {
+        Self::dune_activity_html(page_config, &index, spaced_dune, Some(from))
+    }
+
+    fn dune_activity_html(
+        page_config: Arc<PageConfig>,
+        index: &Index,
+        spaced_dune: SpacedDune,
+        from: Option<DuneEventId>,
+    ) -> ServerResult<PageHtml<DuneActivityHtml>> {
+        let (_id, entry, events, prev, next) = Self::dune_activity_page(index, spaced_dune, from)?;
+
         Ok(
-            DunesHtml {
-                entries: index.dunes()?,
+            DuneActivityHtml {
+                entry,
+                events,
+                prev,
+                next,
             }
                 .page(page_config),
         )
     }
 
+    async fn dune_activity_json_unpaginated(
+        Extension(index): Extension<Arc<Index>>,
+        Path(DeserializeFromStr(spaced_dune)): Path<DeserializeFromStr<SpacedDune>>,
+    ) -> ServerResult<Response> {
+        Self::get_dune_activity_json(&index, spaced_dune, None)
+    }
+
+    async fn dune_activity_json(
+        Extension(index): Extension<Arc<Index>>,
+        Path((DeserializeFromStr(spaced_dune), DeserializeFromStr(from))): Path<(
+            DeserializeFromStr<SpacedDune>,
+            DeserializeFromStr<DuneEventId>,
+        )>,
+    ) -> ServerResult<Response> {
+        Self::get_dune_activity_json(&index, spaced_dune, Some(from))
+    }
+
+    fn get_dune_activity_json(
+        index: &Index,
+        spaced_dune: SpacedDune,
+        from: Option<DuneEventId>,
+    ) -> ServerResult<Response> {
+        let (id, entry, events, prev, next) = Self::dune_activity_page(index, spaced_dune, from)?;
+
+        Ok(
+            Json(DuneActivityJson {
+                dune: entry.spaced_dune(),
+                id,
+                events,
+                prev: prev.map(|prev| prev.to_string()),
+                next: next.map(|next| next.to_string()),
+            })
+                .into_response(),
+        )
+    }
+
+    fn dune_activity_page(
+        index: &Index,
+        spaced_dune: SpacedDune,
+        from: Option<DuneEventId>,
+    ) -> ServerResult<(
+        DuneId,
+        DuneEntry,
+        Vec<DuneEvent>,
+        Option<DuneEventId>,
+        Option<DuneEventId>,
+    )> {
+        if !index.has_dune_event_index() {
+            return Err(ServerError::NotFound(
+                "dune activity requires index created with `--index-dune-events` flag".into(),
+            ));
+        }
+
+        let (id, entry) = index
+            .dune(spaced_dune.dune)?
+            .ok_or_not_found(|| format!("dune {spaced_dune}"))?;
+
+        let (events, prev, next) = index.get_dune_events(id, from, DUNE_ACTIVITY_PAGE_SIZE)?;
+
+        Ok((id, entry, events, prev, next))
+    }
+
+    async fn dune_holders(
+        Extension(index): Extension<Arc<Index>>,
+        Path(DeserializeFromStr(spaced_dune)): Path<DeserializeFromStr<SpacedDune>>,
+        Query(query): Query<DuneHoldersQuery>,
+    ) -> ServerResult<Response> {
+        if !index.has_dune_history_index() {
+            return Err(ServerError::NotFound(
+                "dune holders require index created with `--index-dune-history` flag".into(),
+            ));
+        }
+
+        let (id, entry) = index
+            .dune(spaced_dune.dune)?
+            .ok_or_not_found(|| format!("dune {spaced_dune}"))?;
+
+        let block_count = index.block_count()?;
+
+        let height = match query.height {
+            Some(height) if height < block_count => height,
+            Some(height) => {
+                return Err(ServerError::BadRequest(format!(
+                    "block {height} has not been indexed"
+                )))
+            }
+            None => block_count.saturating_sub(1),
+        };
+
+        Ok(
+            Json(dunes::snapshot::Output {
+                dune: entry.spaced_dune(),
+                id,
+                divisibility: entry.divisibility,
+                height,
+                holders: index.get_dune_snapshot(id, height)?,
+            })
+                .into_response(),
+        )
+    }
+
+    async fn dune_distribution(
+        Extension(index): Extension<Arc<Index>>,
+        Path(DeserializeFromStr(spaced_dune)): Path<DeserializeFromStr<SpacedDune>>,
+        Query(query): Query<DuneDistributionQuery>,
+    ) -> ServerResult<Response> {
+        let (id, entry) = index
+            .dune(spaced_dune.dune)?
+            .ok_or_not_found(|| format!("dune {spaced_dune}"))?;
+
+        let limit = query.limit.unwrap_or(DUNE_DISTRIBUTION_DEFAULT_LIMIT);
+
+        if limit > DUNE_DISTRIBUTION_MAX_LIMIT {
+            return Err(ServerError::BadRequest(format!(
+                "limit must not exceed {DUNE_DISTRIBUTION_MAX_LIMIT}"
+            )));
+        }
+
+        Ok(
+            Json(DuneDistributionJson {
+                dune: entry.spaced_dune(),
+                id,
+                divisibility: entry.divisibility,
+                distribution: index
+                    .get_dune_distribution(id, &entry, limit)?
+                    .ok_or_else(|| {
+                        ServerError::NotFound(
+                            "dune distribution requires an index that tracks dune holders, migrate or rebuild the index".into(),
+                        )
+                    })?,
+            })
+                .into_response(),
+        )
+    }
+
+    async fn dunes(
+        Extension(page_config): Extension<Arc<PageConfig>>,
+        Extension(index): Extension<Arc<Index>>,
+        accept_json: AcceptJson,
+    ) -> ServerResult<Response> {
+        let dunes = DunesHtml {
+            entries: index.dunes()?,
+            minimum: index.protocol().minimum_dune_at_height(index.block_count()?),
+        };
+
+        Ok(if accept_json.0 {
+            Json(dunes.to_json()).into_response()
+        } else {
+            dunes.page(page_config).into_response()
+        })
+    }
+
     async fn dunes_balances(
         Extension(page_config): Extension<Arc<PageConfig>>,
         Extension(index): Extension<Arc<Index>>,
@@ -812,7 +1301,8 @@ impl Server {
         Extension(page_config): Extension<Arc<PageConfig>>,
         Extension(index): Extension<Arc<Index>>,
         Path(DeserializeFromStr(query)): Path<DeserializeFromStr<query::Block>>,
-    ) -> ServerResult<PageHtml<BlockHtml>> {
+        accept_json: AcceptJson,
+    ) -> ServerResult<Response> {
         let (block, height) = match query {
             query::Block::Height(height) => {
                 let block = index
@@ -834,21 +1324,27 @@ impl Server {
             }
         };
 
-        Ok(
-            BlockHtml::new(block, Height(height), Self::index_height(&index)?)
-                .page(page_config),
-        )
+        let block = BlockHtml::new(block, Height(height), Self::index_height(&index)?);
+
+        Ok(if accept_json.0 {
+            Json(block.to_json()).into_response()
+        } else {
+            block.page(page_config).into_response()
+        })
     }
 
     async fn transaction(
         Extension(page_config): Extension<Arc<PageConfig>>,
         Extension(index): Extension<Arc<Index>>,
         Path(txid): Path<Txid>,
-    ) -> ServerResult<PageHtml<TransactionHtml>> {
+        accept_json: AcceptJson,
+    ) -> ServerResult<Response> {
         let inscription = index.get_inscription_by_id(txid.into())?;
 
         let etching = index.get_etching(txid)?;
 
+        let cenotaph = index.get_cenotaph(txid)?;
+
         let mut blockhash = None;
         let mut confirmations = None;
 
@@ -856,8 +1352,7 @@ impl Server {
             blockhash = block_hash_info.hash;
             confirmations = block_hash_info.confirmations;
         }
-    Ok(
-        TransactionHtml::new(
+        let transaction = TransactionHtml::new(
             index
                 .get_transaction(txid)?
                 .ok_or_not_found(|| format!("transaction {txid}"))?,
@@ -865,11 +1360,52 @@ impl Server {
             confirmations,
             inscription.map(|_| txid.into()),
             page_config.chain,
-            etching
+            etching,
+            cenotaph,
+        );
+
+        Ok(if accept_json.0 {
+            Json(transaction.to_json()).into_response()
+        } else {
+            transaction.page(page_config).into_response()
+        })
+    }
+
+    async fn events(
+        Extension(index): Extension<Arc<Index>>,
+        Query(query): Query<EventsQuery>,
+    ) -> ServerResult<Sse<impl Stream<Item = Result<sse::Event, axum::Error>>>> {
+        let dune = match query.dune {
+            Some(dune) => Some(
+                index
+                    .dune(dune.dune)?
+                    .ok_or_not_found(|| format!("dune {dune}"))?
+                    .0,
+            ),
+            None => None,
+        };
+
+        let address = query.address;
+
+        let events = futures::stream::unfold(index.subscribe(), |mut receiver| async move {
+            loop {
+                match receiver.recv().await {
+                    Ok(event) => return Some((event, receiver)),
+                    // Subscribers that fall behind miss events instead of
+                    // holding up the indexer
+                    Err(broadcast::error::RecvError::Lagged(_)) => continue,
+                    Err(broadcast::error::RecvError::Closed) => return None,
+                }
+            }
+        });
+
+        Ok(Sse::new(
+            events
+                .filter(move |event| event.matches(dune, address.as_deref()))
+                .map(|event| sse::Event::default().event(event.name()).json_data(&event)),
         )
-      .page(page_config),
-    )
-  }
+            .keep_alive(KeepAlive::default()))
+    }
 
     async fn status(Extension(index): Extension<Arc<Index>>) -> (StatusCode, &'static str) {
         if index.is_reorged() {
@@ -1168,7 +1704,8 @@ impl Server {
         Extension(page_config): Extension<Arc<PageConfig>>,
         Extension(index): Extension<Arc<Index>>,
         Path(inscription_id): Path<InscriptionId>,
-    ) -> ServerResult<PageHtml<InscriptionHtml>> {
+        accept_json: AcceptJson,
+    ) -> ServerResult<Response> {
         let entry = index
             .get_inscription_entry(inscription_id)?
             .ok_or_not_found(|| format!("inscription {inscription_id}"))?;
@@ -1203,55 +1740,65 @@ impl Server {
 
         let dune = index.get_dune_by_inscription_id(inscription_id)?;
 
-    Ok(
-      InscriptionHtml {
-        chain: page_config.chain,
-        genesis_fee: entry.fee,
-        genesis_height: entry.height,
-        inscription,
-        inscription_id,
-        next,
-        inscription_number: entry.inscription_number,
-        output,
-        previous,
-        sat: entry.sat,
-        satpoint,
-        timestamp: timestamp(entry.timestamp.into()),
-        dune,
-      }
-      .page(page_config),
-    )
-  }
+        let inscription = InscriptionHtml {
+            chain: page_config.chain,
+            genesis_fee: entry.fee,
+            genesis_height: entry.height,
+            inscription,
+            inscription_id,
+            next,
+            inscription_number: entry.inscription_number,
+            output,
+            previous,
+            sat: entry.sat,
+            satpoint,
+            timestamp: timestamp(entry.timestamp.into()),
+            dune,
+        };
+
+        Ok(if accept_json.0 {
+            Json(inscription.to_json()).into_response()
+        } else {
+            inscription.page(page_config).into_response()
+        })
+    }
 
     async fn inscriptions(
         Extension(page_config): Extension<Arc<PageConfig>>,
         Extension(index): Extension<Arc<Index>>,
-    ) -> ServerResult<PageHtml<InscriptionsHtml>> {
-        Self::inscriptions_inner(page_config, index, None).await
+        accept_json: AcceptJson,
+    ) -> ServerResult<Response> {
+        Self::inscriptions_inner(page_config, index, None, accept_json).await
     }
 
     async fn inscriptions_from(
         Extension(page_config): Extension<Arc<PageConfig>>,
         Extension(index): Extension<Arc<Index>>,
         Path(from): Path<u64>,
-    ) -> ServerResult<PageHtml<InscriptionsHtml>> {
-        Self::inscriptions_inner(page_config, index, Some(from)).await
+        accept_json: AcceptJson,
+    ) -> ServerResult<Response> {
+        Self::inscriptions_inner(page_config, index, Some(from), accept_json).await
     }
 
     async fn inscriptions_inner(
         page_config: Arc<PageConfig>,
         index: Arc<Index>,
         from: Option<u64>,
-    ) -> ServerResult<PageHtml<InscriptionsHtml>> {
-        let (inscriptions, prev, next) = index.get_latest_inscriptions_with_prev_and_next(100, from)?;
-        Ok(
-            InscriptionsHtml {
-                inscriptions,
-                next,
-                prev,
-            }
-                .page(page_config),
-        )
+        accept_json: AcceptJson,
+    ) -> ServerResult<Response> {
+        let (inscriptions, prev, next) = index.get_latest_inscriptions_with_prev_and_next(100, from)?;
+
+        let inscriptions = InscriptionsHtml {
+            inscriptions,
+            next,
+            prev,
+        };
+
+        Ok(if accept_json.0 {
+            Json(inscriptions.to_json()).into_response()
+        } else {
+            inscriptions.page(page_config).into_response()
+        })
     }
 
     async fn redirect_http_to_https(
@@ -1271,6 +1818,7 @@ mod tests {
     use bitcoin::blockdata::constants::COIN_VALUE;
 
     use {
+        bitcoin::PubkeyHash,
         reqwest::Url,
         std::net::TcpListener,
         super::*,
@@ -1393,6 +1941,17 @@ mod tests {
             reqwest::blocking::get(self.join_url(path.as_ref())).unwrap()
         }
 
+        fn get_json(&self, path: impl AsRef<str>) -> reqwest::blocking::Response {
+            if let Err(error) = self.index.update() {
+                log::error!("{error}");
+            }
+            reqwest::blocking::Client::new()
+                .get(self.join_url(path.as_ref()))
+                .header(header::ACCEPT, "application/json")
+                .send()
+                .unwrap()
+        }
+
         fn join_url(&self, url: &str) -> Url {
             self.url.join(url).unwrap()
         }
@@ -1773,6 +2332,92 @@ mod tests {
         );
     }
 
+    #[test]
+    fn dune_distribution_of_unknown_dune_is_not_found() {
+        TestServer::new_with_regtest_with_index_dunes().assert_response_regex(
+            "/dunes/distribution/AAAAAAAAAAAAA",
+            StatusCode::NOT_FOUND,
+            ".*",
+        );
+    }
+
+    #[test]
+    fn dune_distribution_limit_is_capped() {
+        let server = TestServer::new_with_regtest_with_index_dunes();
+
+        server.mine_blocks(1);
+
+        server.bitcoin_rpc_server.broadcast_tx(TransactionTemplate {
+            inputs: &[(1, 0, 0)],
+            op_return: Some(
+                Dunestone {
+                    edicts: vec![Edict {
+                        id: 0,
+                        amount: u128::max_value(),
+                        output: 0,
+                    }],
+                    etching: Some(Etching {
+                        dune: Dune(u128::from(21_000_000 * COIN_VALUE)),
+                        ..Default::default()
+                    }),
+                    ..Default::default()
+                }
+                    .encipher(),
+            ),
+            ..Default::default()
+        });
+
+        server.mine_blocks(1);
+
+        server.assert_response(
+            "/dunes/distribution/NVTDIJZYIPU?limit=1001",
+            StatusCode::BAD_REQUEST,
+            "limit must not exceed 1000",
+        );
+
+        server.assert_response_regex(
+            "/dunes/distribution/NVTDIJZYIPU",
+            StatusCode::OK,
+            r#".*"holders":0,"top":\[\].*"#,
+        );
+    }
+
+    #[test]
+    fn dune_activity_requires_dune_event_index() {
+        TestServer::new_with_args(&["--index-dunes"], &[]).assert_response(
+            "/dune/AAAAAAAAAAAAA/activity",
+            StatusCode::NOT_FOUND,
+            "dune activity requires index created with `--index-dune-events` flag",
+        );
+    }
+
+    #[test]
+    fn dune_activity_of_unknown_dune_returns_404() {
+        TestServer::new_with_args(&["--index-dunes", "--index-dune-events"], &[]).assert_response(
+            "/dunes/activity/AAAAAAAAAAAAA",
+            StatusCode::NOT_FOUND,
+            "dune AAAAAAAAAAAAA not found",
+        );
+    }
+
+    #[test]
+    fn invalid_dune_activity_event_id_returns_400() {
+        TestServer::new_with_args(&["--index-dunes", "--index-dune-events"], &[]).assert_response(
+            "/dune/AAAAAAAAAAAAA/activity/1:2",
+            StatusCode::BAD_REQUEST,
+            "Invalid URL: invalid dune event id `1:2`",
+        );
+    }
+
+    #[test]
+    fn dune_holders_requires_dune_history_index() {
+        TestServer::new_with_args(&["--index-dunes"], &[]).assert_response(
+            "/dune/AAAAAAAAAAAAA/holders",
+            StatusCode::NOT_FOUND,
+            "dune holders require index created with `--index-dune-history` flag",
+        );
+    }
+
     #[test]
     fn http_to_https_redirect_with_path() {
         TestServer::new_with_args(&[], &["--redirect-http-to-https", "--https"]).assert_redirect(
@@ -2122,14 +2767,19 @@ mod tests {
     fn transaction() {
         let test_server = TestServer::new();
 
-        let coinbase_tx = test_server.mine_blocks(1)[0].txdata[0].clone();
-        let txid = coinbase_tx.txid();
+        let block = test_server.mine_blocks(1)[0].clone();
+        let blockhash = block.block_hash();
+        let txid = block.txdata[0].txid();
 
         test_server.assert_response_regex(
             format!("/tx/{txid}"),
             StatusCode::OK,
             format!(
                 ".*<title>Transaction {txid}</title>.*<h1>Transaction <span class=monospace>{txid}</span></h1>
+<dl>
+  <dt>block</dt>
+  <dd><a href=/block/{blockhash} class=monospace>{blockhash}</a></dd>
+</dl>
 <h2>1 Input</h2>
 <ul>
   <li><a class=monospace href=/output/0000000000000000000000000000000000000000000000000000000000000000:4294967295>0000000000000000000000000000000000000000000000000000000000000000:4294967295</a></li>
@@ -2907,4 +3557,311 @@ mod tests {
             &fs::read_to_string("templates/preview-unknown.html").unwrap(),
         );
     }
+
+    #[test]
+    fn json_request_is_served_html_when_json_api_is_disabled() {
+        let server = TestServer::new();
+
+        let response = server.get_json("/sat/0");
+
+        assert_eq!(response.status(), StatusCode::OK);
+        assert!(response.headers()[header::CONTENT_TYPE]
+            .to_str()
+            .unwrap()
+            .starts_with("text/html"));
+    }
+
+    #[test]
+    fn json_is_served_for_accept_headers_with_media_range_parameters() {
+        let server = TestServer::new_with_args(&[], &["--enable-json-api"]);
+
+        for accept in ["application/json, */*;q=0.8", "application/json; charset=utf-8"] {
+            let response = reqwest::blocking::Client::new()
+                .get(server.join_url("/sat/0"))
+                .header(header::ACCEPT, accept)
+                .send()
+                .unwrap();
+
+            assert_eq!(response.status(), StatusCode::OK);
+            assert_eq!(
+                response.headers().get(header::CONTENT_TYPE).unwrap(),
+                "application/json",
+                "{accept}"
+            );
+        }
+    }
+
+    #[test]
+    fn html_is_served_when_json_api_is_enabled() {
+        TestServer::new_with_args(&[], &["--enable-json-api"]).assert_response_regex(
+            "/sat/0",
+            StatusCode::OK,
+            ".*<h1>Sat 0</h1>.*",
+        );
+    }
+
+    #[test]
+    fn sat_json() {
+        let server = TestServer::new_with_args(&[], &["--enable-json-api"]);
+
+        let response = server.get_json("/sat/0");
+
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(
+            response.headers().get(header::CONTENT_TYPE).unwrap(),
+            "application/json"
+        );
+
+        let json: serde_json::Value = serde_json::from_str(&response.text().unwrap()).unwrap();
+
+        assert_eq!(json["number"], 0);
+        assert_eq!(json["decimal"], "0.0");
+        assert_eq!(json["rarity"], "mythic");
+    }
+
+    #[test]
+    fn dune_json() {
+        let server = TestServer::new_with_args(&["--index-dunes"], &["--enable-json-api"]);
+
+        server.mine_blocks(1);
+
+        server.dogecoin_rpc_server.broadcast_tx(TransactionTemplate {
+            inputs: &[(1, 0, 0)],
+            op_return: Some(
+                Dunestone {
+                    edicts: vec![Edict {
+                        id: 0,
+                        amount: u128::max_value(),
+                        output: 0,
+                    }],
+                    etching: Some(Etching {
+                        dune: Dune(u128::from(21_000_000 * COIN_VALUE)),
+                        ..Default::default()
+                    }),
+                    ..Default::default()
+                }
+                    .encipher(),
+            ),
+            ..Default::default()
+        });
+
+        server.mine_blocks(1);
+
+        let response = server.get_json("/dune/NVTDIJZYIPU");
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let json: serde_json::Value = serde_json::from_str(&response.text().unwrap()).unwrap();
+
+        assert_eq!(json["entry"]["dune"], "NVTDIJZYIPU");
+        assert_eq!(json["mintable"], false);
+        assert_eq!(json["distribution"]["holders"], 0);
+
+        let response = server.get_json("/dunes");
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let json: serde_json::Value = serde_json::from_str(&response.text().unwrap()).unwrap();
+
+        assert_eq!(json["entries"][0][1]["dune"], "NVTDIJZYIPU");
+        assert_eq!(json["minimum"], "AAAAAAAAAAAAA");
+    }
+
+    fn p2pkh_address() -> String {
+        Chain::Mainnet
+            .address_from_script(&Script::new_p2pkh(&PubkeyHash::all_zeros()))
+            .unwrap()
+            .to_string()
+    }
+
+    #[test]
+    fn address_outputs_limit_is_validated() {
+        let server = TestServer::new();
+        let address = p2pkh_address();
+
+        server.assert_response(
+            format!("/utxos/balance/{address}?limit=0"),
+            StatusCode::BAD_REQUEST,
+            "limit must be between 1 and 1000",
+        );
+
+        server.assert_response(
+            format!("/dunes/balance/{address}?limit=1001"),
+            StatusCode::BAD_REQUEST,
+            "limit must be between 1 and 1000",
+        );
+    }
+
+    #[test]
+    fn address_outputs_of_invalid_address_is_bad_request() {
+        TestServer::new().assert_response_regex(
+            "/utxos/balance/foo",
+            StatusCode::BAD_REQUEST,
+            "invalid address `foo`: .*",
+        );
+    }
+
+    #[test]
+    fn events_stream_committed_blocks() {
+        let server = TestServer::new();
+        server.index.update().unwrap();
+
+        let response = reqwest::blocking::get(server.join_url("/events")).unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+
+        server.mine_blocks(1);
+
+        let mut lines = std::io::BufRead::lines(std::io::BufReader::new(response));
+        assert_eq!(lines.next().unwrap().unwrap(), "event: block");
+        assert!(lines
+            .next()
+            .unwrap()
+            .unwrap()
+            .starts_with(r#"data: {"type":"block","height":1,"hash":"#));
+    }
+
+    #[test]
+    fn events_for_unknown_dune_are_not_found() {
+        TestServer::new().assert_response(
+            "/events?dune=FOO",
+            StatusCode::NOT_FOUND,
+            "dune FOO not found",
+        );
+    }
+
+    #[test]
+    fn address_outputs_without_outputs_are_empty() {
+        let server = TestServer::new();
+        let address = p2pkh_address();
+
+        server.assert_response(
+            format!("/utxos/balance/{address}"),
+            StatusCode::OK,
+            r#"{"utxos":[],"pending":[],"next":null}"#,
+        );
+
+        server.assert_response(
+            format!("/dunes/balance/{address}?cursor=1:{}:0", Txid::all_zeros()),
+            StatusCode::OK,
+            r#"{"dunes":[],"pending":[],"next":null}"#,
+        );
+    }
+
+    #[test]
+    fn address_outputs_include_pending_and_spent_in_mempool_outputs() {
+        let dogecoin_rpc_server = test_bitcoincore_rpc::spawn();
+        let script = Script::new_p2pkh(&PubkeyHash::all_zeros());
+        let address = p2pkh_address();
+
+        dogecoin_rpc_server.mine_blocks(1);
+
+        let confirmed = dogecoin_rpc_server.broadcast_tx(TransactionTemplate {
+            inputs: &[(1, 0, 0)],
+            output_scripts: &[script.clone()],
+            ..Default::default()
+        });
+
+        dogecoin_rpc_server.mine_blocks(1);
+
+        let pending = dogecoin_rpc_server.broadcast_tx(TransactionTemplate {
+            inputs: &[(2, 1, 0)],
+            output_scripts: &[script],
+            ..Default::default()
+        });
+
+        let server = TestServer::new_server(dogecoin_rpc_server, None, &[], &["--watch-mempool"]);
+
+        let utxos = || {
+            let response = serde_json::from_str::<UtxoAddressJson>(
+                &server.get_json(format!("/utxos/balance/{address}")).text().unwrap(),
+            )
+            .unwrap();
+
+            let summarize = |utxos: Vec<Utxo>| {
+                utxos
+                    .into_iter()
+                    .map(|utxo| {
+                        (
+                            OutPoint::new(utxo.txid, utxo.vout),
+                            utxo.confirmations,
+                            utxo.spent_in_mempool,
+                        )
+                    })
+                    .collect::<Vec<(OutPoint, Option<u32>, bool)>>()
+            };
+
+            (summarize(response.utxos), summarize(response.pending))
+        };
+
+        // The mempool is polled in the background
+        let mut response = utxos();
+        for _ in 0..400 {
+            if !response.1.is_empty() {
+                break;
+            }
+            thread::sleep(Duration::from_millis(25));
+            response = utxos();
+        }
+
+        assert_eq!(
+            response,
+            (
+                vec![(OutPoint::new(confirmed, 0), Some(1), true)],
+                vec![(OutPoint::new(pending, 0), None, false)],
+            ),
+        );
+    }
+
+    #[test]
+    fn legacy_address_endpoints_are_paginated_by_page_number() {
+        let server = TestServer::new();
+        let address = p2pkh_address();
+
+        server.assert_response(
+            format!("/dunes/balance/{address}/1"),
+            StatusCode::OK,
+            r#"{"dunes":[],"total_dunes":0,"total_elements":0}"#,
+        );
+
+        server.mine_blocks(1);
+
+        let script = Script::new_p2pkh(&PubkeyHash::all_zeros());
+
+        server.dogecoin_rpc_server.broadcast_tx(TransactionTemplate {
+            inputs: &[(1, 0, 0)],
+            outputs: 3,
+            output_scripts: &[script.clone(), script.clone(), script],
+            ..Default::default()
+        });
+
+        server.mine_blocks(1);
+
+        let utxos = |path: String| {
+            serde_json::from_str::<LegacyUtxoAddressJson>(&server.get_json(path).text().unwrap())
+                .unwrap()
+        };
+
+        let first = utxos(format!("/utxos/balance/{address}/1?limit=2"));
+        assert_eq!(first.utxos.len(), 2);
+        assert_eq!(first.total_utxos, 3);
+        assert_eq!(first.total_inscription_shibes, 0);
+
+        let second = utxos(format!("/utxos/balance/{address}/2?limit=2"));
+        assert_eq!(second.utxos.len(), 1);
+        assert_eq!(second.total_utxos, 3);
+
+        let all = utxos(format!("/utxos/balance/{address}?show_all=true"));
+        assert_eq!(all.utxos.len(), 3);
+        assert_eq!(
+            all.total_shibes,
+            all.utxos.iter().map(|utxo| u128::from(utxo.shibes)).sum::<u128>(),
+        );
+        assert_eq!(
+            first
+                .utxos
+                .iter()
+                .chain(&second.utxos)
+                .cloned()
+                .collect::<Vec<Utxo>>(),
+            all.utxos,
+        );
+    }
 }
@@ -0,0 +1,90 @@
+use {
+  super::*,
+  axum::{extract::FromRequestParts, http::request::Parts},
+};
+
+/// Whether the client prefers JSON, according to its `Accept` header. Requests
+/// for JSON are served HTML unless the server was started with
+/// `--enable-json-api`.
+pub(super) struct AcceptJson(pub(super) bool);
+
+impl AcceptJson {
+  /// Whether `application/json` is acceptable and has the highest quality of
+  /// the media ranges in `accept`
+  fn prefers_json(accept: &str) -> bool {
+    let mut json = None;
+    let mut other = 0.0f32;
+
+    for media_range in accept.split(',') {
+      let mut parameters = media_range.split(';').map(str::trim);
+
+      let media_type = parameters.next().unwrap_or_default();
+
+      let quality = parameters
+        .filter_map(|parameter| parameter.split_once('='))
+        .find(|(name, _)| name.trim().eq_ignore_ascii_case("q"))
+        .map(|(_, value)| value.trim().parse::<f32>().unwrap_or_default())
+        .unwrap_or(1.0);
+
+      if media_type.eq_ignore_ascii_case("application/json") {
+        json = Some(json.unwrap_or(0.0f32).max(quality));
+      } else {
+        other = other.max(quality);
+      }
+    }
+
+    json.map_or(false, |json| json > 0.0 && json >= other)
+  }
+}
+
+#[axum::async_trait]
+impl<S> FromRequestParts<S> for AcceptJson
+where
+  S: Send + Sync,
+{
+  type Rejection = std::convert::Infallible;
+
+  async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+    let json_api_enabled = parts
+      .extensions
+      .get::<Arc<PageConfig>>()
+      .map(|page_config| page_config.json_api_enabled)
+      .unwrap_or_default();
+
+    let accept = parts
+      .headers
+      .get_all(header::ACCEPT)
+      .iter()
+      .filter_map(|value| value.to_str().ok())
+      .collect::<Vec<&str>>()
+      .join(",");
+
+    Ok(Self(json_api_enabled && Self::prefers_json(&accept)))
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn prefers_json() {
+    assert!(AcceptJson::prefers_json("application/json"));
+    assert!(AcceptJson::prefers_json("Application/JSON"));
+    assert!(AcceptJson::prefers_json("application/json; charset=utf-8"));
+    assert!(AcceptJson::prefers_json("application/json, */*;q=0.8"));
+    assert!(AcceptJson::prefers_json(
+      "text/html;q=0.5, application/json"
+    ));
+
+    assert!(!AcceptJson::prefers_json(""));
+    assert!(!AcceptJson::prefers_json("*/*"));
+    assert!(!AcceptJson::prefers_json("application/json;q=0"));
+    assert!(!AcceptJson::prefers_json(
+      "text/html,application/xhtml+xml,application/xml;q=0.9,*/*;q=0.8"
+    ));
+    assert!(!AcceptJson::prefers_json(
+      "text/html, application/json;q=0.9"
+    ));
+  }
+}
@@ -60,3 +60,31 @@ impl FromStr for Dune {
         }
     }
 }
+
+/// Position in an address's outputs, formatted as `<HEIGHT>:<TXID>:<VOUT>`
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub(super) struct Cursor {
+    pub(super) height: u32,
+    pub(super) outpoint: OutPoint,
+}
+
+impl FromStr for Cursor {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (height, outpoint) = s
+            .split_once(':')
+            .ok_or_else(|| anyhow!("invalid cursor `{s}`"))?;
+
+        Ok(Self {
+            height: height.parse()?,
+            outpoint: outpoint.parse()?,
+        })
+    }
+}
+
+impl Display for Cursor {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        write!(f, "{}:{}", self.height, self.outpoint)
+    }
+}
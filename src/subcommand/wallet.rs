@@ -17,15 +17,20 @@ use {
 };
 
 pub mod balance;
+pub mod broadcast;
+pub mod consolidate_dunes;
 pub mod create;
 pub mod etch;
 pub(crate) mod inscribe;
 pub mod inscriptions;
+pub mod mint;
 pub mod outputs;
+pub mod psbt;
 pub mod receive;
 mod restore;
 pub mod sats;
 pub mod send;
+pub mod send_dunes;
 pub(crate) mod transaction_builder;
 pub mod transactions;
 
@@ -33,6 +38,10 @@ pub mod transactions;
 pub(crate) enum Wallet {
   #[clap(about = "Get wallet balance")]
   Balance,
+  #[clap(about = "Finalize and broadcast a PSBT")]
+  Broadcast(broadcast::Broadcast),
+  #[clap(about = "Consolidate dune outputs")]
+  ConsolidateDunes(consolidate_dunes::ConsolidateDunes),
   #[clap(about = "Create new wallet")]
   Create(create::Create),
   #[clap(about = "Create dune")]
@@ -40,7 +49,9 @@ pub(crate) enum Wallet {
   #[clap(about = "Create inscription")]
   Inscribe(inscribe::Inscribe),
   #[clap(about = "List wallet inscriptions")]
-  Inscriptions,
+  Inscriptions(inscriptions::Inscriptions),
+  #[clap(about = "Mint dune")]
+  Mint(mint::Mint),
   #[clap(about = "Generate receive address")]
   Receive,
   #[clap(about = "Restore wallet")]
@@ -49,6 +60,8 @@ pub(crate) enum Wallet {
   Sats(sats::Sats),
   #[clap(about = "Send sat or inscription")]
   Send(send::Send),
+  #[clap(about = "Send dunes to many recipients")]
+  SendDunes(send_dunes::SendDunes),
   #[clap(about = "See wallet transactions")]
   Transactions(transactions::Transactions),
   #[clap(about = "List wallet outputs")]
@@ -59,26 +72,24 @@ impl Wallet {
   pub(crate) fn run(self, options: Options) -> SubcommandResult {
     match self {
       Self::Balance => balance::run(options),
+      Self::Broadcast(broadcast) => broadcast.run(options),
+      Self::ConsolidateDunes(consolidate_dunes) => consolidate_dunes.run(options),
       Self::Create(create) => create.run(options),
       Self::Etch(etch) => etch.run(options),
       Self::Inscribe(inscribe) => inscribe.run(options),
-      Self::Inscriptions => inscriptions::run(options),
+      Self::Inscriptions(inscriptions) => inscriptions.run(options),
+      Self::Mint(mint) => mint.run(options),
       Self::Receive => receive::run(options),
       Self::Restore(restore) => restore.run(options),
       Self::Sats(sats) => sats.run(options),
       Self::Send(send) => send.run(options),
+      Self::SendDunes(send_dunes) => send_dunes.run(options),
       Self::Transactions(transactions) => transactions.run(options),
       Self::Outputs => outputs::run(options),
     }
   }
 }
 
-fn get_change_address(client: &Client) -> Result<Address> {
-  client
-    .call("getrawchangeaddress", &["bech32m".into()])
-    .context("could not get change addresses from wallet")
-}
-
 pub(crate) fn initialize_wallet(options: &Options, seed: [u8; 64]) -> Result {
   let client = options.dogecoin_rpc_client_for_wallet_command(true)?;
   let network = options.chain().network();
@@ -15,7 +15,7 @@ pub(crate) fn run(options: Options) -> SubcommandResult {
     let index = Index::open(&options)?;
     index.update()?;
 
-    let unspent_outputs = index.get_unspent_outputs(Wallet::load(&options)?)?;
+    let unspent_outputs = index.get_unspent_outputs(&Wallet::load(&options)?)?;
 
     let inscription_outputs = index
         .get_inscriptions(None)?
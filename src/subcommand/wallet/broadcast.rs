@@ -0,0 +1,46 @@
+use {super::*, crate::wallet::Wallet, inscribe::Inscribe};
+
+#[derive(Debug, Parser)]
+pub(crate) struct Broadcast {
+  #[arg(
+    long,
+    help = "Finalize and broadcast <PSBT>, signing it with the wallet if none of its inputs are signed."
+  )]
+  psbt: PathBuf,
+}
+
+impl Broadcast {
+  pub(crate) fn run(self, options: Options) -> SubcommandResult {
+    let psbt = psbt::load(&self.psbt)?;
+
+    let client = options.dogecoin_rpc_client_for_wallet_command(false)?;
+
+    let transaction = match psbt::finalize(&psbt)? {
+      Some(transaction) => transaction,
+      None => {
+        let index = Index::open(&options)?;
+        index.update()?;
+
+        consensus::encode::deserialize(&Wallet::load(&options)?.sign_transaction(
+          &index,
+          &client,
+          &psbt.unsigned_tx,
+        )?)?
+      }
+    };
+
+    if let Some(reveal_chain) = psbt.proprietary.get(&psbt::key(psbt::REVEAL_CHAIN)) {
+      return Ok(Box::new(Inscribe::reveal_psbt(
+        &options,
+        &client,
+        &transaction,
+        reveal_chain,
+        psbt::fee(&psbt)?,
+      )?));
+    }
+
+    Ok(Box::new(send::Output {
+      transaction: client.send_raw_transaction(&transaction)?,
+    }))
+  }
+}
@@ -0,0 +1,265 @@
+use {super::*, crate::wallet::Wallet, bitcoin::PackedLockTime, etch::DryRunOutput};
+
+#[derive(Debug, Parser)]
+pub(crate) struct ConsolidateDunes {
+  #[clap(long, help = "Only consolidate outputs holding <DUNE>.")]
+  dune: Option<SpacedDune>,
+  #[clap(long, help = "Spend at most <MAX_INPUTS> dune outputs.")]
+  max_inputs: Option<usize>,
+  #[clap(
+    long,
+    help = "Merge all dunes into a single output instead of one output per dune."
+  )]
+  single_output: bool,
+  #[clap(long, help = "Use fee rate of <FEE_RATE> sats/vB.")]
+  fee_rate: FeeRate,
+  #[clap(
+    long,
+    help = "Don't sign or broadcast the transaction. Print the decoded dunestone, its cenotaph flaw if any, and its OP_RETURN size instead."
+  )]
+  dry_run: bool,
+  #[clap(
+    long,
+    conflicts_with = "dry_run",
+    help = "Write the unsigned transaction to <PSBT_OUT> as a PSBT instead of signing and broadcasting it."
+  )]
+  psbt_out: Option<PathBuf>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct Output {
+  pub transaction: Txid,
+  pub inputs: usize,
+  pub outputs: usize,
+}
+
+impl ConsolidateDunes {
+  pub(crate) fn run(self, options: Options) -> SubcommandResult {
+    let index = Index::open(&options)?;
+
+    ensure!(
+      index.has_dune_index(),
+      "`ord wallet consolidate-dunes` requires index created with `--index-dunes` flag",
+    );
+
+    ensure!(
+      self.max_inputs != Some(0) && self.max_inputs != Some(1),
+      "`--max-inputs` must be at least 2",
+    );
+
+    index.update()?;
+
+    let client = options.dogecoin_rpc_client_for_wallet_command(false)?;
+
+    let rules = index
+      .protocol()
+      .dunestone_rules(u32::try_from(client.get_block_count()?).unwrap() + 1);
+
+    let mut wallet = Wallet::load(&options)?;
+
+    let id = match self.dune {
+      Some(spaced_dune) => Some(
+        index
+          .dune(spaced_dune.dune)?
+          .with_context(|| format!("dune `{spaced_dune}` has not been etched"))?
+          .0,
+      ),
+      None => None,
+    };
+
+    let unspent_outputs = index.get_unspent_outputs(&wallet)?;
+
+    let mut balances = BTreeMap::<OutPoint, BTreeMap<DuneId, u128>>::new();
+
+    for (outpoint, output_balances) in index.get_dune_balances_for_outpoints(
+      &unspent_outputs.keys().cloned().collect::<Vec<OutPoint>>(),
+    )? {
+      if balances.len() == self.max_inputs.unwrap_or(usize::MAX) {
+        break;
+      }
+
+      if !id.map_or(true, |id| output_balances.contains_key(&id))
+        || !index.get_inscriptions_on_output(outpoint)?.is_empty()
+      {
+        continue;
+      }
+
+      balances.insert(outpoint, output_balances);
+    }
+
+    ensure!(
+      balances.len() >= 2,
+      "wallet contains {} consolidatable dune outputs, nothing to consolidate",
+      balances.len(),
+    );
+
+    let (dunestone, outputs) = Self::dunestone(&balances, self.single_output)?;
+
+    ensure!(
+      dunestone.encipher().len() <= index.protocol().max_op_return_size,
+      "dunestone consolidating {} dunes greater than maximum OP_RETURN size, use `--dune` or `--single-output` to consolidate fewer dunes",
+      dunestone.edicts.len(),
+    );
+
+    let mut output = vec![TxOut {
+      script_pubkey: dunestone.encipher(),
+      value: 0,
+    }];
+
+    for _ in 0..outputs {
+      output.push(TxOut {
+        script_pubkey: wallet.get_change_address(&client)?.script_pubkey(),
+        value: TARGET_POSTAGE.to_sat(),
+      });
+    }
+
+    let unfunded_transaction = Transaction {
+      version: 1,
+      lock_time: PackedLockTime::ZERO,
+      input: balances
+        .keys()
+        .map(|previous_output| TxIn {
+          previous_output: *previous_output,
+          script_sig: Script::new(),
+          sequence: Sequence::MAX,
+          witness: Witness::new(),
+        })
+        .collect(),
+      output,
+    };
+
+    if self.dry_run {
+      return Ok(Box::new(DryRunOutput::new(&unfunded_transaction, rules)));
+    }
+
+    if let Some(flaw) = Dunestone::from_transaction_with_rules(&unfunded_transaction, rules)
+      .and_then(|dunestone| dunestone.flaw)
+    {
+      bail!("dunestone would be a cenotaph and burn the consolidated dunes: {flaw}");
+    }
+
+    wallet.lock_non_cardinal_outputs(&index, &client, &unspent_outputs)?;
+
+    let unsigned_transaction =
+      wallet.fund_transaction(&index, &client, self.fee_rate, &unfunded_transaction)?;
+
+    if let Some(path) = &self.psbt_out {
+      return Ok(Box::new(psbt::export(
+        &index,
+        &consensus::encode::deserialize(&unsigned_transaction)?,
+        BTreeMap::new(),
+        path,
+      )?));
+    }
+
+    let signed_transaction = wallet.sign_transaction(&index, &client, &unsigned_transaction)?;
+
+    Ok(Box::new(Output {
+      transaction: client.send_raw_transaction(&signed_transaction)?,
+      inputs: balances.len(),
+      outputs,
+    }))
+  }
+
+  /// Build a dunestone with an edict transferring the total balance of each
+  /// dune held by `balances` to its own output, or to output 1 if
+  /// `single_output` is set, returning it along with the number of dune
+  /// outputs it requires
+  fn dunestone(
+    balances: &BTreeMap<OutPoint, BTreeMap<DuneId, u128>>,
+    single_output: bool,
+  ) -> Result<(Dunestone, usize)> {
+    let mut totals = BTreeMap::<DuneId, u128>::new();
+
+    for (id, balance) in balances.values().flatten() {
+      let total = totals.entry(*id).or_default();
+      *total = total
+        .checked_add(*balance)
+        .with_context(|| format!("total balance of dune {id} overflows"))?;
+    }
+
+    let edicts = totals
+      .into_iter()
+      .enumerate()
+      .map(|(i, (id, amount))| Edict {
+        id: id.into(),
+        amount,
+        output: if single_output {
+          1
+        } else {
+          u128::try_from(i + 1).unwrap()
+        },
+      })
+      .collect::<Vec<Edict>>();
+
+    let outputs = if single_output { 1 } else { edicts.len() };
+
+    Ok((
+      Dunestone {
+        edicts,
+        ..Default::default()
+      },
+      outputs,
+    ))
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn id(index: u32) -> DuneId {
+    DuneId {
+      height: 840000,
+      index,
+    }
+  }
+
+  fn balances() -> BTreeMap<OutPoint, BTreeMap<DuneId, u128>> {
+    [
+      (outpoint(1), [(id(1), 100), (id(2), 5)].into()),
+      (outpoint(2), [(id(1), 50)].into()),
+      (outpoint(3), [(id(2), 7)].into()),
+    ]
+    .into()
+  }
+
+  #[test]
+  fn one_output_per_dune() {
+    let (dunestone, outputs) = ConsolidateDunes::dunestone(&balances(), false).unwrap();
+
+    assert_eq!(outputs, 2);
+    assert_eq!(
+      dunestone.edicts,
+      [
+        Edict {
+          id: id(1).into(),
+          amount: 150,
+          output: 1,
+        },
+        Edict {
+          id: id(2).into(),
+          amount: 12,
+          output: 2,
+        },
+      ],
+    );
+    assert_eq!(dunestone.pointer, None);
+  }
+
+  #[test]
+  fn single_output() {
+    let (dunestone, outputs) = ConsolidateDunes::dunestone(&balances(), true).unwrap();
+
+    assert_eq!(outputs, 1);
+    assert!(dunestone.edicts.iter().all(|edict| edict.output == 1));
+    assert_eq!(
+      dunestone
+        .edicts
+        .iter()
+        .map(|edict| edict.amount)
+        .sum::<u128>(),
+      162,
+    );
+  }
+}
@@ -14,6 +14,11 @@ pub(crate) struct Create {
     help = "Use <PASSPHRASE> to derive wallet seed."
   )]
   pub(crate) passphrase: String,
+  #[clap(
+    long,
+    help = "Keep wallet keys in ord's data directory and sign locally instead of in Dogecoin Core. Keys are stored unencrypted."
+  )]
+  pub(crate) native: bool,
 }
 
 impl Create {
@@ -23,7 +28,13 @@ impl Create {
 
     let mnemonic = Mnemonic::from_entropy(&entropy)?;
 
-    initialize_wallet(&options, mnemonic.to_seed(self.passphrase.clone()))?;
+    let seed = mnemonic.to_seed(self.passphrase.clone());
+
+    if self.native {
+      crate::wallet::Wallet::create_native(&options, seed)?;
+    } else {
+      initialize_wallet(&options, seed)?;
+    }
 
     Ok(Box::new(Output {
       mnemonic,
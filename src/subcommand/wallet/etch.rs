@@ -9,10 +9,43 @@ pub(crate) struct Etch {
   fee_rate: FeeRate,
   #[clap(long, help = "Etch dune <DUNE>. May contain `.` or `•`as spacers.")]
   dune: SpacedDune,
-  #[clap(long, help = "Set supply to <SUPPLY>.")]
+  #[clap(
+    long,
+    help = "Set supply to <SUPPLY>. Must equal <PREMINE> plus <CAP> times <LIMIT>."
+  )]
   supply: Decimal,
   #[clap(long, help = "Set currency symbol to <SYMBOL>.")]
   symbol: char,
+  #[clap(
+    long,
+    help = "Allocate <PREMINE> to the etcher. Defaults to <SUPPLY> if no mint terms are given, otherwise 0."
+  )]
+  premine: Option<Decimal>,
+  #[clap(long, help = "Allow at most <CAP> mints.")]
+  cap: Option<u128>,
+  #[clap(long, help = "Mint <LIMIT> dunes per mint transaction.")]
+  limit: Option<Decimal>,
+  #[clap(long, help = "Open mints at block <HEIGHT_START>.")]
+  height_start: Option<u64>,
+  #[clap(long, help = "Close mints at block <HEIGHT_END>.")]
+  height_end: Option<u64>,
+  #[clap(long, help = "Open mints <OFFSET_START> blocks after the etching block.")]
+  offset_start: Option<u64>,
+  #[clap(long, help = "Close mints <OFFSET_END> blocks after the etching block.")]
+  offset_end: Option<u64>,
+  #[clap(long, help = "Opt in to future protocol changes.")]
+  turbo: bool,
+  #[clap(
+    long,
+    help = "Don't sign or broadcast the etching. Print the decoded dunestone, its cenotaph flaw if any, and its OP_RETURN size instead."
+  )]
+  dry_run: bool,
+  #[clap(
+    long,
+    conflicts_with = "dry_run",
+    help = "Write the unsigned etching to <PSBT_OUT> as a PSBT instead of signing and broadcasting it."
+  )]
+  psbt_out: Option<PathBuf>,
 }
 
 #[derive(Serialize, Deserialize, Debug)]
@@ -20,6 +53,25 @@ pub struct Output {
   pub transaction: Txid,
 }
 
+#[derive(Serialize, Debug)]
+pub struct DryRunOutput {
+  pub cenotaph: Option<Cenotaph>,
+  pub dunestone: Option<Dunestone>,
+  pub op_return_size: usize,
+}
+
+impl DryRunOutput {
+  pub(crate) fn new(transaction: &Transaction, rules: Rules) -> Self {
+    let dunestone = Dunestone::from_transaction_with_rules(transaction, rules);
+
+    Self {
+      cenotaph: dunestone.as_ref().and_then(Dunestone::cenotaph),
+      dunestone,
+      op_return_size: transaction.output[0].script_pubkey.len(),
+    }
+  }
+}
+
 impl Etch {
   pub(crate) fn run(self, options: Options) -> SubcommandResult {
     let index = Index::open(&options)?;
@@ -43,8 +95,13 @@ impl Etch {
       dune,
     );
 
-    let minimum_at_height =
-        Dune::minimum_at_height(options.chain(), Height((u32::try_from(count).unwrap() + 1)));
+    let protocol = index.protocol();
+
+    let height = u32::try_from(count).unwrap() + 1;
+
+    let rules = protocol.dunestone_rules(height);
+
+    let minimum_at_height = protocol.minimum_dune_at_height(height);
 
     ensure!(
       dune >= minimum_at_height,
@@ -52,42 +109,58 @@ impl Etch {
       dune,
     );
 
-    ensure!(!dune.is_reserved(), "dune `{}` is reserved", dune);
+    ensure!(!protocol.is_reserved(dune), "dune `{}` is reserved", dune);
 
     ensure!(
       self.divisibility <= crate::dunes::MAX_DIVISIBILITY,
       "<DIVISIBILITY> must be equal to or less than 38"
     );
 
-    let destination = get_change_address(&client)?;
+    let terms = self.terms(u64::try_from(count).unwrap() + 1)?;
+
+    let supply = self.supply.to_amount(self.divisibility)?;
+
+    let premine = match self.premine {
+      Some(premine) => premine.to_amount(self.divisibility)?,
+      None if terms.is_some() => 0,
+      None => supply,
+    };
+
+    let mintable = match terms {
+      Some(terms) => terms
+        .cap
+        .unwrap_or_default()
+        .checked_mul(terms.limit.unwrap_or_default())
+        .context("<CAP> times <LIMIT> overflows")?,
+      None => 0,
+    };
+
+    ensure!(
+      premine.checked_add(mintable) == Some(supply),
+      "<SUPPLY> must equal <PREMINE> plus <CAP> times <LIMIT>: {supply} != {premine} + {mintable}",
+    );
+
+    let mut wallet = crate::wallet::Wallet::load(&options)?;
+
+    let destination = wallet.get_change_address(&client)?;
 
     let dunestone = Dunestone {
       etching: Some(Etching {
         divisibility: Some(self.divisibility),
-        terms: None,
-        premine: None,
+        terms,
+        premine: (premine > 0).then_some(premine),
         dune: Some(dune),
         spacers: Some(spacers),
         symbol: Some(self.symbol),
-        turbo: false,
+        turbo: self.turbo,
       }),
-      edicts: vec![Edict {
-        amount: self.supply.to_amount(self.divisibility)?,
-        id: 0,
-        output: 1,
-      }],
+      edicts: Vec::new(),
       pointer: None,
-      cenotaph: false,
+      flaw: None,
     };
 
     let script_pubkey = dunestone.encipher();
 
-    ensure!(
-      script_pubkey.len() <= 82,
-      "dunestone greater than maximum OP_RETURN size: {} > 82",
-      script_pubkey.len()
-    );
-
     let unfunded_transaction = Transaction {
       version: 1,
       lock_time: PackedLockTime::ZERO,
@@ -104,27 +177,106 @@ impl Etch {
       ],
     };
 
-    let unspent_outputs = index.get_unspent_outputs(crate::wallet::Wallet::load(&options)?)?;
+    if self.dry_run {
+      return Ok(Box::new(DryRunOutput::new(&unfunded_transaction, rules)));
+    }
 
-    let inscriptions = index
-      .get_inscriptions(None)?
-      .keys()
-      .map(|satpoint| satpoint.outpoint)
-      .collect::<Vec<OutPoint>>();
+    ensure!(
+      unfunded_transaction.output[0].script_pubkey.len() <= protocol.max_op_return_size,
+      "dunestone greater than maximum OP_RETURN size: {} > {}",
+      unfunded_transaction.output[0].script_pubkey.len(),
+      protocol.max_op_return_size,
+    );
 
-    if !client.lock_unspent(&inscriptions)? {
-      bail!("failed to lock UTXOs");
+    match Dunestone::from_transaction_with_rules(&unfunded_transaction, rules) {
+      Some(Dunestone { flaw: None, .. }) => {}
+      Some(Dunestone {
+        flaw: Some(flaw), ..
+      }) => bail!("dunestone would be a cenotaph and burn the etched dunes: {flaw}"),
+      None => bail!("failed to decipher dunestone"),
     }
 
-    let unsigned_transaction = fund_raw_transaction(&client, self.fee_rate, &unfunded_transaction)?;
+    let unspent_outputs = index.get_unspent_outputs(&wallet)?;
 
-    let signed_transaction = client
-        .sign_raw_transaction_with_wallet(&unsigned_transaction, None, None)?
-        .hex;
+    wallet.lock_non_cardinal_outputs(&index, &client, &unspent_outputs)?;
+
+    let unsigned_transaction =
+      wallet.fund_transaction(&index, &client, self.fee_rate, &unfunded_transaction)?;
+
+    if let Some(path) = &self.psbt_out {
+      return Ok(Box::new(psbt::export(
+        &index,
+        &consensus::encode::deserialize(&unsigned_transaction)?,
+        BTreeMap::new(),
+        path,
+      )?));
+    }
+
+    let signed_transaction = wallet.sign_transaction(&index, &client, &unsigned_transaction)?;
 
     let transaction = client.send_raw_transaction(&signed_transaction)?;
 
     Ok(Box::new(Output { transaction }))
   }
-}
 
+  fn terms(&self, height: u64) -> Result<Option<Terms>> {
+    if self.cap.is_none()
+      && self.limit.is_none()
+      && self.height_start.is_none()
+      && self.height_end.is_none()
+      && self.offset_start.is_none()
+      && self.offset_end.is_none()
+    {
+      return Ok(None);
+    }
+
+    let (Some(cap), Some(limit)) = (self.cap, self.limit) else {
+      bail!("mint terms require both <CAP> and <LIMIT>");
+    };
+
+    let limit = limit.to_amount(self.divisibility)?;
+
+    ensure!(cap > 0, "<CAP> must be greater than zero");
+
+    ensure!(limit > 0, "<LIMIT> must be greater than zero");
+
+    ensure!(
+      limit <= crate::dunes::MAX_LIMIT,
+      "<LIMIT> must be equal to or less than {}",
+      crate::dunes::MAX_LIMIT,
+    );
+
+    if let (Some(start), Some(end)) = (self.height_start, self.height_end) {
+      ensure!(
+        start < end,
+        "<HEIGHT_START> must be less than <HEIGHT_END>: {start} >= {end}"
+      );
+    }
+
+    if let (Some(start), Some(end)) = (self.offset_start, self.offset_end) {
+      ensure!(
+        start < end,
+        "<OFFSET_START> must be less than <OFFSET_END>: {start} >= {end}"
+      );
+    }
+
+    if let Some(end) = self.height_end {
+      ensure!(
+        end > height,
+        "<HEIGHT_END> must be greater than the height of the next block: {end} <= {height}"
+      );
+    }
+
+    ensure!(
+      self.offset_end != Some(0),
+      "<OFFSET_END> must be greater than zero"
+    );
+
+    Ok(Some(Terms {
+      cap: Some(cap),
+      limit: Some(limit),
+      height: (self.height_start, self.height_end),
+      offset: (self.offset_start, self.offset_end),
+    }))
+  }
+}
@@ -1,33 +1,46 @@
-use bitcoin::SchnorrSighashType;
-use bitcoin::secp256k1::schnorr::Signature;
 use {
   super::*,
-  crate::wallet::Wallet,
+  crate::{sat_point::SatPoint, wallet::Wallet},
   bitcoin::{
     blockdata::{opcodes, script},
-    PrivateKey,
-    util::schnorr::{TapTweak, TweakedKeyPair, TweakedPublicKey, UntweakedKeyPair},
-    util::sighash::{Prevouts, SighashCache},
+    hashes::hex::FromHex,
     locktime::PackedLockTime,
-    policy::MAX_STANDARD_TX_WEIGHT,
-    secp256k1::{self, constants::SCHNORR_SIGNATURE_SIZE, rand, Secp256k1, XOnlyPublicKey},
-    util::taproot::{ControlBlock, LeafVersion, TapLeafHash, TaprootBuilder},
-    Witness,
+    secp256k1::{self, rand, Secp256k1},
+    EcdsaSig, EcdsaSighashType, PrivateKey, PublicKey,
   },
-  bitcoincore_rpc::bitcoincore_rpc_json::{ImportDescriptors, Timestamp},
   bitcoincore_rpc::Client,
 };
-use crate::sat_point::SatPoint;
+
+/// Dogecoin Core's default limit on the number of unconfirmed ancestors of a
+/// mempool transaction, which bounds how much of an inscription chain can be
+/// broadcast before its first links confirm.
+const MEMPOOL_CHAIN_LIMIT: usize = 25;
+
+/// Size of a DER-encoded ECDSA signature with a low R value plus its sighash
+/// byte, used to estimate reveal fees before the reveals are signed.
+const SIGNATURE_SIZE: usize = 71;
 
 #[derive(Serialize, Deserialize, Debug, PartialEq)]
 pub struct Output {
   commit: Txid,
   inscription: InscriptionId,
   reveal: Txid,
+  reveals: Vec<Txid>,
   fees: u64,
 }
 
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+pub struct AbandonOutput {
+  pub transaction: Txid,
+  pub value: u64,
+}
+
 #[derive(Debug, Parser)]
+#[command(group(
+  ArgGroup::new("source")
+    .required(true)
+    .args(&["file", "resume", "abandon"]),
+))]
 pub(crate) struct Inscribe {
   #[clap(long, help = "Shibescribe <SATPOINT>")]
   pub(crate) satpoint: Option<SatPoint>,
@@ -43,103 +56,370 @@ pub(crate) struct Inscribe {
   )]
   pub(crate) commit_fee_rate: Option<FeeRate>,
   #[clap(help = "Shibescribe sat with contents of <FILE>")]
-  pub(crate) file: PathBuf,
+  pub(crate) file: Option<PathBuf>,
+  #[clap(
+    long,
+    help = "Finish broadcasting the saved transactions of inscription <RESUME>, after an earlier `ord wallet inscribe` stopped part way through."
+  )]
+  pub(crate) resume: Option<Txid>,
+  #[clap(
+    long,
+    help = "Abandon partially revealed inscription <ABANDON>, sending the value locked in its last unspent P2SH output back to the wallet using the recovery key. Requires the record of the inscription's transactions saved by `ord wallet inscribe`."
+  )]
+  pub(crate) abandon: Option<Txid>,
   #[clap(long, help = "Do not back up recovery key.")]
   pub(crate) no_backup: bool,
   #[clap(
     long,
-    help = "Do not check that transactions are equal to or below the MAX_STANDARD_TX_WEIGHT of 400,000 weight units. Transactions over this limit are currently nonstandard and will not be relayed by bitcoind in its default configuration. Do not use this flag unless you understand the implications."
+    help = "Do not check that the inscription fits in a chain of at most 25 transactions. Dogecoin Core does not relay more than 25 unconfirmed transactions in a chain in its default configuration, so broadcasting longer chains stops once that limit is reached and must be resumed after earlier transactions confirm. Do not use this flag unless you understand the implications."
   )]
   pub(crate) no_limit: bool,
   #[clap(long, help = "Don't sign or broadcast transactions.")]
   pub(crate) dry_run: bool,
+  #[clap(
+    long,
+    conflicts_with_all = ["dry_run", "no_backup"],
+    help = "Write the unsigned commit transaction to <PSBT_OUT> as a PSBT instead of signing and broadcasting it. The reveal transactions are built and broadcast by `ord wallet broadcast` once the commit transaction is signed, using the recovery key backed up to the wallet, which is not included in the PSBT."
+  )]
+  pub(crate) psbt_out: Option<PathBuf>,
   #[clap(long, help = "Send inscription to <DESTINATION>.")]
   pub(crate) destination: Option<Address>,
 }
 
+/// The P2SH transactions that reveal an inscription after its commit
+/// transaction. Each link spends the previous link's P2SH output, carrying
+/// the next fragment of the inscription envelope in its `script_sig`, and
+/// locks its own output to the script that the next link unlocks. The last
+/// link sends the inscribed sat to the destination.
+#[derive(Debug)]
+struct RevealChain {
+  destination: Script,
+  fees: Vec<Amount>,
+  locks: Vec<Script>,
+  partials: Vec<Script>,
+  private_key: PrivateKey,
+}
+
+/// A reveal chain as saved in a PSBT, with scripts in hex. The recovery key
+/// is left out, since anyone holding the PSBT could use it to spend the
+/// commit output, and is looked up in the wallet when the chain is revealed.
+#[derive(Serialize, Deserialize)]
+struct EncodedRevealChain {
+  destination: String,
+  fees: Vec<u64>,
+  locks: Vec<String>,
+  partials: Vec<String>,
+}
+
+/// Signed transactions of an inscription chain that are saved before
+/// broadcasting, so that a chain that fails to broadcast part way through can
+/// be finished later. The transactions are dropped once they have all been
+/// broadcast, but the journal is kept, so that the chain can still be
+/// abandoned if its later transactions never confirm.
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+struct Journal {
+  broadcast: usize,
+  fees: u64,
+  /// Number of envelope pushes dropped by the script locking the P2SH output
+  /// of each transaction of the chain but the last
+  pushes: Vec<usize>,
+  transactions: Vec<String>,
+}
+
 impl Inscribe {
   pub(crate) fn run(self, options: Options) -> SubcommandResult {
-    let inscription = Inscription::from_file(options.chain(), &self.file)?;
+    if let Some(txid) = self.resume {
+      return Self::resume(&options, txid);
+    }
+
+    if let Some(txid) = self.abandon {
+      return Self::abandon(&options, txid, self.fee_rate);
+    }
+
+    // clap requires exactly one of <FILE>, `--resume` and `--abandon`
+    let inscription = Inscription::from_file(options.chain(), self.file.as_ref().unwrap())?;
 
     let index = Index::open(&options)?;
     index.update()?;
 
     let client = options.dogecoin_rpc_client_for_wallet_command(false)?;
 
-    let mut utxos = index.get_unspent_outputs(Wallet::load(&options)?)?;
+    let mut wallet = Wallet::load(&options)?;
+
+    let utxos = index.get_unspent_outputs(&wallet)?;
 
     let dunic_utxos = index.get_dunic_outputs(&utxos.keys().cloned().collect::<Vec<OutPoint>>())?;
 
     let inscriptions = index.get_inscriptions(None)?;
 
-    let commit_tx_change = [get_change_address(&client)?, get_change_address(&client)?];
+    let commit_tx_change = [
+      wallet.get_change_address(&client)?,
+      wallet.get_change_address(&client)?,
+    ];
 
     let reveal_tx_destination = self
       .destination
       .map(Ok)
-      .unwrap_or_else(|| get_change_address(&client))?;
-
-    let (unsigned_commit_tx, reveal_tx, recovery_key_pair) =
-      Inscribe::create_inscription_transactions(
-        self.satpoint,
-        inscription,
-        inscriptions,
-        options.chain().network(),
-        utxos.clone(),
-        dunic_utxos,
-        commit_tx_change,
-        reveal_tx_destination,
-        self.commit_fee_rate.unwrap_or(self.fee_rate),
-        self.fee_rate,
-        self.no_limit,
+      .unwrap_or_else(|| wallet.get_change_address(&client))?;
+
+    let (unsigned_commit_tx, reveal_chain) = Inscribe::create_inscription_transactions(
+      self.satpoint,
+      inscription,
+      inscriptions,
+      options.chain().network(),
+      utxos.clone(),
+      dunic_utxos,
+      commit_tx_change,
+      reveal_tx_destination,
+      self.commit_fee_rate.unwrap_or(self.fee_rate),
+      self.fee_rate,
+      self.no_limit,
+    )?;
+
+    if let Some(path) = &self.psbt_out {
+      wallet.backup_recovery_key(&client, reveal_chain.private_key)?;
+
+      return Ok(Box::new(psbt::export(
+        &index,
+        &unsigned_commit_tx,
+        [(psbt::REVEAL_CHAIN, reveal_chain.encode()?)].into(),
+        path,
+      )?));
+    }
+
+    let fees = Self::calculate_fee(&unsigned_commit_tx, &utxos) + reveal_chain.fee().to_sat();
+
+    let commit_tx = if self.dry_run {
+      unsigned_commit_tx
+    } else {
+      let signed_raw_commit_tx = wallet.sign_transaction(&index, &client, &unsigned_commit_tx)?;
+
+      consensus::encode::deserialize(&signed_raw_commit_tx)?
+    };
+
+    if !self.dry_run && !self.no_backup {
+      wallet.backup_recovery_key(&client, reveal_chain.private_key)?;
+    }
+
+    Ok(Box::new(Self::reveal(
+      &options,
+      &client,
+      &commit_tx,
+      &reveal_chain,
+      fees,
+      !self.dry_run,
+    )?))
+  }
+
+  /// Build the reveal transactions of the signed commit transaction of a PSBT
+  /// exported by `--psbt-out`, and broadcast the commit and reveals
+  pub(super) fn reveal_psbt(
+    options: &Options,
+    client: &Client,
+    commit_tx: &Transaction,
+    reveal_chain: &[u8],
+    commit_fee: u64,
+  ) -> Result<Output> {
+    let wallet = Wallet::load(options)?;
+
+    let reveal_chain = RevealChain::decode(reveal_chain, |public_key| {
+      wallet.recovery_key(client, options.chain().network(), public_key)
+    })?;
+
+    let fees = commit_fee + reveal_chain.fee().to_sat();
+
+    Self::reveal(options, client, commit_tx, &reveal_chain, fees, true)
+  }
+
+  fn reveal(
+    options: &Options,
+    client: &Client,
+    commit_tx: &Transaction,
+    reveal_chain: &RevealChain,
+    fees: u64,
+    broadcast: bool,
+  ) -> Result<Output> {
+    let reveal_txs = reveal_chain.transactions(commit_tx)?;
+
+    let reveals = reveal_txs
+      .iter()
+      .map(Transaction::txid)
+      .collect::<Vec<Txid>>();
+
+    let inscription = InscriptionId::from(reveals[0]);
+
+    if broadcast {
+      let journal = Journal {
+        broadcast: 0,
+        fees,
+        pushes: reveal_chain.pushes(),
+        transactions: std::iter::once(commit_tx)
+          .chain(&reveal_txs)
+          .map(consensus::encode::serialize_hex)
+          .collect(),
+      };
+
+      Inscribe::broadcast(
+        client,
+        &Inscribe::journal_path(options, inscription)?,
+        journal,
       )?;
+    }
 
-    utxos.insert(
-      reveal_tx.input[0].previous_output,
-      Amount::from_sat(
-        unsigned_commit_tx.output[reveal_tx.input[0].previous_output.vout as usize].value,
-      ),
+    Ok(Output {
+      commit: commit_tx.txid(),
+      inscription,
+      reveal: *reveals.last().unwrap(),
+      reveals,
+      fees,
+    })
+  }
+
+  fn resume(options: &Options, txid: Txid) -> SubcommandResult {
+    let inscription = InscriptionId::from(txid);
+
+    let path = Self::journal_path(options, inscription)?;
+
+    ensure!(
+      path.exists(),
+      "no saved transactions for inscription {inscription}"
     );
 
-    let fees =
-      Self::calculate_fee(&unsigned_commit_tx, &utxos) + Self::calculate_fee(&reveal_tx, &utxos);
+    let journal = Journal::load(&path)?;
 
-    let mut reveal = Txid::all_zeros();
-    let mut commit = Txid::all_zeros();
-    let mut inscription = InscriptionId::default();
+    ensure!(
+      !journal.transactions.is_empty(),
+      "all transactions of inscription {inscription} have been broadcast"
+    );
 
-    if self.dry_run {
-      reveal = reveal_tx.txid();
-      commit = unsigned_commit_tx.txid();
-      inscription = reveal_tx.txid().into();
-    } else {
-      if !self.no_backup {
-        Inscribe::backup_recovery_key(&client, recovery_key_pair, options.chain().network())?;
-      }
+    let transactions = journal
+      .transactions
+      .iter()
+      .map(|hex| Journal::decode(hex))
+      .collect::<Result<Vec<Transaction>>>()?;
 
-      let signed_raw_commit_tx = client
-        .sign_raw_transaction_with_wallet(&unsigned_commit_tx, None, None)?
-        .hex;
+    let client = options.dogecoin_rpc_client_for_wallet_command(false)?;
 
-      commit = client
-        .send_raw_transaction(&signed_raw_commit_tx)
-        .context("Failed to send commit transaction")?;
+    let fees = journal.fees;
 
-      reveal = client
-        .send_raw_transaction(&reveal_tx)
-        .context("Failed to send reveal transaction")?;
+    Self::broadcast(&client, &path, journal)?;
 
-      inscription = reveal.into();
-    };
+    let reveals = transactions[1..]
+      .iter()
+      .map(Transaction::txid)
+      .collect::<Vec<Txid>>();
 
     Ok(Box::new(Output {
-      commit,
-      reveal,
-      inscription: reveal.into(),
+      commit: transactions[0].txid(),
+      inscription,
+      reveal: *reveals.last().unwrap(),
+      reveals,
       fees,
     }))
   }
 
+  fn abandon(options: &Options, txid: Txid, fee_rate: FeeRate) -> SubcommandResult {
+    let inscription = InscriptionId::from(txid);
+
+    let path = Self::journal_path(options, inscription)?;
+
+    ensure!(
+      path.exists(),
+      "no saved transactions for inscription {inscription}"
+    );
+
+    let journal = Journal::load(&path)?;
+
+    let index = Index::open(options)?;
+    index.update()?;
+
+    // Transactions of the chain that have been broadcast, by position in the
+    // chain, where the commit transaction is at position 0. Once all of them
+    // have been broadcast, only the confirmed ones are known, from the index.
+    let links = if journal.transactions.is_empty() {
+      index
+        .get_partial_inscriptions()?
+        .into_iter()
+        .find(|txs| txs[0].txid() == txid)
+        .ok_or_else(|| anyhow!("inscription {inscription} is not pending"))?
+        .into_iter()
+        .enumerate()
+        .map(|(i, tx)| (i + 1, tx))
+        .collect::<Vec<(usize, Transaction)>>()
+    } else {
+      journal.transactions[..journal.broadcast]
+        .iter()
+        .map(|hex| Journal::decode(hex))
+        .collect::<Result<Vec<Transaction>>>()?
+        .into_iter()
+        .enumerate()
+        .collect()
+    };
+
+    let public_key = links
+      .iter()
+      .find(|(position, _)| *position == 1)
+      .and_then(|(_, reveal)| Self::lock_public_key(reveal))
+      .ok_or_else(|| anyhow!("inscription {inscription} is not locked by a recovery key"))?;
+
+    // Later transactions may have been dropped from the mempool, leaving the
+    // output of an earlier one unspent
+    let (outpoint, value, lock, pushes) = journal
+      .locked_output(&links, &public_key, |outpoint| {
+        index.is_unspent_including_mempool(outpoint)
+      })?
+      .ok_or_else(|| {
+        anyhow!("locked output of inscription {inscription} has already been spent")
+      })?;
+
+    let client = options.dogecoin_rpc_client_for_wallet_command(false)?;
+
+    let mut wallet = Wallet::load(options)?;
+
+    let private_key = wallet
+      .recovery_key(&client, options.chain().network(), public_key)?
+      .ok_or_else(|| anyhow!("recovery key for inscription {inscription} not found in wallet"))?;
+
+    let fragment = (0..pushes)
+      .fold(script::Builder::new(), |builder, _| builder.push_int(0))
+      .into_script();
+
+    let destination = wallet.get_change_address(&client)?;
+
+    let build = |value: u64, signature: &[u8]| Transaction {
+      input: vec![TxIn {
+        previous_output: outpoint,
+        script_sig: RevealChain::unlock_script(&fragment, signature, &lock),
+        witness: Witness::new(),
+        sequence: Sequence::ENABLE_RBF_NO_LOCKTIME,
+      }],
+      output: vec![TxOut {
+        script_pubkey: destination.script_pubkey(),
+        value,
+      }],
+      lock_time: PackedLockTime::ZERO,
+      version: 1,
+    };
+
+    let fee = fee_rate.fee(build(0, &[0; SIGNATURE_SIZE]).vsize());
+
+    let value = value
+      .checked_sub(fee.to_sat())
+      .context("locked output value insufficient to pay fee")?;
+
+    if value < destination.script_pubkey().dust_value().to_sat() {
+      bail!("output sweeping locked value would be dust");
+    }
+
+    let signature = RevealChain::sign(&private_key, &build(value, &[]), &lock);
+
+    let transaction = client.send_raw_transaction(&build(value, &signature))?;
+
+    fs::remove_file(&path)?;
+
+    Ok(Box::new(AbandonOutput { transaction, value }))
+  }
+
   fn calculate_fee(tx: &Transaction, utxos: &BTreeMap<OutPoint, Amount>) -> u64 {
     tx.input
       .iter()
@@ -161,7 +441,7 @@ impl Inscribe {
     commit_fee_rate: FeeRate,
     reveal_fee_rate: FeeRate,
     no_limit: bool,
-  ) -> Result<(Transaction, Transaction, TweakedKeyPair)> {
+  ) -> Result<(Transaction, RevealChain)> {
     let satpoint = if let Some(satpoint) = satpoint {
       satpoint
     } else {
@@ -193,255 +473,661 @@ impl Inscribe {
       }
     }
 
-    let secp256k1 = Secp256k1::new();
-    let key_pair = UntweakedKeyPair::new(&secp256k1, &mut rand::thread_rng());
-    let (public_key, _parity) = XOnlyPublicKey::from_keypair(&key_pair);
+    let private_key = PrivateKey::new(secp256k1::SecretKey::new(&mut rand::thread_rng()), network);
 
-    let reveal_script = inscription.append_reveal_script(
-      script::Builder::new()
-        .push_slice(&public_key.serialize())
-        .push_opcode(opcodes::all::OP_CHECKSIG),
+    let reveal_chain = RevealChain::new(
+      &inscription,
+      private_key,
+      destination.script_pubkey(),
+      reveal_fee_rate,
     );
 
-    let taproot_spend_info = TaprootBuilder::new()
-      .add_leaf(0, reveal_script.clone())
-      .expect("adding leaf should work")
-      .finalize(&secp256k1, public_key)
-      .expect("finalizing taproot builder should work");
-
-    let control_block = taproot_spend_info
-      .control_block(&(reveal_script.clone(), LeafVersion::TapScript))
-      .expect("should compute control block");
+    let transaction_count = reveal_chain.partials.len() + 1;
 
-    let commit_tx_address = Address::p2tr_tweaked(taproot_spend_info.output_key(), network);
+    if !no_limit && transaction_count > MEMPOOL_CHAIN_LIMIT {
+      bail!(
+        "inscription requires {transaction_count} transactions, more than the {MEMPOOL_CHAIN_LIMIT} unconfirmed transactions Dogecoin Core relays in a chain by default"
+      );
+    }
 
-    let (_, reveal_fee) = Self::build_reveal_transaction(
-      &control_block,
-      reveal_fee_rate,
-      OutPoint::null(),
-      TxOut {
-        script_pubkey: destination.script_pubkey(),
-        value: 0,
-      },
-      &reveal_script,
-    );
+    let commit_tx_address = Address::p2sh(&reveal_chain.locks[0], network)?;
 
     let unsigned_commit_tx = TransactionBuilder::build_transaction_with_value(
       satpoint,
       inscriptions,
       utxos,
-      commit_tx_address.clone(),
+      commit_tx_address,
       dunic_utxos,
       change,
       commit_fee_rate,
-      reveal_fee + TransactionBuilder::TARGET_POSTAGE,
+      reveal_chain.fee() + TransactionBuilder::TARGET_POSTAGE,
     )?;
 
-    let (vout, output) = unsigned_commit_tx
-      .output
-      .iter()
-      .enumerate()
-      .find(|(_vout, output)| output.script_pubkey == commit_tx_address.script_pubkey())
-      .expect("should find sat commit/inscription output");
+    Ok((unsigned_commit_tx, reveal_chain))
+  }
 
-    let (mut reveal_tx, fee) = Self::build_reveal_transaction(
-      &control_block,
-      reveal_fee_rate,
-      OutPoint {
-        txid: unsigned_commit_tx.txid(),
+  /// Returns the recovery public key in the script locking the output that
+  /// `tx` spends as part of an inscription chain, if any.
+  pub(super) fn lock_public_key(tx: &Transaction) -> Option<PublicKey> {
+    let Some(Ok(Instruction::PushBytes(lock))) = tx.input.first()?.script_sig.instructions().last()
+    else {
+      return None;
+    };
+
+    Self::script_public_key(&Script::from(lock.to_vec()))
+  }
+
+  fn script_public_key(lock: &Script) -> Option<PublicKey> {
+    let Some(Ok(Instruction::PushBytes(public_key))) = lock.instructions().next() else {
+      return None;
+    };
+
+    PublicKey::from_slice(public_key).ok()
+  }
+
+  pub(super) fn journal_path(options: &Options, inscription: InscriptionId) -> Result<PathBuf> {
+    Ok(
+      options
+        .data_dir()?
+        .join("inscriptions")
+        .join(format!("{}.json", inscription.txid)),
+    )
+  }
+
+  /// Whether the chain of `inscription` has saved transactions that have not
+  /// all been broadcast yet
+  pub(super) fn is_resumable(options: &Options, inscription: InscriptionId) -> Result<bool> {
+    let path = Self::journal_path(options, inscription)?;
+
+    Ok(path.exists() && !Journal::load(&path)?.transactions.is_empty())
+  }
+
+  /// Broadcasts the transactions in `journal` that have not been broadcast
+  /// yet, recording progress in the journal at `path`. The transactions are
+  /// dropped from the journal once every one has been broadcast.
+  fn broadcast(client: &Client, path: &Path, mut journal: Journal) -> Result {
+    journal.save(path)?;
+
+    while journal.broadcast < journal.transactions.len() {
+      let transaction = &journal.transactions[journal.broadcast];
+
+      if let Err(err) = client.send_raw_transaction(transaction.as_str()) {
+        bail!(
+          "failed to broadcast transaction {} of {}: {err}\nremaining transactions saved to {}",
+          journal.broadcast + 1,
+          journal.transactions.len(),
+          path.display(),
+        );
+      }
+
+      journal.broadcast += 1;
+
+      journal.save(path)?;
+    }
+
+    journal.transactions.clear();
+
+    journal.save(path)
+  }
+}
+
+impl Journal {
+  fn load(path: &Path) -> Result<Self> {
+    serde_json::from_str(&fs::read_to_string(path)?)
+      .with_context(|| format!("failed to parse {}", path.display()))
+  }
+
+  fn save(&self, path: &Path) -> Result {
+    if let Some(parent) = path.parent() {
+      fs::create_dir_all(parent)?;
+    }
+
+    fs::write(path, serde_json::to_string_pretty(self)?)?;
+
+    Ok(())
+  }
+
+  fn decode(hex: &str) -> Result<Transaction> {
+    Ok(consensus::encode::deserialize(&Vec::from_hex(hex)?)?)
+  }
+
+  /// Returns the last P2SH output of `links`, the transactions of the chain
+  /// by position, that is unspent according to `is_unspent`, along with its
+  /// value, the script locking it and the number of pushes the script drops
+  fn locked_output(
+    &self,
+    links: &[(usize, Transaction)],
+    public_key: &PublicKey,
+    mut is_unspent: impl FnMut(OutPoint) -> Result<bool>,
+  ) -> Result<Option<(OutPoint, u64, Script, usize)>> {
+    for (position, tx) in links.iter().rev() {
+      let Some(pushes) = self.pushes.get(*position).copied() else {
+        continue;
+      };
+
+      let lock = RevealChain::lock_script(public_key, pushes);
+
+      let Some((vout, output)) = tx
+        .output
+        .iter()
+        .enumerate()
+        .find(|(_vout, output)| output.script_pubkey == lock.to_p2sh())
+      else {
+        continue;
+      };
+
+      let outpoint = OutPoint {
+        txid: tx.txid(),
         vout: vout.try_into().unwrap(),
-      },
-      TxOut {
-        script_pubkey: destination.script_pubkey(),
-        value: output.value,
-      },
-      &reveal_script,
-    );
+      };
 
-    reveal_tx.output[0].value = reveal_tx.output[0]
-      .value
-      .checked_sub(fee.to_sat())
-      .context("commit transaction output value insufficient to pay transaction fee")?;
+      if is_unspent(outpoint)? {
+        return Ok(Some((outpoint, output.value, lock, pushes)));
+      }
+    }
+
+    Ok(None)
+  }
+}
+
+impl RevealChain {
+  fn new(
+    inscription: &Inscription,
+    private_key: PrivateKey,
+    destination: Script,
+    fee_rate: FeeRate,
+  ) -> Self {
+    let public_key = private_key.public_key(&Secp256k1::new());
+
+    let partials = inscription.to_p2sh_partials();
+
+    let locks = partials
+      .iter()
+      .map(|partial| Self::lock_script(&public_key, partial.instructions().count()))
+      .collect::<Vec<Script>>();
+
+    let mut reveal_chain = Self {
+      destination,
+      fees: Vec::new(),
+      locks,
+      partials,
+      private_key,
+    };
 
-    if reveal_tx.output[0].value < reveal_tx.output[0].script_pubkey.dust_value().to_sat() {
-      bail!("commit transaction output would be dust");
+    reveal_chain.fees = (0..reveal_chain.partials.len())
+      .map(|i| {
+        let transaction =
+          reveal_chain.build_transaction(i, OutPoint::null(), 0, &[0; SIGNATURE_SIZE]);
+
+        fee_rate.fee(transaction.vsize())
+      })
+      .collect();
+
+    reveal_chain
+  }
+
+  /// Returns the redeem script that checks a signature by `public_key` and
+  /// drops the `pushes` envelope pushes preceding it.
+  fn lock_script(public_key: &PublicKey, pushes: usize) -> Script {
+    let mut builder = script::Builder::new()
+      .push_key(public_key)
+      .push_opcode(opcodes::all::OP_CHECKSIGVERIFY);
+
+    for _ in 0..pushes {
+      builder = builder.push_opcode(opcodes::all::OP_DROP);
     }
 
-    let mut sighash_cache = SighashCache::new(&mut reveal_tx);
+    builder.push_opcode(opcodes::OP_TRUE).into_script()
+  }
 
-    let signature_hash = sighash_cache
-      .taproot_script_spend_signature_hash(
-        0,
-        &Prevouts::All(&[output]),
-        TapLeafHash::from_script(&reveal_script, LeafVersion::TapScript),
-        SchnorrSighashType::Default,
-      )
-      .expect("signature hash should compute");
+  fn unlock_script(fragment: &Script, signature: &[u8], lock: &Script) -> Script {
+    if signature.is_empty() {
+      return Script::new();
+    }
+
+    let mut script_sig = fragment.to_bytes();
 
-    let signature = secp256k1.sign_schnorr(
-      &secp256k1::Message::from_slice(signature_hash.as_inner())
-        .expect("should be cryptographically secure hash"),
-      &key_pair,
+    script_sig.extend_from_slice(
+      script::Builder::new()
+        .push_slice(signature)
+        .push_slice(lock.as_bytes())
+        .into_script()
+        .as_bytes(),
     );
 
-    let witness = sighash_cache
-      .witness_mut(0)
-      .expect("getting mutable witness reference should work");
-    witness.push(signature.as_ref());
-    witness.push(reveal_script);
-    witness.push(&control_block.serialize());
+    Script::from(script_sig)
+  }
 
-    let recovery_key_pair = key_pair.tap_tweak(&secp256k1, taproot_spend_info.merkle_root());
+  fn sign(private_key: &PrivateKey, transaction: &Transaction, lock: &Script) -> Vec<u8> {
+    let signature_hash = transaction.signature_hash(0, lock, EcdsaSighashType::All.to_u32());
 
-    let (x_only_pub_key, _parity) = recovery_key_pair.to_inner().x_only_public_key();
-    assert_eq!(
-      Address::p2tr_tweaked(
-        TweakedPublicKey::dangerous_assume_tweaked(x_only_pub_key),
-        network,
+    EcdsaSig::sighash_all(
+      Secp256k1::new().sign_ecdsa_low_r(
+        &secp256k1::Message::from_slice(&signature_hash[..])
+          .expect("should be cryptographically secure hash"),
+        &private_key.inner,
       ),
-      commit_tx_address
+    )
+    .to_vec()
+  }
+
+  fn fee(&self) -> Amount {
+    self.fees.iter().copied().sum()
+  }
+
+  fn encode(&self) -> Result<Vec<u8>> {
+    Ok(serde_json::to_vec(&EncodedRevealChain {
+      destination: hex::encode(self.destination.as_bytes()),
+      fees: self.fees.iter().map(|fee| fee.to_sat()).collect(),
+      locks: self
+        .locks
+        .iter()
+        .map(|lock| hex::encode(lock.as_bytes()))
+        .collect(),
+      partials: self
+        .partials
+        .iter()
+        .map(|partial| hex::encode(partial.as_bytes()))
+        .collect(),
+    })?)
+  }
+
+  /// Number of envelope pushes dropped by each lock of the chain
+  fn pushes(&self) -> Vec<usize> {
+    self
+      .partials
+      .iter()
+      .map(|partial| partial.instructions().count())
+      .collect()
+  }
+
+  /// Decode a reveal chain saved in a PSBT, taking its key from
+  /// `recovery_key`, which looks up the key of a public key in the wallet
+  fn decode(
+    buffer: &[u8],
+    recovery_key: impl FnOnce(PublicKey) -> Result<Option<PrivateKey>>,
+  ) -> Result<Self> {
+    let encoded = serde_json::from_slice::<EncodedRevealChain>(buffer)
+      .context("failed to parse PSBT reveal chain")?;
+
+    let script = |hex: &str| -> Result<Script> { Ok(Script::from(Vec::from_hex(hex)?)) };
+
+    ensure!(
+      !encoded.locks.is_empty()
+        && encoded.locks.len() == encoded.partials.len()
+        && encoded.locks.len() == encoded.fees.len(),
+      "PSBT reveal chain is malformed",
     );
 
-    let reveal_weight = reveal_tx.weight();
+    let locks = encoded
+      .locks
+      .iter()
+      .map(|lock| script(lock))
+      .collect::<Result<Vec<Script>>>()?;
+
+    let public_key = Inscribe::script_public_key(&locks[0])
+      .context("PSBT reveal chain is not locked by a recovery key")?;
+
+    let private_key = recovery_key(public_key)?
+      .context("recovery key for PSBT reveal chain not found in wallet")?;
+
+    Ok(Self {
+      destination: script(&encoded.destination)?,
+      fees: encoded.fees.into_iter().map(Amount::from_sat).collect(),
+      locks,
+      partials: encoded
+        .partials
+        .iter()
+        .map(|partial| script(partial))
+        .collect::<Result<Vec<Script>>>()?,
+      private_key,
+    })
+  }
+
+  /// Builds and signs the reveal transactions spending the P2SH output of
+  /// `commit`.
+  fn transactions(&self, commit: &Transaction) -> Result<Vec<Transaction>> {
+    let commit_script = self.locks[0].to_p2sh();
 
-    if !no_limit && reveal_weight > MAX_STANDARD_TX_WEIGHT.try_into().unwrap() {
-      bail!(
-        "reveal transaction weight greater than {MAX_STANDARD_TX_WEIGHT} (MAX_STANDARD_TX_WEIGHT): {reveal_weight}"
+    let (vout, output) = commit
+      .output
+      .iter()
+      .enumerate()
+      .find(|(_vout, output)| output.script_pubkey == commit_script)
+      .expect("should find sat commit/inscription output");
+
+    let mut input = OutPoint {
+      txid: commit.txid(),
+      vout: vout.try_into().unwrap(),
+    };
+
+    let mut value = output.value;
+
+    let mut transactions = Vec::new();
+
+    for (i, fee) in self.fees.iter().enumerate() {
+      value = value
+        .checked_sub(fee.to_sat())
+        .context("commit transaction output value insufficient to pay reveal fees")?;
+
+      let signature = Self::sign(
+        &self.private_key,
+        &self.build_transaction(i, input, value, &[]),
+        &self.locks[i],
       );
-    }
 
-    Ok((unsigned_commit_tx, reveal_tx, recovery_key_pair))
-  }
+      let transaction = self.build_transaction(i, input, value, &signature);
 
-  fn backup_recovery_key(
-    client: &Client,
-    recovery_key_pair: TweakedKeyPair,
-    network: Network,
-  ) -> Result {
-    let recovery_private_key = PrivateKey::new(recovery_key_pair.to_inner().secret_key(), network);
-
-    let info = client.get_descriptor_info(&format!("rawtr({})", recovery_private_key.to_wif()))?;
-
-    let response = client.import_descriptors(ImportDescriptors {
-      descriptor: format!("rawtr({})#{}", recovery_private_key.to_wif(), info.checksum),
-      timestamp: Timestamp::Now,
-      active: Some(false),
-      range: None,
-      next_index: None,
-      internal: Some(false),
-      label: Some("commit tx recovery key".to_string()),
-    })?;
+      input = OutPoint {
+        txid: transaction.txid(),
+        vout: 0,
+      };
 
-    for result in response {
-      if !result.success {
-        return Err(anyhow!("commit tx recovery key import failed"));
-      }
+      transactions.push(transaction);
     }
 
-    Ok(())
+    if value < self.destination.dust_value().to_sat() {
+      bail!("reveal transaction output would be dust");
+    }
+
+    Ok(transactions)
   }
 
-  fn build_reveal_transaction(
-    control_block: &ControlBlock,
-    fee_rate: FeeRate,
+  fn build_transaction(
+    &self,
+    i: usize,
     input: OutPoint,
-    output: TxOut,
-    script: &Script,
-  ) -> (Transaction, Amount) {
-    let reveal_tx = Transaction {
+    value: u64,
+    signature: &[u8],
+  ) -> Transaction {
+    let script_pubkey = match self.locks.get(i + 1) {
+      Some(lock) => lock.to_p2sh(),
+      None => self.destination.clone(),
+    };
+
+    Transaction {
       input: vec![TxIn {
         previous_output: input,
-        script_sig: script::Builder::new().into_script(),
+        script_sig: Self::unlock_script(&self.partials[i], signature, &self.locks[i]),
         witness: Witness::new(),
         sequence: Sequence::ENABLE_RBF_NO_LOCKTIME,
       }],
-      output: vec![output],
+      output: vec![TxOut {
+        script_pubkey,
+        value,
+      }],
       lock_time: PackedLockTime::ZERO,
       version: 1,
-    };
+    }
+  }
+}
 
-    let fee = {
-      let mut reveal_tx = reveal_tx.clone();
+#[cfg(test)]
+mod tests {
+  use {
+    super::*, crate::inscription::ParsedInscription, bitcoin::blockdata::constants::COIN_VALUE,
+  };
 
-      reveal_tx.input[0].witness.push(
-        Signature::from_slice(&[0; SCHNORR_SIGNATURE_SIZE])
-          .unwrap()
-          .as_ref(),
-      );
-      reveal_tx.input[0].witness.push(script);
-      reveal_tx.input[0].witness.push(&control_block.serialize());
+  fn create(
+    utxos: Vec<(OutPoint, Amount)>,
+    inscriptions: BTreeMap<SatPoint, InscriptionId>,
+    satpoint: Option<SatPoint>,
+    inscription: Inscription,
+    commit_fee_rate: f64,
+    reveal_fee_rate: f64,
+    no_limit: bool,
+  ) -> Result<(Transaction, Vec<Transaction>)> {
+    let (commit_tx, reveal_chain) = Inscribe::create_inscription_transactions(
+      satpoint,
+      inscription,
+      inscriptions,
+      Network::Bitcoin,
+      utxos.into_iter().collect(),
+      BTreeSet::new(),
+      [change(0), change(1)],
+      recipient(),
+      FeeRate::try_from(commit_fee_rate).unwrap(),
+      FeeRate::try_from(reveal_fee_rate).unwrap(),
+      no_limit,
+    )?;
 
-      fee_rate.fee(reveal_tx.vsize())
-    };
+    let reveal_txs = reveal_chain.transactions(&commit_tx)?;
 
-    (reveal_tx, fee)
+    Ok((commit_tx, reveal_txs))
   }
-}
 
-#[cfg(test)]
-mod tests {
-  use bitcoin::blockdata::constants::COIN_VALUE;
-  use super::*;
+  fn reveal_fee(commit_tx: &Transaction, reveal_txs: &[Transaction]) -> u64 {
+    commit_tx
+      .output
+      .iter()
+      .find(|output| output.script_pubkey.is_p2sh())
+      .unwrap()
+      .value
+      - reveal_txs.last().unwrap().output[0].value
+  }
 
   #[test]
   fn reveal_transaction_pays_fee() {
-    let utxos = vec![(outpoint(1), Amount::from_sat(20000))];
-    let inscription = inscription("text/plain", "ord");
-    let commit_address = change(0);
-    let reveal_address = recipient();
-
-    let (commit_tx, reveal_tx, _private_key) = Inscribe::create_inscription_transactions(
+    let (commit_tx, reveal_txs) = create(
+      vec![(outpoint(1), Amount::from_sat(20000))],
+      BTreeMap::new(),
       Some(satpoint(1, 0)),
-      inscription,
+      inscription("text/plain", "ord"),
+      1.0,
+      1.0,
+      false,
+    )
+    .unwrap();
+
+    assert_eq!(reveal_txs.len(), 1);
+
+    let vsize = u64::try_from(reveal_txs[0].vsize()).unwrap();
+
+    let fee = reveal_fee(&commit_tx, &reveal_txs);
+
+    assert!(fee >= vsize && fee <= vsize + 2, "{fee} {vsize}");
+
+    assert_eq!(
+      reveal_txs[0].output[0].value,
+      TransactionBuilder::TARGET_POSTAGE.to_sat()
+    );
+  }
+
+  #[test]
+  fn reveal_transactions_spend_p2sh_chain() {
+    let inscription = inscription("text/plain", [7; 5000]);
+
+    let (commit_tx, reveal_txs) = create(
+      vec![(outpoint(1), Amount::from_sat(50 * COIN_VALUE))],
+      BTreeMap::new(),
+      None,
+      inscription.clone(),
+      1.0,
+      1.0,
+      false,
+    )
+    .unwrap();
+
+    assert!(reveal_txs.len() > 1);
+
+    assert!(commit_tx.output[0].script_pubkey.is_p2sh());
+
+    let mut previous = commit_tx.txid();
+
+    for reveal_tx in &reveal_txs {
+      assert_eq!(reveal_tx.input.len(), 1);
+      assert_eq!(reveal_tx.input[0].previous_output.txid, previous);
+      previous = reveal_tx.txid();
+    }
+
+    for reveal_tx in &reveal_txs[..reveal_txs.len() - 1] {
+      assert!(reveal_tx.output[0].script_pubkey.is_p2sh());
+    }
+
+    assert_eq!(
+      reveal_txs.last().unwrap().output[0].script_pubkey,
+      recipient().script_pubkey()
+    );
+
+    assert_eq!(
+      Inscription::from_transactions(reveal_txs[..reveal_txs.len() - 1].to_vec()),
+      ParsedInscription::Partial,
+    );
+
+    assert_eq!(
+      Inscription::from_transactions(reveal_txs),
+      ParsedInscription::Complete(inscription),
+    );
+  }
+
+  #[test]
+  fn reveal_chain_survives_psbt_encoding() {
+    let (commit_tx, reveal_chain) = Inscribe::create_inscription_transactions(
+      None,
+      inscription("text/plain", [7; 5000]),
       BTreeMap::new(),
       Network::Bitcoin,
-      utxos.into_iter().collect(),
-      [commit_address, change(1)],
-      reveal_address,
+      [(outpoint(1), Amount::from_sat(50 * COIN_VALUE))]
+        .into_iter()
+        .collect(),
+      BTreeSet::new(),
+      [change(0), change(1)],
+      recipient(),
       FeeRate::try_from(1.0).unwrap(),
       FeeRate::try_from(1.0).unwrap(),
       false,
     )
     .unwrap();
 
-    #[allow(clippy::cast_possible_truncation)]
-    #[allow(clippy::cast_sign_loss)]
-    let fee = Amount::from_sat((1.0 * (reveal_tx.vsize() as f64)).ceil() as u64);
+    let encoded = reveal_chain.encode().unwrap();
+
+    assert!(!String::from_utf8(encoded.clone())
+      .unwrap()
+      .contains(&reveal_chain.private_key.to_wif()));
+
+    assert_eq!(
+      RevealChain::decode(&encoded, |_| Ok(None))
+        .err()
+        .unwrap()
+        .to_string(),
+      "recovery key for PSBT reveal chain not found in wallet",
+    );
+
+    let decoded = RevealChain::decode(&encoded, |public_key| {
+      Ok(
+        Some(reveal_chain.private_key)
+          .filter(|private_key| private_key.public_key(&Secp256k1::new()) == public_key),
+      )
+    })
+    .unwrap();
+
+    assert_eq!(decoded.fee(), reveal_chain.fee());
 
     assert_eq!(
-      reveal_tx.output[0].value,
-      20000 - fee.to_sat() - (20000 - commit_tx.output[0].value),
+      decoded.transactions(&commit_tx).unwrap(),
+      reveal_chain.transactions(&commit_tx).unwrap(),
     );
   }
 
   #[test]
-  fn inscript_tansactions_opt_in_to_rbf() {
-    let utxos = vec![(outpoint(1), Amount::from_sat(20000))];
-    let inscription = inscription("text/plain", "ord");
-    let commit_address = change(0);
-    let reveal_address = recipient();
-
-    let (commit_tx, reveal_tx, _) = Inscribe::create_inscription_transactions(
-      Some(satpoint(1, 0)),
-      inscription,
+  fn journal_locked_output_is_last_unspent_lock() {
+    let (commit_tx, reveal_chain) = Inscribe::create_inscription_transactions(
+      None,
+      inscription("text/plain", [7; 10_000]),
       BTreeMap::new(),
       Network::Bitcoin,
-      utxos.into_iter().collect(),
-      [commit_address, change(1)],
-      reveal_address,
+      [(outpoint(1), Amount::from_sat(50 * COIN_VALUE))]
+        .into_iter()
+        .collect(),
+      BTreeSet::new(),
+      [change(0), change(1)],
+      recipient(),
       FeeRate::try_from(1.0).unwrap(),
       FeeRate::try_from(1.0).unwrap(),
       false,
     )
     .unwrap();
 
+    let reveal_txs = reveal_chain.transactions(&commit_tx).unwrap();
+
+    assert!(reveal_txs.len() > 2);
+
+    let journal = Journal {
+      broadcast: reveal_txs.len() + 1,
+      fees: 0,
+      pushes: reveal_chain.pushes(),
+      transactions: Vec::new(),
+    };
+
+    let links = iter::once(commit_tx)
+      .chain(reveal_txs.clone())
+      .enumerate()
+      .collect::<Vec<(usize, Transaction)>>();
+
+    let public_key = Inscribe::lock_public_key(&reveal_txs[0]).unwrap();
+
+    let spent = reveal_txs[1..]
+      .iter()
+      .map(|tx| tx.input[0].previous_output)
+      .collect::<BTreeSet<OutPoint>>();
+
+    let (outpoint, value, lock, pushes) = journal
+      .locked_output(&links, &public_key, |outpoint| {
+        Ok(!spent.contains(&outpoint))
+      })
+      .unwrap()
+      .unwrap();
+
+    let last = &reveal_txs[reveal_txs.len() - 2];
+
+    assert_eq!(
+      outpoint,
+      OutPoint {
+        txid: last.txid(),
+        vout: 0,
+      }
+    );
+    assert_eq!(value, last.output[0].value);
+    assert_eq!(lock.to_p2sh(), last.output[0].script_pubkey);
+    assert_eq!(pushes, journal.pushes[reveal_txs.len() - 1]);
+
+    let (outpoint, _, lock, _) = journal
+      .locked_output(&links, &public_key, |outpoint| {
+        Ok(outpoint.txid == reveal_txs[0].txid())
+      })
+      .unwrap()
+      .unwrap();
+
+    assert_eq!(outpoint, reveal_txs[1].input[0].previous_output);
+    assert_eq!(lock.to_p2sh(), reveal_txs[0].output[0].script_pubkey);
+
+    assert_eq!(
+      journal
+        .locked_output(&links, &public_key, |_| Ok(false))
+        .unwrap(),
+      None,
+    );
+  }
+
+  #[test]
+  fn inscript_tansactions_opt_in_to_rbf() {
+    let (commit_tx, reveal_txs) = create(
+      vec![(outpoint(1), Amount::from_sat(20000))],
+      BTreeMap::new(),
+      Some(satpoint(1, 0)),
+      inscription("text/plain", "ord"),
+      1.0,
+      1.0,
+      false,
+    )
+    .unwrap();
+
     assert!(commit_tx.is_explicitly_rbf());
-    assert!(reveal_tx.is_explicitly_rbf());
+    assert!(reveal_txs.iter().all(Transaction::is_explicitly_rbf));
   }
 
   #[test]
   fn inscribe_with_no_satpoint_and_no_cardinal_utxos() {
-    let utxos = vec![(outpoint(1), Amount::from_sat(1000))];
     let mut inscriptions = BTreeMap::new();
     inscriptions.insert(
       SatPoint {
@@ -451,21 +1137,13 @@ mod tests {
       inscription_id(1),
     );
 
-    let inscription = inscription("text/plain", "ord");
-    let satpoint = None;
-    let commit_address = change(0);
-    let reveal_address = recipient();
-
-    let error = Inscribe::create_inscription_transactions(
-      satpoint,
-      inscription,
+    let error = create(
+      vec![(outpoint(1), Amount::from_sat(1000))],
       inscriptions,
-      Network::Bitcoin,
-      utxos.into_iter().collect(),
-      [commit_address, change(1)],
-      reveal_address,
-      FeeRate::try_from(1.0).unwrap(),
-      FeeRate::try_from(1.0).unwrap(),
+      None,
+      inscription("text/plain", "ord"),
+      1.0,
+      1.0,
       false,
     )
     .unwrap_err()
@@ -480,10 +1158,6 @@ mod tests {
 
   #[test]
   fn inscribe_with_no_satpoint_and_enough_cardinal_utxos() {
-    let utxos = vec![
-      (outpoint(1), Amount::from_sat(20_000)),
-      (outpoint(2), Amount::from_sat(20_000)),
-    ];
     let mut inscriptions = BTreeMap::new();
     inscriptions.insert(
       SatPoint {
@@ -493,21 +1167,16 @@ mod tests {
       inscription_id(1),
     );
 
-    let inscription = inscription("text/plain", "ord");
-    let satpoint = None;
-    let commit_address = change(0);
-    let reveal_address = recipient();
-
-    assert!(Inscribe::create_inscription_transactions(
-      satpoint,
-      inscription,
+    assert!(create(
+      vec![
+        (outpoint(1), Amount::from_sat(20_000)),
+        (outpoint(2), Amount::from_sat(20_000)),
+      ],
       inscriptions,
-      Network::Bitcoin,
-      utxos.into_iter().collect(),
-      [commit_address, change(1)],
-      reveal_address,
-      FeeRate::try_from(1.0).unwrap(),
-      FeeRate::try_from(1.0).unwrap(),
+      None,
+      inscription("text/plain", "ord"),
+      1.0,
+      1.0,
       false,
     )
     .is_ok())
@@ -515,181 +1184,63 @@ mod tests {
 
   #[test]
   fn inscribe_with_custom_fee_rate() {
-    let utxos = vec![
-      (outpoint(1), Amount::from_sat(10_000)),
-      (outpoint(2), Amount::from_sat(20_000)),
-    ];
-    let mut inscriptions = BTreeMap::new();
-    inscriptions.insert(
-      SatPoint {
-        outpoint: outpoint(1),
-        offset: 0,
-      },
-      inscription_id(1),
-    );
+    let fee_rate = FeeRate::try_from(3.3).unwrap();
 
-    let inscription = inscription("text/plain", "ord");
-    let satpoint = None;
-    let commit_address = change(0);
-    let reveal_address = recipient();
-    let fee_rate = 3.3;
-
-    let (commit_tx, reveal_tx, _private_key) = Inscribe::create_inscription_transactions(
-      satpoint,
-      inscription,
-      inscriptions,
-      bitcoin::Network::Signet,
-      utxos.into_iter().collect(),
-      [commit_address, change(1)],
-      reveal_address,
-      FeeRate::try_from(fee_rate).unwrap(),
-      FeeRate::try_from(fee_rate).unwrap(),
-      false,
-    )
-    .unwrap();
-
-    let sig_vbytes = 17;
-    let fee = FeeRate::try_from(fee_rate)
-      .unwrap()
-      .fee(commit_tx.vsize() + sig_vbytes)
-      .to_sat();
-
-    let reveal_value = commit_tx
-      .output
-      .iter()
-      .map(|o| o.value)
-      .reduce(|acc, i| acc + i)
-      .unwrap();
-
-    assert_eq!(reveal_value, 20_000 - fee);
-
-    let fee = FeeRate::try_from(fee_rate)
-      .unwrap()
-      .fee(reveal_tx.vsize())
-      .to_sat();
-
-    assert_eq!(
-      reveal_tx.output[0].value,
-      20_000 - fee - (20_000 - commit_tx.output[0].value),
-    );
-  }
-
-  #[test]
-  fn inscribe_with_commit_fee_rate() {
-    let utxos = vec![
-      (outpoint(1), Amount::from_sat(10_000)),
-      (outpoint(2), Amount::from_sat(20_000)),
-    ];
-    let mut inscriptions = BTreeMap::new();
-    inscriptions.insert(
-      SatPoint {
-        outpoint: outpoint(1),
-        offset: 0,
-      },
-      inscription_id(1),
-    );
-
-    let inscription = inscription("text/plain", "ord");
-    let satpoint = None;
-    let commit_address = change(0);
-    let reveal_address = recipient();
-    let commit_fee_rate = 3.3;
-    let fee_rate = 1.0;
-
-    let (commit_tx, reveal_tx, _private_key) = Inscribe::create_inscription_transactions(
-      satpoint,
-      inscription,
-      inscriptions,
-      bitcoin::Network::Signet,
-      utxos.into_iter().collect(),
-      [commit_address, change(1)],
-      reveal_address,
-      FeeRate::try_from(commit_fee_rate).unwrap(),
-      FeeRate::try_from(fee_rate).unwrap(),
+    let (commit_tx, reveal_txs) = create(
+      vec![(outpoint(1), Amount::from_sat(20_000))],
+      BTreeMap::new(),
+      None,
+      inscription("text/plain", "ord"),
+      3.3,
+      3.3,
       false,
     )
     .unwrap();
 
-    let sig_vbytes = 17;
-    let fee = FeeRate::try_from(commit_fee_rate)
-      .unwrap()
-      .fee(commit_tx.vsize() + sig_vbytes)
-      .to_sat();
+    let vsize = reveal_txs[0].vsize();
 
-    let reveal_value = commit_tx
-      .output
-      .iter()
-      .map(|o| o.value)
-      .reduce(|acc, i| acc + i)
-      .unwrap();
-
-    assert_eq!(reveal_value, 20_000 - fee);
-
-    let fee = FeeRate::try_from(fee_rate)
-      .unwrap()
-      .fee(reveal_tx.vsize())
-      .to_sat();
+    let fee = reveal_fee(&commit_tx, &reveal_txs);
 
-    assert_eq!(
-      reveal_tx.output[0].value,
-      20_000 - fee - (20_000 - commit_tx.output[0].value),
-    );
+    assert!(fee >= fee_rate.fee(vsize).to_sat());
+    assert!(fee <= fee_rate.fee(vsize + 2).to_sat());
   }
 
   #[test]
-  fn inscribe_over_max_standard_tx_weight() {
-    let utxos = vec![(outpoint(1), Amount::from_sat(50 * COIN_VALUE))];
-
-    let inscription = inscription("text/plain", [0; MAX_STANDARD_TX_WEIGHT as usize]);
-    let satpoint = None;
-    let commit_address = change(0);
-    let reveal_address = recipient();
-
-    let error = Inscribe::create_inscription_transactions(
-      satpoint,
-      inscription,
+  fn inscribe_over_mempool_chain_limit() {
+    let error = create(
+      vec![(outpoint(1), Amount::from_sat(50 * COIN_VALUE))],
       BTreeMap::new(),
-      Network::Bitcoin,
-      utxos.into_iter().collect(),
-      [commit_address, change(1)],
-      reveal_address,
-      FeeRate::try_from(1.0).unwrap(),
-      FeeRate::try_from(1.0).unwrap(),
+      None,
+      inscription("text/plain", [0; 50_000]),
+      1.0,
+      1.0,
       false,
     )
     .unwrap_err()
     .to_string();
 
     assert!(
-      error.contains(&format!("reveal transaction weight greater than {MAX_STANDARD_TX_WEIGHT} (MAX_STANDARD_TX_WEIGHT): 402799")),
+      error.contains(
+        "more than the 25 unconfirmed transactions Dogecoin Core relays in a chain by default"
+      ),
       "{}",
       error
     );
   }
 
   #[test]
-  fn inscribe_with_no_max_standard_tx_weight() {
-    let utxos = vec![(outpoint(1), Amount::from_sat(50 * COIN_VALUE))];
-
-    let inscription = inscription("text/plain", [0; MAX_STANDARD_TX_WEIGHT as usize]);
-    let satpoint = None;
-    let commit_address = change(0);
-    let reveal_address = recipient();
-
-    let (_commit_tx, reveal_tx, _private_key) = Inscribe::create_inscription_transactions(
-      satpoint,
-      inscription,
+  fn inscribe_with_no_limit() {
+    let (_commit_tx, reveal_txs) = create(
+      vec![(outpoint(1), Amount::from_sat(50 * COIN_VALUE))],
       BTreeMap::new(),
-      Network::Bitcoin,
-      utxos.into_iter().collect(),
-      [commit_address, change(1)],
-      reveal_address,
-      FeeRate::try_from(1.0).unwrap(),
-      FeeRate::try_from(1.0).unwrap(),
+      None,
+      inscription("text/plain", [0; 50_000]),
+      1.0,
+      1.0,
       true,
     )
     .unwrap();
 
-    assert!(reveal_tx.size() >= MAX_STANDARD_TX_WEIGHT as usize);
+    assert!(reveal_txs.len() >= MEMPOOL_CHAIN_LIMIT);
   }
 }
@@ -1,5 +1,26 @@
-use {super::*, crate::wallet::Wallet};
 use crate::sat_point::SatPoint;
+use {super::inscribe::Inscribe, super::*, crate::wallet::Wallet};
+
+#[derive(Debug, Parser)]
+pub(crate) struct Inscriptions {
+  #[command(subcommand)]
+  subcommand: Option<InscriptionsSubcommand>,
+}
+
+#[derive(Debug, Parser)]
+enum InscriptionsSubcommand {
+  #[command(about = "List partially revealed wallet inscriptions")]
+  Pending,
+}
+
+impl Inscriptions {
+  pub(crate) fn run(self, options: Options) -> SubcommandResult {
+    match self.subcommand {
+      Some(InscriptionsSubcommand::Pending) => pending(options),
+      None => run(options),
+    }
+  }
+}
 
 #[derive(Serialize, Deserialize)]
 pub struct Output {
@@ -8,12 +29,21 @@ pub struct Output {
   pub explorer: String,
 }
 
-pub(crate) fn run(options: Options) -> SubcommandResult {
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+pub struct PendingOutput {
+  pub inscription: InscriptionId,
+  pub transaction: Txid,
+  pub pieces_remaining: u64,
+  pub locked: u64,
+  pub resumable: bool,
+}
+
+fn run(options: Options) -> SubcommandResult {
   let index = Index::open(&options)?;
   index.update()?;
 
   let inscriptions = index.get_inscriptions(None)?;
-  let unspent_outputs = index.get_unspent_outputs(Wallet::load(&options)?)?;
+  let unspent_outputs = index.get_unspent_outputs(&Wallet::load(&options)?)?;
 
   let explorer = match options.chain() {
     Chain::Mainnet => "https://ordinals.com/shibescription/",
@@ -36,3 +66,49 @@ pub(crate) fn run(options: Options) -> SubcommandResult {
 
   Ok(Box::new(output))
 }
+
+fn pending(options: Options) -> SubcommandResult {
+  let index = Index::open(&options)?;
+  index.update()?;
+
+  let client = options.dogecoin_rpc_client_for_wallet_command(false)?;
+
+  let wallet = Wallet::load(&options)?;
+
+  let mut output = Vec::new();
+
+  for txs in index.get_partial_inscriptions()? {
+    let Some(last) = txs.last() else {
+      continue;
+    };
+
+    let inscription = InscriptionId::from(txs[0].txid());
+
+    let resumable = Inscribe::is_resumable(&options, inscription)?;
+
+    let owned = match Inscribe::lock_public_key(last) {
+      Some(public_key) => wallet
+        .recovery_key(&client, options.chain().network(), public_key)?
+        .is_some(),
+      None => false,
+    };
+
+    if !owned && !resumable {
+      continue;
+    }
+
+    output.push(PendingOutput {
+      inscription,
+      transaction: last.txid(),
+      pieces_remaining: Inscription::remaining_pieces(&txs).unwrap_or_default(),
+      locked: last
+        .output
+        .first()
+        .map(|output| output.value)
+        .unwrap_or_default(),
+      resumable,
+    });
+  }
+
+  Ok(Box::new(output))
+}
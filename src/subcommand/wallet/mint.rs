@@ -0,0 +1,143 @@
+use {super::*, bitcoin::PackedLockTime, crate::dunes::CLAIM_BIT};
+
+#[derive(Debug, Parser)]
+pub(crate) struct Mint {
+  #[clap(long, help = "Mint <DUNE>. May contain `.` or `•`as spacers.")]
+  dune: SpacedDune,
+  #[clap(long, help = "Mint with fee rate of <FEE_RATE> sats/vB.")]
+  fee_rate: FeeRate,
+  #[clap(long, help = "Send minted dunes to <DESTINATION>.")]
+  destination: Option<Address>,
+  #[clap(
+    long,
+    default_value = "1",
+    help = "Broadcast <COUNT> mint transactions."
+  )]
+  count: u32,
+  #[clap(
+    long,
+    help = "Write the unsigned mint to <PSBT_OUT> as a PSBT instead of signing and broadcasting it. Requires a <COUNT> of one."
+  )]
+  psbt_out: Option<PathBuf>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct Output {
+  pub dune: SpacedDune,
+  pub pile: Pile,
+  pub transactions: Vec<Txid>,
+}
+
+impl Mint {
+  pub(crate) fn run(self, options: Options) -> SubcommandResult {
+    let index = Index::open(&options)?;
+
+    ensure!(
+      index.has_dune_index(),
+      "`ord wallet mint` requires index created with `--index-dunes` flag",
+    );
+
+    ensure!(self.count > 0, "<COUNT> must be greater than zero");
+
+    ensure!(
+      self.psbt_out.is_none() || self.count == 1,
+      "`--psbt-out` can only write one mint, but <COUNT> is {}",
+      self.count,
+    );
+
+    index.update()?;
+
+    let client = options.dogecoin_rpc_client_for_wallet_command(false)?;
+
+    let (id, entry) = index
+      .dune(self.dune.dune)?
+      .with_context(|| format!("dune `{}` has not been etched", self.dune))?;
+
+    let height = client.get_block_count()? + 1;
+
+    let limit = entry
+      .mintable(height)
+      .map_err(|err| anyhow!("dune `{}` cannot be minted in block {height}: {err}", self.dune))?;
+
+    if let Some(cap) = entry.terms.and_then(|terms| terms.cap) {
+      let remaining = cap.saturating_sub(entry.mints);
+      ensure!(
+        u128::from(self.count) <= remaining,
+        "dune `{}` only has {remaining} mints remaining",
+        self.dune,
+      );
+    }
+
+    let mut wallet = crate::wallet::Wallet::load(&options)?;
+
+    let destination = match self.destination {
+      Some(destination) => destination,
+      None => wallet.get_change_address(&client)?,
+    };
+
+    let dunestone = Dunestone {
+      edicts: vec![Edict {
+        id: u128::from(id) | CLAIM_BIT,
+        amount: limit,
+        output: 1,
+      }],
+      ..Default::default()
+    };
+
+    let script_pubkey = dunestone.encipher();
+
+    let max_op_return_size = index.protocol().max_op_return_size;
+
+    ensure!(
+      script_pubkey.len() <= max_op_return_size,
+      "dunestone greater than maximum OP_RETURN size: {} > {max_op_return_size}",
+      script_pubkey.len()
+    );
+
+    let unspent_outputs = index.get_unspent_outputs(&wallet)?;
+
+    wallet.lock_non_cardinal_outputs(&index, &client, &unspent_outputs)?;
+
+    let mut transactions = Vec::new();
+
+    for _ in 0..self.count {
+      let unfunded_transaction = Transaction {
+        version: 1,
+        lock_time: PackedLockTime::ZERO,
+        input: Vec::new(),
+        output: vec![
+          TxOut {
+            script_pubkey: script_pubkey.clone(),
+            value: 0,
+          },
+          TxOut {
+            script_pubkey: destination.script_pubkey(),
+            value: TARGET_POSTAGE.to_sat(),
+          },
+        ],
+      };
+
+      let unsigned_transaction =
+        wallet.fund_transaction(&index, &client, self.fee_rate, &unfunded_transaction)?;
+
+      if let Some(path) = &self.psbt_out {
+        return Ok(Box::new(psbt::export(
+          &index,
+          &consensus::encode::deserialize(&unsigned_transaction)?,
+          BTreeMap::new(),
+          path,
+        )?));
+      }
+
+      let signed_transaction = wallet.sign_transaction(&index, &client, &unsigned_transaction)?;
+
+      transactions.push(client.send_raw_transaction(&signed_transaction)?);
+    }
+
+    Ok(Box::new(Output {
+      dune: self.dune,
+      pile: entry.pile(limit.saturating_mul(self.count.into())),
+      transactions,
+    }))
+  }
+}
@@ -0,0 +1,256 @@
+use {
+  super::*,
+  crate::index::List,
+  bitcoin::util::psbt::{raw::ProprietaryKey, PartiallySignedTransaction},
+};
+
+/// Prefix of ord's proprietary PSBT keys
+const PREFIX: &[u8] = b"ord";
+
+/// Input key whose value is the input's sat ranges, as pairs of little endian
+/// `u64` start and end
+pub(super) const SAT_RANGES: u8 = 0;
+
+/// Input key whose value is the input's dune balances, as varint encoded pairs
+/// of dune ID and amount
+pub(super) const DUNE_BALANCES: u8 = 1;
+
+/// Global key whose value is the reveal chain of an inscription, which is
+/// built and signed once the commit transaction has been signed
+pub(super) const REVEAL_CHAIN: u8 = 2;
+
+#[derive(Serialize, Deserialize)]
+pub struct Output {
+  pub psbt: PathBuf,
+}
+
+pub(super) fn key(subtype: u8) -> ProprietaryKey {
+  ProprietaryKey {
+    prefix: PREFIX.to_vec(),
+    subtype,
+    key: Vec::new(),
+  }
+}
+
+/// Write `unsigned_transaction` to `path` as a base64 encoded PSBT, along with
+/// each input's previous transaction, sat ranges and dune balances, and the
+/// global `proprietary` values
+pub(super) fn export(
+  index: &Index,
+  unsigned_transaction: &Transaction,
+  proprietary: BTreeMap<u8, Vec<u8>>,
+  path: &Path,
+) -> Result<Output> {
+  let mut psbt = PartiallySignedTransaction::from_unsigned_tx(unsigned_transaction.clone())?;
+
+  for (input, psbt_input) in unsigned_transaction.input.iter().zip(&mut psbt.inputs) {
+    let outpoint = input.previous_output;
+
+    psbt_input.non_witness_utxo = Some(
+      index
+        .get_transaction(outpoint.txid)?
+        .with_context(|| format!("transaction {} not found", outpoint.txid))?,
+    );
+
+    if let Some(List::Unspent(ranges)) = index.list(outpoint)? {
+      psbt_input.proprietary.insert(
+        key(SAT_RANGES),
+        ranges
+          .iter()
+          .flat_map(|(start, end)| start.to_le_bytes().into_iter().chain(end.to_le_bytes()))
+          .collect(),
+      );
+    }
+
+    if let Some(balances) = index.get_encoded_dune_balances(outpoint)? {
+      psbt_input.proprietary.insert(key(DUNE_BALANCES), balances);
+    }
+  }
+
+  for (subtype, value) in proprietary {
+    psbt.proprietary.insert(key(subtype), value);
+  }
+
+  fs::write(path, base64::encode(consensus::encode::serialize(&psbt)))
+    .with_context(|| format!("failed to write PSBT to `{}`", path.display()))?;
+
+  Ok(Output { psbt: path.into() })
+}
+
+pub(super) fn load(path: &Path) -> Result<PartiallySignedTransaction> {
+  let psbt = fs::read_to_string(path)
+    .with_context(|| format!("failed to read PSBT from `{}`", path.display()))?;
+
+  Ok(consensus::encode::deserialize(&base64::decode(
+    psbt.trim(),
+  )?)?)
+}
+
+/// Fee paid by the PSBT's transaction, calculated from its inputs' previous
+/// transactions
+pub(super) fn fee(psbt: &PartiallySignedTransaction) -> Result<u64> {
+  let mut input_value = 0;
+
+  for (input, psbt_input) in psbt.unsigned_tx.input.iter().zip(&psbt.inputs) {
+    let previous = psbt_input.non_witness_utxo.as_ref().with_context(|| {
+      format!(
+        "PSBT input {} is missing its previous transaction",
+        input.previous_output
+      )
+    })?;
+
+    input_value += previous
+      .output
+      .get(usize::try_from(input.previous_output.vout).unwrap())
+      .with_context(|| format!("PSBT input {} does not exist", input.previous_output))?
+      .value;
+  }
+
+  input_value
+    .checked_sub(
+      psbt
+        .unsigned_tx
+        .output
+        .iter()
+        .map(|output| output.value)
+        .sum::<u64>(),
+    )
+    .context("PSBT outputs exceed its inputs")
+}
+
+/// Build the final script signature of each input, from its final script
+/// signature if the signer finalized it, or from its signature if it spends a
+/// P2PKH output and has exactly one. Returns `None` if no input is signed, so
+/// that the wallet can sign the transaction instead.
+pub(super) fn finalize(psbt: &PartiallySignedTransaction) -> Result<Option<Transaction>> {
+  let mut transaction = psbt.unsigned_tx.clone();
+
+  let mut finalized = 0;
+
+  for (i, psbt_input) in psbt.inputs.iter().enumerate() {
+    let script_sig = if let Some(script_sig) = &psbt_input.final_script_sig {
+      script_sig.clone()
+    } else if let Some((public_key, signature)) = psbt_input.partial_sigs.iter().next() {
+      let outpoint = transaction.input[i].previous_output;
+
+      let script_pubkey = psbt_input
+        .non_witness_utxo
+        .as_ref()
+        .and_then(|previous| previous.output.get(usize::try_from(outpoint.vout).unwrap()))
+        .map(|output| &output.script_pubkey);
+
+      ensure!(
+        psbt_input.partial_sigs.len() == 1
+          && script_pubkey == Some(&Script::new_p2pkh(&public_key.pubkey_hash())),
+        "cannot finalize PSBT input {outpoint}: only P2PKH inputs with one signature are supported, finalize it with its signer instead",
+      );
+
+      script::Builder::new()
+        .push_slice(&signature.to_vec())
+        .push_key(public_key)
+        .into_script()
+    } else {
+      continue;
+    };
+
+    transaction.input[i].script_sig = script_sig;
+
+    finalized += 1;
+  }
+
+  match finalized {
+    0 => Ok(None),
+    n if n == transaction.input.len() => Ok(Some(transaction)),
+    n => bail!(
+      "PSBT has {n} of {} inputs signed, all inputs must be signed",
+      transaction.input.len()
+    ),
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use {
+    super::*,
+    bitcoin::{
+      secp256k1::{self, SecretKey},
+      EcdsaSig, PackedLockTime, PrivateKey,
+    },
+  };
+
+  fn transaction() -> Transaction {
+    Transaction {
+      version: 1,
+      lock_time: PackedLockTime::ZERO,
+      input: vec![TxIn {
+        previous_output: OutPoint {
+          txid: Txid::all_zeros(),
+          vout: 0,
+        },
+        script_sig: Script::new(),
+        sequence: Sequence::MAX,
+        witness: Witness::new(),
+      }],
+      output: vec![TxOut {
+        script_pubkey: Script::new(),
+        value: 1000,
+      }],
+    }
+  }
+
+  #[test]
+  fn unsigned_psbts_are_not_finalized() {
+    let psbt = PartiallySignedTransaction::from_unsigned_tx(transaction()).unwrap();
+    assert_eq!(finalize(&psbt).unwrap(), None);
+  }
+
+  #[test]
+  fn p2pkh_signatures_are_finalized() {
+    let secp = Secp256k1::new();
+
+    let private_key = PrivateKey::new(SecretKey::from_slice(&[1; 32]).unwrap(), Network::Bitcoin);
+
+    let public_key = private_key.public_key(&secp);
+
+    let previous = Transaction {
+      input: Vec::new(),
+      output: vec![TxOut {
+        script_pubkey: Script::new_p2pkh(&public_key.pubkey_hash()),
+        value: 2000,
+      }],
+      ..transaction()
+    };
+
+    let mut unsigned = transaction();
+    unsigned.input[0].previous_output.txid = previous.txid();
+
+    let mut psbt = PartiallySignedTransaction::from_unsigned_tx(unsigned).unwrap();
+
+    psbt.inputs[0].non_witness_utxo = Some(previous);
+
+    assert_eq!(fee(&psbt).unwrap(), 1000);
+
+    let signature = EcdsaSig::sighash_all(secp.sign_ecdsa_low_r(
+      &secp256k1::Message::from_slice(&[1; 32]).unwrap(),
+      &private_key.inner,
+    ));
+
+    psbt.inputs[0].partial_sigs.insert(public_key, signature);
+
+    let finalized = finalize(&psbt).unwrap().unwrap();
+
+    assert_eq!(
+      finalized.input[0].script_sig,
+      script::Builder::new()
+        .push_slice(&signature.to_vec())
+        .push_key(&public_key)
+        .into_script()
+    );
+  }
+
+  #[test]
+  fn proprietary_keys_are_namespaced() {
+    assert_eq!(key(REVEAL_CHAIN).prefix, b"ord");
+    assert_eq!(key(REVEAL_CHAIN).subtype, 2);
+  }
+}
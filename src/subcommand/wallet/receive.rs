@@ -6,9 +6,10 @@ pub struct Output {
 }
 
 pub(crate) fn run(options: Options) -> SubcommandResult {
-  let address = options
-    .dogecoin_rpc_client_for_wallet_command(false)?
-    .get_new_address(None, Some(bitcoincore_rpc::json::AddressType::Bech32m))?;
+  let mut wallet = crate::wallet::Wallet::load(&options)?;
+
+  let address =
+    wallet.get_receive_address(&options.dogecoin_rpc_client_for_wallet_command(false)?)?;
 
   Ok(Box::new(Output {address}))
 }
@@ -10,11 +10,23 @@ pub(crate) struct Restore {
     help = "Use <PASSPHRASE> when deriving wallet"
   )]
   pub(crate) passphrase: String,
+  #[clap(
+    long,
+    help = "Keep wallet keys in ord's data directory and sign locally instead of in Dogecoin Core."
+  )]
+  pub(crate) native: bool,
 }
 
 impl Restore {
   pub(crate) fn run(self, options: Options) -> SubcommandResult {
-    initialize_wallet(&options, self.mnemonic.to_seed(self.passphrase))?;
+    let seed = self.mnemonic.to_seed(self.passphrase);
+
+    if self.native {
+      crate::wallet::Wallet::create_native(&options, seed)?;
+    } else {
+      initialize_wallet(&options, seed)?;
+    }
+
     Ok(Box::new(Empty {}))
   }
 }
@@ -1,5 +1,5 @@
 use bitcoin::PackedLockTime;
-use {super::*, crate::wallet::Wallet};
+use {super::*, crate::wallet::Wallet, etch::DryRunOutput};
 
 #[derive(Debug, Parser)]
 pub(crate) struct Send {
@@ -7,6 +7,17 @@ pub(crate) struct Send {
   outgoing: Outgoing,
   #[arg(long, help = "Use fee rate of <FEE_RATE> sats/vB")]
   fee_rate: FeeRate,
+  #[arg(
+    long,
+    help = "Don't sign or broadcast a dune transfer. Print the decoded dunestone, its cenotaph flaw if any, and its OP_RETURN size instead."
+  )]
+  dry_run: bool,
+  #[arg(
+    long,
+    conflicts_with = "dry_run",
+    help = "Write the unsigned transaction to <PSBT_OUT> as a PSBT instead of signing and broadcasting it."
+  )]
+  psbt_out: Option<PathBuf>,
 }
 
 #[derive(Serialize, Deserialize)]
@@ -20,12 +31,19 @@ impl Send {
         .address
         .clone();
 
+    ensure!(
+      !self.dry_run || matches!(self.outgoing, Outgoing::Dune { .. }),
+      "`--dry-run` is only supported when sending dunes",
+    );
+
     let index = Index::open(&options)?;
     index.update()?;
 
     let client = options.dogecoin_rpc_client_for_wallet_command(false)?;
 
-    let unspent_outputs = index.get_unspent_outputs(Wallet::load(&options)?)?;
+    let mut wallet = Wallet::load(&options)?;
+
+    let unspent_outputs = index.get_unspent_outputs(&wallet)?;
 
     let inscriptions = index.get_inscriptions(None)?;
 
@@ -34,25 +52,33 @@ impl Send {
 
     let satpoint = match self.outgoing {
       Outgoing::Amount(amount) => {
-        let transaction = Self::send_amount(&client, amount, address, self.fee_rate)?;
-        return Ok(Box::new(Output { transaction }));
+        return Self::send_amount(
+          &mut wallet,
+          &index,
+          &client,
+          amount,
+          address,
+          self.fee_rate,
+          self.psbt_out.as_deref(),
+        );
       }
       Outgoing::InscriptionId(id) => index
           .get_inscription_satpoint_by_id(id)?
           .ok_or_else(|| anyhow!("inscription {id} not found"))?,
       Outgoing::Dune { decimal, dune } => {
-        let transaction = Self::send_dunes(
+        return Self::send_dunes(
           address,
+          &mut wallet,
           &client,
           decimal,
           self.fee_rate,
           &index,
           inscriptions,
           dune,
-          dunic_outputs,
           unspent_outputs,
-        )?;
-        return Ok(Box::new(Output { transaction }));
+          self.dry_run,
+          self.psbt_out.as_deref(),
+        );
       }
       Outgoing::SatPoint(satpoint) => {
         for inscription_satpoint in inscriptions.keys() {
@@ -70,7 +96,10 @@ impl Send {
       }
     };
 
-    let change = [get_change_address(&client)?, get_change_address(&client)?];
+    let change = [
+      wallet.get_change_address(&client)?,
+      wallet.get_change_address(&client)?,
+    ];
 
     let unsigned_transaction = TransactionBuilder::build_transaction_with_postage(
       satpoint,
@@ -82,9 +111,16 @@ impl Send {
       self.fee_rate,
     )?;
 
-    let signed_tx = client
-      .sign_raw_transaction_with_wallet(&unsigned_transaction, None, None)?
-      .hex;
+    if let Some(path) = &self.psbt_out {
+      return Ok(Box::new(psbt::export(
+        &index,
+        &unsigned_transaction,
+        BTreeMap::new(),
+        path,
+      )?));
+    }
+
+    let signed_tx = wallet.sign_transaction(&index, &client, &unsigned_transaction)?;
 
     let txid = client.send_raw_transaction(&signed_tx)?;
 
@@ -94,12 +130,47 @@ impl Send {
   }
 
   fn send_amount(
+    wallet: &mut Wallet,
+    index: &Index,
     client: &Client,
     amount: Amount,
     address: Address,
     fee_rate: FeeRate,
-  ) -> Result<Txid> {
-    Ok(client.call(
+    psbt_out: Option<&Path>,
+  ) -> SubcommandResult {
+    // Dogecoin Core's `sendtoaddress` signs and broadcasts, so transactions
+    // exported as PSBTs and those of native wallets are funded separately
+    if psbt_out.is_some() || matches!(wallet, Wallet::Native(_)) {
+      let unfunded_transaction = Transaction {
+        version: 1,
+        lock_time: PackedLockTime::ZERO,
+        input: Vec::new(),
+        output: vec![TxOut {
+          script_pubkey: address.script_pubkey(),
+          value: amount.to_sat(),
+        }],
+      };
+
+      let unsigned_transaction =
+          wallet.fund_transaction(index, client, fee_rate, &unfunded_transaction)?;
+
+      if let Some(path) = psbt_out {
+        return Ok(Box::new(psbt::export(
+          index,
+          &consensus::encode::deserialize(&unsigned_transaction)?,
+          BTreeMap::new(),
+          path,
+        )?));
+      }
+
+      let signed_transaction = wallet.sign_transaction(index, client, &unsigned_transaction)?;
+
+      return Ok(Box::new(Output {
+        transaction: client.send_raw_transaction(&signed_transaction)?,
+      }));
+    }
+
+    let transaction = client.call::<Txid>(
       "sendtoaddress",
       &[
         address.to_string().into(), //  1. address
@@ -113,20 +184,24 @@ impl Send {
         serde_json::Value::Null,    //  9. avoid_reuse
         fee_rate.n().into(),        // 10. fee_rate
       ],
-    )?)
+    )?;
+
+    Ok(Box::new(Output { transaction }))
   }
 
   fn send_dunes(
     address: Address,
+    wallet: &mut Wallet,
     client: &Client,
     decimal: Decimal,
     fee_rate: FeeRate,
     index: &Index,
     inscriptions: BTreeMap<SatPoint, InscriptionId>,
     spaced_dune: SpacedDune,
-    dunic_outputs: BTreeSet<OutPoint>,
     unspent_outputs: BTreeMap<OutPoint, Amount>,
-  ) -> Result<Txid> {
+    dry_run: bool,
+    psbt_out: Option<&Path>,
+  ) -> SubcommandResult {
     ensure!(
       index.has_dune_index(),
       "sending dunes with `ord send` requires index created with `--index-dunes` flag",
@@ -138,85 +213,64 @@ impl Send {
 
     let amount = decimal.to_amount(entry.divisibility)?;
 
-    let inscribed_outputs = inscriptions
-        .keys()
-        .map(|satpoint| satpoint.outpoint)
-        .collect::<HashSet<OutPoint>>();
-
-    let mut input_dunes = 0;
-    let mut input = Vec::new();
+    let dune_balances = index.get_dune_balances_for_outpoints(
+      &unspent_outputs.keys().cloned().collect::<Vec<OutPoint>>(),
+    )?;
 
-    for output in dunic_outputs {
-      if inscribed_outputs.contains(&output) {
-        continue;
-      }
+    let change = [
+      wallet.get_change_address(client)?,
+      wallet.get_change_address(client)?,
+    ];
 
-      let balance = index.get_dune_balance(output, id)?;
+    let unsigned_transaction = match TransactionBuilder::build_transaction_with_dunes(
+      id,
+      amount,
+      inscriptions,
+      unspent_outputs,
+      dune_balances,
+      address,
+      change,
+      fee_rate,
+    ) {
+      Err(transaction_builder::Error::InsufficientDuneBalance { balance, .. }) => bail!(
+        "insufficient `{}` balance, only {} in wallet",
+        spaced_dune,
+        Pile {
+          amount: balance,
+          divisibility: entry.divisibility,
+          symbol: entry.symbol
+        },
+      ),
+      result => result?,
+    };
 
-      if balance > 0 {
-        input_dunes += balance;
-        input.push(output);
-      }
+    let rules = index
+      .protocol()
+      .dunestone_rules(u32::try_from(client.get_block_count()?).unwrap() + 1);
 
-      if input_dunes >= amount {
-        break;
-      }
+    if dry_run {
+      return Ok(Box::new(DryRunOutput::new(&unsigned_transaction, rules)));
     }
 
-    ensure! {
-      input_dunes >= amount,
-      "insufficient `{}` balance, only {} in wallet",
-      spaced_dune,
-      Pile {
-        amount: input_dunes,
-        divisibility: entry.divisibility,
-        symbol: entry.symbol
-      },
+    if let Some(flaw) = Dunestone::from_transaction_with_rules(&unsigned_transaction, rules)
+      .and_then(|dunestone| dunestone.flaw)
+    {
+      bail!("dunestone would be a cenotaph and burn the sent dunes: {flaw}");
     }
 
-    let dunestone = Dunestone {
-      edicts: vec![Edict {
-        amount,
-        id: id.into(),
-        output: 2,
-      }],
-      ..Default::default()
-    };
-
-    let unfunded_transaction = Transaction {
-      version: 1,
-      lock_time: PackedLockTime::ZERO,
-      input: input
-          .into_iter()
-          .map(|previous_output| TxIn {
-            previous_output,
-            script_sig: Script::new(),
-            sequence: Sequence::MAX,
-            witness: Witness::new(),
-          })
-          .collect(),
-      output: vec![
-        TxOut {
-          script_pubkey: dunestone.encipher(),
-          value: 0,
-        },
-        TxOut {
-          script_pubkey: get_change_address(client)?.script_pubkey(),
-          value: TARGET_POSTAGE.to_sat(),
-        },
-        TxOut {
-          script_pubkey: address.script_pubkey(),
-          value: TARGET_POSTAGE.to_sat(),
-        },
-      ],
-    };
-
-    let unsigned_transaction = fund_raw_transaction(client, fee_rate, &unfunded_transaction)?;
+    if let Some(path) = psbt_out {
+      return Ok(Box::new(psbt::export(
+        index,
+        &unsigned_transaction,
+        BTreeMap::new(),
+        path,
+      )?));
+    }
 
-    let signed_transaction = client
-        .sign_raw_transaction_with_wallet(&unsigned_transaction, None, None)?
-        .hex;
+    let signed_transaction = wallet.sign_transaction(index, client, &unsigned_transaction)?;
 
-    Ok(client.send_raw_transaction(&signed_transaction)?)
+    Ok(Box::new(Output {
+      transaction: client.send_raw_transaction(&signed_transaction)?,
+    }))
   }
 }
@@ -0,0 +1,435 @@
+use {
+  super::*, crate::wallet::Wallet, bitcoin::PackedLockTime, etch::DryRunOutput, std::ffi::OsStr,
+  std::mem,
+};
+
+#[derive(Debug, Parser)]
+pub(crate) struct SendDunes {
+  #[clap(
+    long,
+    help = "Send dunes to the recipients in <BATCH>, either a CSV file with `address,dune,amount` rows or, if <BATCH> ends in `.json`, a JSON array of objects with `address`, `dune` and `amount` string fields."
+  )]
+  batch: PathBuf,
+  #[clap(long, help = "Use fee rate of <FEE_RATE> sats/vB.")]
+  fee_rate: FeeRate,
+  #[clap(
+    long,
+    help = "Don't sign or broadcast the transfers. Print the decoded dunestone, its cenotaph flaw if any, and its OP_RETURN size for each transaction instead."
+  )]
+  dry_run: bool,
+  #[clap(
+    long,
+    conflicts_with = "dry_run",
+    help = "Write the unsigned transfer to <PSBT_OUT> as a PSBT instead of signing and broadcasting it. The batch must fit in a single transaction."
+  )]
+  psbt_out: Option<PathBuf>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct Output {
+  pub transactions: Vec<Txid>,
+}
+
+#[derive(Serialize, Debug)]
+pub struct DryRunBatchOutput {
+  pub transactions: Vec<DryRunOutput>,
+}
+
+#[derive(Debug, PartialEq)]
+struct Recipient {
+  address: Address,
+  dune: SpacedDune,
+  amount: Decimal,
+}
+
+impl FromStr for Recipient {
+  type Err = Error;
+
+  fn from_str(s: &str) -> Result<Self, Self::Err> {
+    let fields = s.split(',').map(str::trim).collect::<Vec<&str>>();
+
+    let [address, dune, amount] = fields.as_slice() else {
+      bail!("expected `address,dune,amount`, found {} fields", fields.len());
+    };
+
+    Ok(Self {
+      address: address.parse()?,
+      dune: dune.parse()?,
+      amount: amount.parse()?,
+    })
+  }
+}
+
+#[derive(Deserialize)]
+struct RecipientJson {
+  address: DeserializeFromStr<Address>,
+  dune: SpacedDune,
+  amount: DeserializeFromStr<Decimal>,
+}
+
+impl From<RecipientJson> for Recipient {
+  fn from(recipient: RecipientJson) -> Self {
+    Self {
+      address: recipient.address.0,
+      dune: recipient.dune,
+      amount: recipient.amount.0,
+    }
+  }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct Transfer {
+  id: DuneId,
+  amount: u128,
+  address: Address,
+}
+
+impl SendDunes {
+  pub(crate) fn run(self, options: Options) -> SubcommandResult {
+    let recipients = Self::load_batch(&self.batch)?;
+
+    let index = Index::open(&options)?;
+
+    ensure!(
+      index.has_dune_index(),
+      "`ord wallet send-dunes` requires index created with `--index-dunes` flag",
+    );
+
+    index.update()?;
+
+    let client = options.dogecoin_rpc_client_for_wallet_command(false)?;
+
+    let rules = index
+      .protocol()
+      .dunestone_rules(u32::try_from(client.get_block_count()?).unwrap() + 1);
+
+    let mut wallet = Wallet::load(&options)?;
+
+    let unspent_outputs = index.get_unspent_outputs(&wallet)?;
+
+    let dunic_outputs =
+      index.get_dunic_outputs(&unspent_outputs.keys().cloned().collect::<Vec<OutPoint>>())?;
+
+    let mut transfers = Vec::new();
+    let mut required = BTreeMap::<DuneId, u128>::new();
+    let mut entries = BTreeMap::new();
+
+    for Recipient {
+      address,
+      dune,
+      amount,
+    } in recipients
+    {
+      let (id, entry) = index
+        .dune(dune.dune)?
+        .with_context(|| format!("dune `{dune}` has not been etched"))?;
+
+      let amount = amount.to_amount(entry.divisibility)?;
+
+      ensure!(amount > 0, "cannot send zero `{dune}` to {address}");
+
+      let total = required.entry(id).or_default();
+      *total = total
+        .checked_add(amount)
+        .with_context(|| format!("total `{dune}` amount overflows"))?;
+
+      entries.insert(id, (dune, entry));
+
+      transfers.push(Transfer {
+        id,
+        amount,
+        address,
+      });
+    }
+
+    let mut inputs = Vec::new();
+    let mut input_dunes = BTreeMap::<DuneId, u128>::new();
+
+    for output in dunic_outputs {
+      let missing = |input_dunes: &BTreeMap<DuneId, u128>, id: &DuneId| {
+        input_dunes.get(id).copied().unwrap_or_default() < required[id]
+      };
+
+      if !required.keys().any(|id| missing(&input_dunes, id)) {
+        break;
+      }
+
+      if !index.get_inscriptions_on_output(output)?.is_empty() {
+        continue;
+      }
+
+      let mut balances = Vec::new();
+
+      for id in required.keys() {
+        let balance = index.get_dune_balance(output, *id)?;
+        if balance > 0 {
+          balances.push((*id, balance));
+        }
+      }
+
+      if balances
+        .iter()
+        .any(|(id, _)| missing(&input_dunes, id))
+      {
+        for (id, balance) in balances {
+          *input_dunes.entry(id).or_default() += balance;
+        }
+        inputs.push(output);
+      }
+    }
+
+    for (id, amount) in &required {
+      let (spaced_dune, entry) = &entries[id];
+      let balance = input_dunes.get(id).copied().unwrap_or_default();
+
+      ensure! {
+        balance >= *amount,
+        "insufficient `{}` balance, only {} in wallet",
+        spaced_dune,
+        entry.pile(balance),
+      }
+    }
+
+    // Dunes held in the inputs that are not transferred are sent to the change
+    // output by the dunestone's pointer, and spent by the next transaction
+    let change = wallet.get_change_address(&client)?;
+
+    if !self.dry_run {
+      wallet.lock_non_cardinal_outputs(&index, &client, &unspent_outputs)?;
+    }
+
+    let chunks = Self::pack(transfers, index.protocol().max_op_return_size)?;
+
+    ensure!(
+      self.psbt_out.is_none() || chunks.len() == 1,
+      "batch needs {} transactions, but `--psbt-out` can only write one",
+      chunks.len(),
+    );
+
+    let mut transactions = Vec::new();
+    let mut dry_runs = Vec::new();
+
+    for chunk in chunks {
+      let unfunded_transaction = Transaction {
+        version: 1,
+        lock_time: PackedLockTime::ZERO,
+        input: inputs
+          .iter()
+          .map(|previous_output| TxIn {
+            previous_output: *previous_output,
+            script_sig: Script::new(),
+            sequence: Sequence::MAX,
+            witness: Witness::new(),
+          })
+          .collect(),
+        output: [
+          TxOut {
+            script_pubkey: Self::dunestone(&chunk).encipher(),
+            value: 0,
+          },
+          TxOut {
+            script_pubkey: change.script_pubkey(),
+            value: TARGET_POSTAGE.to_sat(),
+          },
+        ]
+        .into_iter()
+        .chain(chunk.iter().map(|transfer| TxOut {
+          script_pubkey: transfer.address.script_pubkey(),
+          value: TARGET_POSTAGE.to_sat(),
+        }))
+        .collect(),
+      };
+
+      if let Some(flaw) = Dunestone::from_transaction_with_rules(&unfunded_transaction, rules)
+        .and_then(|dunestone| dunestone.flaw)
+      {
+        bail!("dunestone would be a cenotaph and burn the sent dunes: {flaw}");
+      }
+
+      let txid = if self.dry_run {
+        dry_runs.push(DryRunOutput::new(&unfunded_transaction, rules));
+        unfunded_transaction.txid()
+      } else {
+        let unsigned_transaction =
+          wallet.fund_transaction(&index, &client, self.fee_rate, &unfunded_transaction)?;
+
+        if let Some(path) = &self.psbt_out {
+          return Ok(Box::new(psbt::export(
+            &index,
+            &consensus::encode::deserialize(&unsigned_transaction)?,
+            BTreeMap::new(),
+            path,
+          )?));
+        }
+
+        let signed_transaction = wallet.sign_transaction(&index, &client, &unsigned_transaction)?;
+
+        client.send_raw_transaction(&signed_transaction)?
+      };
+
+      transactions.push(txid);
+
+      inputs = vec![OutPoint { txid, vout: 1 }];
+    }
+
+    if self.dry_run {
+      return Ok(Box::new(DryRunBatchOutput {
+        transactions: dry_runs,
+      }));
+    }
+
+    Ok(Box::new(Output { transactions }))
+  }
+
+  fn load_batch(path: &Path) -> Result<Vec<Recipient>> {
+    let batch = fs::read_to_string(path)
+      .with_context(|| format!("failed to read batch file `{}`", path.display()))?;
+
+    let recipients = if path.extension() == Some(OsStr::new("json")) {
+      serde_json::from_str::<Vec<RecipientJson>>(&batch)
+        .with_context(|| format!("failed to parse batch file `{}`", path.display()))?
+        .into_iter()
+        .map(Recipient::from)
+        .collect()
+    } else {
+      Self::parse_csv(&batch)?
+    };
+
+    ensure!(
+      !recipients.is_empty(),
+      "batch file `{}` contains no recipients",
+      path.display()
+    );
+
+    Ok(recipients)
+  }
+
+  fn parse_csv(batch: &str) -> Result<Vec<Recipient>> {
+    batch
+      .lines()
+      .enumerate()
+      .map(|(i, line)| (i + 1, line.trim()))
+      .filter(|(_, line)| !line.is_empty() && !line.starts_with('#'))
+      .filter(|(number, line)| !(*number == 1 && line.replace(' ', "") == "address,dune,amount"))
+      .map(|(number, line)| {
+        line
+          .parse()
+          .with_context(|| format!("invalid recipient on line {number}: `{line}`"))
+      })
+      .collect()
+  }
+
+  fn dunestone(transfers: &[Transfer]) -> Dunestone {
+    Dunestone {
+      edicts: transfers
+        .iter()
+        .enumerate()
+        .map(|(i, transfer)| Edict {
+          id: transfer.id.into(),
+          amount: transfer.amount,
+          output: u128::try_from(i + 2).unwrap(),
+        })
+        .collect(),
+      pointer: Some(1),
+      ..Default::default()
+    }
+  }
+
+  /// Split transfers into groups whose dunestones fit in an OP_RETURN of at
+  /// most `max_op_return_size` bytes
+  fn pack(transfers: Vec<Transfer>, max_op_return_size: usize) -> Result<Vec<Vec<Transfer>>> {
+    let mut chunks = Vec::new();
+    let mut chunk = Vec::new();
+
+    for transfer in transfers {
+      chunk.push(transfer);
+
+      if Self::dunestone(&chunk).encipher().len() <= max_op_return_size {
+        continue;
+      }
+
+      let transfer = chunk.pop().unwrap();
+
+      ensure!(
+        !chunk.is_empty(),
+        "dunestone for transfer to {} greater than maximum OP_RETURN size",
+        transfer.address,
+      );
+
+      chunks.push(mem::take(&mut chunk));
+      chunk.push(transfer);
+    }
+
+    if !chunk.is_empty() {
+      chunks.push(chunk);
+    }
+
+    Ok(chunks)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn address() -> Address {
+    "bc1qw508d6qejxtdg4y5r3zarvary0c5xw7kv8f3t4"
+      .parse()
+      .unwrap()
+  }
+
+  #[test]
+  fn parse_csv() {
+    assert_eq!(
+      SendDunes::parse_csv(
+        "address,dune,amount\n\n# comment\nbc1qw508d6qejxtdg4y5r3zarvary0c5xw7kv8f3t4, A•B, 1.5\n"
+      )
+      .unwrap(),
+      vec![Recipient {
+        address: address(),
+        dune: "A•B".parse().unwrap(),
+        amount: "1.5".parse().unwrap(),
+      }],
+    );
+  }
+
+  #[test]
+  fn parse_csv_reports_line_number() {
+    assert_eq!(
+      SendDunes::parse_csv("bc1qw508d6qejxtdg4y5r3zarvary0c5xw7kv8f3t4,A,1\nfoo,bar")
+        .unwrap_err()
+        .to_string(),
+      "invalid recipient on line 2: `foo,bar`",
+    );
+  }
+
+  #[test]
+  fn transfers_are_packed_into_standard_op_returns() {
+    let transfers = (0..20)
+      .map(|i| Transfer {
+        id: DuneId {
+          height: 840000,
+          index: 1,
+        },
+        amount: 1000 + i,
+        address: address(),
+      })
+      .collect::<Vec<Transfer>>();
+
+    let chunks = SendDunes::pack(
+      transfers.clone(),
+      ProtocolParameters::MAX_STANDARD_OP_RETURN_SIZE,
+    )
+    .unwrap();
+
+    assert!(chunks.len() > 1);
+
+    assert_eq!(chunks.concat(), transfers);
+
+    for chunk in chunks {
+      let dunestone = SendDunes::dunestone(&chunk);
+      assert!(dunestone.encipher().len() <= ProtocolParameters::MAX_STANDARD_OP_RETURN_SIZE);
+      assert_eq!(dunestone.pointer, Some(1));
+    }
+  }
+}
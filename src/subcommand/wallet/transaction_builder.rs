@@ -22,6 +22,12 @@
 //! `TransactionBuilder::build_transaction_with_value` ensures that the
 //! outgoing value is exactly the requested amount,
 //!
+//! `TransactionBuilder::build_transaction_with_dunes` sends an amount of a
+//! dune instead of a sat. It selects the fewest dune-bearing UTXOs that cover
+//! the amount, transfers it to the recipient with an edict, and points the
+//! dunestone at a dune change output that receives all other dunes in the
+//! inputs, so that none are burned.
+//!
 //! Internally, `TransactionBuilder` calls multiple methods that implement
 //! transformations responsible for individual concerns, such as ensuring that
 //! the transaction fee is paid, and that outgoing outputs aren't too large.
@@ -51,6 +57,11 @@ pub enum Error {
     output_value: Amount,
     dust_value: Amount,
   },
+  InsufficientDuneBalance {
+    id: DuneId,
+    balance: u128,
+    amount: u128,
+  },
   NotEnoughCardinalUtxos,
   NotInWallet(SatPoint),
   OutOfRange(SatPoint, u64),
@@ -77,6 +88,11 @@ impl fmt::Display for Error {
       } => write!(f, "output value is below dust value: {output_value} < {dust_value}"),
       Error::NotInWallet(outgoing_satpoint) => write!(f, "outgoing satpoint {outgoing_satpoint} not in wallet"),
       Error::OutOfRange(outgoing_satpoint, maximum) => write!(f, "outgoing satpoint {outgoing_satpoint} offset higher than maximum {maximum}"),
+      Error::InsufficientDuneBalance {
+        id,
+        balance,
+        amount,
+      } => write!(f, "insufficient balance of dune {id} in wallet: {balance} < {amount}"),
       Error::NotEnoughCardinalUtxos => write!(
         f,
         "wallet does not contain enough cardinal UTXOs, please add additional funds to wallet."
@@ -101,6 +117,8 @@ impl std::error::Error for Error {}
 pub struct TransactionBuilder {
   amounts: BTreeMap<OutPoint, Amount>,
   change_addresses: BTreeSet<Address>,
+  dune_balances: BTreeMap<OutPoint, BTreeMap<DuneId, u128>>,
+  dunestone: Option<Dunestone>,
   fee_rate: FeeRate,
   inputs: Vec<OutPoint>,
   inscriptions: BTreeMap<SatPoint, InscriptionId>,
@@ -176,6 +194,38 @@ impl TransactionBuilder {
     .build_transaction()
   }
 
+  pub fn build_transaction_with_dunes(
+    id: DuneId,
+    amount: u128,
+    inscriptions: BTreeMap<SatPoint, InscriptionId>,
+    amounts: BTreeMap<OutPoint, Amount>,
+    dune_balances: BTreeMap<OutPoint, BTreeMap<DuneId, u128>>,
+    recipient: Address,
+    change: [Address; 2],
+    fee_rate: FeeRate,
+  ) -> Result<Transaction> {
+    let mut builder = Self::new(
+      SatPoint {
+        outpoint: OutPoint::null(),
+        offset: 0,
+      },
+      inscriptions,
+      amounts,
+      dune_balances.keys().cloned().collect(),
+      recipient,
+      change,
+      fee_rate,
+      Target::Postage,
+    )?;
+
+    builder.dune_balances = dune_balances;
+
+    builder
+      .select_dunes(id, amount)?
+      .fund_dunes()?
+      .build()
+  }
+
   pub fn build_transaction(self) -> Result<Transaction> {
     self
       .select_outgoing()?
@@ -209,6 +259,8 @@ impl TransactionBuilder {
       utxos: amounts.keys().cloned().collect(),
       amounts,
       change_addresses: change.iter().cloned().collect(),
+      dune_balances: BTreeMap::new(),
+      dunestone: None,
       fee_rate,
       inputs: Vec::new(),
       inscriptions,
@@ -256,6 +308,183 @@ impl TransactionBuilder {
     Ok(self)
   }
 
+  /// Select the dune-bearing UTXOs to transfer `amount` of dune `id` from,
+  /// preferring the single UTXO with the smallest sufficient balance, and
+  /// otherwise the fewest UTXOs with the largest balances. The first sat of
+  /// the first selected UTXO is treated as the outgoing sat, so the recipient
+  /// output comes first, followed by a dune change output if the selected
+  /// UTXOs hold any dunes that are not transferred.
+  fn select_dunes(mut self, id: DuneId, amount: u128) -> Result<Self> {
+    let inscribed_utxos = self
+      .inscriptions
+      .keys()
+      .map(|satpoint| satpoint.outpoint)
+      .collect::<BTreeSet<OutPoint>>();
+
+    let mut candidates = self
+      .dune_balances
+      .iter()
+      .filter(|(outpoint, _balances)| {
+        self.utxos.contains(outpoint) && !inscribed_utxos.contains(outpoint)
+      })
+      .filter_map(|(outpoint, balances)| Some((*outpoint, *balances.get(&id)?)))
+      .collect::<Vec<(OutPoint, u128)>>();
+
+    let balance = candidates.iter().map(|(_outpoint, balance)| balance).sum::<u128>();
+
+    if balance < amount || candidates.is_empty() {
+      return Err(Error::InsufficientDuneBalance {
+        id,
+        balance,
+        amount,
+      });
+    }
+
+    let selected = match candidates
+      .iter()
+      .filter(|(_outpoint, balance)| *balance >= amount)
+      .min_by_key(|(_outpoint, balance)| *balance)
+    {
+      Some((outpoint, _balance)) => vec![*outpoint],
+      None => {
+        candidates.sort_by_key(|(_outpoint, balance)| cmp::Reverse(*balance));
+
+        let mut selected = Vec::new();
+        let mut total = 0;
+
+        for (outpoint, balance) in candidates {
+          if total >= amount {
+            break;
+          }
+
+          selected.push(outpoint);
+          total += balance;
+        }
+
+        selected
+      }
+    };
+
+    for outpoint in &selected {
+      self.utxos.remove(outpoint);
+      self.inputs.push(*outpoint);
+    }
+
+    self.outgoing = SatPoint {
+      outpoint: selected[0],
+      offset: 0,
+    };
+
+    let mut unallocated = BTreeMap::<DuneId, u128>::new();
+    for outpoint in &selected {
+      for (id, balance) in &self.dune_balances[outpoint] {
+        *unallocated.entry(*id).or_default() += balance;
+      }
+    }
+    *unallocated.get_mut(&id).unwrap() -= amount;
+    unallocated.retain(|_id, balance| *balance > 0);
+
+    self.outputs.push((self.recipient.clone(), Self::TARGET_POSTAGE));
+
+    // The dunestone is the first output, so edict and pointer outputs are
+    // offset by one from `self.outputs`
+    let pointer = if unallocated.is_empty() {
+      None
+    } else {
+      self.outputs.push((
+        self
+          .unused_change_addresses
+          .pop()
+          .expect("not enough change addresses"),
+        Self::TARGET_POSTAGE,
+      ));
+      Some(2)
+    };
+
+    self.dunestone = Some(Dunestone {
+      edicts: vec![Edict {
+        id: id.into(),
+        amount,
+        output: 1,
+      }],
+      pointer,
+      ..Default::default()
+    });
+
+    tprintln!(
+      "selected {} dune outpoints with {} unallocated dunes",
+      selected.len(),
+      unallocated.len()
+    );
+
+    Ok(self)
+  }
+
+  /// Add cardinal inputs until the dune transfer pays its fee, and send the
+  /// excess to a change output, or to the recipient if it would be dust.
+  fn fund_dunes(mut self) -> Result<Self> {
+    let output_value = self
+      .outputs
+      .iter()
+      .map(|(_address, amount)| *amount)
+      .sum::<Amount>();
+
+    loop {
+      let input_value = self
+        .inputs
+        .iter()
+        .map(|outpoint| self.amounts[outpoint])
+        .sum::<Amount>();
+
+      let total = output_value
+        .checked_add(self.estimate_fee())
+        .ok_or(Error::ValueOverflow)?;
+
+      match total.checked_sub(input_value) {
+        Some(deficit) if deficit > Amount::ZERO => {
+          let needed = deficit
+            .checked_add(self.fee_rate.fee(Self::ADDITIONAL_INPUT_VBYTES))
+            .ok_or(Error::ValueOverflow)?;
+          let (utxo, value) = self.select_cardinal_utxo(needed)?;
+          self.inputs.push(utxo);
+          tprintln!("added {value} sat input to cover {deficit} sat deficit");
+        }
+        _ => break,
+      }
+    }
+
+    let input_value = self
+      .inputs
+      .iter()
+      .map(|outpoint| self.amounts[outpoint])
+      .sum::<Amount>();
+
+    let change = self
+      .unused_change_addresses
+      .last()
+      .expect("not enough change addresses")
+      .clone();
+
+    self.outputs.push((change.clone(), Amount::ZERO));
+
+    let excess = input_value - output_value;
+
+    match excess.checked_sub(self.estimate_fee()) {
+      Some(value) if value >= change.script_pubkey().dust_value() => {
+        self.unused_change_addresses.pop();
+        self.outputs.last_mut().unwrap().1 = value;
+        tprintln!("sent {value} sat change");
+      }
+      _ => {
+        self.outputs.pop();
+        self.outputs[0].1 += excess - self.estimate_fee();
+        tprintln!("sent {excess} sat excess to recipient");
+      }
+    }
+
+    Ok(self)
+  }
+
   fn align_outgoing(mut self) -> Self {
     assert_eq!(self.outputs.len(), 1, "invariant: only one output");
 
@@ -429,7 +658,17 @@ impl TransactionBuilder {
         .map(|(address, _amount)| address)
         .cloned()
         .collect(),
-    )
+    ) + self
+      .dunestone
+      .as_ref()
+      .map(|dunestone| {
+        consensus::encode::serialize(&TxOut {
+          value: 0,
+          script_pubkey: dunestone.encipher(),
+        })
+        .len()
+      })
+      .unwrap_or_default()
   }
 
   fn estimate_vbytes_with(inputs: usize, outputs: Vec<Address>) -> usize {
@@ -476,12 +715,16 @@ impl TransactionBuilder {
         })
         .collect(),
       output: self
-        .outputs
+        .dunestone
         .iter()
-        .map(|(address, amount)| TxOut {
+        .map(|dunestone| TxOut {
+          value: 0,
+          script_pubkey: dunestone.encipher(),
+        })
+        .chain(self.outputs.iter().map(|(address, amount)| TxOut {
           value: amount.to_sat(),
           script_pubkey: address.script_pubkey(),
-        })
+        }))
         .collect(),
     };
 
@@ -587,7 +830,7 @@ impl TransactionBuilder {
           offset, sat_offset,
           "invariant: sat is at first position in recipient output"
         );
-      } else {
+      } else if !output.script_pubkey.is_op_return() {
         assert!(
           self
             .change_addresses
@@ -621,11 +864,82 @@ impl TransactionBuilder {
 
     for tx_out in &transaction.output {
       assert!(
-        Amount::from_sat(tx_out.value) >= tx_out.script_pubkey.dust_value(),
+        tx_out.script_pubkey.is_op_return()
+          || Amount::from_sat(tx_out.value) >= tx_out.script_pubkey.dust_value(),
         "invariant: all outputs are above dust limit",
       );
     }
 
+    let mut unallocated = BTreeMap::<DuneId, u128>::new();
+    for input in &transaction.input {
+      for (id, balance) in self.dune_balances.get(&input.previous_output).into_iter().flatten() {
+        *unallocated.entry(*id).or_default() += balance;
+      }
+    }
+
+    let pointer = match &self.dunestone {
+      Some(dunestone) => {
+        // a dunestone without flaws under the strict rules has none under the
+        // legacy rules either
+        let deciphered = Dunestone::from_transaction_with_rules(&transaction, Rules::Strict);
+
+        assert!(
+          deciphered
+            .as_ref()
+            .map(|deciphered| !deciphered.is_cenotaph())
+            .unwrap_or_default(),
+          "invariant: dunestone is not a cenotaph",
+        );
+
+        for edict in &dunestone.edicts {
+          let id = DuneId::try_from(edict.id).unwrap();
+
+          let balance = unallocated.entry(id).or_default();
+
+          assert!(
+            *balance >= edict.amount,
+            "invariant: inputs hold transferred dunes",
+          );
+
+          assert_eq!(
+            transaction.output[usize::try_from(edict.output).unwrap()].script_pubkey,
+            recipient,
+            "invariant: transferred dunes are sent to recipient",
+          );
+
+          *balance -= edict.amount;
+        }
+
+        dunestone.pointer.map(|pointer| usize::try_from(pointer).unwrap())
+      }
+      None => None,
+    };
+
+    if unallocated.values().any(|balance| *balance > 0) {
+      let output = pointer
+        .or_else(|| {
+          transaction
+            .output
+            .iter()
+            .position(|tx_out| !tx_out.script_pubkey.is_op_return())
+        })
+        .and_then(|pointer| transaction.output.get(pointer));
+
+      assert!(
+        output
+          .map(|output| {
+            output.script_pubkey == recipient
+              || self
+                .change_addresses
+                .iter()
+                .any(|change_address| change_address.script_pubkey() == output.script_pubkey)
+          })
+          .unwrap_or_default()
+          && (self.dunestone.is_none() || pointer.is_some()),
+        "invariant: unallocated dunes are sent to dune change",
+      );
+    }
+
     Ok(transaction)
   }
 
@@ -1632,4 +1946,236 @@ mod tests {
       }),
     );
   }
+
+  fn dune_id(n: u32) -> DuneId {
+    DuneId {
+      height: 1,
+      index: n,
+    }
+  }
+
+  fn dune_balances(
+    balances: &[(u64, &[(u32, u128)])],
+  ) -> BTreeMap<OutPoint, BTreeMap<DuneId, u128>> {
+    balances
+      .iter()
+      .map(|(n, balances)| {
+        (
+          outpoint(*n),
+          balances
+            .iter()
+            .map(|(id, balance)| (dune_id(*id), *balance))
+            .collect(),
+        )
+      })
+      .collect()
+  }
+
+  fn dune_transfer_builder(
+    balances: &[(u64, &[(u32, u128)])],
+    utxos: Vec<(OutPoint, Amount)>,
+  ) -> TransactionBuilder {
+    let dune_balances = dune_balances(balances);
+
+    let mut builder = TransactionBuilder::new(
+      satpoint(1, 0),
+      BTreeMap::new(),
+      utxos.into_iter().collect(),
+      dune_balances.keys().cloned().collect(),
+      recipient(),
+      [change(0), change(1)],
+      FeeRate::try_from(1.0).unwrap(),
+      Target::Postage,
+    )
+    .unwrap();
+
+    builder.dune_balances = dune_balances;
+
+    builder
+  }
+
+  #[test]
+  fn dune_transfer_selects_smallest_sufficient_output() {
+    let builder = dune_transfer_builder(
+      &[(1, &[(0, 1000)]), (2, &[(0, 100)]), (3, &[(0, 10)])],
+      vec![
+        (outpoint(1), Amount::from_sat(10_000)),
+        (outpoint(2), Amount::from_sat(10_000)),
+        (outpoint(3), Amount::from_sat(10_000)),
+      ],
+    )
+    .select_dunes(dune_id(0), 50)
+    .unwrap();
+
+    assert_eq!(builder.inputs, [outpoint(2)]);
+    assert_eq!(builder.outgoing, satpoint(2, 0));
+    assert_eq!(
+      builder.outputs,
+      [
+        (recipient(), TransactionBuilder::TARGET_POSTAGE),
+        (change(1), TransactionBuilder::TARGET_POSTAGE),
+      ]
+    );
+    assert_eq!(
+      builder.dunestone,
+      Some(Dunestone {
+        edicts: vec![Edict {
+          id: dune_id(0).into(),
+          amount: 50,
+          output: 1,
+        }],
+        pointer: Some(2),
+        ..Default::default()
+      })
+    );
+  }
+
+  #[test]
+  fn dune_transfer_selects_fewest_outputs_when_none_suffice() {
+    let builder = dune_transfer_builder(
+      &[(1, &[(0, 10)]), (2, &[(0, 60)]), (3, &[(0, 50)])],
+      vec![
+        (outpoint(1), Amount::from_sat(10_000)),
+        (outpoint(2), Amount::from_sat(10_000)),
+        (outpoint(3), Amount::from_sat(10_000)),
+      ],
+    )
+    .select_dunes(dune_id(0), 110)
+    .unwrap();
+
+    assert_eq!(builder.inputs, [outpoint(2), outpoint(3)]);
+    assert_eq!(
+      builder.outputs,
+      [(recipient(), TransactionBuilder::TARGET_POSTAGE)]
+    );
+    assert_eq!(builder.dunestone.unwrap().pointer, None);
+  }
+
+  #[test]
+  fn unrequested_dunes_are_sent_to_dune_change() {
+    let transaction = TransactionBuilder::build_transaction_with_dunes(
+      dune_id(0),
+      50,
+      BTreeMap::new(),
+      vec![
+        (outpoint(1), Amount::from_sat(10_000)),
+        (outpoint(2), Amount::from_sat(100_000)),
+      ]
+      .into_iter()
+      .collect(),
+      dune_balances(&[(1, &[(0, 100), (1, 7)])]),
+      recipient(),
+      [change(0), change(1)],
+      FeeRate::try_from(1.0).unwrap(),
+    )
+    .unwrap();
+
+    assert_eq!(
+      transaction.input,
+      [tx_in(outpoint(1)), tx_in(outpoint(2))]
+    );
+
+    let dunestone = Dunestone::from_transaction_with_rules(&transaction, Rules::Strict).unwrap();
+
+    assert_eq!(dunestone.pointer, Some(2));
+    assert_eq!(
+      transaction.output[1].script_pubkey,
+      recipient().script_pubkey()
+    );
+    assert_eq!(
+      transaction.output[2].script_pubkey,
+      change(1).script_pubkey()
+    );
+    assert_eq!(
+      transaction.output[3].script_pubkey,
+      change(0).script_pubkey()
+    );
+  }
+
+  #[test]
+  fn insufficient_dune_balance() {
+    pretty_assert_eq!(
+      TransactionBuilder::build_transaction_with_dunes(
+        dune_id(0),
+        200,
+        BTreeMap::new(),
+        vec![(outpoint(1), Amount::from_sat(10_000))]
+          .into_iter()
+          .collect(),
+        dune_balances(&[(1, &[(0, 100)])]),
+        recipient(),
+        [change(0), change(1)],
+        FeeRate::try_from(1.0).unwrap(),
+      ),
+      Err(Error::InsufficientDuneBalance {
+        id: dune_id(0),
+        balance: 100,
+        amount: 200,
+      })
+    );
+  }
+
+  #[test]
+  fn dunic_utxos_are_not_used_as_cardinal_inputs() {
+    pretty_assert_eq!(
+      TransactionBuilder::build_transaction_with_dunes(
+        dune_id(0),
+        100,
+        BTreeMap::new(),
+        vec![
+          (outpoint(1), Amount::from_sat(10_000)),
+          (outpoint(2), Amount::from_sat(100_000)),
+        ]
+        .into_iter()
+        .collect(),
+        dune_balances(&[(1, &[(0, 100)]), (2, &[(1, 100)])]),
+        recipient(),
+        [change(0), change(1)],
+        FeeRate::try_from(1.0).unwrap(),
+      ),
+      Err(Error::NotEnoughCardinalUtxos)
+    );
+  }
+
+  #[test]
+  #[should_panic(expected = "invariant: unallocated dunes are sent to dune change")]
+  fn invariant_unallocated_dunes_are_sent_to_dune_change() {
+    let mut builder = dune_transfer_builder(
+      &[(1, &[(0, 100)])],
+      vec![
+        (outpoint(1), Amount::from_sat(10_000)),
+        (outpoint(2), Amount::from_sat(100_000)),
+      ],
+    )
+    .select_dunes(dune_id(0), 50)
+    .unwrap()
+    .fund_dunes()
+    .unwrap();
+
+    let fee = builder.estimate_fee();
+    builder.dunestone.as_mut().unwrap().pointer = None;
+    builder.outputs.last_mut().unwrap().1 += fee - builder.estimate_fee();
+
+    builder.build().unwrap();
+  }
+
+  #[test]
+  #[should_panic(expected = "invariant: inputs hold transferred dunes")]
+  fn invariant_inputs_hold_transferred_dunes() {
+    let mut builder = dune_transfer_builder(
+      &[(1, &[(0, 100)])],
+      vec![
+        (outpoint(1), Amount::from_sat(10_000)),
+        (outpoint(2), Amount::from_sat(100_000)),
+      ],
+    )
+    .select_dunes(dune_id(0), 100)
+    .unwrap()
+    .fund_dunes()
+    .unwrap();
+
+    builder.dunestone.as_mut().unwrap().edicts[0].amount = 101;
+
+    builder.build().unwrap();
+  }
 }
@@ -17,9 +17,10 @@ pub(crate) use {
   range::RangeHtml,
   rare::RareTxt,
   dune::DuneEntryJson,
-  dune::DuneHtml,
+  dune::{DuneDistribution, DuneDistributionJson, DuneHolder, DuneHtml},
+  dune_activity::{DuneActivityHtml, DuneActivityJson},
   utxo::Utxo,
-  dune::{DuneAddressJson, DuneJson, DuneOutputJson, DuneBalance, DuneOutput},
+  dune::{DuneAddressJson, DuneJson, DuneOutputJson, DuneBalance, DuneOutput, LegacyDuneAddressJson},
   dune_balances::DuneBalancesHtml,
   dunes::DunesHtml,
   sat::SatHtml,
@@ -37,6 +38,7 @@ mod preview;
 mod range;
 mod rare;
 mod dune;
+mod dune_activity;
 mod dune_balances;
 mod dunes;
 mod sat;
@@ -114,6 +116,7 @@ mod tests {
         chain: Chain::Mainnet,
         domain: Some("signet.ordinals.com".into()),
         index_sats: true,
+        json_api_enabled: false,
       }),),
       r"<!doctype html>
 <html lang=en>
@@ -158,6 +161,7 @@ mod tests {
         chain: Chain::Mainnet,
         domain: None,
         index_sats: true,
+        json_api_enabled: false,
       }),),
       r".*<nav>\s*<a href=/>Dunes<sup>alpha</sup></a>.*"
     );
@@ -170,6 +174,7 @@ mod tests {
         chain: Chain::Mainnet,
         domain: None,
         index_sats: false,
+        json_api_enabled: false,
       }),),
       r".*<nav>\s*<a href=/>Dunes<sup>alpha</sup></a>.*\s*<form action=/search.*",
     );
@@ -182,6 +187,7 @@ mod tests {
         chain: Chain::Signet,
         domain: None,
         index_sats: true,
+        json_api_enabled: false,
       }),),
       r".*<nav>\s*<a href=/>Dunes<sup>signet</sup></a>.*"
     );
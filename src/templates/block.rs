@@ -10,6 +10,19 @@ pub(crate) struct BlockHtml {
   height: Height,
 }
 
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+pub(crate) struct BlockJson {
+  pub(crate) hash: BlockHash,
+  pub(crate) target: BlockHash,
+  pub(crate) best_height: u32,
+  pub(crate) height: u32,
+  pub(crate) previous_blockhash: Option<BlockHash>,
+  pub(crate) size: usize,
+  pub(crate) timestamp: u32,
+  pub(crate) transactions: Vec<Txid>,
+  pub(crate) weight: usize,
+}
+
 pub(crate) struct BlockHashAndConfirmations {
   pub(crate) hash: Option<BlockHash>,
   pub(crate) confirmations: Option<u32>,
@@ -27,6 +40,20 @@ impl BlockHtml {
       best_height,
     }
   }
+
+  pub(crate) fn to_json(&self) -> BlockJson {
+    BlockJson {
+      hash: self.hash,
+      target: self.target,
+      best_height: self.best_height.n(),
+      height: self.height.n(),
+      previous_blockhash: (self.height.n() > 0).then_some(self.block.header.prev_blockhash),
+      size: self.block.size(),
+      timestamp: self.block.header.time,
+      transactions: self.block.txdata.iter().map(Transaction::txid).collect(),
+      weight: self.block.weight(),
+    }
+  }
 }
 
 impl PageContent for BlockHtml {
@@ -80,4 +107,24 @@ mod tests {
       r"<h1>Block 1</h1>.*<a class=prev href=/block/0>prev</a>\s*next.*",
     );
   }
+
+  #[test]
+  fn json() {
+    let block = Chain::Mainnet.genesis_block();
+
+    assert_eq!(
+      BlockHtml::new(block.clone(), Height(0), Height(1)).to_json(),
+      BlockJson {
+        hash: block.block_hash(),
+        target: BlockHtml::new(block.clone(), Height(0), Height(1)).target,
+        best_height: 1,
+        height: 0,
+        previous_blockhash: None,
+        size: 224,
+        timestamp: block.header.time,
+        transactions: vec![block.txdata[0].txid()],
+        weight: 896,
+      }
+    );
+  }
 }
@@ -6,6 +6,30 @@ pub(crate) struct DuneHtml {
   pub(crate) id: DuneId,
   pub(crate) mintable: bool,
   pub(crate) inscription: Option<InscriptionId>,
+  pub(crate) distribution: Option<DuneDistribution>,
+}
+
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+pub(crate) struct DuneDistribution {
+  pub(crate) holders: u64,
+  pub(crate) top: Vec<DuneHolder>,
+  pub(crate) top_10_share: f64,
+  pub(crate) top_100_share: f64,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub(crate) struct DuneHolder {
+  pub(crate) address: String,
+  pub(crate) balance: u128,
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+pub(crate) struct DuneDistributionJson {
+  pub(crate) dune: SpacedDune,
+  pub(crate) id: DuneId,
+  pub(crate) divisibility: u8,
+  #[serde(flatten)]
+  pub(crate) distribution: DuneDistribution,
 }
 
 #[derive(Debug, PartialEq, Serialize, Deserialize)]
@@ -28,12 +52,32 @@ pub(crate) struct DuneJson {
   pub(crate) id: DuneId,
   pub(crate) mintable: bool,
   pub(crate) inscription: Option<InscriptionId>,
+  pub(crate) distribution: Option<DuneDistribution>,
+}
+
+impl From<DuneEntry> for DuneEntryJson {
+  fn from(entry: DuneEntry) -> Self {
+    Self {
+      burned: entry.burned,
+      divisibility: entry.divisibility,
+      etching: entry.etching,
+      mint: entry.terms,
+      mints: entry.mints,
+      number: entry.number,
+      dune: entry.spaced_dune(),
+      supply: entry.supply,
+      symbol: entry.symbol,
+      timestamp: entry.timestamp,
+    }
+  }
 }
 
 #[derive(Debug, PartialEq, Serialize, Deserialize)]
 pub(crate) struct DuneOutputJson {
   pub(crate) dune: SpacedDune,
   pub(crate) balances: Pile,
+  pub(crate) pending: bool,
+  pub(crate) spent_in_mempool: bool,
 }
 
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
@@ -53,15 +97,37 @@ pub(crate) struct DuneOutput {
   pub(crate) script: Script,
   pub(crate) shibes: u64,
   pub(crate) balance: u128,
+  pub(crate) spent_in_mempool: bool,
 }
 
 #[derive(Debug, PartialEq, Serialize, Deserialize)]
 pub(crate) struct DuneAddressJson {
+  pub(crate) dunes: Vec<DuneBalance>,
+  pub(crate) pending: Vec<DuneBalance>,
+  pub(crate) next: Option<String>,
+}
+
+/// Response of the deprecated page numbered `/dunes/balance/:address/:page`
+/// endpoint
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+pub(crate) struct LegacyDuneAddressJson {
   pub(crate) dunes: Vec<DuneBalance>,
   pub(crate) total_dunes: usize,
   pub(crate) total_elements: u32,
 }
 
+impl DuneHtml {
+  pub(crate) fn to_json(&self) -> DuneJson {
+    DuneJson {
+      entry: self.entry.into(),
+      id: self.id,
+      mintable: self.mintable,
+      inscription: self.inscription,
+      distribution: self.distribution.clone(),
+    }
+  }
+}
+
 impl PageContent for DuneHtml {
   fn title(&self) -> String {
     format!("Dune {}", self.entry.spaced_dune())
@@ -100,6 +166,7 @@ mod tests {
           txid: Txid::all_zeros(),
           index: 0,
         }),
+        distribution: None,
       },
       r"<h1>BCGDENLQRQWDSLRUGSNLBTMFIJAV</h1>
 <iframe .* src=/preview/0{64}i0></iframe>
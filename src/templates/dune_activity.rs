@@ -0,0 +1,27 @@
+use {
+  super::*,
+  crate::index::{DuneEvent, DuneEventId},
+};
+
+#[derive(Boilerplate)]
+pub(crate) struct DuneActivityHtml {
+  pub(crate) entry: DuneEntry,
+  pub(crate) events: Vec<DuneEvent>,
+  pub(crate) prev: Option<DuneEventId>,
+  pub(crate) next: Option<DuneEventId>,
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+pub(crate) struct DuneActivityJson {
+  pub(crate) dune: SpacedDune,
+  pub(crate) id: DuneId,
+  pub(crate) events: Vec<DuneEvent>,
+  pub(crate) prev: Option<String>,
+  pub(crate) next: Option<String>,
+}
+
+impl PageContent for DuneActivityHtml {
+  fn title(&self) -> String {
+    format!("Dune {} Activity", self.entry.spaced_dune())
+  }
+}
@@ -3,6 +3,26 @@ use super::*;
 #[derive(Boilerplate)]
 pub(crate) struct DunesHtml {
   pub(crate) entries: Vec<(DuneId, DuneEntry)>,
+  pub(crate) minimum: Dune,
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+pub(crate) struct DunesJson {
+  pub(crate) entries: Vec<(DuneId, DuneEntryJson)>,
+  pub(crate) minimum: Dune,
+}
+
+impl DunesHtml {
+  pub(crate) fn to_json(&self) -> DunesJson {
+    DunesJson {
+      entries: self
+        .entries
+        .iter()
+        .map(|(id, entry)| (*id, (*entry).into()))
+        .collect(),
+      minimum: self.minimum,
+    }
+  }
 }
 
 impl PageContent for DunesHtml {
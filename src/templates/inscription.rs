@@ -19,6 +19,49 @@ pub(crate) struct InscriptionHtml {
   pub(crate) timestamp: DateTime<Utc>,
 }
 
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+pub(crate) struct InscriptionJson {
+  pub(crate) address: Option<String>,
+  pub(crate) content_length: Option<usize>,
+  pub(crate) content_type: Option<String>,
+  pub(crate) genesis_fee: u64,
+  pub(crate) genesis_height: u32,
+  pub(crate) inscription_id: InscriptionId,
+  pub(crate) inscription_number: u64,
+  pub(crate) next: Option<InscriptionId>,
+  pub(crate) output_value: u64,
+  pub(crate) previous: Option<InscriptionId>,
+  pub(crate) dune: Option<SpacedDune>,
+  pub(crate) sat: Option<Sat>,
+  pub(crate) satpoint: SatPoint,
+  pub(crate) timestamp: i64,
+}
+
+impl InscriptionHtml {
+  pub(crate) fn to_json(&self) -> InscriptionJson {
+    InscriptionJson {
+      address: self
+        .chain
+        .address_from_script(&self.output.script_pubkey)
+        .ok()
+        .map(|address| address.to_string()),
+      content_length: self.inscription.content_length(),
+      content_type: self.inscription.content_type().map(str::to_string),
+      genesis_fee: self.genesis_fee,
+      genesis_height: self.genesis_height,
+      inscription_id: self.inscription_id,
+      inscription_number: self.inscription_number,
+      next: self.next,
+      output_value: self.output.value,
+      previous: self.previous,
+      dune: self.dune,
+      sat: self.sat,
+      satpoint: self.satpoint,
+      timestamp: self.timestamp.timestamp(),
+    }
+  }
+}
+
 impl PageContent for InscriptionHtml {
   fn title(&self) -> String {
     format!("Shibescription {}", self.inscription_number)
@@ -166,4 +209,39 @@ mod tests {
       .unindent()
     );
   }
+
+  #[test]
+  fn json() {
+    assert_eq!(
+      InscriptionHtml {
+        genesis_fee: 1,
+        inscription: inscription("text/plain;charset=utf-8", "HELLOWORLD"),
+        inscription_id: inscription_id(2),
+        next: Some(inscription_id(3)),
+        inscription_number: 1,
+        output: tx_out(1, address()),
+        previous: Some(inscription_id(1)),
+        sat: Some(Sat(1)),
+        satpoint: satpoint(1, 0),
+        ..Default::default()
+      }
+      .to_json(),
+      InscriptionJson {
+        address: Some("bc1qw508d6qejxtdg4y5r3zarvary0c5xw7kv8f3t4".into()),
+        content_length: Some(10),
+        content_type: Some("text/plain;charset=utf-8".into()),
+        genesis_fee: 1,
+        genesis_height: 0,
+        inscription_id: inscription_id(2),
+        inscription_number: 1,
+        next: Some(inscription_id(3)),
+        output_value: 1,
+        previous: Some(inscription_id(1)),
+        dune: None,
+        sat: Some(Sat(1)),
+        satpoint: satpoint(1, 0),
+        timestamp: 0,
+      }
+    );
+  }
 }
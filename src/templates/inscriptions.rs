@@ -7,6 +7,23 @@ pub(crate) struct InscriptionsHtml {
   pub(crate) next: Option<u64>,
 }
 
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+pub(crate) struct InscriptionsJson {
+  pub(crate) inscriptions: Vec<InscriptionId>,
+  pub(crate) prev: Option<u64>,
+  pub(crate) next: Option<u64>,
+}
+
+impl InscriptionsHtml {
+  pub(crate) fn to_json(&self) -> InscriptionsJson {
+    InscriptionsJson {
+      inscriptions: self.inscriptions.clone(),
+      prev: self.prev,
+      next: self.next,
+    }
+  }
+}
+
 impl PageContent for InscriptionsHtml {
   fn title(&self) -> String {
     "Shibescription".into()
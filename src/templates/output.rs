@@ -10,6 +10,18 @@ pub(crate) struct OutputHtml {
   pub(crate) dunes: Vec<(SpacedDune, Pile)>,
 }
 
+impl OutputHtml {
+  pub(crate) fn to_json(&self) -> OutputJson {
+    OutputJson::new(
+      self.chain,
+      self.inscriptions.clone(),
+      self.outpoint,
+      self.output.clone(),
+      self.dunes.clone(),
+    )
+  }
+}
+
 impl PageContent for OutputHtml {
   fn title(&self) -> String {
     format!("Output {}", self.outpoint)
@@ -10,6 +10,35 @@ pub(crate) struct SatHtml {
   pub(crate) inscription: Option<InscriptionId>,
 }
 
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+pub(crate) struct SatJson {
+  pub(crate) number: u64,
+  pub(crate) decimal: String,
+  pub(crate) epoch: u32,
+  pub(crate) block: u32,
+  pub(crate) offset: u64,
+  pub(crate) rarity: Rarity,
+  pub(crate) timestamp: i64,
+  pub(crate) inscription: Option<InscriptionId>,
+  pub(crate) satpoint: Option<SatPoint>,
+}
+
+impl SatHtml {
+  pub(crate) fn to_json(&self) -> SatJson {
+    SatJson {
+      number: self.sat.n(),
+      decimal: self.sat.decimal().to_string(),
+      epoch: self.sat.epoch().0,
+      block: self.sat.height().n(),
+      offset: self.sat.third(),
+      rarity: self.sat.rarity(),
+      timestamp: self.blocktime.unix_timestamp(),
+      inscription: self.inscription,
+      satpoint: self.satpoint,
+    }
+  }
+}
+
 impl PageContent for SatHtml {
   fn title(&self) -> String {
     format!("Sat {}", self.sat)
@@ -86,4 +115,28 @@ mod tests {
       "<h1>Sat 0</h1>.*<dt>location</dt><dd class=monospace>1{64}:1:0</dd>.*",
     );
   }
+
+  #[test]
+  fn json() {
+    assert_eq!(
+      SatHtml {
+        sat: Sat(0),
+        satpoint: Some(satpoint(1, 0)),
+        blocktime: Blocktime::confirmed(0),
+        inscription: Some(inscription_id(1)),
+      }
+      .to_json(),
+      SatJson {
+        number: 0,
+        decimal: "0.0".into(),
+        epoch: 0,
+        block: 0,
+        offset: 0,
+        rarity: Rarity::Mythic,
+        timestamp: 0,
+        inscription: Some(inscription_id(1)),
+        satpoint: Some(satpoint(1, 0)),
+      }
+    );
+  }
 }
@@ -3,6 +3,7 @@ use super::*;
 #[derive(Boilerplate)]
 pub(crate) struct TransactionHtml {
   blockhash: Option<BlockHash>,
+  cenotaph: Option<Cenotaph>,
   confirmations: Option<u32>,
   chain: Chain,
   etching: Option<SpacedDune>,
@@ -11,6 +12,26 @@ pub(crate) struct TransactionHtml {
   txid: Txid,
 }
 
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+pub(crate) struct TransactionJson {
+  pub(crate) blockhash: Option<BlockHash>,
+  pub(crate) cenotaph: Option<Cenotaph>,
+  pub(crate) confirmations: Option<u32>,
+  pub(crate) etching: Option<SpacedDune>,
+  pub(crate) inscription: Option<InscriptionId>,
+  pub(crate) inputs: Vec<OutPoint>,
+  pub(crate) outputs: Vec<TransactionOutputJson>,
+  pub(crate) txid: Txid,
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+pub(crate) struct TransactionOutputJson {
+  pub(crate) address: Option<String>,
+  pub(crate) outpoint: OutPoint,
+  pub(crate) script_pubkey: String,
+  pub(crate) value: u64,
+}
+
 impl TransactionHtml {
   pub(crate) fn new(
     transaction: Transaction,
@@ -19,10 +40,12 @@ impl TransactionHtml {
     inscription: Option<InscriptionId>,
     chain: Chain,
     etching: Option<SpacedDune>,
+    cenotaph: Option<Cenotaph>,
   ) -> Self {
     Self {
       txid: transaction.txid(),
       blockhash,
+      cenotaph,
       confirmations,
       chain,
       etching,
@@ -30,6 +53,39 @@ impl TransactionHtml {
       transaction,
     }
   }
+
+  pub(crate) fn to_json(&self) -> TransactionJson {
+    TransactionJson {
+      blockhash: self.blockhash,
+      cenotaph: self.cenotaph,
+      confirmations: self.confirmations,
+      etching: self.etching,
+      inscription: self.inscription,
+      inputs: self
+        .transaction
+        .input
+        .iter()
+        .map(|input| input.previous_output)
+        .collect(),
+      outputs: self
+        .transaction
+        .output
+        .iter()
+        .enumerate()
+        .map(|(vout, output)| TransactionOutputJson {
+          address: self
+            .chain
+            .address_from_script(&output.script_pubkey)
+            .ok()
+            .map(|address| address.to_string()),
+          outpoint: OutPoint::new(self.txid, vout.try_into().unwrap()),
+          script_pubkey: output.script_pubkey.asm(),
+          value: output.value,
+        })
+        .collect(),
+      txid: self.txid,
+    }
+  }
 }
 
 impl PageContent for TransactionHtml {
@@ -137,4 +193,42 @@ mod tests {
       .unindent()
     );
   }
+
+  #[test]
+  fn with_cenotaph() {
+    let transaction = Transaction {
+      version: 0,
+      lock_time: PackedLockTime(0),
+      input: Vec::new(),
+      output: Vec::new(),
+    };
+
+    assert_regex_match!(
+      TransactionHtml::new(
+        transaction,
+        None,
+        None,
+        None,
+        Chain::Mainnet,
+        None,
+        Some(Cenotaph {
+          etching: Some(Dune(4)),
+          flaw: Flaw::UnrecognizedEvenTag,
+        }),
+      ),
+      "
+        <h1>Transaction <span class=monospace>[[:xdigit:]]{64}</span></h1>
+        <h2>Cenotaph</h2>
+        <dl>
+          <dt>flaw</dt>
+          <dd>unrecognized even tag</dd>
+          <dt>etching</dt>
+          <dd><a href=/dune/E>E</a></dd>
+        </dl>
+        <p>All dunes in this transaction's inputs were burned.</p>
+        .*
+      "
+      .unindent()
+    );
+  }
 }
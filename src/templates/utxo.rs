@@ -7,4 +7,5 @@ pub(crate) struct Utxo {
     pub(crate) script: Script,
     pub(crate) shibes: u64,
     pub(crate) confirmations: Option<u32>,
+    pub(crate) spent_in_mempool: bool,
 }
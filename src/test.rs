@@ -85,6 +85,11 @@ pub(crate) fn change(n: u64) -> Address {
   .unwrap()
 }
 
+/// A P2PKH script, whose address is recorded as a dune holder
+pub(crate) fn p2pkh(n: u8) -> Script {
+  Script::new_p2pkh(&bitcoin::PubkeyHash::from_inner([n; 20]))
+}
+
 pub(crate) fn tx_in(previous_output: OutPoint) -> TxIn {
   TxIn {
     previous_output,
@@ -1,14 +1,177 @@
-use super::*;
+use {
+  super::*,
+  bitcoin::{secp256k1::Secp256k1, PrivateKey, PublicKey},
+  bitcoincore_rpc::RawTx,
+  native::NativeWallet,
+};
 
-#[derive(Copy, Clone)]
-pub(crate) struct Wallet {
-  _private: (),
+mod native;
+
+/// The wallet used by wallet commands. Core wallets keep their keys in
+/// Dogecoin Core and use it for addresses, funding and signing. Native
+/// wallets keep their keys in ord's data directory, track their outputs in
+/// the index and only use Dogecoin Core to broadcast.
+pub(crate) enum Wallet {
+  Core,
+  Native(NativeWallet),
 }
 
 impl Wallet {
   pub(crate) fn load(options: &Options) -> Result<Self> {
+    let path = options.native_wallet_path()?;
+
+    if path.exists() {
+      return Ok(Self::Native(NativeWallet::load(&path, options.chain())?));
+    }
+
     options.dogecoin_rpc_client_for_wallet_command(false)?;
 
-    Ok(Self { _private: () })
+    Ok(Self::Core)
+  }
+
+  pub(crate) fn create_native(options: &Options, seed: [u8; 64]) -> Result {
+    NativeWallet::create(&options.native_wallet_path()?, options.chain(), seed)
+  }
+
+  pub(crate) fn get_change_address(&mut self, client: &Client) -> Result<Address> {
+    match self {
+      Self::Core => client
+        .call("getrawchangeaddress", &["bech32m".into()])
+        .context("could not get change addresses from wallet"),
+      Self::Native(wallet) => wallet.next_address(true),
+    }
+  }
+
+  pub(crate) fn get_receive_address(&mut self, client: &Client) -> Result<Address> {
+    match self {
+      Self::Core => {
+        Ok(client.get_new_address(None, Some(bitcoincore_rpc::json::AddressType::Bech32m))?)
+      }
+      Self::Native(wallet) => wallet.next_address(false),
+    }
+  }
+
+  /// Lock the outputs in `outputs` that hold inscriptions or dunes, so that
+  /// Dogecoin Core does not select them when funding a transaction, which
+  /// would move them to the transaction's outputs. Native wallets never
+  /// select them, so nothing is locked.
+  pub(crate) fn lock_non_cardinal_outputs(
+    &self,
+    index: &Index,
+    client: &Client,
+    outputs: &BTreeMap<OutPoint, Amount>,
+  ) -> Result {
+    if let Self::Native(_) = self {
+      return Ok(());
+    }
+
+    let outpoints = outputs.keys().copied().collect::<Vec<OutPoint>>();
+
+    let mut locked = index.get_dunic_outputs(&outpoints)?;
+
+    for outpoint in outpoints {
+      if !index.get_inscriptions_on_output(outpoint)?.is_empty() {
+        locked.insert(outpoint);
+      }
+    }
+
+    if !client.lock_unspent(&locked.into_iter().collect::<Vec<OutPoint>>())? {
+      bail!("failed to lock UTXOs");
+    }
+
+    Ok(())
+  }
+
+  /// Add inputs and a change output after the existing outputs so that
+  /// `unfunded_transaction` pays `fee_rate`, returning the serialized,
+  /// unsigned transaction
+  pub(crate) fn fund_transaction(
+    &mut self,
+    index: &Index,
+    client: &Client,
+    fee_rate: FeeRate,
+    unfunded_transaction: &Transaction,
+  ) -> Result<Vec<u8>> {
+    match self {
+      Self::Core => fund_raw_transaction(client, fee_rate, unfunded_transaction),
+      Self::Native(wallet) => Ok(consensus::encode::serialize(&wallet.fund_transaction(
+        index,
+        fee_rate,
+        unfunded_transaction,
+      )?)),
+    }
+  }
+
+  /// Sign all of the wallet's inputs of `transaction`, returning the
+  /// serialized, signed transaction
+  pub(crate) fn sign_transaction<R: RawTx>(
+    &mut self,
+    index: &Index,
+    client: &Client,
+    transaction: R,
+  ) -> Result<Vec<u8>> {
+    match self {
+      Self::Core => Ok(
+        client
+          .sign_raw_transaction_with_wallet(transaction, None, None)?
+          .hex,
+      ),
+      Self::Native(wallet) => {
+        let transaction: Transaction =
+          consensus::encode::deserialize(&hex::decode(transaction.raw_hex())?)?;
+        Ok(consensus::encode::serialize(
+          &wallet.sign_transaction(index, &transaction)?,
+        ))
+      }
+    }
+  }
+
+  /// Save the key locking an inscription's reveal chain, so that the chain
+  /// can be abandoned if it stalls
+  pub(crate) fn backup_recovery_key(
+    &mut self,
+    client: &Client,
+    recovery_private_key: PrivateKey,
+  ) -> Result {
+    if let Self::Native(wallet) = self {
+      return wallet.backup_recovery_key(recovery_private_key);
+    }
+
+    client.import_private_key(
+      &recovery_private_key,
+      Some("inscription recovery key"),
+      Some(false),
+    )?;
+
+    Ok(())
+  }
+
+  /// Returns the inscription recovery key for `public_key` backed up by the
+  /// wallet, if any. Dogecoin Core wallets are asked for the key of the
+  /// public key's P2PKH address.
+  pub(crate) fn recovery_key(
+    &self,
+    client: &Client,
+    network: Network,
+    public_key: PublicKey,
+  ) -> Result<Option<PrivateKey>> {
+    if let Self::Native(wallet) = self {
+      let secp256k1 = Secp256k1::new();
+
+      return Ok(
+        wallet
+          .recovery_keys()?
+          .into_iter()
+          .find(|private_key| private_key.public_key(&secp256k1) == public_key),
+      );
+    }
+
+    match client.dump_private_key(&Address::p2pkh(&public_key, network)) {
+      Ok(private_key) => Ok(Some(private_key)),
+      Err(bitcoincore_rpc::Error::JsonRpc(bitcoincore_rpc::jsonrpc::error::Error::Rpc(
+        bitcoincore_rpc::jsonrpc::error::RpcError { code: -4, .. },
+      ))) => Ok(None),
+      Err(err) => Err(err.into()),
+    }
   }
 }
@@ -0,0 +1,569 @@
+use {
+  super::*,
+  bitcoin::{
+    secp256k1::{self, All, Secp256k1},
+    util::bip32::{ChildNumber, DerivationPath, ExtendedPrivKey},
+    EcdsaSig, EcdsaSighashType, PrivateKey, PublicKey,
+  },
+  std::cmp::Reverse,
+};
+
+/// Number of unused addresses past the last one handed out that are scanned
+/// for outputs, so that restored wallets find outputs sent to addresses
+/// handed out before the wallet was restored.
+const GAP_LIMIT: u32 = 20;
+
+/// Size of a P2PKH script signature: a low R DER signature with its sighash
+/// byte and a compressed public key, each with their push opcode.
+const SCRIPT_SIG_SIZE: usize = 1 + 71 + 1 + 33;
+
+#[derive(Serialize, Deserialize)]
+struct State {
+  /// Extended private key of the BIP44 account, `m/44'/<COIN>'/0'`
+  account: String,
+  /// Index of the next unused receive address
+  receive: u32,
+  /// Index of the next unused change address
+  change: u32,
+  /// Inscription recovery keys, in WIF
+  #[serde(default)]
+  recovery: Vec<String>,
+}
+
+struct Key {
+  address: Address,
+  private_key: PrivateKey,
+  public_key: PublicKey,
+}
+
+/// A wallet whose keys are derived from a BIP39 seed and held by ord rather
+/// than Dogecoin Core. Keys are P2PKH keys at `m/44'/<COIN>'/0'/<CHANGE>/<N>`.
+///
+/// Outputs are read from the index. Transactions signed by the wallet are
+/// remembered until the wallet is dropped, so that a command can fund and
+/// sign a chain of transactions before any of them confirm.
+///
+/// The wallet is stored as plaintext JSON, so the account's extended private
+/// key and any recovery keys are only protected by the file's permissions,
+/// which are set to be readable by its owner only on Unix.
+pub(crate) struct NativeWallet {
+  account: ExtendedPrivKey,
+  network: Network,
+  path: PathBuf,
+  secp: Secp256k1<All>,
+  spent: HashSet<OutPoint>,
+  state: State,
+  unconfirmed: BTreeMap<OutPoint, (Amount, bool, u32)>,
+}
+
+impl NativeWallet {
+  pub(crate) fn create(path: &Path, chain: Chain, seed: [u8; 64]) -> Result {
+    ensure!(
+      !path.exists(),
+      "native wallet already exists at `{}`",
+      path.display()
+    );
+
+    let secp = Secp256k1::new();
+
+    let network = chain.network();
+
+    let coin = if network == Network::Bitcoin { 3 } else { 1 };
+
+    let account = ExtendedPrivKey::new_master(network, &seed)?.derive_priv(
+      &secp,
+      &DerivationPath::master()
+        .child(ChildNumber::Hardened { index: 44 })
+        .child(ChildNumber::Hardened { index: coin })
+        .child(ChildNumber::Hardened { index: 0 }),
+    )?;
+
+    Self {
+      account,
+      network,
+      path: path.into(),
+      secp,
+      spent: HashSet::new(),
+      state: State {
+        account: account.to_string(),
+        receive: 0,
+        change: 0,
+        recovery: Vec::new(),
+      },
+      unconfirmed: BTreeMap::new(),
+    }
+    .save()
+  }
+
+  pub(crate) fn load(path: &Path, chain: Chain) -> Result<Self> {
+    let state = serde_json::from_str::<State>(
+      &fs::read_to_string(path)
+        .with_context(|| format!("failed to read native wallet `{}`", path.display()))?,
+    )
+    .with_context(|| format!("failed to parse native wallet `{}`", path.display()))?;
+
+    Ok(Self {
+      account: state.account.parse()?,
+      network: chain.network(),
+      path: path.into(),
+      secp: Secp256k1::new(),
+      spent: HashSet::new(),
+      state,
+      unconfirmed: BTreeMap::new(),
+    })
+  }
+
+  fn save(&self) -> Result {
+    if let Some(parent) = self.path.parent() {
+      fs::create_dir_all(parent)?;
+    }
+
+    fs::write(&self.path, serde_json::to_string_pretty(&self.state)?)?;
+
+    #[cfg(unix)]
+    {
+      use std::os::unix::fs::PermissionsExt;
+      fs::set_permissions(&self.path, fs::Permissions::from_mode(0o600))?;
+    }
+
+    Ok(())
+  }
+
+  fn key(&self, change: bool, index: u32) -> Result<Key> {
+    let private_key = self
+      .account
+      .derive_priv(
+        &self.secp,
+        &[
+          ChildNumber::Normal {
+            index: change.into(),
+          },
+          ChildNumber::Normal { index },
+        ],
+      )?
+      .to_priv();
+
+    let public_key = private_key.public_key(&self.secp);
+
+    Ok(Key {
+      address: Address::p2pkh(&public_key, self.network),
+      private_key,
+      public_key,
+    })
+  }
+
+  /// Hand out the next unused receive or change address
+  pub(crate) fn next_address(&mut self, change: bool) -> Result<Address> {
+    let index = if change {
+      &mut self.state.change
+    } else {
+      &mut self.state.receive
+    };
+
+    let n = *index;
+    *index += 1;
+
+    self.save()?;
+
+    Ok(self.key(change, n)?.address)
+  }
+
+  pub(crate) fn backup_recovery_key(&mut self, private_key: PrivateKey) -> Result {
+    self.state.recovery.push(private_key.to_wif());
+    self.save()
+  }
+
+  pub(crate) fn recovery_keys(&self) -> Result<Vec<PrivateKey>> {
+    self
+      .state
+      .recovery
+      .iter()
+      .map(|wif| Ok(PrivateKey::from_wif(wif)?))
+      .collect()
+  }
+
+  /// Keys that are scanned for outputs, by chain and index
+  fn keys(&self) -> Result<Vec<(bool, u32, Key)>> {
+    let mut keys = Vec::new();
+
+    for (change, next) in [(false, self.state.receive), (true, self.state.change)] {
+      for n in 0..next + GAP_LIMIT {
+        keys.push((change, n, self.key(change, n)?));
+      }
+    }
+
+    Ok(keys)
+  }
+
+  /// Unspent outputs of the wallet's addresses, along with the chain and
+  /// index of the key that can spend them
+  fn outputs(&self, index: &Index) -> Result<BTreeMap<OutPoint, (Amount, bool, u32)>> {
+    let mut outputs = self.unconfirmed.clone();
+
+    for (change, n, key) in self.keys()? {
+      // The index keys addresses by their mainnet encoding
+      let address = Chain::Mainnet
+        .address_from_script(&key.address.script_pubkey())?
+        .to_string();
+
+      let (address_outputs, _) =
+        index.get_address_outputs(&address, None, usize::MAX, usize::MAX, |_, _| Ok(true))?;
+
+      for (_, outpoint, value) in address_outputs {
+        outputs.insert(outpoint, (Amount::from_sat(value), change, n));
+      }
+    }
+
+    outputs.retain(|outpoint, _| !self.spent.contains(outpoint));
+
+    Ok(outputs)
+  }
+
+  pub(crate) fn get_unspent_outputs(&self, index: &Index) -> Result<BTreeMap<OutPoint, Amount>> {
+    Ok(
+      self
+        .outputs(index)?
+        .into_iter()
+        .map(|(outpoint, (amount, _, _))| (outpoint, amount))
+        .collect(),
+    )
+  }
+
+  /// Whether `outpoint` may be added to a transaction to fund it. Outputs
+  /// holding inscriptions are never added, nor are confirmed outputs spent by
+  /// a transaction in Dogecoin Core's mempool, which the index doesn't see.
+  fn is_cardinal(&self, index: &Index, outpoint: OutPoint) -> Result<bool> {
+    if !index.get_inscriptions_on_output(outpoint)?.is_empty() {
+      return Ok(false);
+    }
+
+    // Outputs of transactions signed by this wallet may not be broadcast yet
+    if self.unconfirmed.contains_key(&outpoint) {
+      return Ok(true);
+    }
+
+    index.is_unspent_including_mempool(outpoint)
+  }
+
+  /// Add cardinal inputs, largest first, and a change output after the
+  /// existing outputs until `unfunded` pays `fee_rate`. Outputs holding
+  /// inscriptions or dunes are never added.
+  pub(crate) fn fund_transaction(
+    &mut self,
+    index: &Index,
+    fee_rate: FeeRate,
+    unfunded: &Transaction,
+  ) -> Result<Transaction> {
+    let outputs = self.get_unspent_outputs(index)?;
+
+    let dunic = index.get_dunic_outputs(&outputs.keys().copied().collect::<Vec<OutPoint>>())?;
+
+    let mut transaction = unfunded.clone();
+
+    let mut input_value = 0;
+
+    for input in &transaction.input {
+      input_value += outputs
+        .get(&input.previous_output)
+        .with_context(|| format!("input {} is not a wallet output", input.previous_output))?
+        .to_sat();
+    }
+
+    let output_value = transaction
+      .output
+      .iter()
+      .map(|output| output.value)
+      .sum::<u64>();
+
+    let mut cardinal = outputs
+      .iter()
+      .filter(|(outpoint, _)| {
+        !dunic.contains(outpoint)
+          && !transaction
+            .input
+            .iter()
+            .any(|input| input.previous_output == **outpoint)
+      })
+      .map(|(outpoint, amount)| (*outpoint, amount.to_sat()))
+      .collect::<Vec<(OutPoint, u64)>>();
+
+    cardinal.sort_by_key(|(_, value)| Reverse(*value));
+
+    let mut cardinal = cardinal.into_iter();
+
+    let change = self.next_address(true)?.script_pubkey();
+
+    transaction.output.push(TxOut {
+      script_pubkey: change.clone(),
+      value: 0,
+    });
+
+    loop {
+      let fee = fee_rate
+        .fee(transaction.vsize() + transaction.input.len() * SCRIPT_SIG_SIZE)
+        .to_sat();
+
+      if input_value >= output_value + fee {
+        let change_value = input_value - output_value - fee;
+
+        if change_value >= change.dust_value().to_sat() {
+          transaction.output.last_mut().unwrap().value = change_value;
+        } else {
+          transaction.output.pop();
+        }
+
+        break;
+      }
+
+      let (outpoint, value) = loop {
+        let (outpoint, value) = cardinal.next().context(
+          "wallet does not contain enough cardinal UTXOs, please add additional funds to wallet.",
+        )?;
+
+        if self.is_cardinal(index, outpoint)? {
+          break (outpoint, value);
+        }
+      };
+
+      transaction.input.push(TxIn {
+        previous_output: outpoint,
+        script_sig: Script::new(),
+        sequence: Sequence::MAX,
+        witness: Witness::new(),
+      });
+
+      input_value += value;
+    }
+
+    Ok(transaction)
+  }
+
+  /// Sign every input of `transaction`, all of which must spend wallet
+  /// outputs, and remember the outputs it spends and creates
+  pub(crate) fn sign_transaction(
+    &mut self,
+    index: &Index,
+    transaction: &Transaction,
+  ) -> Result<Transaction> {
+    let outputs = self.outputs(index)?;
+
+    let mut signed = transaction.clone();
+
+    for (i, input) in transaction.input.iter().enumerate() {
+      let (_, change, n) = outputs.get(&input.previous_output).with_context(|| {
+        format!(
+          "cannot sign input {}: not a wallet output",
+          input.previous_output
+        )
+      })?;
+
+      let key = self.key(*change, *n)?;
+
+      let signature_hash = transaction.signature_hash(
+        i,
+        &key.address.script_pubkey(),
+        EcdsaSighashType::All.to_u32(),
+      );
+
+      let signature = EcdsaSig::sighash_all(
+        self.secp.sign_ecdsa_low_r(
+          &secp256k1::Message::from_slice(&signature_hash[..])
+            .expect("should be cryptographically secure hash"),
+          &key.private_key.inner,
+        ),
+      );
+
+      signed.input[i].script_sig = script::Builder::new()
+        .push_slice(&signature.to_vec())
+        .push_key(&key.public_key)
+        .into_script();
+    }
+
+    let txid = signed.txid();
+
+    let keys = self.keys()?;
+
+    for (vout, output) in signed.output.iter().enumerate() {
+      if let Some((change, n, _)) = keys
+        .iter()
+        .find(|(_, _, key)| key.address.script_pubkey() == output.script_pubkey)
+      {
+        self.unconfirmed.insert(
+          OutPoint {
+            txid,
+            vout: vout.try_into().unwrap(),
+          },
+          (Amount::from_sat(output.value), *change, *n),
+        );
+      }
+    }
+
+    self
+      .spent
+      .extend(signed.input.iter().map(|input| input.previous_output));
+
+    Ok(signed)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use {
+    super::*,
+    crate::index::testing::Context,
+    bitcoin::blockdata::{constants::COIN_VALUE, script::Instruction},
+    tempfile::TempDir,
+    test_bitcoincore_rpc::TransactionTemplate,
+  };
+
+  #[test]
+  fn addresses_are_derived_deterministically_and_persisted() {
+    let dir = TempDir::new().unwrap();
+    let path = dir.path().join("wallets/ord.json");
+
+    NativeWallet::create(&path, Chain::Mainnet, [0; 64]).unwrap();
+
+    assert!(NativeWallet::create(&path, Chain::Mainnet, [0; 64]).is_err());
+
+    let mut wallet = NativeWallet::load(&path, Chain::Mainnet).unwrap();
+
+    let first = wallet.next_address(false).unwrap();
+    let change = wallet.next_address(true).unwrap();
+
+    assert_ne!(first, change);
+
+    let mut wallet = NativeWallet::load(&path, Chain::Mainnet).unwrap();
+
+    let second = wallet.next_address(false).unwrap();
+
+    assert_ne!(first, second);
+    assert_eq!(first, wallet.key(false, 0).unwrap().address);
+    assert_eq!(second, wallet.key(false, 1).unwrap().address);
+  }
+
+  #[test]
+  fn script_sig_size_matches_p2pkh_script_sig() {
+    let dir = TempDir::new().unwrap();
+    let path = dir.path().join("ord.json");
+
+    NativeWallet::create(&path, Chain::Mainnet, [0; 64]).unwrap();
+
+    let wallet = NativeWallet::load(&path, Chain::Mainnet).unwrap();
+
+    let key = wallet.key(false, 0).unwrap();
+
+    let script_sig = script::Builder::new()
+      .push_slice(&[0; 71])
+      .push_key(&key.public_key)
+      .into_script();
+
+    assert_eq!(script_sig.len(), SCRIPT_SIG_SIZE);
+    assert!(key.address.script_pubkey().is_p2pkh());
+  }
+
+  #[test]
+  fn transactions_are_funded_and_signed_without_outputs_spent_in_mempool() {
+    let context = Context::builder().build();
+    let path = context.tempdir.path().join("ord.json");
+
+    NativeWallet::create(&path, Chain::Regtest, [0; 64]).unwrap();
+
+    let mut wallet = NativeWallet::load(&path, Chain::Regtest).unwrap();
+
+    let address = wallet.next_address(false).unwrap();
+
+    context.mine_blocks(1);
+
+    let txid = context.rpc_server.broadcast_tx(TransactionTemplate {
+      inputs: &[(1, 0, 0)],
+      output_scripts: &[address.script_pubkey()],
+      ..Default::default()
+    });
+
+    context.mine_blocks(1);
+
+    let unfunded = Transaction {
+      version: 1,
+      lock_time: PackedLockTime::ZERO,
+      input: Vec::new(),
+      output: vec![TxOut {
+        script_pubkey: address.script_pubkey(),
+        value: COIN_VALUE,
+      }],
+    };
+
+    let fee_rate = FeeRate::try_from(1.0).unwrap();
+
+    let funded = wallet
+      .fund_transaction(&context.index, fee_rate, &unfunded)
+      .unwrap();
+
+    assert_eq!(funded.input.len(), 1);
+    assert_eq!(funded.input[0].previous_output, OutPoint { txid, vout: 0 });
+    assert_eq!(funded.output.len(), 2);
+    assert_eq!(
+      funded.output[1].value,
+      50 * COIN_VALUE - COIN_VALUE - fee_rate.fee(funded.vsize() + SCRIPT_SIG_SIZE).to_sat(),
+    );
+
+    let signed = wallet.sign_transaction(&context.index, &funded).unwrap();
+
+    assert!(signed.vsize() <= funded.vsize() + SCRIPT_SIG_SIZE);
+
+    let instructions = signed.input[0]
+      .script_sig
+      .instructions()
+      .collect::<Result<Vec<Instruction>, script::Error>>()
+      .unwrap();
+
+    let [Instruction::PushBytes(signature), Instruction::PushBytes(public_key)] =
+      instructions.as_slice()
+    else {
+      panic!("unexpected script sig {}", signed.input[0].script_sig);
+    };
+
+    let public_key = PublicKey::from_slice(public_key).unwrap();
+
+    assert_eq!(Address::p2pkh(&public_key, Network::Regtest), address);
+
+    Secp256k1::verification_only()
+      .verify_ecdsa(
+        &secp256k1::Message::from_slice(
+          &signed.signature_hash(0, &address.script_pubkey(), EcdsaSighashType::All.to_u32())[..],
+        )
+        .unwrap(),
+        &EcdsaSig::from_slice(signature).unwrap().sig,
+        &public_key.inner,
+      )
+      .unwrap();
+
+    context
+      .options
+      .dogecoin_rpc_client()
+      .unwrap()
+      .send_raw_transaction(&signed)
+      .unwrap();
+
+    let funded = wallet
+      .fund_transaction(&context.index, fee_rate, &unfunded)
+      .unwrap();
+
+    assert_eq!(
+      funded.input[0].previous_output,
+      OutPoint {
+        txid: signed.txid(),
+        vout: 1,
+      },
+    );
+
+    let mut wallet = NativeWallet::load(&path, Chain::Regtest).unwrap();
+
+    assert_eq!(
+      wallet
+        .fund_transaction(&context.index, fee_rate, &unfunded)
+        .unwrap_err()
+        .to_string(),
+      "wallet does not contain enough cardinal UTXOs, please add additional funds to wallet.",
+    );
+  }
+}
@@ -89,6 +89,9 @@ pub trait Api {
     include_watchonly: Option<bool>,
   ) -> Result<Value, jsonrpc_core::Error>;
 
+  #[rpc(name = "getrawmempool")]
+  fn get_raw_mempool(&self) -> Result<Vec<Txid>, jsonrpc_core::Error>;
+
   #[rpc(name = "getrawtransaction")]
   fn get_raw_transaction(
     &self,
@@ -97,6 +100,14 @@ pub trait Api {
     blockhash: Option<BlockHash>,
   ) -> Result<Value, jsonrpc_core::Error>;
 
+  #[rpc(name = "gettxout")]
+  fn get_tx_out(
+    &self,
+    txid: Txid,
+    vout: u32,
+    include_mempool: Option<bool>,
+  ) -> Result<Option<Value>, jsonrpc_core::Error>;
+
   #[rpc(name = "listunspent")]
   fn list_unspent(
     &self,
@@ -128,6 +139,17 @@ pub trait Api {
     req: Vec<ImportDescriptors>,
   ) -> Result<Vec<ImportMultiResult>, jsonrpc_core::Error>;
 
+  #[rpc(name = "importprivkey")]
+  fn import_private_key(
+    &self,
+    privkey: String,
+    label: Option<String>,
+    rescan: Option<bool>,
+  ) -> Result<(), jsonrpc_core::Error>;
+
+  #[rpc(name = "dumpprivkey")]
+  fn dump_private_key(&self, address: bitcoin::Address) -> Result<String, jsonrpc_core::Error>;
+
   #[rpc(name = "getnewaddress")]
   fn get_new_address(
     &self,
@@ -152,7 +174,10 @@ pub trait Api {
   ) -> Result<bool, jsonrpc_core::Error>;
 
   #[rpc(name = "listdescriptors")]
-  fn list_descriptors(&self) -> Result<ListDescriptorsResult, jsonrpc_core::Error>;
+  fn list_descriptors(
+    &self,
+    _private: Option<bool>,
+  ) -> Result<ListDescriptorsResult, jsonrpc_core::Error>;
 
   #[rpc(name = "loadwallet")]
   fn load_wallet(&self, wallet: String) -> Result<LoadWalletResult, jsonrpc_core::Error>;
@@ -9,11 +9,12 @@ use {
     hash_types::BlockHash,
     hashes::Hash,
     util::amount::SignedAmount,
-    Address, Amount, Block, BlockHeader, Network, OutPoint, PackedLockTime, Script, Sequence,
-    Transaction, TxIn, TxMerkleNode, TxOut, Txid, Witness, Wtxid,
+    Address, Amount, Block, BlockHeader, Network, OutPoint, PackedLockTime, PrivateKey, Script,
+    Sequence, Transaction, TxIn, TxMerkleNode, TxOut, Txid, Witness, Wtxid,
   },
   bitcoincore_rpc::json::{
-    Bip125Replaceable, CreateRawTransactionInput, Descriptor, EstimateMode, GetBalancesResult,
+    Bip125Replaceable, CreateRawTransactionInput, Descriptor, EstimateMode,
+    FundRawTransactionOptions, FundRawTransactionResult, GetBalancesResult,
     GetBalancesResultEntry, GetBlockHeaderResult, GetBlockchainInfoResult, GetDescriptorInfoResult,
     GetNetworkInfoResult, GetRawTransactionResult, GetTransactionResult,
     GetTransactionResultDetail, GetTransactionResultDetailCategory, GetWalletInfoResult,
@@ -118,8 +119,10 @@ pub struct TransactionTemplate<'a> {
   pub inputs: &'a [(usize, usize, usize)],
   pub op_return: Option<Script>,
   pub op_return_index: Option<usize>,
+  pub output_scripts: &'a [Script],
   pub output_values: &'a [u64],
   pub outputs: usize,
+  pub script_sig: Script,
   pub witness: Witness,
 }
 
@@ -152,8 +155,10 @@ impl<'a> Default for TransactionTemplate<'a> {
       inputs: &[],
       op_return: None,
       op_return_index: None,
+      output_scripts: &[],
       output_values: &[],
       outputs: 1,
+      script_sig: Script::new(),
       witness: Witness::default(),
     }
   }
@@ -210,10 +215,18 @@ impl Handle {
     self.state().mempool().to_vec()
   }
 
+  pub fn clear_mempool(&self) {
+    self.state().mempool.clear();
+  }
+
   pub fn descriptors(&self) -> Vec<String> {
     self.state().descriptors.clone()
   }
 
+  pub fn private_keys(&self) -> Vec<PrivateKey> {
+    self.state().private_keys.clone()
+  }
+
   pub fn import_descriptor(&self, desc: String) {
     self.state().descriptors.push(desc);
   }
@@ -1,6 +1,7 @@
 use {
   super::*,
   bitcoin::{
+    consensus::Decodable,
     psbt::serialize::Deserialize,
     secp256k1::{rand, KeyPair, Secp256k1, XOnlyPublicKey},
     Address, Witness,
@@ -234,6 +235,96 @@ impl Api for Server {
     })
   }
 
+  fn fund_raw_transaction(
+    &self,
+    tx: String,
+    options: Option<FundRawTransactionOptions>,
+    _is_witness: Option<bool>,
+  ) -> Result<FundRawTransactionResult, jsonrpc_core::Error> {
+    // Transactions without inputs are ambiguous with segwit serialization,
+    // so their fields are decoded one by one
+    let mut cursor = std::io::Cursor::new(hex::decode(tx).unwrap());
+
+    let mut transaction = Transaction {
+      version: Decodable::consensus_decode_from_finite_reader(&mut cursor).unwrap(),
+      input: Decodable::consensus_decode_from_finite_reader(&mut cursor).unwrap(),
+      output: Decodable::consensus_decode_from_finite_reader(&mut cursor).unwrap(),
+      lock_time: Decodable::consensus_decode_from_finite_reader(&mut cursor).unwrap(),
+    };
+
+    let change_position = transaction.output.len();
+
+    if let Some(options) = options {
+      assert_eq!(
+        options.change_position,
+        Some(change_position.try_into().unwrap())
+      );
+    }
+
+    let state = self.state();
+
+    let value = |outpoint: &OutPoint| {
+      state.transactions[&outpoint.txid].output[usize::try_from(outpoint.vout).unwrap()].value
+    };
+
+    let mut input_value = transaction
+      .input
+      .iter()
+      .map(|input| value(&input.previous_output))
+      .sum::<u64>();
+
+    let output_value = transaction
+      .output
+      .iter()
+      .map(|output| output.value)
+      .sum::<u64>();
+
+    let mut utxos = state
+      .utxos
+      .iter()
+      .filter(|(outpoint, _)| {
+        !state.locked.contains(outpoint)
+          && !transaction
+            .input
+            .iter()
+            .any(|input| input.previous_output == **outpoint)
+      })
+      .map(|(outpoint, amount)| (*outpoint, *amount))
+      .collect::<Vec<(OutPoint, Amount)>>();
+
+    utxos.sort_by_key(|(_, amount)| std::cmp::Reverse(*amount));
+
+    let mut utxos = utxos.into_iter();
+
+    while input_value < output_value {
+      let Some((outpoint, amount)) = utxos.next() else {
+        return Err(jsonrpc_core::Error::new(
+          jsonrpc_core::types::error::ErrorCode::ServerError(-4),
+        ));
+      };
+
+      transaction.input.push(TxIn {
+        previous_output: outpoint,
+        script_sig: Script::new(),
+        sequence: Sequence::MAX,
+        witness: Witness::new(),
+      });
+
+      input_value += amount.to_sat();
+    }
+
+    transaction.output.push(TxOut {
+      value: input_value - output_value,
+      script_pubkey: Script::new(),
+    });
+
+    Ok(FundRawTransactionResult {
+      hex: serialize(&transaction),
+      fee: Amount::ZERO,
+      change_position: change_position.try_into().unwrap(),
+    })
+  }
+
   fn sign_raw_transaction_with_wallet(
     &self,
     tx: String,
@@ -332,6 +423,10 @@ impl Api for Server {
     }
   }
 
+  fn get_raw_mempool(&self) -> Result<Vec<Txid>, jsonrpc_core::Error> {
+    Ok(self.state().mempool.iter().map(|tx| tx.txid()).collect())
+  }
+
   fn get_raw_transaction(
     &self,
     txid: Txid,
@@ -340,7 +435,8 @@ impl Api for Server {
   ) -> Result<Value, jsonrpc_core::Error> {
     assert_eq!(blockhash, None, "Blockhash param is unsupported");
     if verbose.unwrap_or(false) {
-      match self.state().transactions.get(&txid) {
+      let state = self.state();
+      match state.transactions.get(&txid) {
         Some(_) => Ok(
           serde_json::to_value(GetRawTransactionResult {
             in_active_chain: Some(true),
@@ -353,7 +449,7 @@ impl Api for Server {
             locktime: 0,
             vin: Vec::new(),
             vout: Vec::new(),
-            blockhash: None,
+            blockhash: state.get_block_hash_of(txid),
             confirmations: Some(1),
             time: None,
             blocktime: None,
@@ -363,13 +459,70 @@ impl Api for Server {
         None => Err(Self::not_found()),
       }
     } else {
-      match self.state().transactions.get(&txid) {
+      let state = self.state();
+      match state
+        .transactions
+        .get(&txid)
+        .or_else(|| state.mempool.iter().find(|tx| tx.txid() == txid))
+      {
         Some(tx) => Ok(Value::String(hex::encode(serialize(tx)))),
         None => Err(Self::not_found()),
       }
     }
   }
 
+  fn get_tx_out(
+    &self,
+    txid: Txid,
+    vout: u32,
+    include_mempool: Option<bool>,
+  ) -> Result<Option<Value>, jsonrpc_core::Error> {
+    let state = self.state();
+
+    let outpoint = OutPoint { txid, vout };
+
+    let include_mempool = include_mempool.unwrap_or(true);
+
+    if include_mempool
+      && state
+        .mempool
+        .iter()
+        .any(|tx| tx.input.iter().any(|input| input.previous_output == outpoint))
+    {
+      return Ok(None);
+    }
+
+    let output = if state.utxos.contains_key(&outpoint) {
+      state.transactions[&txid]
+        .output
+        .get(usize::try_from(vout).unwrap())
+        .map(|output| (output, state.get_confirmations(&state.transactions[&txid])))
+    } else if include_mempool {
+      state
+        .mempool
+        .iter()
+        .find(|tx| tx.txid() == txid)
+        .and_then(|tx| tx.output.get(usize::try_from(vout).unwrap()))
+        .map(|output| (output, 0))
+    } else {
+      None
+    };
+
+    Ok(output.map(|(output, confirmations)| {
+      serde_json::json!({
+        "bestblock": state.hashes.last().unwrap(),
+        "confirmations": confirmations,
+        "value": Amount::from_sat(output.value).to_btc(),
+        "scriptPubKey": {
+          "asm": output.script_pubkey.asm(),
+          "hex": hex::encode(output.script_pubkey.as_bytes()),
+          "addresses": [],
+        },
+        "coinbase": false,
+      })
+    }))
+  }
+
   fn list_unspent(
     &self,
     minconf: Option<usize>,
@@ -544,7 +697,40 @@ impl Api for Server {
     Ok(true)
   }
 
-  fn list_descriptors(&self) -> Result<ListDescriptorsResult, jsonrpc_core::Error> {
+  fn import_private_key(
+    &self,
+    privkey: String,
+    _label: Option<String>,
+    _rescan: Option<bool>,
+  ) -> Result<(), jsonrpc_core::Error> {
+    self
+      .state()
+      .private_keys
+      .push(PrivateKey::from_wif(&privkey).unwrap());
+
+    Ok(())
+  }
+
+  fn dump_private_key(&self, address: Address) -> Result<String, jsonrpc_core::Error> {
+    let secp256k1 = Secp256k1::new();
+
+    self
+      .state()
+      .private_keys
+      .iter()
+      .find(|private_key| {
+        Address::p2pkh(&private_key.public_key(&secp256k1), self.network) == address
+      })
+      .map(PrivateKey::to_wif)
+      .ok_or_else(|| {
+        jsonrpc_core::Error::new(jsonrpc_core::types::error::ErrorCode::ServerError(-4))
+      })
+  }
+
+  fn list_descriptors(
+    &self,
+    _private: Option<bool>,
+  ) -> Result<ListDescriptorsResult, jsonrpc_core::Error> {
     Ok(ListDescriptorsResult {
       wallet_name: "ord".into(),
       descriptors: self
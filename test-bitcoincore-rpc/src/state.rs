@@ -9,6 +9,7 @@ pub(crate) struct State {
   pub(crate) mempool: Vec<Transaction>,
   pub(crate) network: Network,
   pub(crate) nonce: u32,
+  pub(crate) private_keys: Vec<PrivateKey>,
   pub(crate) sent: Vec<Sent>,
   pub(crate) transactions: BTreeMap<Txid, Transaction>,
   pub(crate) utxos: BTreeMap<OutPoint, Amount>,
@@ -44,6 +45,7 @@ impl State {
       mempool: Vec::new(),
       network,
       nonce: 0,
+      private_keys: Vec::new(),
       sent: Vec::new(),
       transactions: BTreeMap::new(),
       utxos: BTreeMap::new(),
@@ -144,7 +146,11 @@ impl State {
       total_value += tx.output[*vout].value;
       input.push(TxIn {
         previous_output: OutPoint::new(tx.txid(), *vout as u32),
-        script_sig: Script::new(),
+        script_sig: if i == 0 {
+          template.script_sig.clone()
+        } else {
+          Script::new()
+        },
         sequence: Sequence::MAX,
         witness: if i == 0 {
           template.witness.clone()
@@ -171,7 +177,11 @@ impl State {
             .get(i)
             .cloned()
             .unwrap_or(value_per_output),
-          script_pubkey: script::Builder::new().into_script(),
+          script_pubkey: template
+            .output_scripts
+            .get(i)
+            .cloned()
+            .unwrap_or_else(|| script::Builder::new().into_script()),
         })
         .collect(),
     };
@@ -195,6 +205,18 @@ impl State {
     &self.mempool
   }
 
+  pub(crate) fn get_block_hash_of(&self, txid: Txid) -> Option<BlockHash> {
+    self.hashes.iter().copied().find(|hash| {
+      self
+        .blocks
+        .get(hash)
+        .unwrap()
+        .txdata
+        .iter()
+        .any(|tx| tx.txid() == txid)
+    })
+  }
+
   pub(crate) fn get_confirmations(&self, tx: &Transaction) -> i32 {
     for (confirmations, hash) in self.hashes.iter().rev().enumerate() {
       if self.blocks.get(hash).unwrap().txdata.contains(tx) {
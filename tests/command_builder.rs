@@ -64,6 +64,10 @@ impl CommandBuilder {
     }
   }
 
+  pub(crate) fn temp_dir(self, tempdir: Arc<TempDir>) -> Self {
+    Self { tempdir, ..self }
+  }
+
   pub(crate) fn stdout_regex(self, expected_stdout: impl AsRef<str>) -> Self {
     Self {
       expected_stdout: Expected::regex(expected_stdout.as_ref()),
@@ -0,0 +1,37 @@
+use super::*;
+
+const TRANSACTION: &str = concat!(
+  "01000000",
+  "01",
+  "0000000000000000000000000000000000000000000000000000000000000000",
+  "ffffffff",
+  "00",
+  "ffffffff",
+  "01",
+  "0000000000000000",
+  "00",
+  "00000000",
+);
+
+#[test]
+fn decode_works_offline() {
+  CommandBuilder::new(format!("decode --tx {TRANSACTION}"))
+    .stdout_regex(r#".*"dunestone": null,.*"integers": null.*"inscription": null.*"#)
+    .run();
+}
+
+#[test]
+fn decode_requires_a_source() {
+  CommandBuilder::new("decode")
+    .stderr_regex("error: the following required arguments were not provided:.*")
+    .expected_exit_code(2)
+    .run();
+}
+
+#[test]
+fn invalid_hex_is_an_error() {
+  CommandBuilder::new("decode --tx xyz")
+    .stderr_regex("error: transaction is neither hex nor a path to a file\nbecause: .*\n")
+    .expected_exit_code(1)
+    .run();
+}
@@ -35,3 +35,161 @@ fn re_opening_database_does_not_trigger_schema_check() {
     .rpc_server(&rpc_server)
     .run();
 }
+
+#[test]
+fn export_and_import_snapshot() {
+  let rpc_server = test_bitcoincore_rpc::spawn();
+  rpc_server.mine_blocks(5);
+
+  let tempdir = TempDir::new().unwrap();
+
+  let snapshot = tempdir.path().join("snapshot");
+
+  let export = CommandBuilder::new(format!(
+    "--index {} index export --height 3 {}",
+    tempdir.path().join("exported.redb").display(),
+    snapshot.display(),
+  ))
+  .rpc_server(&rpc_server)
+  .output::<ord::subcommand::index::export::Output>();
+
+  assert_eq!(export.height, 3);
+  assert_eq!(export.size, fs::metadata(&snapshot).unwrap().len());
+
+  let imported = tempdir.path().join("imported.redb");
+
+  let import = CommandBuilder::new(format!(
+    "--index {} index import {}",
+    imported.display(),
+    snapshot.display(),
+  ))
+  .rpc_server(&rpc_server)
+  .output::<ord::subcommand::index::import::Output>();
+
+  assert_eq!(import.height, 3);
+  assert_eq!(import.block_hash, export.block_hash);
+  assert!(imported.is_file());
+
+  CommandBuilder::new(format!("--index {} index", imported.display()))
+    .rpc_server(&rpc_server)
+    .run();
+}
+
+#[test]
+fn export_requires_index_at_height() {
+  let rpc_server = test_bitcoincore_rpc::spawn();
+  rpc_server.mine_blocks(5);
+
+  let tempdir = TempDir::new().unwrap();
+
+  let index_path = tempdir.path().join("index.redb");
+
+  CommandBuilder::new(format!("--index {} index", index_path.display()))
+    .rpc_server(&rpc_server)
+    .run();
+
+  CommandBuilder::new(format!(
+    "--index {} index export --height 3 {}",
+    index_path.display(),
+    tempdir.path().join("snapshot").display(),
+  ))
+  .rpc_server(&rpc_server)
+  .expected_stderr("error: index is at height 5, cannot export snapshot at height 3\n")
+  .expected_exit_code(1)
+  .run();
+}
+
+#[test]
+fn import_rejects_corrupted_snapshot() {
+  let rpc_server = test_bitcoincore_rpc::spawn();
+  rpc_server.mine_blocks(1);
+
+  let tempdir = TempDir::new().unwrap();
+
+  let snapshot = tempdir.path().join("snapshot");
+
+  CommandBuilder::new(format!(
+    "--index {} index export --height 1 {}",
+    tempdir.path().join("exported.redb").display(),
+    snapshot.display(),
+  ))
+  .rpc_server(&rpc_server)
+  .output::<ord::subcommand::index::export::Output>();
+
+  let mut contents = fs::read(&snapshot).unwrap();
+  *contents.last_mut().unwrap() ^= 1;
+  fs::write(&snapshot, contents).unwrap();
+
+  let imported = tempdir.path().join("imported.redb");
+
+  CommandBuilder::new(format!(
+    "--index {} index import {}",
+    imported.display(),
+    snapshot.display(),
+  ))
+  .rpc_server(&rpc_server)
+  .stderr_regex(
+    "error: snapshot checksum [[:xdigit:]]{64} does not match expected checksum [[:xdigit:]]{64}\n",
+  )
+  .expected_exit_code(1)
+  .run();
+
+  assert!(!imported.exists());
+}
+
+#[test]
+fn import_rejects_snapshot_not_in_best_chain() {
+  let rpc_server = test_bitcoincore_rpc::spawn();
+  rpc_server.mine_blocks(2);
+
+  let tempdir = TempDir::new().unwrap();
+
+  let snapshot = tempdir.path().join("snapshot");
+
+  CommandBuilder::new(format!(
+    "--index {} index export --height 2 {}",
+    tempdir.path().join("exported.redb").display(),
+    snapshot.display(),
+  ))
+  .rpc_server(&rpc_server)
+  .output::<ord::subcommand::index::export::Output>();
+
+  rpc_server.invalidate_tip();
+
+  CommandBuilder::new(format!(
+    "--index {} index import {}",
+    tempdir.path().join("imported.redb").display(),
+    snapshot.display(),
+  ))
+  .rpc_server(&rpc_server)
+  .expected_stderr("error: Dogecoin Core has no block at snapshot height 2\n")
+  .expected_exit_code(1)
+  .run();
+}
+
+#[test]
+fn migrating_up_to_date_index_does_nothing() {
+  let rpc_server = test_bitcoincore_rpc::spawn();
+
+  let tempdir = TempDir::new().unwrap();
+
+  let index_path = tempdir.path().join("index.redb");
+
+  CommandBuilder::new(format!("--index {} index", index_path.display()))
+    .rpc_server(&rpc_server)
+    .run();
+
+  CommandBuilder::new(format!(
+    "--index {} index migrate --dry-run",
+    index_path.display()
+  ))
+  .rpc_server(&rpc_server)
+  .stdout_regex(
+    r#"\{
+  "dry_run": true,
+  "migrations": \[\]
+\}
+"#,
+  )
+  .run();
+}
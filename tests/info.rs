@@ -8,7 +8,9 @@ fn json_with_satoshi_index() {
     .stdout_regex(
       r#"\{
   "blocks_indexed": 1,
+  "blocks_per_second": null,
   "branch_pages": \d+,
+  "eta_seconds": null,
   "fragmented_bytes": \d+,
   "index_file_size": \d+,
   "index_path": ".*\.redb",
@@ -40,7 +42,9 @@ fn json_without_satoshi_index() {
     .stdout_regex(
       r#"\{
   "blocks_indexed": 1,
+  "blocks_per_second": null,
   "branch_pages": \d+,
+  "eta_seconds": null,
   "fragmented_bytes": \d+,
   "index_file_size": \d+,
   "index_path": ".*\.redb",
@@ -71,6 +71,7 @@ fn create_wallet(rpc_server: &test_bitcoincore_rpc::Handle) {
 
 mod command_builder;
 mod core;
+mod decode;
 mod epochs;
 mod expected;
 mod find;
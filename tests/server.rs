@@ -1,4 +1,8 @@
-use super::*;
+use {
+  super::*,
+  ord::{Dune, Dunestone, Edict, Etching},
+  test_bitcoincore_rpc::TransactionTemplate,
+};
 
 #[test]
 fn run() {
@@ -298,3 +302,173 @@ fn expected_sat_time_is_rounded() {
     r".*<dt>timestamp</dt><dd><time>.* \d+:\d+:\d+ UTC</time> \(expected\)</dd>.*",
   );
 }
+
+#[test]
+fn json_request_is_served_html_without_enable_json_api() {
+  let rpc_server = test_bitcoincore_rpc::spawn();
+
+  let response = TestServer::spawn_with_args(&rpc_server, &[]).json_request("/block/0");
+
+  assert_eq!(response.status(), StatusCode::OK);
+  assert!(response.headers()[reqwest::header::CONTENT_TYPE]
+    .to_str()
+    .unwrap()
+    .starts_with("text/html"));
+}
+
+#[test]
+fn block_json() {
+  let rpc_server = test_bitcoincore_rpc::spawn();
+  rpc_server.mine_blocks(1);
+
+  let json = TestServer::spawn_with_server_args(&rpc_server, &[], &["--enable-json-api"])
+    .assert_json_response("/block/1");
+
+  assert_eq!(json["height"], 1);
+  assert_eq!(json["best_height"], 1);
+  assert_eq!(json["transactions"].as_array().unwrap().len(), 1);
+  assert!(json["previous_blockhash"].is_string());
+}
+
+#[test]
+fn transaction_json() {
+  let rpc_server = test_bitcoincore_rpc::spawn();
+  create_wallet(&rpc_server);
+
+  let Inscribe {
+    reveal,
+    inscription,
+    ..
+  } = inscribe(&rpc_server);
+
+  let json = TestServer::spawn_with_server_args(&rpc_server, &[], &["--enable-json-api"])
+    .assert_json_response(format!("/tx/{reveal}"));
+
+  assert_eq!(json["txid"], reveal.to_string());
+  assert_eq!(json["inscription"], inscription);
+  assert_eq!(json["outputs"][0]["outpoint"], format!("{reveal}:0"));
+  assert!(json["blockhash"].is_string());
+}
+
+#[test]
+fn output_json() {
+  let rpc_server = test_bitcoincore_rpc::spawn();
+  create_wallet(&rpc_server);
+
+  let Inscribe {
+    reveal,
+    inscription,
+    ..
+  } = inscribe(&rpc_server);
+
+  let json = TestServer::spawn_with_server_args(&rpc_server, &[], &["--enable-json-api"])
+    .assert_json_response(format!("/output/{reveal}:0"));
+
+  assert_eq!(json["transaction"], reveal.to_string());
+  assert_eq!(json["inscriptions"][0], inscription);
+  assert_eq!(json["value"], 10000);
+}
+
+#[test]
+fn sat_json() {
+  let rpc_server = test_bitcoincore_rpc::spawn();
+
+  let json = TestServer::spawn_with_server_args(&rpc_server, &[], &["--enable-json-api"])
+    .assert_json_response("/sat/0");
+
+  assert_eq!(json["number"], 0);
+  assert_eq!(json["block"], 0);
+  assert_eq!(json["rarity"], "mythic");
+  assert!(json["inscription"].is_null());
+}
+
+#[test]
+fn inscription_json() {
+  let rpc_server = test_bitcoincore_rpc::spawn();
+  create_wallet(&rpc_server);
+
+  let Inscribe {
+    reveal,
+    inscription,
+    ..
+  } = inscribe(&rpc_server);
+
+  let json = TestServer::spawn_with_server_args(&rpc_server, &[], &["--enable-json-api"])
+    .assert_json_response(format!("/shibescription/{inscription}"));
+
+  assert_eq!(json["inscription_id"], inscription);
+  assert_eq!(json["inscription_number"], 0);
+  assert_eq!(json["content_type"], "text/plain;charset=utf-8");
+  assert_eq!(json["content_length"], 3);
+  assert_eq!(json["satpoint"], format!("{reveal}:0:0"));
+}
+
+#[test]
+fn inscriptions_json() {
+  let rpc_server = test_bitcoincore_rpc::spawn();
+  create_wallet(&rpc_server);
+
+  let Inscribe { inscription, .. } = inscribe(&rpc_server);
+
+  let json = TestServer::spawn_with_server_args(&rpc_server, &[], &["--enable-json-api"])
+    .assert_json_response("/shibescriptions");
+
+  assert_eq!(json["inscriptions"][0], inscription);
+  assert!(json["prev"].is_null());
+  assert!(json["next"].is_null());
+}
+
+#[test]
+fn dunes_json() {
+  let rpc_server = test_bitcoincore_rpc::spawn();
+
+  let json = TestServer::spawn_with_server_args(&rpc_server, &[], &["--enable-json-api"])
+    .assert_json_response("/dunes");
+
+  assert_eq!(json["entries"], serde_json::json!([]));
+}
+
+#[test]
+fn dune_json() {
+  let rpc_server = test_bitcoincore_rpc::builder()
+    .network(Network::Regtest)
+    .build();
+
+  rpc_server.mine_blocks(1);
+
+  let etching = rpc_server.broadcast_tx(TransactionTemplate {
+    inputs: &[(1, 0, 0)],
+    op_return: Some(
+      Dunestone {
+        edicts: vec![Edict {
+          id: 0,
+          amount: 1000,
+          output: 0,
+        }],
+        etching: Some(Etching {
+          dune: Some(Dune(u128::from(21_000_000 * COIN_VALUE))),
+          premine: Some(1000),
+          ..Default::default()
+        }),
+        ..Default::default()
+      }
+      .encipher(),
+    ),
+    ..Default::default()
+  });
+
+  rpc_server.mine_blocks(1);
+
+  let json = TestServer::spawn_with_server_args(
+    &rpc_server,
+    &["--regtest", "--index-dunes"],
+    &["--enable-json-api"],
+  )
+  .assert_json_response("/dune/NVTDIJZYIPU");
+
+  assert_eq!(json["id"], "2:1");
+  assert_eq!(json["entry"]["dune"], "NVTDIJZYIPU");
+  assert_eq!(json["entry"]["etching"], etching.to_string());
+  assert_eq!(json["entry"]["supply"], 1000);
+  assert_eq!(json["mintable"], false);
+}
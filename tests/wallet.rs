@@ -1,12 +1,17 @@
 use super::*;
 
 mod balance;
+mod broadcast;
+mod consolidate_dunes;
 mod create;
+mod etch;
 mod inscribe;
 mod inscriptions;
+mod mint;
 mod outputs;
 mod receive;
 mod restore;
 mod sats;
 mod send;
+mod send_dunes;
 mod transactions;
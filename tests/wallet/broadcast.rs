@@ -0,0 +1,43 @@
+use {super::*, ord::subcommand::wallet::send::Output};
+
+#[test]
+fn broadcast_signs_and_sends_exported_psbt() {
+  let rpc_server = test_bitcoincore_rpc::spawn();
+  create_wallet(&rpc_server);
+  rpc_server.mine_blocks(1);
+
+  let Inscribe { inscription, .. } = inscribe(&rpc_server);
+
+  let tempdir = TempDir::new().unwrap();
+
+  let psbt = tempdir.path().join("send.psbt");
+
+  CommandBuilder::new(format!(
+    "wallet send --fee-rate 1 bc1qw508d6qejxtdg4y5r3zarvary0c5xw7kv8f3t4 {inscription} --psbt-out {}",
+    psbt.display(),
+  ))
+  .rpc_server(&rpc_server)
+  .output::<ord::subcommand::wallet::psbt::Output>();
+
+  assert!(rpc_server.mempool().is_empty());
+
+  let output = CommandBuilder::new(format!("wallet broadcast --psbt {}", psbt.display()))
+    .rpc_server(&rpc_server)
+    .output::<Output>();
+
+  assert_eq!(rpc_server.mempool().len(), 1);
+  assert_eq!(rpc_server.mempool()[0].txid(), output.transaction);
+}
+
+#[test]
+fn broadcast_rejects_files_that_are_not_psbts() {
+  let rpc_server = test_bitcoincore_rpc::spawn();
+  create_wallet(&rpc_server);
+
+  CommandBuilder::new("wallet broadcast --psbt foo.psbt")
+    .write("foo.psbt", "foo")
+    .rpc_server(&rpc_server)
+    .expected_exit_code(1)
+    .stderr_regex("error: .*\n")
+    .run();
+}
@@ -0,0 +1,209 @@
+use {
+  super::*,
+  bitcoin::Transaction,
+  ord::{
+    subcommand::wallet::consolidate_dunes::Output, Dune, DuneId, Dunestone, Edict, Etching, Rules,
+  },
+  test_bitcoincore_rpc::TransactionTemplate,
+};
+
+#[test]
+fn flag_is_required() {
+  let rpc_server = test_bitcoincore_rpc::builder()
+    .network(Network::Regtest)
+    .build();
+
+  CommandBuilder::new("--regtest wallet consolidate-dunes --fee-rate 1")
+    .rpc_server(&rpc_server)
+    .expected_exit_code(1)
+    .expected_stderr(
+      "error: `ord wallet consolidate-dunes` requires index created with `--index-dunes` flag\n",
+    )
+    .run();
+}
+
+#[test]
+fn max_inputs_must_be_at_least_two() {
+  let rpc_server = test_bitcoincore_rpc::builder()
+    .network(Network::Regtest)
+    .build();
+
+  CommandBuilder::new(
+    "--index-dunes --regtest wallet consolidate-dunes --fee-rate 1 --max-inputs 1",
+  )
+  .rpc_server(&rpc_server)
+  .expected_exit_code(1)
+  .expected_stderr("error: `--max-inputs` must be at least 2\n")
+  .run();
+}
+
+#[test]
+fn nothing_to_consolidate() {
+  let rpc_server = test_bitcoincore_rpc::builder()
+    .network(Network::Regtest)
+    .build();
+
+  create_wallet(&rpc_server);
+
+  CommandBuilder::new("--index-dunes --regtest wallet consolidate-dunes --fee-rate 1")
+    .rpc_server(&rpc_server)
+    .expected_exit_code(1)
+    .expected_stderr(
+      "error: wallet contains 0 consolidatable dune outputs, nothing to consolidate\n",
+    )
+    .run();
+}
+
+#[test]
+fn dune_outputs_are_consolidated() {
+  let rpc_server = test_bitcoincore_rpc::builder()
+    .network(Network::Regtest)
+    .build();
+
+  create_wallet(&rpc_server);
+
+  rpc_server.mine_blocks(1);
+
+  let etching = rpc_server.broadcast_tx(TransactionTemplate {
+    inputs: &[(1, 0, 0)],
+    outputs: 2,
+    op_return: Some(
+      Dunestone {
+        edicts: vec![
+          Edict {
+            id: 0,
+            amount: 600,
+            output: 0,
+          },
+          Edict {
+            id: 0,
+            amount: 400,
+            output: 1,
+          },
+        ],
+        etching: Some(Etching {
+          dune: Some(Dune(u128::from(21_000_000 * COIN_VALUE))),
+          premine: Some(1000),
+          ..Default::default()
+        }),
+        ..Default::default()
+      }
+      .encipher(),
+    ),
+    ..Default::default()
+  });
+
+  rpc_server.mine_blocks(1);
+
+  let output = CommandBuilder::new("--index-dunes --regtest wallet consolidate-dunes --fee-rate 1")
+    .rpc_server(&rpc_server)
+    .output::<Output>();
+
+  assert_eq!(output.inputs, 2);
+  assert_eq!(output.outputs, 1);
+
+  let [transaction] = <[Transaction; 1]>::try_from(rpc_server.mempool()).unwrap();
+
+  assert_eq!(transaction.txid(), output.transaction);
+
+  assert_eq!(
+    transaction
+      .input
+      .iter()
+      .map(|input| input.previous_output)
+      .collect::<Vec<OutPoint>>(),
+    [
+      OutPoint {
+        txid: etching,
+        vout: 0,
+      },
+      OutPoint {
+        txid: etching,
+        vout: 1,
+      },
+    ],
+  );
+
+  assert_eq!(
+    Dunestone::from_transaction_with_rules(&transaction, Rules::Strict)
+      .unwrap()
+      .edicts,
+    [Edict {
+      id: DuneId {
+        height: 2,
+        index: 1,
+      }
+      .into(),
+      amount: 1000,
+      output: 1,
+    }],
+  );
+}
+
+#[test]
+fn consolidation_is_written_to_psbt() {
+  let rpc_server = test_bitcoincore_rpc::builder()
+    .network(Network::Regtest)
+    .build();
+
+  create_wallet(&rpc_server);
+
+  rpc_server.mine_blocks(1);
+
+  let etching = rpc_server.broadcast_tx(TransactionTemplate {
+    inputs: &[(1, 0, 0)],
+    outputs: 2,
+    op_return: Some(
+      Dunestone {
+        edicts: vec![
+          Edict {
+            id: 0,
+            amount: 600,
+            output: 0,
+          },
+          Edict {
+            id: 0,
+            amount: 400,
+            output: 1,
+          },
+        ],
+        etching: Some(Etching {
+          dune: Some(Dune(u128::from(21_000_000 * COIN_VALUE))),
+          premine: Some(1000),
+          ..Default::default()
+        }),
+        ..Default::default()
+      }
+      .encipher(),
+    ),
+    ..Default::default()
+  });
+
+  rpc_server.mine_blocks(1);
+
+  let tempdir = TempDir::new().unwrap();
+
+  let psbt = tempdir.path().join("consolidate.psbt");
+
+  let output = CommandBuilder::new(format!(
+    "--index-dunes --regtest wallet consolidate-dunes --fee-rate 1 --psbt-out {}",
+    psbt.display(),
+  ))
+  .rpc_server(&rpc_server)
+  .output::<ord::subcommand::wallet::psbt::Output>();
+
+  assert_eq!(output.psbt, psbt);
+  assert!(rpc_server.mempool().is_empty());
+
+  let output = CommandBuilder::new(format!(
+    "--regtest wallet broadcast --psbt {}",
+    psbt.display()
+  ))
+  .rpc_server(&rpc_server)
+  .output::<ord::subcommand::wallet::send::Output>();
+
+  let [transaction] = <[Transaction; 1]>::try_from(rpc_server.mempool()).unwrap();
+
+  assert_eq!(output.transaction, transaction.txid());
+  assert_eq!(transaction.input[0].previous_output.txid, etching);
+}
@@ -1,4 +1,4 @@
-use super::*;
+use {super::*, ord::subcommand::wallet::receive::Output, std::sync::Arc};
 
 #[test]
 fn create() {
@@ -93,3 +93,32 @@ fn create_with_different_name() {
 
   assert!(rpc_server.wallets().contains("inscription-wallet"));
 }
+
+#[test]
+fn create_native() {
+  let rpc_server = test_bitcoincore_rpc::spawn();
+  let tempdir = Arc::new(TempDir::new().unwrap());
+
+  CommandBuilder::new("wallet create --native")
+    .rpc_server(&rpc_server)
+    .temp_dir(tempdir.clone())
+    .output::<Create>();
+
+  assert!(!rpc_server.wallets().contains("ord"));
+  assert!(tempdir.path().join("wallets/ord.json").exists());
+
+  let Output { address } = CommandBuilder::new("wallet receive")
+    .rpc_server(&rpc_server)
+    .temp_dir(tempdir.clone())
+    .output::<Output>();
+
+  assert!(address.is_valid_for_network(Network::Bitcoin));
+  assert!(address.script_pubkey().is_p2pkh());
+
+  CommandBuilder::new("wallet create --native")
+    .rpc_server(&rpc_server)
+    .temp_dir(tempdir)
+    .stderr_regex("error: native wallet already exists at `.*ord.json`\n")
+    .expected_exit_code(1)
+    .run();
+}
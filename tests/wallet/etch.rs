@@ -0,0 +1,193 @@
+use {
+  super::*,
+  ord::{subcommand::wallet::etch::Output, Dune},
+};
+
+fn etch(rpc_server: &test_bitcoincore_rpc::Handle, args: &str) -> CommandBuilder {
+  create_wallet(rpc_server);
+
+  rpc_server.mine_blocks(1);
+
+  CommandBuilder::new(format!(
+    "--index-dunes --regtest wallet etch --dune AAAAAAAAAAAAA --divisibility 0 --fee-rate 1 --symbol ¢ {args}"
+  ))
+  .rpc_server(rpc_server)
+}
+
+#[test]
+fn supply_must_equal_premine_plus_cap_times_limit() {
+  let rpc_server = test_bitcoincore_rpc::builder()
+    .network(Network::Regtest)
+    .build();
+
+  etch(
+    &rpc_server,
+    "--supply 1000 --premine 100 --cap 10 --limit 100",
+  )
+  .expected_exit_code(1)
+  .expected_stderr(
+    "error: <SUPPLY> must equal <PREMINE> plus <CAP> times <LIMIT>: 1000 != 100 + 1000\n",
+  )
+  .run();
+}
+
+#[test]
+fn premine_defaults_to_zero_with_mint_terms() {
+  let rpc_server = test_bitcoincore_rpc::builder()
+    .network(Network::Regtest)
+    .build();
+
+  etch(&rpc_server, "--supply 1000 --cap 9 --limit 100")
+    .expected_exit_code(1)
+    .expected_stderr(
+      "error: <SUPPLY> must equal <PREMINE> plus <CAP> times <LIMIT>: 1000 != 0 + 900\n",
+    )
+    .run();
+}
+
+#[test]
+fn mint_terms_require_cap_and_limit() {
+  let rpc_server = test_bitcoincore_rpc::builder()
+    .network(Network::Regtest)
+    .build();
+
+  etch(&rpc_server, "--supply 1000 --cap 10")
+    .expected_exit_code(1)
+    .expected_stderr("error: mint terms require both <CAP> and <LIMIT>\n")
+    .run();
+}
+
+#[test]
+fn cap_must_be_greater_than_zero() {
+  let rpc_server = test_bitcoincore_rpc::builder()
+    .network(Network::Regtest)
+    .build();
+
+  etch(
+    &rpc_server,
+    "--supply 1000 --premine 1000 --cap 0 --limit 100",
+  )
+  .expected_exit_code(1)
+  .expected_stderr("error: <CAP> must be greater than zero\n")
+  .run();
+}
+
+#[test]
+fn height_start_must_be_less_than_height_end() {
+  let rpc_server = test_bitcoincore_rpc::builder()
+    .network(Network::Regtest)
+    .build();
+
+  etch(
+    &rpc_server,
+    "--supply 1000 --cap 10 --limit 100 --height-start 20 --height-end 20",
+  )
+  .expected_exit_code(1)
+  .expected_stderr("error: <HEIGHT_START> must be less than <HEIGHT_END>: 20 >= 20\n")
+  .run();
+}
+
+#[test]
+fn height_end_must_be_after_next_block() {
+  let rpc_server = test_bitcoincore_rpc::builder()
+    .network(Network::Regtest)
+    .build();
+
+  etch(
+    &rpc_server,
+    "--supply 1000 --cap 10 --limit 100 --height-end 2",
+  )
+  .expected_exit_code(1)
+  .expected_stderr(
+    "error: <HEIGHT_END> must be greater than the height of the next block: 2 <= 2\n",
+  )
+  .run();
+}
+
+#[test]
+fn offset_end_must_be_greater_than_zero() {
+  let rpc_server = test_bitcoincore_rpc::builder()
+    .network(Network::Regtest)
+    .build();
+
+  etch(
+    &rpc_server,
+    "--supply 1000 --cap 10 --limit 100 --offset-end 0",
+  )
+  .expected_exit_code(1)
+  .expected_stderr("error: <OFFSET_END> must be greater than zero\n")
+  .run();
+}
+
+#[test]
+fn dry_run_prints_dunestone_without_broadcasting() {
+  let rpc_server = test_bitcoincore_rpc::builder()
+    .network(Network::Regtest)
+    .build();
+
+  etch(
+    &rpc_server,
+    "--supply 1000 --premine 100 --cap 9 --limit 100 --dry-run",
+  )
+  .stdout_regex(r#"\{\s*"cenotaph": null,\s*"dunestone": \{.*"premine": 100,.*\}\s*"#)
+  .run();
+
+  assert!(rpc_server.mempool().is_empty());
+}
+
+#[test]
+fn etching_with_premine_and_mint_terms_can_be_minted() {
+  let rpc_server = test_bitcoincore_rpc::builder()
+    .network(Network::Regtest)
+    .build();
+
+  etch(
+    &rpc_server,
+    "--supply 1000 --premine 100 --cap 9 --limit 100 --offset-end 10",
+  )
+  .output::<Output>();
+
+  rpc_server.mine_blocks(1);
+
+  CommandBuilder::new("--index-dunes --regtest wallet mint --dune AAAAAAAAAAAAA --fee-rate 1")
+    .rpc_server(&rpc_server)
+    .output::<ord::subcommand::wallet::mint::Output>();
+
+  rpc_server.mine_blocks(1);
+
+  let balance = CommandBuilder::new("--index-dunes --regtest wallet balance")
+    .rpc_server(&rpc_server)
+    .output::<ord::subcommand::wallet::balance::Output>();
+
+  assert_eq!(
+    balance.dunes.unwrap()[&Dune::from_str("AAAAAAAAAAAAA").unwrap()],
+    200
+  );
+}
+
+#[test]
+fn etching_does_not_spend_dunic_outputs() {
+  let rpc_server = test_bitcoincore_rpc::builder()
+    .network(Network::Regtest)
+    .build();
+
+  create_wallet(&rpc_server);
+
+  rpc_server.mine_blocks_with_subsidy(1, 10000);
+
+  CommandBuilder::new(
+    "--index-dunes --regtest wallet etch --dune AAAAAAAAAAAAA --divisibility 0 --fee-rate 0 --supply 1000 --symbol ¢",
+  )
+  .rpc_server(&rpc_server)
+  .output::<Output>();
+
+  rpc_server.mine_blocks_with_subsidy(1, 0);
+
+  CommandBuilder::new(
+    "--index-dunes --regtest wallet etch --dune AAAAAAAAAAAAB --divisibility 0 --fee-rate 0 --supply 1000 --symbol ¢",
+  )
+  .rpc_server(&rpc_server)
+  .expected_exit_code(1)
+  .stderr_regex("error: JSON-RPC error: .*")
+  .run();
+}
@@ -1,17 +1,20 @@
-use super::*;
+use {
+  super::*, bitcoin::consensus::encode::serialize_hex,
+  ord::subcommand::wallet::inscriptions::PendingOutput, std::sync::Arc,
+};
 
 #[test]
 fn inscribe_creates_inscriptions() {
   let rpc_server = test_bitcoincore_rpc::spawn();
   rpc_server.mine_blocks(1);
 
-  assert_eq!(rpc_server.descriptors().len(), 0);
+  assert_eq!(rpc_server.private_keys().len(), 0);
 
   create_wallet(&rpc_server);
 
   let Inscribe { inscription, .. } = inscribe(&rpc_server);
 
-  assert_eq!(rpc_server.descriptors().len(), 3);
+  assert_eq!(rpc_server.private_keys().len(), 1);
 
   let request =
     TestServer::spawn_with_args(&rpc_server, &[]).request(format!("/content/{inscription}"));
@@ -31,7 +34,7 @@ fn inscribe_works_with_huge_expensive_inscriptions() {
   let txid = rpc_server.mine_blocks(1)[0].txdata[0].txid();
 
   CommandBuilder::new(format!(
-    "wallet inscribe foo.txt --satpoint {txid}:0:0 --fee-rate 10"
+    "wallet inscribe foo.txt --satpoint {txid}:0:0 --fee-rate 10 --no-limit"
   ))
   .write("foo.txt", [0; 350_000])
   .rpc_server(&rpc_server)
@@ -58,14 +61,13 @@ fn inscribe_no_backup() {
   rpc_server.mine_blocks(1);
 
   create_wallet(&rpc_server);
-  assert_eq!(rpc_server.descriptors().len(), 2);
 
   CommandBuilder::new("wallet inscribe hello.txt --no-backup")
     .write("hello.txt", "HELLOWORLD")
     .rpc_server(&rpc_server)
     .output::<Inscribe>();
 
-  assert_eq!(rpc_server.descriptors().len(), 2);
+  assert_eq!(rpc_server.private_keys().len(), 0);
 }
 
 #[test]
@@ -396,3 +398,267 @@ fn inscribe_with_no_limit() {
     .write("degenerate.png", four_megger)
     .rpc_server(&rpc_server);
 }
+
+#[test]
+fn inscribe_resume_requires_saved_transactions() {
+  let rpc_server = test_bitcoincore_rpc::spawn();
+  create_wallet(&rpc_server);
+  let txid = rpc_server.mine_blocks(1)[0].txdata[0].txid();
+
+  CommandBuilder::new(format!("wallet inscribe --resume {txid}"))
+    .rpc_server(&rpc_server)
+    .expected_exit_code(1)
+    .expected_stderr(format!(
+      "error: no saved transactions for inscription {txid}i0\n"
+    ))
+    .run();
+}
+
+#[test]
+fn inscribe_abandon_requires_saved_transactions() {
+  let rpc_server = test_bitcoincore_rpc::spawn();
+  create_wallet(&rpc_server);
+  let txid = rpc_server.mine_blocks(1)[0].txdata[0].txid();
+
+  CommandBuilder::new(format!("wallet inscribe --abandon {txid}"))
+    .rpc_server(&rpc_server)
+    .expected_exit_code(1)
+    .expected_stderr(format!(
+      "error: no saved transactions for inscription {txid}i0\n"
+    ))
+    .run();
+}
+
+/// Inscribes a multi-transaction chain with a data dir of `tempdir`,
+/// returning its inscription, its transactions and its saved journal
+fn inscribe_chain(
+  rpc_server: &test_bitcoincore_rpc::Handle,
+  tempdir: &Arc<TempDir>,
+) -> (String, Vec<bitcoin::Transaction>, serde_json::Value) {
+  create_wallet(rpc_server);
+  rpc_server.mine_blocks(1);
+
+  let Inscribe { inscription, .. } = CommandBuilder::new("wallet inscribe foo.txt")
+    .temp_dir(tempdir.clone())
+    .write("foo.txt", [0; 5000])
+    .rpc_server(rpc_server)
+    .output();
+
+  let transactions = rpc_server.mempool();
+
+  assert!(transactions.len() > 2);
+  assert_eq!(inscription, format!("{}i0", transactions[1].txid()));
+
+  let journal = serde_json::from_str::<serde_json::Value>(
+    &fs::read_to_string(
+      tempdir
+        .path()
+        .join(format!("inscriptions/{}.json", transactions[1].txid())),
+    )
+    .unwrap(),
+  )
+  .unwrap();
+
+  assert_eq!(journal["transactions"], serde_json::json!([]));
+
+  (inscription, transactions, journal)
+}
+
+fn write_journal(
+  tempdir: &TempDir,
+  transactions: &[bitcoin::Transaction],
+  mut journal: serde_json::Value,
+  broadcast: usize,
+) {
+  journal["broadcast"] = broadcast.into();
+  journal["transactions"] = transactions.iter().map(serialize_hex).collect();
+
+  fs::write(
+    tempdir
+      .path()
+      .join(format!("inscriptions/{}.json", transactions[1].txid())),
+    journal.to_string(),
+  )
+  .unwrap();
+}
+
+#[test]
+fn inscribe_resume_broadcasts_remaining_transactions() {
+  let rpc_server = test_bitcoincore_rpc::spawn();
+  let tempdir = Arc::new(TempDir::new().unwrap());
+
+  let (inscription, transactions, journal) = inscribe_chain(&rpc_server, &tempdir);
+
+  rpc_server.clear_mempool();
+
+  write_journal(&tempdir, &transactions, journal, 1);
+
+  let output = CommandBuilder::new(format!(
+    "wallet inscribe --resume {}",
+    transactions[1].txid()
+  ))
+  .temp_dir(tempdir.clone())
+  .rpc_server(&rpc_server)
+  .output::<Inscribe>();
+
+  assert_eq!(output.inscription, inscription);
+  assert_eq!(output.reveal, transactions.last().unwrap().txid());
+  assert_eq!(rpc_server.mempool(), transactions[1..]);
+
+  CommandBuilder::new(format!(
+    "wallet inscribe --resume {}",
+    transactions[1].txid()
+  ))
+  .temp_dir(tempdir)
+  .rpc_server(&rpc_server)
+  .expected_exit_code(1)
+  .expected_stderr(format!(
+    "error: all transactions of inscription {inscription} have been broadcast\n"
+  ))
+  .run();
+}
+
+#[test]
+fn inscribe_abandon_sweeps_locked_output_of_partial_inscription() {
+  let rpc_server = test_bitcoincore_rpc::spawn();
+  let tempdir = Arc::new(TempDir::new().unwrap());
+
+  let (_inscription, transactions, journal) = inscribe_chain(&rpc_server, &tempdir);
+
+  assert_eq!(rpc_server.private_keys().len(), 1);
+
+  // Confirm the commit and first reveal, as if the later reveals had been
+  // dropped from the mempool
+  rpc_server.clear_mempool();
+
+  write_journal(&tempdir, &transactions[..2], journal, 0);
+
+  CommandBuilder::new(format!(
+    "wallet inscribe --resume {}",
+    transactions[1].txid()
+  ))
+  .temp_dir(tempdir.clone())
+  .rpc_server(&rpc_server)
+  .output::<Inscribe>();
+
+  rpc_server.mine_blocks(1);
+
+  let pending = CommandBuilder::new("wallet inscriptions pending")
+    .temp_dir(tempdir.clone())
+    .rpc_server(&rpc_server)
+    .output::<Vec<PendingOutput>>();
+
+  assert_eq!(pending.len(), 1);
+  assert_eq!(pending[0].transaction, transactions[1].txid());
+  assert!(!pending[0].resumable);
+
+  let output = CommandBuilder::new(format!(
+    "wallet inscribe --abandon {}",
+    transactions[1].txid()
+  ))
+  .temp_dir(tempdir.clone())
+  .rpc_server(&rpc_server)
+  .output::<serde_json::Value>();
+
+  let mempool = rpc_server.mempool();
+
+  assert_eq!(mempool.len(), 1);
+  assert_eq!(output["transaction"], mempool[0].txid().to_string());
+  assert_eq!(
+    mempool[0].input[0].previous_output,
+    OutPoint {
+      txid: transactions[1].txid(),
+      vout: 0,
+    }
+  );
+  assert!(!tempdir
+    .path()
+    .join(format!("inscriptions/{}.json", transactions[1].txid()))
+    .exists());
+
+  rpc_server.mine_blocks(1);
+
+  assert!(CommandBuilder::new("wallet inscriptions pending")
+    .temp_dir(tempdir)
+    .rpc_server(&rpc_server)
+    .output::<Vec<PendingOutput>>()
+    .is_empty());
+}
+
+#[test]
+fn inscribe_requires_file_resume_or_abandon() {
+  CommandBuilder::new("wallet inscribe")
+    .expected_exit_code(2)
+    .stderr_regex("error: the following required arguments were not provided:.*")
+    .run();
+}
+
+#[test]
+fn inscribe_psbt_out_is_revealed_by_broadcast() {
+  let rpc_server = test_bitcoincore_rpc::spawn();
+  create_wallet(&rpc_server);
+  rpc_server.mine_blocks(1);
+
+  let tempdir = TempDir::new().unwrap();
+
+  let psbt = tempdir.path().join("commit.psbt");
+
+  let output = CommandBuilder::new(format!(
+    "wallet inscribe foo.txt --psbt-out {}",
+    psbt.display()
+  ))
+  .write("foo.txt", "FOO")
+  .rpc_server(&rpc_server)
+  .output::<ord::subcommand::wallet::psbt::Output>();
+
+  assert_eq!(output.psbt, psbt);
+  assert!(rpc_server.mempool().is_empty());
+  assert_eq!(rpc_server.private_keys().len(), 1);
+
+  let recovery_key = rpc_server.private_keys()[0].to_wif();
+
+  let exported = fs::read_to_string(&psbt).unwrap();
+
+  assert!(!exported.contains(&recovery_key));
+  assert!(
+    !String::from_utf8_lossy(&base64::decode(exported.trim()).unwrap()).contains(&recovery_key)
+  );
+
+  let Inscribe {
+    commit,
+    inscription,
+    reveal,
+    ..
+  } = CommandBuilder::new(format!("wallet broadcast --psbt {}", psbt.display()))
+    .rpc_server(&rpc_server)
+    .output();
+
+  let mempool = rpc_server.mempool();
+
+  assert_eq!(mempool.len(), 2);
+  assert_eq!(mempool[0].txid(), commit);
+  assert_eq!(mempool[1].txid(), reveal);
+  assert_eq!(mempool[1].input[0].previous_output.txid, commit);
+
+  rpc_server.mine_blocks(1);
+
+  let request =
+    TestServer::spawn_with_args(&rpc_server, &[]).request(format!("/content/{inscription}"));
+
+  assert_eq!(request.status(), 200);
+  assert_eq!(request.text().unwrap(), "FOO");
+}
+
+#[test]
+fn inscribe_psbt_out_requires_backup() {
+  let rpc_server = test_bitcoincore_rpc::spawn();
+  create_wallet(&rpc_server);
+  rpc_server.mine_blocks(1);
+
+  CommandBuilder::new("wallet inscribe foo.txt --psbt-out commit.psbt --no-backup")
+    .write("foo.txt", "FOO")
+    .rpc_server(&rpc_server)
+    .expected_exit_code(2)
+    .stderr_regex("error: the argument '--psbt-out <PSBT_OUT>' cannot be used with '--no-backup'.*")
+    .run();
+}
@@ -1,6 +1,9 @@
 use {
   super::*,
-  ord::subcommand::wallet::{inscriptions::Output, receive},
+  ord::subcommand::wallet::{
+    inscriptions::{Output, PendingOutput},
+    receive,
+  },
 };
 
 #[test]
@@ -79,3 +82,18 @@ fn inscriptions_includes_locked_utxos() {
   assert_eq!(output[0].inscription, inscription.parse().unwrap());
   assert_eq!(output[0].location, format!("{reveal}:0:0").parse().unwrap());
 }
+
+#[test]
+fn pending_inscriptions_exclude_complete_inscriptions() {
+  let rpc_server = test_bitcoincore_rpc::spawn();
+  create_wallet(&rpc_server);
+  rpc_server.mine_blocks(1);
+
+  inscribe(&rpc_server);
+
+  let output = CommandBuilder::new("wallet inscriptions pending")
+    .rpc_server(&rpc_server)
+    .output::<Vec<PendingOutput>>();
+
+  assert!(output.is_empty());
+}
@@ -0,0 +1,209 @@
+use {
+  super::*,
+  bitcoin::Transaction,
+  ord::{
+    subcommand::wallet::mint::Output, Dune, DuneId, Dunestone, Edict, Etching, Rules, Terms,
+    CLAIM_BIT,
+  },
+  test_bitcoincore_rpc::TransactionTemplate,
+};
+
+fn etch(rpc_server: &test_bitcoincore_rpc::Handle, terms: Terms) -> DuneId {
+  rpc_server.mine_blocks(1);
+
+  rpc_server.broadcast_tx(TransactionTemplate {
+    inputs: &[(1, 0, 0)],
+    op_return: Some(
+      Dunestone {
+        etching: Some(Etching {
+          dune: Some(Dune(u128::from(21_000_000 * COIN_VALUE))),
+          terms: Some(terms),
+          ..Default::default()
+        }),
+        ..Default::default()
+      }
+      .encipher(),
+    ),
+    ..Default::default()
+  });
+
+  rpc_server.mine_blocks(1);
+
+  DuneId {
+    height: 2,
+    index: 1,
+  }
+}
+
+#[test]
+fn flag_is_required() {
+  let rpc_server = test_bitcoincore_rpc::builder()
+    .network(Network::Regtest)
+    .build();
+
+  CommandBuilder::new("--regtest wallet mint --dune AAAAAAAAAAAAA --fee-rate 1")
+    .rpc_server(&rpc_server)
+    .expected_exit_code(1)
+    .expected_stderr("error: `ord wallet mint` requires index created with `--index-dunes` flag\n")
+    .run();
+}
+
+#[test]
+fn unknown_dune_is_an_error() {
+  let rpc_server = test_bitcoincore_rpc::builder()
+    .network(Network::Regtest)
+    .build();
+
+  create_wallet(&rpc_server);
+
+  rpc_server.mine_blocks(1);
+
+  CommandBuilder::new("--index-dunes --regtest wallet mint --dune AAAAAAAAAAAAA --fee-rate 1")
+    .rpc_server(&rpc_server)
+    .expected_exit_code(1)
+    .expected_stderr("error: dune `AAAAAAAAAAAAA` has not been etched\n")
+    .run();
+}
+
+#[test]
+fn psbt_out_requires_a_count_of_one() {
+  let rpc_server = test_bitcoincore_rpc::builder()
+    .network(Network::Regtest)
+    .build();
+
+  create_wallet(&rpc_server);
+
+  CommandBuilder::new(
+    "--index-dunes --regtest wallet mint --dune AAAAAAAAAAAAA --fee-rate 1 --count 2 --psbt-out mint.psbt",
+  )
+  .rpc_server(&rpc_server)
+  .expected_exit_code(1)
+  .expected_stderr("error: `--psbt-out` can only write one mint, but <COUNT> is 2\n")
+  .run();
+}
+
+#[test]
+fn mint_is_sent() {
+  let rpc_server = test_bitcoincore_rpc::builder()
+    .network(Network::Regtest)
+    .build();
+
+  create_wallet(&rpc_server);
+
+  let id = etch(
+    &rpc_server,
+    Terms {
+      limit: Some(1000),
+      cap: Some(10),
+      ..Default::default()
+    },
+  );
+
+  let output =
+    CommandBuilder::new("--index-dunes --regtest wallet mint --dune NVTDIJZYIPU --fee-rate 1")
+      .rpc_server(&rpc_server)
+      .output::<Output>();
+
+  let [transaction] = <[Transaction; 1]>::try_from(rpc_server.mempool()).unwrap();
+
+  assert_eq!(output.transactions, [transaction.txid()]);
+  assert_eq!(output.pile.amount, 1000);
+
+  assert_eq!(
+    Dunestone::from_transaction_with_rules(&transaction, Rules::Strict),
+    Some(Dunestone {
+      edicts: vec![Edict {
+        id: u128::from(id) | CLAIM_BIT,
+        amount: 1000,
+        output: 1,
+      }],
+      ..Default::default()
+    }),
+  );
+}
+
+#[test]
+fn mint_of_dune_with_no_mints_remaining_is_an_error() {
+  let rpc_server = test_bitcoincore_rpc::builder()
+    .network(Network::Regtest)
+    .build();
+
+  create_wallet(&rpc_server);
+
+  etch(
+    &rpc_server,
+    Terms {
+      limit: Some(1000),
+      cap: Some(1),
+      ..Default::default()
+    },
+  );
+
+  CommandBuilder::new("--index-dunes --regtest wallet mint --dune NVTDIJZYIPU --fee-rate 1")
+    .rpc_server(&rpc_server)
+    .output::<Output>();
+
+  rpc_server.mine_blocks(1);
+
+  CommandBuilder::new("--index-dunes --regtest wallet mint --dune NVTDIJZYIPU --fee-rate 1")
+    .rpc_server(&rpc_server)
+    .expected_exit_code(1)
+    .expected_stderr("error: dune `NVTDIJZYIPU` cannot be minted in block 4: limited to 1 mints\n")
+    .run();
+}
+
+#[test]
+fn mint_before_start_height_is_an_error() {
+  let rpc_server = test_bitcoincore_rpc::builder()
+    .network(Network::Regtest)
+    .build();
+
+  create_wallet(&rpc_server);
+
+  etch(
+    &rpc_server,
+    Terms {
+      limit: Some(1000),
+      height: (Some(10), None),
+      ..Default::default()
+    },
+  );
+
+  CommandBuilder::new("--index-dunes --regtest wallet mint --dune NVTDIJZYIPU --fee-rate 1")
+    .rpc_server(&rpc_server)
+    .expected_exit_code(1)
+    .expected_stderr(
+      "error: dune `NVTDIJZYIPU` cannot be minted in block 3: mint starts on block 10\n",
+    )
+    .run();
+
+  assert!(rpc_server.mempool().is_empty());
+}
+
+#[test]
+fn mint_after_end_height_is_an_error() {
+  let rpc_server = test_bitcoincore_rpc::builder()
+    .network(Network::Regtest)
+    .build();
+
+  create_wallet(&rpc_server);
+
+  etch(
+    &rpc_server,
+    Terms {
+      limit: Some(1000),
+      height: (None, Some(3)),
+      ..Default::default()
+    },
+  );
+
+  CommandBuilder::new("--index-dunes --regtest wallet mint --dune NVTDIJZYIPU --fee-rate 1")
+    .rpc_server(&rpc_server)
+    .expected_exit_code(1)
+    .expected_stderr(
+      "error: dune `NVTDIJZYIPU` cannot be minted in block 3: mint ended on block 3\n",
+    )
+    .run();
+
+  assert!(rpc_server.mempool().is_empty());
+}
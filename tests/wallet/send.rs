@@ -344,3 +344,17 @@ fn user_must_provide_fee_rate_to_send() {
   )
   .run();
 }
+
+#[test]
+fn dry_run_is_only_supported_for_dunes() {
+  let rpc_server = test_bitcoincore_rpc::spawn();
+  create_wallet(&rpc_server);
+
+  CommandBuilder::new(
+    "wallet send --fee-rate 1 --dry-run bc1qw508d6qejxtdg4y5r3zarvary0c5xw7kv8f3t4 1btc",
+  )
+  .rpc_server(&rpc_server)
+  .expected_exit_code(1)
+  .expected_stderr("error: `--dry-run` is only supported when sending dunes\n")
+  .run();
+}
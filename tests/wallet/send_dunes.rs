@@ -0,0 +1,186 @@
+use {
+  super::*,
+  bitcoin::{Address, Transaction},
+  ord::{subcommand::wallet::send_dunes::Output, Dune, DuneId, Dunestone, Edict, Etching, Rules},
+  test_bitcoincore_rpc::TransactionTemplate,
+};
+
+#[test]
+fn flag_is_required() {
+  let rpc_server = test_bitcoincore_rpc::builder()
+    .network(Network::Regtest)
+    .build();
+
+  CommandBuilder::new("--regtest wallet send-dunes --batch batch.csv --fee-rate 1")
+    .write(
+      "batch.csv",
+      "bcrt1q6rhpng9evdsfnn833a4f4vej0asu6dk5srld6x,AAAAAAAAAAAAA,1\n",
+    )
+    .rpc_server(&rpc_server)
+    .expected_exit_code(1)
+    .expected_stderr(
+      "error: `ord wallet send-dunes` requires index created with `--index-dunes` flag\n",
+    )
+    .run();
+}
+
+#[test]
+fn empty_batch_is_an_error() {
+  let rpc_server = test_bitcoincore_rpc::builder()
+    .network(Network::Regtest)
+    .build();
+
+  CommandBuilder::new("--index-dunes --regtest wallet send-dunes --batch batch.json --fee-rate 1")
+    .write("batch.json", "[]")
+    .rpc_server(&rpc_server)
+    .expected_exit_code(1)
+    .stderr_regex("error: batch file `.*batch.json` contains no recipients\n")
+    .run();
+}
+
+#[test]
+fn batch_is_sent() {
+  let rpc_server = test_bitcoincore_rpc::builder()
+    .network(Network::Regtest)
+    .build();
+
+  create_wallet(&rpc_server);
+
+  rpc_server.mine_blocks(1);
+
+  let etching = rpc_server.broadcast_tx(TransactionTemplate {
+    inputs: &[(1, 0, 0)],
+    op_return: Some(
+      Dunestone {
+        edicts: vec![Edict {
+          id: 0,
+          amount: 1000,
+          output: 0,
+        }],
+        etching: Some(Etching {
+          dune: Some(Dune(u128::from(21_000_000 * COIN_VALUE))),
+          premine: Some(1000),
+          ..Default::default()
+        }),
+        ..Default::default()
+      }
+      .encipher(),
+    ),
+    ..Default::default()
+  });
+
+  rpc_server.mine_blocks(1);
+
+  let recipient = "bcrt1q6rhpng9evdsfnn833a4f4vej0asu6dk5srld6x";
+
+  let output =
+    CommandBuilder::new("--index-dunes --regtest wallet send-dunes --batch batch.csv --fee-rate 1")
+      .write("batch.csv", format!("{recipient},NVTDIJZYIPU,300\n"))
+      .rpc_server(&rpc_server)
+      .output::<Output>();
+
+  let [transaction] = <[Transaction; 1]>::try_from(rpc_server.mempool()).unwrap();
+
+  assert_eq!(output.transactions, [transaction.txid()]);
+
+  assert_eq!(
+    transaction.input[0].previous_output,
+    OutPoint {
+      txid: etching,
+      vout: 0,
+    },
+  );
+
+  assert_eq!(
+    transaction.output[2].script_pubkey,
+    recipient.parse::<Address>().unwrap().script_pubkey(),
+  );
+
+  let dunestone = Dunestone::from_transaction_with_rules(&transaction, Rules::Strict).unwrap();
+
+  assert_eq!(dunestone.pointer, Some(1));
+
+  assert_eq!(
+    dunestone.edicts,
+    [Edict {
+      id: DuneId {
+        height: 2,
+        index: 1,
+      }
+      .into(),
+      amount: 300,
+      output: 2,
+    }],
+  );
+}
+
+#[test]
+fn batch_is_written_to_psbt() {
+  let rpc_server = test_bitcoincore_rpc::builder()
+    .network(Network::Regtest)
+    .build();
+
+  create_wallet(&rpc_server);
+
+  rpc_server.mine_blocks(1);
+
+  let etching = rpc_server.broadcast_tx(TransactionTemplate {
+    inputs: &[(1, 0, 0)],
+    op_return: Some(
+      Dunestone {
+        edicts: vec![Edict {
+          id: 0,
+          amount: 1000,
+          output: 0,
+        }],
+        etching: Some(Etching {
+          dune: Some(Dune(u128::from(21_000_000 * COIN_VALUE))),
+          premine: Some(1000),
+          ..Default::default()
+        }),
+        ..Default::default()
+      }
+      .encipher(),
+    ),
+    ..Default::default()
+  });
+
+  rpc_server.mine_blocks(1);
+
+  let tempdir = TempDir::new().unwrap();
+
+  let psbt = tempdir.path().join("send-dunes.psbt");
+
+  let output = CommandBuilder::new(format!(
+    "--index-dunes --regtest wallet send-dunes --batch batch.csv --fee-rate 1 --psbt-out {}",
+    psbt.display(),
+  ))
+  .write(
+    "batch.csv",
+    "bcrt1q6rhpng9evdsfnn833a4f4vej0asu6dk5srld6x,NVTDIJZYIPU,300\n",
+  )
+  .rpc_server(&rpc_server)
+  .output::<ord::subcommand::wallet::psbt::Output>();
+
+  assert_eq!(output.psbt, psbt);
+  assert!(rpc_server.mempool().is_empty());
+
+  let output = CommandBuilder::new(format!(
+    "--regtest wallet broadcast --psbt {}",
+    psbt.display()
+  ))
+  .rpc_server(&rpc_server)
+  .output::<ord::subcommand::wallet::send::Output>();
+
+  let [transaction] = <[Transaction; 1]>::try_from(rpc_server.mempool()).unwrap();
+
+  assert_eq!(output.transaction, transaction.txid());
+
+  assert_eq!(
+    transaction.input[0].previous_output,
+    OutPoint {
+      txid: etching,
+      vout: 0,
+    },
+  );
+}